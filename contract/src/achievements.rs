@@ -0,0 +1,244 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::*;
+
+enum Trigger {
+    /// Checked for the attacker after each capture, given the attack's move record
+    Capture(fn(&MoveRecord) -> bool),
+    /// Checked for each player once the game finishes, given their player index and updated record
+    Finish(fn(&Game, usize, &PlayerRecord) -> bool),
+    /// Checked for the solver after each correct solution
+    Puzzle(fn(&Puzzle) -> bool),
+}
+
+struct Achievement {
+    id: &'static str,
+    trigger: Trigger,
+}
+
+fn won(game: &Game, player_index: usize) -> bool {
+    game.winner == Some(player_index as u8)
+}
+
+const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first_win",
+        trigger: Trigger::Finish(|game, player_index, _| won(game, player_index)),
+    },
+    // Won by capturing every die without the opponent capturing any, a resignation doesn't count
+    Achievement {
+        id: "flawless",
+        trigger: Trigger::Finish(|game, player_index, _| won(game, player_index)
+            && game.finish_reason == Some(FinishReason::CaptureOut)
+            && game.captured[(player_index + 1) % 2].is_empty()),
+    },
+    Achievement {
+        id: "giant_slayer",
+        trigger: Trigger::Capture(|record| record.captured == Some(20)
+            && record.attacker_die_indices.len() == 1
+            && record.dice_before[record.player as usize][record.attacker_die_indices[0] as usize].size == 4),
+    },
+    // Won by 30 points or more, scores are doubled
+    Achievement {
+        id: "puzzle_solver",
+        trigger: Trigger::Puzzle(|_| true),
+    },
+    Achievement {
+        id: "rout",
+        trigger: Trigger::Finish(|game, player_index, _| won(game, player_index)
+            && game.finish_reason == Some(FinishReason::CaptureOut)
+            && score(game, player_index) >= score(game, (player_index + 1) % 2) + 60),
+    },
+    Achievement {
+        id: "veteran",
+        trigger: Trigger::Finish(|_, _, record| record.games_played() >= 100),
+    },
+];
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EarnedAchievement {
+    pub id: String,
+    /// Block timestamp of the move or game that unlocked it
+    pub earned_at: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Achievements the account has unlocked, in the order it did
+    pub fn get_achievements(&self, account_id: AccountId) -> Vec<EarnedAchievement> {
+        self.achievements.get(&account_id.to_string()).unwrap_or_default()
+    }
+
+    /// Unlocks capture achievements for whoever made the game's last move
+    pub(crate) fn check_capture_achievements(&mut self, game: &Game) {
+        let record = match game.history.last() {
+            Some(record) => record,
+            None => return,
+        };
+        for achievement in ACHIEVEMENTS.iter() {
+            if let Trigger::Capture(check) = achievement.trigger {
+                if check(record) {
+                    self.unlock_achievement(&game.players[record.player as usize], achievement.id, &game.id);
+                }
+            }
+        }
+    }
+
+    /// Unlocks finish achievements for both players, after their records took the game in
+    pub(crate) fn check_finish_achievements(&mut self, game: &Game) {
+        for (player_index, player_id) in game.players.iter().enumerate() {
+            let record = self.records.get(player_id).unwrap_or_default();
+            for achievement in ACHIEVEMENTS.iter() {
+                if let Trigger::Finish(check) = achievement.trigger {
+                    if check(game, player_index, &record) {
+                        self.unlock_achievement(player_id, achievement.id, &game.id);
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn check_puzzle_achievements(&mut self, account_id: &str, puzzle: &Puzzle) {
+        for achievement in ACHIEVEMENTS.iter() {
+            if let Trigger::Puzzle(check) = achievement.trigger {
+                if check(puzzle) && self.earn_achievement(account_id, achievement.id) {
+                    emit_event("achievement_unlocked", serde_json::json!({
+                        "account_id": account_id,
+                        "achievement_id": achievement.id,
+                        "puzzle_id": puzzle.id,
+                    }));
+                }
+            }
+        }
+    }
+
+    fn unlock_achievement(&mut self, account_id: &str, achievement_id: &str, game_id: &str) {
+        if !self.earn_achievement(account_id, achievement_id) {
+            return;
+        }
+
+        emit_event("achievement_unlocked", serde_json::json!({
+            "account_id": account_id,
+            "achievement_id": achievement_id,
+            "game_id": game_id,
+        }));
+    }
+
+    /// Adds the achievement to the account's, false if it already had it
+    fn earn_achievement(&mut self, account_id: &str, achievement_id: &str) -> bool {
+        let mut earned = self.achievements.get(&account_id.to_string()).unwrap_or_default();
+        if earned.iter().any(|achievement| achievement.id == achievement_id) {
+            return false;
+        }
+        earned.push(EarnedAchievement { id: achievement_id.to_string(), earned_at: env::block_timestamp() });
+        self.achievements.insert(&account_id.to_string(), &earned);
+        return true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::login_as;
+
+    fn insert_game(contract: &mut Contract, dice: Vec<Vec<Die>>, captured: Vec<Vec<u8>>) {
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice,
+            captured,
+            ..Default::default()
+        });
+    }
+
+    fn earned(contract: &Contract, account_id: &str) -> Vec<String> {
+        contract.get_achievements(account_id.parse().unwrap()).into_iter().map(|achievement| achievement.id).collect()
+    }
+
+    #[test]
+    fn giant_slayer() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract,
+            vec![vec![Die { size: 4, value: 4 }, Die { size: 6, value: 6 }], vec![Die { size: 20, value: 3 }, Die { size: 20, value: 5 }]],
+            vec![vec![], vec![]]);
+        login_as("bob.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        assert_eq!(earned(&contract, "bob.near"), vec!["giant_slayer"]);
+    }
+
+    #[test]
+    fn giant_slayer_needs_a_lone_d4() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract,
+            vec![vec![Die { size: 4, value: 2 }, Die { size: 6, value: 3 }], vec![Die { size: 20, value: 5 }, Die { size: 20, value: 9 }]],
+            vec![vec![], vec![]]);
+        login_as("bob.near");
+        contract.attack("1".to_string(), vec![0, 1], 0, None, None, None);
+        assert_eq!(earned(&contract, "bob.near"), vec![] as Vec<String>);
+    }
+
+    #[test]
+    fn flawless_win() {
+        let mut contract = Contract::default();
+        // 2 * 4 + 20 against nothing, 14 points ahead, short of a rout
+        insert_game(&mut contract, vec![vec![Die { size: 20, value: 19 }], vec![Die { size: 4, value: 1 }]], vec![vec![], vec![]]);
+        login_as("bob.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+
+        assert_eq!(earned(&contract, "bob.near"), vec!["first_win", "flawless"]);
+        assert_eq!(earned(&contract, "alice.near"), vec![] as Vec<String>);
+    }
+
+    #[test]
+    fn rout_with_a_die_lost() {
+        let mut contract = Contract::default();
+        // 2 * (20 + 20 + 4) + 20 against 2 * 6, 48 points ahead
+        insert_game(&mut contract, vec![vec![Die { size: 20, value: 19 }], vec![Die { size: 4, value: 1 }]], vec![vec![20, 20], vec![6]]);
+        login_as("bob.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+
+        assert_eq!(earned(&contract, "bob.near"), vec!["first_win", "rout"]);
+    }
+
+    #[test]
+    fn resignation_is_not_flawless() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract, vec![vec![Die { size: 20, value: 19 }], vec![Die { size: 4, value: 1 }]], vec![vec![20, 20, 20], vec![]]);
+        login_as("bob.near");
+        contract.resign("1".to_string());
+
+        assert_eq!(earned(&contract, "alice.near"), vec!["first_win"]);
+    }
+
+    #[test]
+    fn veteran_after_100_games() {
+        let mut contract = Contract::default();
+        contract.records.insert(&"bob.near".to_string(), &PlayerRecord { draws: 98, ..Default::default() });
+        contract.records.insert(&"alice.near".to_string(), &PlayerRecord { draws: 99, ..Default::default() });
+        insert_game(&mut contract, vec![vec![Die { size: 20, value: 19 }], vec![Die { size: 4, value: 1 }]], vec![vec![], vec![]]);
+        login_as("alice.near");
+        contract.resign("1".to_string());
+
+        assert_eq!(earned(&contract, "alice.near"), vec!["veteran"]);
+        assert_eq!(earned(&contract, "bob.near"), vec!["first_win"]);
+    }
+
+    #[test]
+    fn unlocked_once() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract, vec![vec![Die { size: 20, value: 19 }], vec![Die { size: 4, value: 1 }]], vec![vec![], vec![]]);
+        login_as("alice.near");
+        contract.resign("1".to_string());
+        let first = contract.get_achievements("bob.near".parse().unwrap());
+
+        insert_game(&mut contract, vec![vec![Die { size: 20, value: 19 }], vec![Die { size: 4, value: 1 }]], vec![vec![], vec![]]);
+        login_as("alice.near");
+        contract.resign("1".to_string());
+        assert_eq!(contract.get_achievements("bob.near".parse().unwrap()), first);
+    }
+}
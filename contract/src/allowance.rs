@@ -0,0 +1,194 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Balance, Promise};
+
+use crate::*;
+
+/// Games an account created on a given day, counted against ContractConfig.free_games_per_day.
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DailyGames {
+    /// Days since the unix epoch, from the block timestamp
+    pub day: u64,
+    pub count: u32,
+}
+
+fn current_day() -> u64 {
+    env::block_timestamp() / DAY_NS
+}
+
+#[near_bindgen]
+impl Contract {
+    /// How many more games the account can create today without a fee.
+    pub fn get_free_games_left(&self, account_id: AccountId) -> u32 {
+        let created = match self.daily_games.get(&account_id.to_string()) {
+            Some(daily_games) if daily_games.day == current_day() => daily_games.count,
+            _ => 0,
+        };
+        self.config.free_games_per_day.saturating_sub(created)
+    }
+
+    pub fn set_game_creation_fee(&mut self, free_games_per_day: u32, fee: U128) -> () {
+        self.assert_owner("set the game creation fee");
+
+        self.config.free_games_per_day = free_games_per_day;
+        self.config.game_creation_fee = fee;
+    }
+
+    pub fn withdraw_treasury(&mut self, receiver_id: AccountId, amount: U128) -> () {
+        self.assert_owner("withdraw from the treasury");
+        if amount.0 > self.treasury {
+            fail(Error::InsufficientTreasury { treasury: self.treasury });
+        }
+
+        self.treasury -= amount.0;
+        self.audit("withdraw_treasury", format!("{} yoctoNEAR to {}", amount.0, receiver_id));
+        Promise::new(receiver_id).transfer(amount.0);
+    }
+
+    /// Counts a new game against the caller's daily allowance. Once it's used up the attached deposit
+    /// has to cover ContractConfig.game_creation_fee, which goes to the treasury.
+    /// Returns the fee taken, 0 while the allowance lasts.
+    pub(crate) fn charge_game_creation(&mut self) -> Balance {
+        let account_id = env::predecessor_account_id();
+        let fee = if self.get_free_games_left(account_id.clone()) > 0 { 0 } else { self.config.game_creation_fee.0 };
+        if env::attached_deposit() < fee {
+            fail(Error::DailyLimitReached { free_games: self.config.free_games_per_day, fee });
+        }
+
+        let day = current_day();
+        let mut daily_games = match self.daily_games.get(&account_id.to_string()) {
+            Some(daily_games) if daily_games.day == day => daily_games,
+            _ => DailyGames { day, count: 0 },
+        };
+        daily_games.count += 1;
+        self.daily_games.insert(&account_id.to_string(), &daily_games);
+
+        self.treasury += fee;
+        return fee;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_as, login_as, transfers};
+
+    const FEE: Balance = 10_000;
+
+    fn contract_with_fee() -> Contract {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_game_creation_fee(2, U128(FEE));
+        return contract;
+    }
+
+    #[test]
+    fn free_games_then_fee() {
+        let mut contract = contract_with_fee();
+        call_as("bob.near", DAY_NS, 0);
+        contract.create_game(None);
+        contract.create_game(None);
+        assert_eq!(contract.get_free_games_left("bob.near".parse().unwrap()), 0);
+
+        call_as("bob.near", DAY_NS, FEE);
+        contract.create_game(None);
+        assert_eq!(contract.treasury, FEE);
+
+        // Other accounts have their own allowance
+        call_as("carol.near", DAY_NS, 0);
+        contract.create_game(None);
+        assert_eq!(contract.treasury, FEE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Free daily limit of 2 games reached, attach 10000 yoctoNEAR to create a game")]
+    fn over_limit_without_fee() {
+        let mut contract = contract_with_fee();
+        call_as("bob.near", DAY_NS, 0);
+        contract.create_game(None);
+        contract.create_game(None);
+        contract.create_game(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attach exactly 0 yoctoNEAR to create a game")]
+    fn free_game_with_deposit() {
+        let mut contract = contract_with_fee();
+        call_as("bob.near", DAY_NS, FEE);
+        contract.create_game(None);
+    }
+
+    #[test]
+    fn allowance_resets_next_day() {
+        let mut contract = contract_with_fee();
+        call_as("bob.near", 2 * DAY_NS - 1, 0);
+        contract.create_game(None);
+        contract.create_game(None);
+
+        call_as("bob.near", 2 * DAY_NS, 0);
+        assert_eq!(contract.get_free_games_left("bob.near".parse().unwrap()), 2);
+        contract.create_game(None);
+        assert_eq!(contract.get_free_games_left("bob.near".parse().unwrap()), 1);
+        assert_eq!(contract.treasury, 0);
+    }
+
+    #[test]
+    fn join_game_stays_free() {
+        let mut contract = contract_with_fee();
+        call_as("bob.near", DAY_NS, 0);
+        contract.create_game(None);
+
+        call_as("carol.near", DAY_NS, 0);
+        contract.create_game(None);
+        contract.create_game(None);
+        contract.join_game("1".to_string(), None);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().status, GameStatus::Active);
+    }
+
+    #[test]
+    fn wagered_game_over_limit_pays_fee() {
+        let mut contract = contract_with_fee();
+        call_as("bob.near", DAY_NS, 0);
+        contract.create_game(None);
+        contract.create_game(None);
+
+        call_as("bob.near", DAY_NS, FEE + 500);
+        let game_id = contract.create_wagered_game(None);
+        assert_eq!(contract.get_wager(game_id).unwrap().stake, U128(500));
+        assert_eq!(contract.treasury, FEE);
+    }
+
+    #[test]
+    fn withdraw_treasury() {
+        let mut contract = contract_with_fee();
+        contract.treasury = 3 * FEE;
+
+        login_as("alice.near");
+        contract.withdraw_treasury("carol.near".parse().unwrap(), U128(FEE));
+        assert_eq!(contract.get_treasury(), U128(2 * FEE));
+
+        assert_eq!(transfers(), vec![("carol.near".to_string(), FEE)]);
+        assert_eq!(contract.get_audit_log(0, 10)[0].details, "10000 yoctoNEAR to carol.near");
+    }
+
+    #[test]
+    #[should_panic(expected = "Treasury only holds 10000 yoctoNEAR")]
+    fn withdraw_treasury_too_much() {
+        let mut contract = contract_with_fee();
+        contract.treasury = FEE;
+
+        contract.withdraw_treasury("carol.near".parse().unwrap(), U128(FEE + 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can withdraw from the treasury")]
+    fn withdraw_treasury_not_owner() {
+        let mut contract = contract_with_fee();
+        contract.treasury = FEE;
+
+        login_as("bob.near");
+        contract.withdraw_treasury("bob.near".parse().unwrap(), U128(FEE));
+    }
+}
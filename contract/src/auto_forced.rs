@@ -0,0 +1,187 @@
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Opts the caller in or out of having their forced moves played for them in the game: when it's their turn and
+    /// their only option is a single attack, or passing, the contract plays it as part of the opponent's move.
+    pub fn set_auto_forced(&mut self, game_id: String, enabled: bool) -> () {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+        let player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() }));
+        pauses::assert_running(&mut game);
+
+        game.auto_forced.resize(PLAYER_COUNT, false);
+        game.auto_forced[player_index] = enabled;
+        self.play_forced_moves(&mut game);
+        self.save_game(&game);
+    }
+}
+
+impl Contract {
+    /// The current player's only option, None when they have a choice. Some(None) is a forced pass.
+    /// Fire attacks aren't enumerated, so a player with a Fire button always has a choice.
+    fn forced_move(&self, game: &Game) -> Option<Option<AttackOption>> {
+        let player_index = game.current_player as usize;
+        if self.find_button(&game.buttons[player_index]).is_some_and(|button| button.skills.iter().any(|skill| skill == fire::FIRE_SKILL)) {
+            return None;
+        }
+
+        let mut options = attack_options(game);
+        match options.len() {
+            0 if self.is_pass_allowed(game) => Some(None),
+            1 => options.pop().map(Some),
+            _ => None,
+        }
+    }
+
+    /// Plays the forced moves of opted in players, tagging them as auto-played in the history, until someone has a choice.
+    /// A forced pass right after a pass is left to the player, nothing would change and it could go back and forth forever.
+    pub(crate) fn play_forced_moves(&mut self, game: &mut Game) {
+        while !game.is_finished() && game.auto_forced.get(game.current_player as usize) == Some(&true) {
+            match self.forced_move(game) {
+                Some(Some(option)) => { self.apply_attack(game, option.attacker_die_indices, option.defender_die_index, Some(option.attack_type)); },
+                Some(None) if game.history.iter().rev().find(|record| record.kind.is_move()).is_none_or(|record| record.kind != MoveKind::Pass) => apply_pass(game),
+                _ => return,
+            }
+            if let Some(record) = game.history.last_mut() {
+                record.auto = true;
+            }
+
+            if game.daily.is_some() {
+                self.play_daily_opponent(game);
+            }
+            if game.bot.is_some() {
+                self.play_bot(game);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::login_as;
+
+    fn moves(game: &Game) -> Vec<(u8, MoveKind, bool)> {
+        game.history.iter().map(|record| (record.player, record.kind, record.auto)).collect()
+    }
+
+    /// alice.near, opted in, against bob.near's d4 showing 1 with bob.near to move, who can only pass
+    fn auto_game(contract: &mut Contract, alice_dice: Vec<Die>) {
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            buttons: vec![DEFAULT_BUTTON.to_string(), DEFAULT_BUTTON.to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 1 }], alice_dice],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+        login_as("alice.near");
+        contract.set_auto_forced("1".to_string(), true);
+        login_as("bob.near");
+    }
+
+    #[test]
+    fn forced_attack_played() {
+        let mut contract = Contract::default();
+        auto_game(&mut contract, vec![Die { size: 20, value: 19 }]);
+
+        contract.pass("1".to_string(), None, None);
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(moves(&game), vec![(0, MoveKind::Pass, false), (1, MoveKind::Attack, true)]);
+        assert_eq!(game.captured[1], vec![4]);
+        assert_eq!(game.status, GameStatus::Finished);
+    }
+
+    #[test]
+    fn choice_left_to_player() {
+        let mut contract = Contract::default();
+        // Either die can take the d4
+        auto_game(&mut contract, vec![Die { size: 20, value: 19 }, Die { size: 6, value: 5 }]);
+
+        contract.pass("1".to_string(), None, None);
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(moves(&game), vec![(0, MoveKind::Pass, false)]);
+        assert_eq!(game.current_player, 1);
+    }
+
+    #[test]
+    fn not_opted_in() {
+        let mut contract = Contract::default();
+        auto_game(&mut contract, vec![Die { size: 20, value: 19 }]);
+        login_as("alice.near");
+        contract.set_auto_forced("1".to_string(), false);
+
+        login_as("bob.near");
+        contract.pass("1".to_string(), None, None);
+
+        assert_eq!(moves(&contract.games.get(&"1".to_string()).unwrap()), vec![(0, MoveKind::Pass, false)]);
+    }
+
+    #[test]
+    fn forced_pass_played_on_opting_in() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            buttons: vec![DEFAULT_BUTTON.to_string(), DEFAULT_BUTTON.to_string()],
+            current_player: 1,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 20 }], vec![Die { size: 4, value: 1 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        login_as("alice.near");
+        contract.set_auto_forced("1".to_string(), true);
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(moves(&game), vec![(1, MoveKind::Pass, true)]);
+        assert_eq!(game.current_player, 0);
+    }
+
+    #[test]
+    fn no_pass_after_pass() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            buttons: vec![DEFAULT_BUTTON.to_string(), DEFAULT_BUTTON.to_string()],
+            current_player: 1,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 20 }], vec![Die { size: 4, value: 1 }]],
+            captured: vec![vec![], vec![]],
+            history: vec![MoveRecord::new(0, MoveKind::Pass)],
+            ..Default::default()
+        });
+
+        login_as("alice.near");
+        contract.set_auto_forced("1".to_string(), true);
+
+        assert_eq!(moves(&contract.games.get(&"1".to_string()).unwrap()), vec![(0, MoveKind::Pass, false)]);
+    }
+
+    #[test]
+    fn fire_button_has_a_choice() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.register_button("Blaze".to_string(), vec![20], vec![fire::FIRE_SKILL.to_string()]);
+        auto_game(&mut contract, vec![Die { size: 20, value: 19 }]);
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        game.buttons[1] = "Blaze".to_string();
+        contract.games.insert(&"1".to_string(), &game);
+
+        contract.pass("1".to_string(), None, None);
+
+        assert_eq!(moves(&contract.games.get(&"1".to_string()).unwrap()), vec![(0, MoveKind::Pass, false)]);
+    }
+}
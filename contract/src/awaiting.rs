@@ -0,0 +1,215 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::*;
+
+/// A game waiting on an account, see Contract::get_games_awaiting_move
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AwaitingMove {
+    pub game_id: String,
+    pub opponent: String,
+    /// Left on the account's clock, None for games without one
+    pub time_left_ns: Option<u64>,
+    /// When the game started waiting on the account
+    pub waiting_since: u64,
+}
+
+pub(crate) fn awaiting_key(account_id: &str) -> Vec<u8> {
+    format!("await:{}", account_id).as_bytes().to_vec()
+}
+
+/// Ids of the games waiting on the account, in the order they started to
+fn get_awaiting(account_id: &str) -> Vec<String> {
+    match env::storage_read(&awaiting_key(account_id)) {
+        Some(awaiting_vec) => Vec::<String>::try_from_slice(&awaiting_vec).unwrap(),
+        None => vec![],
+    }
+}
+
+fn set_awaiting(account_id: &str, game_ids: &Vec<String>) {
+    if game_ids.is_empty() {
+        env::storage_remove(&awaiting_key(account_id));
+    } else {
+        env::storage_write(&awaiting_key(account_id), &game_ids.try_to_vec().unwrap());
+    }
+}
+
+/// Whether the game waits on the player: it's their turn, or the game hasn't started because they still have
+/// to accept or decline the auxiliary dice
+fn is_awaiting(game: &Game, player_index: usize) -> bool {
+    match game.status {
+        GameStatus::Active => game.current_player as usize == player_index,
+        GameStatus::Waiting => game.auxiliary_choices.get(player_index) == Some(&None),
+        _ => false,
+    }
+}
+
+/// Brings the index of every player of the game in line with whose move it is now.
+/// Only writes the players whose entry changed.
+pub(crate) fn sync_awaiting(game: &Game) {
+    for (player_index, player_id) in game.players.iter().enumerate() {
+        if player_id.is_empty() {
+            continue;
+        }
+        let mut game_ids = get_awaiting(player_id);
+        let listed = game_ids.contains(&game.id);
+        if is_awaiting(game, player_index) == listed {
+            continue;
+        }
+        if listed {
+            game_ids.retain(|id| id != &game.id);
+        } else {
+            game_ids.push(game.id.clone());
+        }
+        set_awaiting(player_id, &game_ids);
+    }
+}
+
+/// Drops the game from the index of an account that left it
+pub(crate) fn remove_awaiting(account_id: &str, game_id: &str) {
+    let mut game_ids = get_awaiting(account_id);
+    if game_ids.iter().any(|id| id == game_id) {
+        game_ids.retain(|id| id != game_id);
+        set_awaiting(account_id, &game_ids);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Every game waiting on the account, most urgent first: clocked games by the time left, then the others
+    /// by how long they've been waiting. Reads only those games, from an index kept up to date as turns change.
+    pub fn get_games_awaiting_move(&self, account_id: AccountId) -> Vec<AwaitingMove> {
+        let mut awaiting = get_awaiting(account_id.as_str()).into_iter()
+            .filter_map(|game_id| self.games.get(&game_id))
+            .filter_map(|game| {
+                let player_index = game.players.iter().position(|p| p == account_id.as_str())?;
+                Some(AwaitingMove {
+                    opponent: game.players[(player_index + 1) % 2].clone(),
+                    time_left_ns: game.time_control.map(|_| clocks::time_left(&game, player_index)),
+                    waiting_since: if game.status == GameStatus::Active { game.last_move_at } else { game.created_at },
+                    game_id: game.id,
+                })
+            })
+            .collect::<Vec<AwaitingMove>>();
+        awaiting.sort_by_key(|game| (game.time_left_ns.is_none(), game.time_left_ns, game.waiting_since));
+        awaiting
+    }
+}
+
+impl Contract {
+    /// Stores the game and keeps its players' awaiting index in line with it
+    pub(crate) fn save_game(&mut self, game: &Game) {
+        self.games.insert(&game.id, game);
+        sync_awaiting(game);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_at, login_as, request_path};
+
+    const HOUR_NS: u64 = 60 * 60 * 1_000_000_000;
+
+    fn awaiting_ids(contract: &Contract, account_id: &str) -> Vec<String> {
+        contract.get_games_awaiting_move(account_id.parse().unwrap()).into_iter().map(|game| game.game_id).collect()
+    }
+
+    /// bob.near to move, with d20s on both sides so every attack keeps the game going
+    fn active_game(contract: &mut Contract, game_id: &str, time_control: Option<TimeControl>) {
+        let game = Game {
+            id: game_id.to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 20 }; 3], vec![Die { size: 20, value: 1 }; 3]],
+            captured: vec![vec![], vec![]],
+            time_control,
+            remaining_ns: time_control.map(|time_control| vec![time_control.bank_ns; 2]).unwrap_or_default(),
+            last_move_at: env::block_timestamp(),
+            turn_started_at: env::block_timestamp(),
+            ..Default::default()
+        };
+        contract.save_game(&game);
+    }
+
+    #[test]
+    fn index_follows_the_turn() {
+        let mut contract = Contract::default();
+        call_at("bob.near", 0);
+        active_game(&mut contract, "1", None);
+        assert_eq!(awaiting_ids(&contract, "bob.near"), vec!["1"]);
+        assert_eq!(awaiting_ids(&contract, "alice.near"), Vec::<String>::new());
+
+        call_at("bob.near", 10);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        assert_eq!(awaiting_ids(&contract, "bob.near"), Vec::<String>::new());
+        assert_eq!(contract.get_games_awaiting_move("alice.near".parse().unwrap()), vec![AwaitingMove {
+            game_id: "1".to_string(),
+            opponent: "bob.near".to_string(),
+            time_left_ns: None,
+            waiting_since: 10,
+        }]);
+
+        call_at("alice.near", 20);
+        contract.resign("1".to_string());
+        assert_eq!(awaiting_ids(&contract, "alice.near"), Vec::<String>::new());
+        assert_eq!(env::storage_read(&awaiting_key("alice.near")), None);
+    }
+
+    #[test]
+    fn undo_gives_the_turn_back() {
+        let mut contract = Contract::default();
+        call_at("bob.near", 0);
+        active_game(&mut contract, "1", None);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        contract.request_undo("1".to_string());
+
+        call_at("alice.near", 0);
+        contract.approve_undo("1".to_string());
+        assert_eq!(awaiting_ids(&contract, "bob.near"), vec!["1"]);
+        assert_eq!(awaiting_ids(&contract, "alice.near"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn auxiliary_choice_awaits() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.create_game(None);
+        assert_eq!(awaiting_ids(&contract, "bob.near"), Vec::<String>::new());
+
+        login_as("alice.near");
+        contract.join_game("1".to_string(), Some("Trickster".to_string()));
+        assert_eq!(awaiting_ids(&contract, "bob.near"), vec!["1"]);
+        assert_eq!(awaiting_ids(&contract, "alice.near"), vec!["1"]);
+
+        contract.accept_auxiliary("1".to_string(), true);
+        assert_eq!(awaiting_ids(&contract, "alice.near"), Vec::<String>::new());
+        assert_eq!(awaiting_ids(&contract, "bob.near"), vec!["1"]);
+    }
+
+    #[test]
+    fn most_urgent_first() {
+        let mut contract = Contract::default();
+        call_at("bob.near", 0);
+        active_game(&mut contract, "1", None);
+        active_game(&mut contract, "2", Some(TimeControl { bank_ns: DAY_NS, increment_ns: 0, per_move: false }));
+        call_at("bob.near", 5);
+        active_game(&mut contract, "3", None);
+        active_game(&mut contract, "4", Some(TimeControl { bank_ns: HOUR_NS, increment_ns: 0, per_move: false }));
+
+        call_at("bob.near", 10);
+        assert_eq!(awaiting_ids(&contract, "bob.near"), vec!["4", "2", "1", "3"]);
+        assert_eq!(contract.get_games_awaiting_move("bob.near".parse().unwrap())[0].time_left_ns, Some(HOUR_NS - 5));
+
+        match contract.web4_get(request_path("/api/users/bob.near/awaiting")) {
+            Web4Response::Body { body, .. } => {
+                let awaiting: Vec<AwaitingMove> = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert_eq!(awaiting.into_iter().map(|game| game.game_id).collect::<Vec<String>>(), vec!["4", "2", "1", "3"]);
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::*;
+
+const MAX_BLOCKED: usize = 100;
+
+pub(crate) fn blocks_key(account_id: &str) -> Vec<u8> {
+    format!("block:{}", account_id).as_bytes().to_vec()
+}
+
+pub(crate) fn get_blocks(account_id: &str) -> Vec<String> {
+    match env::storage_read(&blocks_key(account_id)) {
+        Some(blocks_vec) => Vec::<String>::try_from_slice(&blocks_vec).unwrap(),
+        None => vec![],
+    }
+}
+
+fn set_blocks(account_id: &str, blocks: &Vec<String>) {
+    if blocks.is_empty() {
+        env::storage_remove(&blocks_key(account_id));
+    } else {
+        env::storage_write(&blocks_key(account_id), &blocks.try_to_vec().unwrap());
+    }
+}
+
+/// Whether blocker_id keeps account_id out of their games
+pub(crate) fn is_blocked(blocker_id: &str, account_id: &str) -> bool {
+    get_blocks(blocker_id).iter().any(|id| id == account_id)
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Keeps the account out of the caller's open games and apart from them in quick_match. Blocking an account twice is a no-op.
+    pub fn block_player(&mut self, account_id: AccountId) -> () {
+        let blocker_id = env::predecessor_account_id().to_string();
+        if blocker_id == account_id.to_string() {
+            fail(Error::SelfBlock);
+        }
+
+        let mut blocks = get_blocks(&blocker_id);
+        if blocks.contains(&account_id.to_string()) {
+            return;
+        }
+        if blocks.len() >= MAX_BLOCKED {
+            fail(Error::TooManyBlocked { max: MAX_BLOCKED });
+        }
+        blocks.push(account_id.to_string());
+        set_blocks(&blocker_id, &blocks);
+    }
+
+    pub fn unblock_player(&mut self, account_id: AccountId) -> () {
+        let blocker_id = env::predecessor_account_id().to_string();
+        let mut blocks = get_blocks(&blocker_id);
+        blocks.retain(|id| id != account_id.as_str());
+        set_blocks(&blocker_id, &blocks);
+    }
+
+    /// The caller's own block list, nobody else's is exposed
+    pub fn get_my_blocks(&self) -> Vec<String> {
+        get_blocks(env::predecessor_account_id().as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{login_as, request_path};
+
+    #[test]
+    fn block_and_unblock() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.block_player("carol.near".parse().unwrap());
+        contract.block_player("dave.near".parse().unwrap());
+        contract.block_player("carol.near".parse().unwrap());
+        assert_eq!(contract.get_my_blocks(), vec!["carol.near", "dave.near"]);
+
+        contract.unblock_player("carol.near".parse().unwrap());
+        assert_eq!(contract.get_my_blocks(), vec!["dave.near"]);
+        login_as("dave.near");
+        assert_eq!(contract.get_my_blocks(), Vec::<String>::new());
+
+        let mut request = request_path("/api/my/blocks");
+        request.account_id = Some("bob.near".to_string());
+        match contract.web4_get(request) {
+            Web4Response::Body { body, .. } => {
+                assert_eq!(String::from_utf8(body.into()).unwrap(), r#"["dave.near"]"#);
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is not open to carol.near")]
+    fn blocked_join() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.block_player("carol.near".parse().unwrap());
+        let game_id = contract.create_game(None);
+
+        login_as("carol.near");
+        contract.join_game(game_id, None);
+    }
+
+    #[test]
+    fn quick_match_skips_blocks_both_ways() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.block_player("carol.near".parse().unwrap());
+        let bob_game = contract.create_game(None);
+
+        // bob.near blocked carol.near
+        login_as("carol.near");
+        contract.block_player("dave.near".parse().unwrap());
+        let carol_game = contract.quick_match(None, None);
+        assert_ne!(carol_game, bob_game);
+
+        // dave.near blocked bob.near and carol.near blocked dave.near
+        login_as("dave.near");
+        contract.block_player("bob.near".parse().unwrap());
+        let dave_game = contract.quick_match(None, None);
+        assert!(dave_game != bob_game && dave_game != carol_game);
+
+        login_as("erin.near");
+        assert_eq!(contract.quick_match(None, None), bob_game);
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't block more than 100 accounts")]
+    fn block_limit() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        for i in 0..=MAX_BLOCKED {
+            contract.block_player(format!("player{}.near", i).parse().unwrap());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't block yourself")]
+    fn block_yourself() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.block_player("bob.near".parse().unwrap());
+    }
+}
@@ -0,0 +1,178 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+/// Seat of the bot in games against it. It never signs anything, the contract moves for it.
+pub const BOT_ACCOUNT: &str = "bot.buttonmen";
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Hint {
+    /// None to pass
+    pub attack: Option<AttackOption>,
+    /// Expected swing in half points: the attack's attack_swing less the opponent's best reply to it
+    pub value: i32,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Starts a game against the bot, which takes the second seat and replies to every move in the same call.
+    /// Both play DEFAULT_BUTTON, whose five dice keep the bot's search small. Nothing rides on it and it's not rated.
+    pub fn create_game_vs_bot(&mut self, difficulty: BotDifficulty) -> String {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let game_id = self.next_game_id();
+        let button = self.find_button(DEFAULT_BUTTON).unwrap_or_else(|| fail(Error::ButtonNotFound { button: DEFAULT_BUTTON.to_string() }));
+        let players = vec![player_id.clone(), BOT_ACCOUNT.to_string()];
+
+        let mut game = Game {
+            id: game_id.clone(),
+            dice: players.iter().map(|_| button.dice.iter().map(|size| Die { size: *size, value: 0 }).collect()).collect(),
+            captured: players.iter().map(|_| vec![]).collect(),
+            buttons: players.iter().map(|_| DEFAULT_BUTTON.to_string()).collect(),
+            players,
+            created_at: env::block_timestamp(),
+            unlisted: true,
+            bot: Some(difficulty),
+            ..Default::default()
+        };
+        add_user_game(player_id.clone(), game_id.clone());
+        self.start_round(&mut game, &player_id);
+        // The bot may have the initiative
+        if game.current_player == 1 {
+            self.play_bot(&mut game);
+            self.save_game(&game);
+        }
+
+        return game_id;
+    }
+
+    /// The Lookahead bot's move for the current player. Only for games against the bot and games created with hints,
+    /// which whoever joins agrees to.
+    pub fn get_hint(&self, game_id: String) -> Hint {
+        let game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        if game.bot.is_none() && !game.hints {
+            fail(Error::NoHint { game_id: game_id.to_string() });
+        }
+        assert_in_progress(&game);
+        if game.status != GameStatus::Active {
+            fail(Error::GameNotStarted { game_id: game_id.to_string() });
+        }
+
+        match policies::lookahead(attack_options(&game), &game) {
+            Some((attack, value)) => Hint { attack: Some(attack), value },
+            None => Hint { attack: None, value: 0 },
+        }
+    }
+
+    /// Makes the bot's move in a game against it, once the player has moved
+    pub(crate) fn play_bot(&mut self, game: &mut Game) {
+        let difficulty = match game.bot {
+            Some(difficulty) if !game.is_finished() => difficulty,
+            _ => return,
+        };
+        let mut seed = env::random_seed();
+        seed.extend(game.id.as_bytes());
+        seed.extend(game.roll_seq.to_le_bytes());
+        match policies::bot_move(game, difficulty, &mut Rng::new(&env::sha256(&seed))) {
+            Some((attackers, target)) => { self.apply_attack(game, attackers, target, None); },
+            None => apply_pass(game),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{login_as, request_path};
+
+    /// Plays the player's side with their first legal attack, or a pass, until the game ends
+    fn play_out(contract: &mut Contract, game_id: &str) -> Game {
+        for _ in 0..100 {
+            let game = contract.games.get(&game_id.to_string()).unwrap();
+            if game.is_finished() {
+                return game;
+            }
+            assert_eq!(game.current_player, 0, "The bot has to move right after the player");
+            let gas_before = env::used_gas();
+            match contract.get_move_options(game_id.to_string(), None).attacks.into_iter().next() {
+                Some(option) => { contract.attack(game_id.to_string(), option.attacker_die_indices, option.defender_die_index, None, None, None); },
+                None => contract.pass(game_id.to_string(), None, None),
+            }
+            // Player's move and the bot's reply, well within a call's 300 TGas
+            assert!(env::used_gas().0 - gas_before.0 < 50_000_000_000_000, "{:?}", env::used_gas());
+        }
+        panic!("Game {} didn't finish", game_id);
+    }
+
+    #[test]
+    fn full_game_vs_bot() {
+        for difficulty in [BotDifficulty::Random, BotDifficulty::Greedy, BotDifficulty::Lookahead] {
+            let mut contract = Contract::default();
+            login_as("bob.near");
+            let game_id = contract.create_game_vs_bot(difficulty);
+            let game = contract.games.get(&game_id).unwrap();
+            assert_eq!(game.players, vec!["bob.near".to_string(), BOT_ACCOUNT.to_string()]);
+            assert_eq!(game.current_player, 0);
+
+            let game = play_out(&mut contract, &game_id);
+            assert!(game.history.iter().any(|record| record.player == 1 && record.kind == MoveKind::Attack));
+            assert!(game.history.iter().any(|record| record.player == 0));
+
+            // Unrated and out of the lobby
+            assert_eq!(game.rating_deltas, Vec::<i32>::new());
+            assert_eq!(contract.get_rating("bob.near".parse().unwrap()), ratings::DEFAULT_RATING);
+            assert!(contract.get_leaderboard(0, 10).is_empty());
+            assert!(contract.get_open_games().is_empty());
+        }
+    }
+
+    fn hint_game(contract: &mut Contract, hints: bool) {
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 6, value: 6 }], vec![Die { size: 4, value: 4 }, Die { size: 20, value: 5 }]],
+            captured: vec![vec![], vec![]],
+            hints,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn hint_takes_best_capture() {
+        let mut contract = Contract::default();
+        hint_game(&mut contract, true);
+
+        // The d20 is worth 40 half points, the d6 gives up 5 to the re-roll and comes back at 3 where the d4 takes it for 9
+        let hint = Hint {
+            attack: Some(AttackOption { attacker_die_indices: vec![0], defender_die_index: 1, attack_type: AttackType::Power, evaluation: None }),
+            value: 26,
+        };
+        assert_eq!(contract.get_hint("1".to_string()), hint);
+        match contract.web4_get(request_path("/api/games/1/hint")) {
+            Web4Response::Body { body, status, .. } => {
+                assert_eq!(status, None);
+                assert_eq!(serde_json::from_slice::<Hint>(&Vec::<u8>::from(body)).unwrap(), hint);
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Hints are off in game 1")]
+    fn no_hints_in_rated_game() {
+        let mut contract = Contract::default();
+        hint_game(&mut contract, false);
+        assert_eq!(contract.web4_get(request_path("/api/games/1/hint")), Web4Response::Body {
+            content_type: "application/json".to_owned(),
+            body: r#"{"error":"no_hint"}"#.as_bytes().to_owned().into(),
+            status: Some(403),
+        });
+
+        contract.get_hint("1".to_string());
+    }
+}
@@ -0,0 +1,166 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::near_bindgen;
+
+use crate::*;
+
+/// Dice a registered button can have. Games search the subsets of a side's dice for skill attacks, so this keeps
+/// attack_options, and the forced moves, bots and hints built on it, cheap enough to run on every move.
+pub(crate) const MAX_BUTTON_DICE: usize = 10;
+
+/// Button as stored by register_button, or one of BUTTONS in the same shape
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ButtonDef {
+    pub name: String,
+    pub dice: Vec<u8>,
+    pub auxiliary: Vec<u8>,
+    pub skills: Vec<String>,
+}
+
+impl From<&Button> for ButtonDef {
+    fn from(button: &Button) -> Self {
+        Self {
+            name: button.name.to_string(),
+            dice: button.dice.to_vec(),
+            auxiliary: button.auxiliary.to_vec(),
+            skills: button.skills.iter().map(|skill| skill.to_string()).collect(),
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Adds a button players can pick in create_game and join_game, next to the built-in BUTTONS.
+    /// Names are taken for good: games refer to their buttons by name.
+    pub fn register_button(&mut self, name: String, dice: Vec<u8>, skills: Vec<String>) -> () {
+        self.assert_owner("register buttons");
+        if name.is_empty() {
+            fail(Error::EmptyButtonName);
+        }
+        if dice.is_empty() {
+            fail(Error::NoButtonDice);
+        }
+        if dice.len() > MAX_BUTTON_DICE {
+            fail(Error::TooManyDice { max: MAX_BUTTON_DICE });
+        }
+        if self.find_button(&name).is_some() {
+            fail(Error::ButtonExists { button: name.to_string() });
+        }
+        dice.iter().for_each(|size| assert_valid_die_size(*size));
+
+        self.buttons.insert(&name, &ButtonDef { name: name.clone(), dice, auxiliary: vec![], skills });
+        self.button_names.push(name.clone());
+        self.audit("register_button", name);
+    }
+
+    /// Built-in buttons followed by registered ones, in the order they were registered
+    pub fn get_buttons(&self) -> Vec<ButtonDef> {
+        BUTTONS.iter().map(ButtonDef::from)
+            .chain(self.button_names.iter().filter_map(|name| self.buttons.get(name)))
+            .collect()
+    }
+
+    pub(crate) fn find_button(&self, name: &str) -> Option<ButtonDef> {
+        match BUTTONS.iter().find(|button| button.name == name) {
+            Some(button) => Some(button.into()),
+            None => self.buttons.get(&name.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{login_as, request_path};
+
+    fn register_giant(contract: &mut Contract) {
+        login_as("alice.near");
+        contract.register_button("Giant".to_string(), vec![20, 20, 30, 30], vec!["Mighty".to_string()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid die size: d0")]
+    fn zero_size_die_rejected() {
+        login_as("alice.near");
+        Contract::default().register_button("Nothing".to_string(), vec![6, 0], vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid die size: d1")]
+    fn one_sided_die_rejected() {
+        login_as("alice.near");
+        Contract::default().register_button("Coin".to_string(), vec![1], vec![]);
+    }
+
+    #[test]
+    fn register_and_play() {
+        let mut contract = Contract::default();
+        register_giant(&mut contract);
+        assert_eq!(contract.find_button("Giant"), Some(ButtonDef {
+            name: "Giant".to_string(),
+            dice: vec![20, 20, 30, 30],
+            auxiliary: vec![],
+            skills: vec!["Mighty".to_string()],
+        }));
+
+        login_as("bob.near");
+        let game_id = contract.create_game(Some(CreateGameOptions { button: Some("Giant".to_string()), ..Default::default() }));
+        login_as("carol.near");
+        contract.join_game(game_id.clone(), Some("Giant".to_string()));
+
+        let game = contract.games.get(&game_id).unwrap();
+        assert_eq!(game.buttons, vec!["Giant".to_string(), "Giant".to_string()]);
+        for dice in game.dice.iter() {
+            assert_eq!(dice.iter().map(|die| die.size).collect::<Vec<u8>>(), vec![20, 20, 30, 30]);
+        }
+    }
+
+    #[test]
+    fn listed_after_built_in() {
+        let mut contract = Contract::default();
+        register_giant(&mut contract);
+
+        let names = contract.get_buttons().into_iter().map(|button| button.name).collect::<Vec<String>>();
+        assert_eq!(names, vec!["Standard", "Brawler", "Trickster", "Giant"]);
+        match contract.web4_get(request_path("/api/buttons")) {
+            Web4Response::Body { body, .. } => {
+                let buttons: Vec<ButtonDef> = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert_eq!(buttons, contract.get_buttons());
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid die size: d7")]
+    fn register_invalid_die_size() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.register_button("Odd".to_string(), vec![6, 7], vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "At most 10 dice a side")]
+    fn register_too_many_dice() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.register_button("Horde".to_string(), vec![4; MAX_BUTTON_DICE + 1], vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Button already exists: Standard")]
+    fn register_built_in_name() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.register_button("Standard".to_string(), vec![6], vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can register buttons")]
+    fn register_not_owner() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.register_button("Giant".to_string(), vec![20], vec![]);
+    }
+}
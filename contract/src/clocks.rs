@@ -0,0 +1,447 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen, Promise};
+
+use crate::*;
+
+const MINUTE_NS: u64 = 60 * 1_000_000_000;
+const HOUR_NS: u64 = 60 * MINUTE_NS;
+/// Bounds on a custom time control's bank, a shorter one couldn't fit a transaction and a longer one is no clock at all
+const MIN_BANK_NS: u64 = MINUTE_NS;
+const MAX_BANK_NS: u64 = 30 * DAY_NS;
+const MAX_INCREMENT_NS: u64 = DAY_NS;
+
+/// Chess-style time control: each player starts with bank_ns and gets increment_ns back after every move they make.
+/// The time since their turn started comes off their bank when they move.
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TimeControl {
+    pub bank_ns: u64,
+    pub increment_ns: u64,
+    /// The bank is filled back up to bank_ns after every move instead, giving each move the same time
+    #[serde(default)]
+    pub per_move: bool,
+}
+
+/// Named time controls offered when creating a game
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum TimePreset {
+    /// An hour, and 5 more minutes per move
+    Blitz,
+    /// A day for every move
+    Daily,
+    /// No clock
+    Casual,
+}
+
+impl TimePreset {
+    pub fn time_control(self) -> Option<TimeControl> {
+        match self {
+            TimePreset::Blitz => Some(TimeControl { bank_ns: HOUR_NS, increment_ns: 5 * MINUTE_NS, per_move: false }),
+            TimePreset::Daily => Some(TimeControl { bank_ns: DAY_NS, increment_ns: 0, per_move: true }),
+            TimePreset::Casual => None,
+        }
+    }
+}
+
+/// The clock a new game gets, from a preset or custom parameters within bounds
+pub(crate) fn resolve_time_control(preset: Option<TimePreset>, custom: Option<TimeControl>) -> Option<TimeControl> {
+    match (preset, custom) {
+        (Some(_), Some(_)) => fail(Error::PresetAndTimeControl),
+        (Some(preset), None) => preset.time_control(),
+        (None, Some(custom)) => {
+            if !(MIN_BANK_NS..=MAX_BANK_NS).contains(&custom.bank_ns) {
+                fail(Error::InvalidBank { min: MIN_BANK_NS, max: MAX_BANK_NS });
+            }
+            if custom.increment_ns > MAX_INCREMENT_NS {
+                fail(Error::InvalidIncrement { max: MAX_INCREMENT_NS });
+            }
+            Some(custom)
+        },
+        (None, None) => None,
+    }
+}
+
+/// Starts the first turn, and for games with a time control fills both banks
+pub(crate) fn start_clocks(game: &mut Game) {
+    if let Some(time_control) = game.time_control {
+        game.remaining_ns = vec![time_control.bank_ns; game.players.len()];
+    }
+    game.turn_started_at = env::block_timestamp();
+}
+
+/// Time the player has left right now, their bank less the time they've been on the clock this turn.
+/// The clock doesn't run while the game is paused.
+pub(crate) fn time_left(game: &Game, player_index: usize) -> u64 {
+    let remaining = game.remaining_ns[player_index];
+    if game.is_finished() || game.current_player as usize != player_index {
+        return remaining;
+    }
+    remaining.saturating_sub(on_clock(game))
+}
+
+/// Time the current player has been on the clock this turn, leaving out pauses
+pub(crate) fn on_clock(game: &Game) -> u64 {
+    env::block_timestamp().saturating_sub(game.turn_started_at).saturating_sub(pauses::paused_for(game))
+}
+
+/// Stops the current player's clock for the move they're making and starts the next turn's.
+/// A player whose time ran out can't move any more, the opponent can claim the win.
+/// Games without a time control only have the turn's start kept, for the time each move took.
+pub(crate) fn charge_clock(game: &mut Game) {
+    if let Some(time_control) = game.time_control {
+        let player_index = game.current_player as usize;
+        let left = time_left(game, player_index);
+        if left == 0 {
+            fail(Error::OutOfTime { player_id: game.players[player_index].to_string(), game_id: game.id.to_string() });
+        }
+        game.remaining_ns[player_index] = if time_control.per_move { time_control.bank_ns } else { left + time_control.increment_ns };
+        game.extension_requested = None;
+    }
+    game.turn_started_at = env::block_timestamp();
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Wins a game with a time control for the caller once the opponent's time has run out on their turn
+    pub fn claim_timeout_win(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+        let player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() }));
+        if game.current_player as usize == player_index {
+            fail(Error::OwnTurnTimeout);
+        }
+        pauses::assert_running(&mut game);
+
+        self.finish_on_time(&mut game);
+    }
+
+    /// Ends a game whose current player ran out of time, the same as their opponent claiming it, for anyone to call.
+    /// The caller gets ContractConfig.finalize_bounty from the treasury when it holds enough.
+    pub fn finalize_expired(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        if game.is_finished() {
+            fail(Error::AlreadyFinalized { game_id: game_id.to_string() });
+        }
+        assert_in_progress(&game);
+        pauses::assert_running(&mut game);
+
+        self.finish_on_time(&mut game);
+
+        let bounty = self.config.finalize_bounty.0;
+        if bounty > 0 && self.treasury >= bounty {
+            self.treasury -= bounty;
+            self.audit("finalize_bounty", format!("{} yoctoNEAR to {} for game {}", bounty, env::predecessor_account_id(), game_id));
+            Promise::new(env::predecessor_account_id()).transfer(bounty);
+        }
+    }
+
+    pub fn set_finalize_bounty(&mut self, bounty: U128) -> () {
+        self.assert_owner("set the finalize bounty");
+
+        self.config.finalize_bounty = bounty;
+    }
+
+    pub fn set_adjudicate_after(&mut self, adjudicate_after_ns: u64) -> () {
+        self.assert_owner("set the adjudication grace period");
+
+        self.config.adjudicate_after_ns = adjudicate_after_ns;
+    }
+}
+
+impl Contract {
+    /// Finishes the game as a loss for the current player, whose time has to have run out. When it ran out
+    /// ContractConfig.adjudicate_after_ns or longer ago, neither player kept up with the game and it's scored
+    /// as it stands instead.
+    fn finish_on_time(&mut self, game: &mut Game) {
+        if game.time_control.is_none() {
+            fail(Error::NoClock { game_id: game.id.to_string() });
+        }
+        let loser_index = game.current_player as usize;
+        if time_left(game, loser_index) > 0 {
+            fail(Error::TimeLeft { player_id: game.players[loser_index].to_string(), game_id: game.id.to_string() });
+        }
+
+        let overdue = on_clock(game) - game.remaining_ns[loser_index];
+        let adjudicate_after = self.config.adjudicate_after_ns;
+        game.last_move_at = env::block_timestamp();
+        game.remaining_ns[loser_index] = 0;
+        if adjudicate_after > 0 && overdue >= adjudicate_after {
+            finish_game(game, None, FinishReason::Adjudicated);
+        } else {
+            finish_game(game, Some((loser_index as u8 + 1) % 2), FinishReason::Timeout);
+        }
+        self.after_game_finished(game);
+        self.save_game(game);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_at, login_as, transfers};
+
+    const SECOND_NS: u64 = 1_000_000_000;
+
+    /// bob.near to move with 100 seconds a side and 10 more per move. bob.near's d20s can take alice.near's d4s,
+    /// which can only take a d20 that comes back showing 1.
+    fn clocked_game(contract: &mut Contract) {
+        call_at("bob.near", 0);
+        let mut game = Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![
+                vec![Die { size: 20, value: 20 }, Die { size: 20, value: 20 }],
+                vec![Die { size: 4, value: 1 }, Die { size: 4, value: 1 }, Die { size: 4, value: 1 }],
+            ],
+            captured: vec![vec![], vec![]],
+            time_control: Some(TimeControl { bank_ns: 100 * SECOND_NS, increment_ns: 10 * SECOND_NS, per_move: false }),
+            ..Default::default()
+        };
+        start_clocks(&mut game);
+        contract.games.insert(&"1".to_string(), &game);
+    }
+
+    #[test]
+    fn bank_accounting() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        call_at("bob.near", 30 * SECOND_NS);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.remaining_ns, vec![80 * SECOND_NS, 100 * SECOND_NS]);
+        assert_eq!(game.turn_started_at, 30 * SECOND_NS);
+
+        call_at("alice.near", 75 * SECOND_NS);
+        contract.pass("1".to_string(), None, None);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().remaining_ns, vec![80 * SECOND_NS, 65 * SECOND_NS]);
+
+        // bob.near's clock is running, alice.near's isn't
+        call_at("carol.near", 95 * SECOND_NS);
+        let view = contract.game_view(&contract.games.get(&"1".to_string()).unwrap(), None);
+        assert_eq!(view.remaining_ns, vec![60 * SECOND_NS, 65 * SECOND_NS]);
+
+        call_at("bob.near", 135 * SECOND_NS);
+        contract.attack("1".to_string(), vec![1], 0, None, None, None);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().remaining_ns, vec![30 * SECOND_NS, 65 * SECOND_NS]);
+    }
+
+    #[test]
+    fn per_move_refills() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        game.time_control = TimePreset::Daily.time_control();
+        start_clocks(&mut game);
+        contract.games.insert(&"1".to_string(), &game);
+
+        call_at("bob.near", 20 * HOUR_NS);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().remaining_ns, vec![DAY_NS, DAY_NS]);
+    }
+
+    #[test]
+    fn presets() {
+        assert_eq!(resolve_time_control(Some(TimePreset::Blitz), None), Some(TimeControl { bank_ns: HOUR_NS, increment_ns: 5 * MINUTE_NS, per_move: false }));
+        assert_eq!(resolve_time_control(Some(TimePreset::Daily), None), Some(TimeControl { bank_ns: DAY_NS, increment_ns: 0, per_move: true }));
+        assert_eq!(resolve_time_control(Some(TimePreset::Casual), None), None);
+        assert_eq!(resolve_time_control(None, None), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bank must be between 60000000000 and 2592000000000000 ns")]
+    fn custom_bank_too_long() {
+        resolve_time_control(None, Some(TimeControl { bank_ns: 365 * DAY_NS, increment_ns: 0, per_move: false }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Bank must be between 60000000000 and 2592000000000000 ns")]
+    fn custom_bank_too_short() {
+        resolve_time_control(None, Some(TimeControl { bank_ns: SECOND_NS, increment_ns: 0, per_move: false }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Increment can't be over 86400000000000 ns")]
+    fn custom_increment_too_long() {
+        resolve_time_control(None, Some(TimeControl { bank_ns: HOUR_NS, increment_ns: 2 * DAY_NS, per_move: false }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Pick a time preset or a custom time control, not both")]
+    fn preset_and_custom() {
+        resolve_time_control(Some(TimePreset::Blitz), Some(TimeControl { bank_ns: HOUR_NS, increment_ns: 0, per_move: false }));
+    }
+
+    #[test]
+    fn claim_at_zero() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        call_at("alice.near", 100 * SECOND_NS);
+        contract.claim_timeout_win("1".to_string());
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, Some(1));
+        assert_eq!(game.finish_reason, Some(FinishReason::Timeout));
+        assert_eq!(contract.get_record("alice.near".parse().unwrap()).wins.timeout, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "bob.near still has time left in game 1")]
+    fn claim_too_early() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        call_at("alice.near", 100 * SECOND_NS - 1);
+        contract.claim_timeout_win("1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "bob.near ran out of time in game 1")]
+    fn no_move_after_time_runs_out() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        call_at("bob.near", 100 * SECOND_NS);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't claim a timeout on your own turn")]
+    fn claim_own_turn() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        call_at("bob.near", 200 * SECOND_NS);
+        contract.claim_timeout_win("1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 has no clock")]
+    fn claim_without_clock() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        game.time_control = None;
+        contract.games.insert(&"1".to_string(), &game);
+
+        call_at("alice.near", 200 * SECOND_NS);
+        contract.claim_timeout_win("1".to_string());
+    }
+
+    #[test]
+    fn finalized_by_anyone() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_finalize_bounty(U128(1_000));
+        contract.treasury = 1_500;
+        clocked_game(&mut contract);
+
+        call_at("carol.near", 100 * SECOND_NS);
+        contract.finalize_expired("1".to_string());
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!((game.status, game.winner, game.finish_reason), (GameStatus::Finished, Some(1), Some(FinishReason::Timeout)));
+        assert_eq!(transfers(), vec![("carol.near".to_string(), 1_000)]);
+        assert_eq!(contract.treasury, 500);
+    }
+
+    #[test]
+    fn no_bounty_from_short_treasury() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_finalize_bounty(U128(1_000));
+        contract.treasury = 999;
+        clocked_game(&mut contract);
+
+        call_at("carol.near", 100 * SECOND_NS);
+        contract.finalize_expired("1".to_string());
+
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().status, GameStatus::Finished);
+        assert_eq!(transfers(), vec![]);
+        assert_eq!(contract.treasury, 999);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is already finalized")]
+    fn finalized_twice() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        call_at("carol.near", 100 * SECOND_NS);
+        contract.finalize_expired("1".to_string());
+        call_at("dave.near", 100 * SECOND_NS);
+        contract.finalize_expired("1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is already finalized")]
+    fn finalized_after_claim() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        call_at("alice.near", 100 * SECOND_NS);
+        contract.claim_timeout_win("1".to_string());
+        call_at("carol.near", 100 * SECOND_NS);
+        contract.finalize_expired("1".to_string());
+    }
+
+    #[test]
+    fn timeout_within_grace() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        call_at("carol.near", 100 * SECOND_NS + 30 * DAY_NS - 1);
+        contract.finalize_expired("1".to_string());
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!((game.winner, game.finish_reason), (Some(1), Some(FinishReason::Timeout)));
+    }
+
+    #[test]
+    fn adjudicated_after_grace() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        // Nobody ended the game for 30 days after bob.near ran out of time, and bob.near is ahead on the board
+        call_at("carol.near", 100 * SECOND_NS + 30 * DAY_NS);
+        contract.finalize_expired("1".to_string());
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!((game.status, game.winner, game.finish_reason), (GameStatus::Finished, Some(0), Some(FinishReason::Adjudicated)));
+        assert_eq!(contract.get_record("bob.near".parse().unwrap()).wins.adjudicated, 1);
+        assert_eq!(contract.get_record("alice.near".parse().unwrap()).losses.adjudicated, 1);
+    }
+
+    #[test]
+    fn adjudication_off() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_adjudicate_after(0);
+        clocked_game(&mut contract);
+
+        call_at("alice.near", 365 * DAY_NS);
+        contract.claim_timeout_win("1".to_string());
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!((game.winner, game.finish_reason), (Some(1), Some(FinishReason::Timeout)));
+    }
+
+    #[test]
+    #[should_panic(expected = "bob.near still has time left in game 1")]
+    fn finalized_too_early() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        call_at("carol.near", 100 * SECOND_NS - 1);
+        contract.finalize_expired("1".to_string());
+    }
+}
@@ -0,0 +1,313 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::*;
+
+const MAX_CLUB_MEMBERS: usize = 50;
+const MAX_CLUB_NAME_LENGTH: usize = 32;
+
+/// A member's results in rated games finished since they joined the club
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ClubMember {
+    pub account_id: String,
+    pub joined_at: u64,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Club {
+    pub id: String,
+    pub name: String,
+    pub created_at: u64,
+    pub members: Vec<ClubMember>,
+}
+
+impl Club {
+    fn wins(&self) -> u32 {
+        self.members.iter().map(|member| member.wins).sum()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ClubEntry {
+    pub club_id: String,
+    pub wins: u32,
+}
+
+/// Club with its standing: current members' results added up and their average current rating
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ClubView {
+    pub id: String,
+    pub name: String,
+    pub members: Vec<ClubMember>,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub average_rating: u32,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Creates a club with the caller as its first member
+    pub fn create_club(&mut self, name: String) -> String {
+        self.assert_not_paused();
+        if name.is_empty() {
+            fail(Error::EmptyClubName);
+        }
+        if name.len() > MAX_CLUB_NAME_LENGTH {
+            fail(Error::ClubNameTooLong { max: MAX_CLUB_NAME_LENGTH });
+        }
+        let account_id = env::predecessor_account_id().to_string();
+        self.assert_not_in_club(&account_id);
+
+        self.last_club_id += 1;
+        let club_id = self.last_club_id.to_string();
+        let club = Club {
+            id: club_id.clone(),
+            name,
+            created_at: env::block_timestamp(),
+            members: vec![new_member(&account_id)],
+        };
+        self.clubs.insert(&club_id, &club);
+        self.club_memberships.insert(&account_id, &club_id);
+        self.update_club_leaderboard(&club);
+        return club_id;
+    }
+
+    pub fn join_club(&mut self, club_id: String) -> () {
+        self.assert_not_paused();
+        let account_id = env::predecessor_account_id().to_string();
+        self.assert_not_in_club(&account_id);
+        let mut club = self.clubs.get(&club_id).unwrap_or_else(|| fail(Error::ClubNotFound { club_id: club_id.to_string() }));
+        if club.members.len() >= MAX_CLUB_MEMBERS {
+            fail(Error::ClubFull { club_id: club_id.to_string() });
+        }
+
+        club.members.push(new_member(&account_id));
+        self.clubs.insert(&club_id, &club);
+        self.club_memberships.insert(&account_id, &club_id);
+    }
+
+    /// Leaves the caller's club, taking their results out of its standing. The last member out closes the club.
+    pub fn leave_club(&mut self) -> () {
+        let account_id = env::predecessor_account_id().to_string();
+        if !self.club_memberships.contains_key(&account_id) {
+            fail(Error::NotInClub { account_id: account_id.to_string() });
+        }
+        self.remove_from_club(&account_id);
+    }
+
+    pub fn get_club(&self, club_id: String) -> Option<ClubView> {
+        self.clubs.get(&club_id).map(|club| self.club_view(club))
+    }
+
+    pub fn get_club_of(&self, account_id: AccountId) -> Option<String> {
+        self.club_memberships.get(&account_id.to_string())
+    }
+
+    /// Clubs with the most wins, best first
+    pub fn get_club_leaderboard(&self, limit: u64) -> Vec<ClubView> {
+        self.club_leaderboard.iter()
+            .take(limit as usize)
+            .filter_map(|entry| self.get_club(entry.club_id.clone()))
+            .collect()
+    }
+
+    /// Counts a rated game's result for players who are in a club
+    pub(crate) fn update_club_results(&mut self, game: &Game) {
+        for (player_index, player_id) in game.players.iter().enumerate() {
+            let mut club = match self.club_memberships.get(player_id).and_then(|club_id| self.clubs.get(&club_id)) {
+                Some(club) => club,
+                None => continue,
+            };
+            let member = match club.members.iter_mut().find(|member| &member.account_id == player_id) {
+                Some(member) => member,
+                None => continue,
+            };
+            match game.winner {
+                Some(winner) if winner as usize == player_index => member.wins += 1,
+                Some(_) => member.losses += 1,
+                None => member.draws += 1,
+            }
+            self.clubs.insert(&club.id, &club);
+            self.update_club_leaderboard(&club);
+        }
+    }
+
+    pub(crate) fn remove_from_club(&mut self, account_id: &str) {
+        let club_id = match self.club_memberships.remove(&account_id.to_string()) {
+            Some(club_id) => club_id,
+            None => return,
+        };
+        let mut club = match self.clubs.get(&club_id) {
+            Some(club) => club,
+            None => return,
+        };
+        club.members.retain(|member| member.account_id != account_id);
+        if club.members.is_empty() {
+            self.clubs.remove(&club_id);
+            self.club_leaderboard.retain(|entry| entry.club_id != club_id);
+        } else {
+            self.clubs.insert(&club_id, &club);
+            self.update_club_leaderboard(&club);
+        }
+    }
+
+    fn assert_not_in_club(&self, account_id: &str) {
+        if let Some(club_id) = self.club_memberships.get(&account_id.to_string()) {
+            fail(Error::AlreadyInClub { account_id: account_id.to_string(), club_id });
+        }
+    }
+
+    fn club_view(&self, club: Club) -> ClubView {
+        let ratings = club.members.iter().map(|member| self.rating_of(&member.account_id) as u64).sum::<u64>();
+        ClubView {
+            wins: club.wins(),
+            losses: club.members.iter().map(|member| member.losses).sum(),
+            draws: club.members.iter().map(|member| member.draws).sum(),
+            average_rating: (ratings / club.members.len() as u64) as u32,
+            id: club.id,
+            name: club.name,
+            members: club.members,
+        }
+    }
+
+    /// Same rules as update_leaderboard, clubs ranked by their members' wins
+    fn update_club_leaderboard(&mut self, club: &Club) {
+        let size = self.config.leaderboard_size as usize;
+        let wins = club.wins();
+
+        match self.club_leaderboard.iter().position(|entry| entry.club_id == club.id) {
+            Some(index) => self.club_leaderboard[index].wins = wins,
+            None => {
+                if self.club_leaderboard.len() >= size && self.club_leaderboard.last().is_none_or(|lowest| wins <= lowest.wins) {
+                    return;
+                }
+                self.club_leaderboard.push(ClubEntry { club_id: club.id.clone(), wins });
+            },
+        }
+        // Stable, so a club that got to a tally first stays ahead
+        self.club_leaderboard.sort_by_key(|entry| std::cmp::Reverse(entry.wins));
+        self.club_leaderboard.truncate(size);
+    }
+}
+
+fn new_member(account_id: &str) -> ClubMember {
+    ClubMember { account_id: account_id.to_string(), joined_at: env::block_timestamp(), wins: 0, losses: 0, draws: 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{login_as, request_path};
+
+    fn play(contract: &mut Contract, winner: &str, loser: &str) {
+        login_as(winner);
+        let game_id = contract.create_game(None);
+        login_as(loser);
+        contract.join_game(game_id.clone(), None);
+        contract.resign(game_id);
+    }
+
+    fn two_clubs(contract: &mut Contract) -> (String, String) {
+        login_as("bob.near");
+        let red = contract.create_club("Red".to_string());
+        login_as("carol.near");
+        contract.join_club(red.clone());
+        login_as("dave.near");
+        let blue = contract.create_club("Blue".to_string());
+        login_as("erin.near");
+        contract.join_club(blue.clone());
+        (red, blue)
+    }
+
+    fn standing(contract: &Contract, club_id: &str) -> (u32, u32, u32, u32) {
+        let club = contract.get_club(club_id.to_string()).unwrap();
+        (club.wins, club.losses, club.draws, club.average_rating)
+    }
+
+    #[test]
+    fn cross_club_standings() {
+        let mut contract = Contract::default();
+        // Results from before joining don't count
+        play(&mut contract, "erin.near", "frank.near");
+        let (red, blue) = two_clubs(&mut contract);
+
+        play(&mut contract, "bob.near", "dave.near");
+        play(&mut contract, "carol.near", "erin.near");
+        play(&mut contract, "erin.near", "bob.near");
+
+        // bob.near ends on 1499, carol.near on 1517, dave.near on 1484 and erin.near on 1516
+        assert_eq!(standing(&contract, &red), (2, 1, 0, 1508));
+        assert_eq!(standing(&contract, &blue), (1, 2, 0, 1500));
+        assert_eq!(contract.get_club_leaderboard(10).into_iter().map(|club| club.name).collect::<Vec<String>>(), vec!["Red", "Blue"]);
+        assert_eq!(contract.get_club_leaderboard(1).len(), 1);
+
+        match contract.web4_get(request_path(&format!("/api/clubs/{}", blue))) {
+            Web4Response::Body { body, .. } => {
+                let club: ClubView = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert_eq!(club, contract.get_club(blue.clone()).unwrap());
+            },
+            _ => panic!("Unexpected response"),
+        }
+        match contract.web4_get(request_path("/api/clubs")) {
+            Web4Response::Body { body, .. } => {
+                let clubs: Vec<ClubView> = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert_eq!(clubs, contract.get_club_leaderboard(10));
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    fn leaving_takes_results_along() {
+        let mut contract = Contract::default();
+        let (red, blue) = two_clubs(&mut contract);
+        play(&mut contract, "bob.near", "dave.near");
+        play(&mut contract, "bob.near", "erin.near");
+
+        login_as("bob.near");
+        contract.leave_club();
+        assert_eq!(contract.get_club_of("bob.near".parse().unwrap()), None);
+        assert_eq!(standing(&contract, &red).0, 0);
+
+        // The last member out closes the club
+        login_as("carol.near");
+        contract.leave_club();
+        assert_eq!(contract.get_club(red), None);
+        assert_eq!(contract.get_club_leaderboard(10).into_iter().map(|club| club.id).collect::<Vec<String>>(), vec![blue.clone()]);
+
+        login_as("bob.near");
+        contract.join_club(blue.clone());
+        assert_eq!(contract.get_club_of("bob.near".parse().unwrap()), Some(blue));
+    }
+
+    #[test]
+    #[should_panic(expected = "bob.near is already in club 1")]
+    fn one_club_per_account() {
+        let mut contract = Contract::default();
+        two_clubs(&mut contract);
+
+        login_as("bob.near");
+        contract.join_club("2".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Club 1 is full")]
+    fn club_full() {
+        let mut contract = Contract::default();
+        login_as("member0.near");
+        let club_id = contract.create_club("Crowd".to_string());
+        for index in 1..=MAX_CLUB_MEMBERS {
+            login_as(&format!("member{}.near", index));
+            contract.join_club(club_id.clone());
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DailyScore {
+    pub account_id: String,
+    /// Points the player ended the game with, captured dice at full size and own remaining dice at half
+    pub score: u32,
+    pub game_id: String,
+}
+
+/// `YYYY-MM-DD` of a block timestamp in UTC
+pub(crate) fn utc_date(timestamp: u64) -> String {
+    let (year, month, day) = civil_date(timestamp);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Year, month and day of the timestamp, in UTC
+pub(crate) fn civil_date(timestamp: u64) -> (i64, i64, i64) {
+    // Civil date from days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = (timestamp / DAY_NS) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// Round seed of the day's challenge, the same for everyone who plays it
+fn daily_seed(date: &str) -> Vec<u8> {
+    env::sha256(format!("daily:{}", date).as_bytes())
+}
+
+fn daily_attempt_key(date: &str, account_id: &str) -> String {
+    format!("{}:{}", date, account_id)
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Starts today's (UTC) challenge for the caller against the contract. The starting position comes from the date,
+    /// so everyone plays the same one, and the caller always moves first. One attempt per account per day.
+    pub fn start_daily(&mut self) -> String {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let date = utc_date(env::block_timestamp());
+        let attempt_key = daily_attempt_key(&date, &player_id);
+        if self.daily_attempts.contains_key(&attempt_key) {
+            fail(Error::DailyPlayed { player_id: player_id.to_string(), date: date.to_string() });
+        }
+
+        let game_id = self.next_game_id();
+        let button = self.find_button(DEFAULT_BUTTON).unwrap_or_else(|| fail(Error::ButtonNotFound { button: DEFAULT_BUTTON.to_string() }));
+        let round_seed = daily_seed(&date);
+        let players = vec![player_id.clone(), env::current_account_id().to_string()];
+        let sizes = players.iter().map(|_| button.dice.iter().map(|size| Die { size: *size, value: 0 }).collect()).collect::<Vec<Vec<Die>>>();
+
+        let game = Game {
+            id: game_id.clone(),
+            dice: roll_round_dice(&round_seed, &sizes),
+            captured: players.iter().map(|_| vec![]).collect(),
+            buttons: players.iter().map(|_| DEFAULT_BUTTON.to_string()).collect(),
+            players,
+            current_player: 0,
+            round_seed,
+            status: GameStatus::Active,
+            created_at: env::block_timestamp(),
+            last_move_at: env::block_timestamp(),
+            daily: Some(date),
+            ..Default::default()
+        };
+        self.save_game(&game);
+        self.daily_attempts.insert(&attempt_key, &game_id);
+        add_user_game(player_id, game_id.clone());
+
+        return game_id;
+    }
+
+    /// Scores of the day's challenge, best first, ties in the order they were set
+    pub fn get_daily_leaderboard(&self, date: String) -> Vec<DailyScore> {
+        self.daily_leaderboards.get(&date).unwrap_or_default()
+    }
+
+    /// Makes the contract's move in a daily challenge, once the player has moved. It captures the biggest die it can.
+    pub(crate) fn play_daily_opponent(&mut self, game: &mut Game) {
+        if game.is_finished() {
+            return;
+        }
+        match policies::biggest_capture(game) {
+            Some((attackers, target)) => { self.apply_attack(game, attackers, target, None); },
+            None => apply_pass(game),
+        }
+    }
+
+    pub(crate) fn record_daily_score(&mut self, game: &Game) {
+        let date = match &game.daily {
+            Some(date) => date.clone(),
+            None => return,
+        };
+        let mut leaderboard = self.get_daily_leaderboard(date.clone());
+        leaderboard.push(DailyScore {
+            account_id: game.players[0].clone(),
+            score: score(game, 0) / 2,
+            game_id: game.id.clone(),
+        });
+        leaderboard.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        leaderboard.truncate(self.config.leaderboard_size as usize);
+        self.daily_leaderboards.insert(&date, &leaderboard);
+    }
+
+    /// Replaces the account on the leaderboard of the daily challenge the game was, and forgets the attempt
+    pub(crate) fn anonymize_daily(&mut self, game: &Game, account_id: &str, replacement: &str) {
+        let date = match &game.daily {
+            Some(date) => date.clone(),
+            None => return,
+        };
+        self.daily_attempts.remove(&daily_attempt_key(&date, account_id));
+        let mut leaderboard = self.get_daily_leaderboard(date.clone());
+        leaderboard.iter_mut().filter(|entry| entry.account_id == account_id).for_each(|entry| entry.account_id = replacement.to_string());
+        self.daily_leaderboards.insert(&date, &leaderboard);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_at, request_path};
+
+    /// 2026-10-17 00:00 UTC
+    const OCT_17: u64 = 20_743 * DAY_NS;
+
+    fn set_dice(contract: &mut Contract, game_id: &str, dice: Vec<Vec<Die>>) {
+        let mut game = contract.games.get(&game_id.to_string()).unwrap();
+        game.dice = dice;
+        contract.games.insert(&game_id.to_string(), &game);
+    }
+
+    #[test]
+    fn utc_dates() {
+        assert_eq!(utc_date(0), "1970-01-01");
+        assert_eq!(utc_date(OCT_17), "2026-10-17");
+        assert_eq!(utc_date(OCT_17 - 1), "2026-10-16");
+        assert_eq!(utc_date(11_016 * DAY_NS), "2000-02-29");
+    }
+
+    #[test]
+    fn same_position_for_everyone() {
+        let mut contract = Contract::default();
+        call_at("bob.near", OCT_17 + 3600 * 1_000_000_000);
+        let bob_game = contract.start_daily();
+        let bob_game = contract.games.get(&bob_game).unwrap();
+        call_at("carol.near", OCT_17 + DAY_NS - 1);
+        let carol_game = contract.start_daily();
+        let carol_game = contract.games.get(&carol_game).unwrap();
+
+        assert_eq!(bob_game.daily, Some("2026-10-17".to_string()));
+        assert_eq!(bob_game.players, vec!["bob.near".to_string(), "alice.near".to_string()]);
+        assert_eq!(bob_game.current_player, 0);
+        assert_eq!(bob_game.round_seed, env::sha256(b"daily:2026-10-17"));
+        assert_eq!(bob_game.dice, vec![
+            vec![Die { size: 4, value: 3 }, Die { size: 6, value: 3 }, Die { size: 8, value: 4 }, Die { size: 10, value: 10 }, Die { size: 20, value: 6 }],
+            vec![Die { size: 4, value: 2 }, Die { size: 6, value: 2 }, Die { size: 8, value: 5 }, Die { size: 10, value: 2 }, Die { size: 20, value: 11 }]]);
+        assert_eq!(carol_game.dice, bob_game.dice);
+
+        // The next day is another position
+        call_at("bob.near", OCT_17 + DAY_NS);
+        let next_game = contract.start_daily();
+        let next_game = contract.games.get(&next_game).unwrap();
+        assert_eq!(next_game.daily, Some("2026-10-18".to_string()));
+        assert_ne!(next_game.dice, bob_game.dice);
+    }
+
+    #[test]
+    #[should_panic(expected = "bob.near already played the daily challenge for 2026-10-17")]
+    fn one_attempt_a_day() {
+        let mut contract = Contract::default();
+        call_at("bob.near", OCT_17);
+        contract.start_daily();
+        call_at("bob.near", OCT_17 + DAY_NS - 1);
+        contract.start_daily();
+    }
+
+    #[test]
+    fn opponent_replies() {
+        let mut contract = Contract::default();
+        call_at("bob.near", OCT_17);
+        let game_id = contract.start_daily();
+        set_dice(&mut contract, &game_id, vec![
+            vec![Die { size: 4, value: 4 }, Die { size: 20, value: 3 }],
+            vec![Die { size: 6, value: 2 }, Die { size: 12, value: 7 }, Die { size: 30, value: 1 }]]);
+
+        contract.attack(game_id.clone(), vec![0], 0, None, None, None);
+        let game = contract.games.get(&game_id).unwrap();
+        // Its d12 power attacks the d20 rather than the d4
+        assert_eq!(game.history.len(), 2);
+        assert_eq!(game.history[1].attacker_die_indices, vec![0]);
+        assert_eq!(game.captured, vec![vec![6], vec![20]]);
+        assert_eq!(game.current_player, 0);
+        // Nothing rides on it, and nobody is notified
+        assert_eq!(contract.get_rating("bob.near".parse().unwrap()), ratings::DEFAULT_RATING);
+        assert!(contract.get_notifications("bob.near".to_string(), 0, 10).is_empty());
+    }
+
+    #[test]
+    fn opponent_skill_attacks_and_passes() {
+        let mut contract = Contract::default();
+        call_at("bob.near", OCT_17);
+        let game_id = contract.start_daily();
+        set_dice(&mut contract, &game_id, vec![
+            vec![Die { size: 4, value: 1 }, Die { size: 20, value: 9 }],
+            vec![Die { size: 4, value: 1 }, Die { size: 6, value: 4 }, Die { size: 8, value: 5 }]]);
+
+        contract.attack(game_id.clone(), vec![0], 0, None, None, None);
+        let game = contract.games.get(&game_id).unwrap();
+        // 4 + 5 takes the d20
+        assert_eq!(game.history[1].attacker_die_indices, vec![0, 1]);
+        assert_eq!(game.captured[1], vec![20]);
+
+        set_dice(&mut contract, &game_id, vec![
+            vec![Die { size: 4, value: 1 }],
+            vec![Die { size: 6, value: 4 }, Die { size: 8, value: 5 }]]);
+        contract.pass(game_id.clone(), None, None);
+        let game = contract.games.get(&game_id).unwrap();
+        assert_eq!(game.history.iter().map(|record| record.kind).collect::<Vec<MoveKind>>(), vec![MoveKind::Attack, MoveKind::Attack, MoveKind::Pass, MoveKind::Attack]);
+    }
+
+    #[test]
+    fn score_on_leaderboard() {
+        let mut contract = Contract::default();
+        call_at("bob.near", OCT_17);
+        let game_id = contract.start_daily();
+        set_dice(&mut contract, &game_id, vec![
+            vec![Die { size: 4, value: 1 }, Die { size: 20, value: 19 }],
+            vec![Die { size: 10, value: 2 }]]);
+        contract.attack(game_id.clone(), vec![1], 0, None, None, None);
+
+        let game = contract.games.get(&game_id).unwrap();
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, Some(0));
+        let leaderboard = contract.get_daily_leaderboard("2026-10-17".to_string());
+        // 10 captured, 2 and 10 for the d4 and d20 still in play
+        assert_eq!(leaderboard, vec![DailyScore { account_id: "bob.near".to_string(), score: 22, game_id: game_id.clone() }]);
+
+        call_at("carol.near", OCT_17);
+        let resigned_id = contract.start_daily();
+        contract.resign(resigned_id.clone());
+        // Resigning keeps the dice in play at half, enough to come out ahead
+        assert_eq!(contract.get_daily_leaderboard("2026-10-17".to_string()), vec![
+            DailyScore { account_id: "carol.near".to_string(), score: 24, game_id: resigned_id },
+            DailyScore { account_id: "bob.near".to_string(), score: 22, game_id },
+        ]);
+
+        for path in ["/api/daily", "/api/daily/2026-10-17"] {
+            match contract.web4_get(request_path(path)) {
+                Web4Response::Body { content_type, body, .. } => {
+                    assert_eq!(content_type, "application/json".to_owned());
+                    assert_eq!(String::from_utf8(body.into()).unwrap(), serde_json::to_string(&serde_json::json!({
+                        "date": "2026-10-17",
+                        "leaderboard": contract.get_daily_leaderboard("2026-10-17".to_string()),
+                    })).unwrap());
+                },
+                _ => panic!("Unexpected response"),
+            }
+        }
+    }
+}
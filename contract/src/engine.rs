@@ -0,0 +1,571 @@
+//! Rules of the game on their own: legal attacks, resolving them, initiative, turns and scoring.
+//! Nothing here touches the blockchain, randomness comes in through DiceRng, so the rules can be tested natively
+//! and run off-chain, e.g. to validate moves client-side. Contract methods handle storage, auth and seeding around it.
+
+use near_rng::Rng;
+
+use crate::errors::{fail, Error};
+use crate::{AttackOption, AttackType, AttackView, Die, Game};
+
+/// Die sizes the game supports. Every die is checked against this when rolled.
+pub const ALLOWED_DIE_SIZES: &[u8] = &[4, 6, 8, 10, 12, 20, 30];
+
+/// Dice attack_options lists every skill attack for, see there
+const SKILL_SUBSET_DICE: usize = 12;
+
+/// Source of die rolls
+pub trait DiceRng {
+    /// A value in 1..=size
+    fn roll(&mut self, size: u8) -> u8;
+
+    /// An index in 0..count, from as many d250 rolls as it takes to cover count
+    fn pick(&mut self, count: usize) -> usize {
+        let mut index = 0;
+        let mut range = 1;
+        while range < count {
+            index = index * 250 + (self.roll(250) - 1) as usize;
+            range *= 250;
+        }
+        index % count
+    }
+}
+
+/// Any seed works, all zeros included: near_rng mixes constants into its state, so an all-zero seed
+/// (what unit tests get from env::random_seed) still gives a working generator.
+impl DiceRng for Rng {
+    fn roll(&mut self, size: u8) -> u8 {
+        // rand_range_u32 needs more than one value to pick from, a d1 only has the one face
+        match size {
+            0 => fail(Error::InvalidDieSize { size: 0 }),
+            1 => 1,
+            _ => self.rand_range_u32(1, size as u32 + 1) as u8,
+        }
+    }
+
+    fn pick(&mut self, count: usize) -> usize {
+        self.rand_bounded_usize(count)
+    }
+}
+
+pub fn assert_valid_die_size(size: u8) {
+    if !ALLOWED_DIE_SIZES.contains(&size) {
+        fail(Error::InvalidDieSize { size });
+    }
+}
+
+pub fn roll_die(rng: &mut impl DiceRng, size: u8) -> Die {
+    assert_valid_die_size(size);
+    Die {
+        size,
+        value: rng.roll(size),
+    }
+}
+
+pub fn roll_dice(rng: &mut impl DiceRng, sizes: Vec<u8>) -> Vec<Die> {
+    sizes.iter().map(|size| roll_die(rng, *size)).collect()
+}
+
+/// Player who goes first: whoever rolled the single lowest number. If the lowest dice are tied, the next lowest
+/// dice are compared, and so on until a starting player is determined. None when every pair is tied.
+pub fn initiative(dice: &[Vec<Die>]) -> Option<u8> {
+    // Sorted dice from lowest to highest for every player
+    let sorted_dice = dice.iter().cloned().map(|mut dice| {
+        dice.sort_by_key(|die| die.value);
+        dice
+    }).collect::<Vec<Vec<Die>>>();
+
+    let mut first = 0;
+    let dice_count = sorted_dice.iter().map(|dice| dice.len()).min().unwrap_or(0);
+    'outer: for i in 0..dice_count {
+        for player in 0..sorted_dice.len() {
+            if sorted_dice[player][i].value < sorted_dice[first][i].value {
+                first = player;
+                break 'outer;
+            }
+        }
+    }
+    let tied = sorted_dice.iter().all(|dice| dice.iter().zip(sorted_dice[0].iter()).all(|(die, other)| die.value == other.value));
+    if tied { None } else { Some(first as u8) }
+}
+
+/// Re-rolls all dice while initiative is tied, at most max_rerolls times, and returns who goes first.
+/// Dice still tied after that go to player 0, the game's creator, rather than re-rolling for as long as the gas lasts.
+pub fn settle_initiative(dice: &mut [Vec<Die>], rng: &mut impl DiceRng, max_rerolls: u32) -> u8 {
+    for _ in 0..max_rerolls {
+        if let Some(first) = initiative(dice) {
+            return first;
+        }
+        for player_dice in dice.iter_mut() {
+            *player_dice = roll_dice(rng, player_dice.iter().map(|die| die.size).collect());
+        }
+    }
+    initiative(dice).unwrap_or(0)
+}
+
+/// Every attack the current player can make: a power attack for each of their dice showing at least
+/// an opponent's die, and a skill attack for each set of two or more dice adding up exactly to one.
+/// Sets are enumerated up to SKILL_SUBSET_DICE dice, 2^n of them per defender die. Beyond that each defender die
+/// gets the one skill attack the subset-sum table finds, the same search find_skill_attack switches to.
+pub fn attack_options(game: &Game) -> Vec<AttackOption> {
+    let own = &game.dice[game.current_player as usize];
+    let targets = &game.dice[(game.current_player as usize + 1) % 2];
+    let mut options = vec![];
+    for (defender_die_index, target) in targets.iter().enumerate() {
+        for (attacker_die_index, die) in own.iter().enumerate() {
+            if die.value >= target.value {
+                options.push(AttackOption { attacker_die_indices: vec![attacker_die_index as u8], defender_die_index: defender_die_index as u8, attack_type: AttackType::Power, evaluation: None });
+            }
+        }
+        if own.len() > SKILL_SUBSET_DICE {
+            let values = own.iter().map(|die| die.value).collect::<Vec<u8>>();
+            if let Some(attackers) = skill_attack_dp(&values, target.value).filter(|attackers| attackers.len() >= 2) {
+                options.push(AttackOption { attacker_die_indices: attackers, defender_die_index: defender_die_index as u8, attack_type: AttackType::Skill, evaluation: None });
+            }
+            continue;
+        }
+        for subset in 1..1u32 << own.len() {
+            if subset.count_ones() < 2 {
+                continue;
+            }
+            let attackers = (0..own.len()).filter(|index| subset & (1 << index) != 0).map(|index| index as u8).collect::<Vec<u8>>();
+            if attackers.iter().map(|index| own[*index as usize].value as u32).sum::<u32>() == target.value as u32 {
+                options.push(AttackOption { attacker_die_indices: attackers, defender_die_index: defender_die_index as u8, attack_type: AttackType::Skill, evaluation: None });
+            }
+        }
+    }
+    return options;
+}
+
+pub fn find_power_attack(game: &Game) -> Option<(usize, usize)> {
+    let current_player_index = game.current_player as usize;
+    let other_player_index = (game.current_player as usize + 1) % 2;
+
+    // Verify that power attack is not possible
+    for attacker_die_index in 0..game.dice[current_player_index].len() {
+        for defender_die_index in 0..game.dice[other_player_index].len() {
+            if game.dice[current_player_index][attacker_die_index].value >= game.dice[other_player_index][defender_die_index].value {
+                return Some((attacker_die_index, defender_die_index));
+            }
+        }
+    }
+
+    return None;
+}
+
+/// Indices of attacker dice adding up exactly to the defender die, found with a subset-sum table
+/// that costs n times the defender's value
+fn skill_attack_dp(attacker_dice_values: &[u8], defender_die_value: u8) -> Option<Vec<u8>> {
+    let target = defender_die_value as usize;
+    // Die that first reached each sum, the rest of that sum is reached by dice before it
+    let mut reached_by: Vec<Option<usize>> = vec![None; target + 1];
+    for (index, value) in attacker_dice_values.iter().enumerate() {
+        let value = *value as usize;
+        // Going down, so each die is used once per sum
+        for sum in (value.max(1)..=target).rev() {
+            if reached_by[sum].is_none() && (sum == value || reached_by[sum - value].is_some()) {
+                reached_by[sum] = Some(index);
+            }
+        }
+    }
+
+    let mut selected_attacker_dice = vec![];
+    let mut sum = target;
+    while sum > 0 {
+        let index = reached_by[sum]?;
+        selected_attacker_dice.push(index as u8);
+        sum -= attacker_dice_values[index] as usize;
+    }
+    selected_attacker_dice.reverse();
+    return Some(selected_attacker_dice);
+}
+
+/// Searches every defender die for a set of attacker dice adding up to it. The recursive search tries
+/// up to 2^n sets of n dice per defender die, so beyond dp_threshold dice,
+/// ContractConfig.skill_search_dp_threshold in the contract,
+/// it switches to a subset-sum table, which costs n times the defender's value instead.
+pub fn find_skill_attack(game: &Game, dp_threshold: u32) -> Option<(Vec<u8>, u8)> {
+    let current_player_index = game.current_player as usize;
+    let other_player_index = (game.current_player as usize + 1) % 2;
+
+    fn find_skill_attack_recursive(attacker_dice_values: &[u8], defender_die_value: u8, selected_attacker_dice: Vec<u8>) -> Option<Vec<u8>> {
+        if attacker_dice_values.is_empty() {
+            if defender_die_value == 0 {
+                return Some(selected_attacker_dice);
+            } else {
+                return None;
+            }
+        }
+
+        if let Some(result) = find_skill_attack_recursive(&attacker_dice_values[1..], defender_die_value, selected_attacker_dice.clone()) {
+            return Some(result);
+        }
+
+        if defender_die_value >= attacker_dice_values[0] {
+            if let Some(result) = find_skill_attack_recursive(&attacker_dice_values[1..], defender_die_value - attacker_dice_values[0], {
+                let mut selected_attacker_dice = selected_attacker_dice.clone();
+                selected_attacker_dice.push(0);
+                selected_attacker_dice
+            }) {
+                return Some(result);
+            }
+        }
+
+        return None;
+    }
+
+    // Verify that skill attack is not possible
+    for defender_die_index in 0..game.dice[other_player_index].len() {
+        let defender_die_value = game.dice[other_player_index][defender_die_index].value;
+        let attacker_dice_values = game.dice[current_player_index].iter().map(|die| die.value).collect::<Vec<u8>>();
+
+        let result = if attacker_dice_values.len() > dp_threshold as usize {
+            skill_attack_dp(&attacker_dice_values, defender_die_value)
+        } else {
+            find_skill_attack_recursive(&attacker_dice_values, defender_die_value, vec![])
+        };
+        if let Some(result) = result {
+            return Some((result, defender_die_value));
+        }
+    }
+
+    return None;
+}
+
+/// Makes the current player's attack: captures the defender die and re-rolls the attacking dice.
+/// Panics when the dice don't make the attack. The turn stays with the attacker, see advance_turn.
+pub fn resolve_attack(game: &mut Game, attacker_die_indices: &[u8], defender_die_index: u8, attack_type: Option<AttackType>, rng: &mut impl DiceRng) -> AttackView {
+    let attacker_dice_idx = game.current_player as usize;
+    let defender_dice_idx = (game.current_player + 1) as usize % 2;
+
+    for (position, index) in attacker_die_indices.iter().enumerate() {
+        if *index as usize >= game.dice[attacker_dice_idx].len() {
+            fail(Error::AttackerNotFound { index: *index as usize });
+        }
+        // Each die adds its value once, counting one twice would make attacks the dice can't
+        if attacker_die_indices[..position].contains(index) {
+            fail(Error::DuplicateAttacker { index: *index as usize });
+        }
+    }
+    if defender_die_index as usize >= game.dice[defender_dice_idx].len() {
+        fail(Error::DefenderNotFound { index: defender_die_index as usize });
+    }
+
+    // Without a declared type a single die makes a power attack and several a skill attack
+    let attack_type = attack_type.unwrap_or(if attacker_die_indices.len() == 1 { AttackType::Power } else { AttackType::Skill });
+    match attack_type {
+        AttackType::Power if attacker_die_indices.len() != 1 => fail(Error::PowerAttackDice),
+        AttackType::Skill if attacker_die_indices.is_empty() => fail(Error::SkillAttackDice),
+        _ => {},
+    }
+    // Summed wide, enough dice showing 30 add up past a u8
+    let attack_value = attacker_die_indices.iter().map(|index| game.dice[attacker_dice_idx][*index as usize].value as u32).sum::<u32>();
+    let defender_value = game.dice[defender_dice_idx][defender_die_index as usize].value as u32;
+    let attack_success = match attack_type {
+        AttackType::Power => attack_value >= defender_value,
+        AttackType::Skill => attack_value == defender_value,
+    };
+
+    if !attack_success {
+        fail(Error::AttackFailed);
+    }
+
+    let previous_values = attacker_die_indices.iter().map(|index| game.dice[attacker_dice_idx][*index as usize].value).collect();
+    // Capture the die
+    let captured = game.dice[defender_dice_idx].remove(defender_die_index as usize);
+    game.captured[attacker_dice_idx].push(captured.size);
+    // Re-roll attacker dice
+    for index in attacker_die_indices.iter() {
+        game.dice[attacker_dice_idx][*index as usize] = roll_die(rng, game.dice[attacker_dice_idx][*index as usize].size);
+    }
+    AttackView {
+        rerolled: attacker_die_indices.iter().map(|index| game.dice[attacker_dice_idx][*index as usize].value).collect(),
+        previous_values: Some(previous_values),
+    }
+}
+
+/// Passes the turn to the next player in seat order who still has dice, wrapping around.
+/// When nobody else has dice the turn goes to the next seat anyway, the move that took their last die ends the game.
+pub fn advance_turn(game: &mut Game) {
+    let player_count = game.players.len();
+    let current = game.current_player as usize;
+    let next = (1..player_count).map(|offset| (current + offset) % player_count)
+        .find(|index| !game.dice[*index].is_empty())
+        .unwrap_or((current + 1) % player_count);
+    game.current_player = next as u8;
+}
+
+/// Score doubled, so that dice still in play can count half their size:
+/// captured dice score their full size, own remaining dice half of it.
+pub fn score(game: &Game, player_index: usize) -> u32 {
+    let captured = game.captured[player_index].iter().map(|size| *size as u32).sum::<u32>();
+    let remaining = game.dice[player_index].iter().map(|die| die.size as u32).sum::<u32>();
+    2 * captured + remaining
+}
+
+pub fn scores(game: &Game) -> Vec<u32> {
+    (0..game.players.len()).map(|player_index| score(game, player_index)).collect()
+}
+
+/// Player with the higher score, None when they're equal
+pub fn leader(scores: &[u32]) -> Option<u8> {
+    if scores[0] == scores[1] {
+        None
+    } else if scores[0] > scores[1] {
+        Some(0)
+    } else {
+        Some(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+    use near_sdk::serde_json;
+
+    use super::*;
+
+    /// Positions of up to five dice a side, rolled from a fixed seed
+    fn positions(count: usize) -> Vec<Game> {
+        let mut rng = Rng::new(&[7; 32].to_vec());
+        (0..count).map(|_| {
+            let mut dice = vec![];
+            for _ in 0..2 {
+                let sizes = (0..rng.roll(5)).map(|_| ALLOWED_DIE_SIZES[rng.roll(ALLOWED_DIE_SIZES.len() as u8) as usize - 1]).collect();
+                dice.push(roll_dice(&mut rng, sizes));
+            }
+            Game { players: vec!["bob.near".to_string(), "alice.near".to_string()], current_player: rng.roll(2) - 1, dice, captured: vec![vec![], vec![]], ..Default::default() }
+        }).collect()
+    }
+
+    #[test]
+    fn every_option_resolves() {
+        let mut rng = Rng::new(&[1; 32].to_vec());
+        for game in positions(200) {
+            let attacker = game.current_player as usize;
+            let defender = (attacker + 1) % 2;
+            for option in attack_options(&game) {
+                let mut after = Game { players: game.players.clone(), dice: game.dice.clone(), captured: game.captured.clone(), current_player: game.current_player, ..Default::default() };
+                let view = resolve_attack(&mut after, &option.attacker_die_indices, option.defender_die_index, Some(option.attack_type), &mut rng);
+
+                assert_eq!(after.dice[defender].len(), game.dice[defender].len() - 1);
+                assert_eq!(after.dice[attacker].len(), game.dice[attacker].len());
+                assert_eq!(after.captured[attacker], vec![game.dice[defender][option.defender_die_index as usize].size]);
+                assert_eq!(view.rerolled.len(), option.attacker_die_indices.len());
+                assert!(after.dice[attacker].iter().all(|die| die.value >= 1 && die.value <= die.size));
+                assert_eq!(after.current_player, game.current_player);
+            }
+        }
+    }
+
+    #[test]
+    fn searches_agree_with_options() {
+        for game in positions(200) {
+            let options = attack_options(&game);
+            assert_eq!(find_power_attack(&game).is_some(), options.iter().any(|option| option.attack_type == AttackType::Power));
+            // The searches also take a single die matching exactly, which attack_options lists as a power attack
+            let skill = find_skill_attack(&game, u32::MAX);
+            assert_eq!(skill.is_some(), find_skill_attack(&game, 0).is_some());
+            if options.iter().any(|option| option.attack_type == AttackType::Skill) {
+                assert!(skill.is_some());
+            }
+            if skill.is_some() {
+                assert!(!options.is_empty());
+            }
+        }
+    }
+
+    /// Self-play games every_game_keeps_invariants plays, each from its own seed
+    const SELF_PLAY_GAMES: u64 = 2_000;
+    /// A self-play game ends here if nobody has run out of dice or passed twice by then
+    const SELF_PLAY_MOVES: usize = 200;
+
+    /// Plays a game out with random legal moves from the seed, checking the rules' invariants after every move.
+    /// Err has the invariant that broke, the moves before it are in moves.
+    fn self_play(seed: u64, moves: &mut Vec<String>) -> Result<(), String> {
+        let mut rng = Rng::new(&seed.to_le_bytes().repeat(4));
+        let recipes = (0..2).map(|_| (0..rng.roll(5)).map(|_| ALLOWED_DIE_SIZES[rng.roll(ALLOWED_DIE_SIZES.len() as u8) as usize - 1]).collect::<Vec<u8>>()).collect::<Vec<Vec<u8>>>();
+        let dice = recipes.iter().map(|sizes| roll_dice(&mut rng, sizes.clone())).collect::<Vec<Vec<Die>>>();
+        let mut game = Game { players: vec!["bob.near".to_string(), "alice.near".to_string()], current_player: initiative(&dice).unwrap_or(0), dice, captured: vec![vec![], vec![]], ..Default::default() };
+        moves.push(format!("recipes {:?}, dice {:?}, player {} starts", recipes, game.dice, game.current_player));
+
+        let mut passes = 0;
+        for _ in 0..SELF_PLAY_MOVES {
+            for (player, recipe) in recipes.iter().enumerate() {
+                let other = (player + 1) % 2;
+                if game.dice[player].len() + game.captured[other].len() != recipe.len() {
+                    return Err(format!("player {} has {} dice and lost {}, started with {}", player, game.dice[player].len(), game.captured[other].len(), recipe.len()));
+                }
+                if let Some(die) = game.dice[player].iter().find(|die| die.value < 1 || die.value > die.size) {
+                    return Err(format!("d{} showing {}", die.size, die.value));
+                }
+            }
+            let bytes = game.try_to_vec().unwrap();
+            if Game::try_from_slice(&bytes).unwrap().try_to_vec().unwrap() != bytes {
+                return Err("borsh round trip changed the game".to_string());
+            }
+            let json = serde_json::to_string(&game).unwrap();
+            if serde_json::to_string(&serde_json::from_str::<Game>(&json).unwrap()).unwrap() != json {
+                return Err("JSON round trip changed the game".to_string());
+            }
+
+            let options = attack_options(&game);
+            if game.dice.iter().any(|dice| dice.is_empty()) {
+                let other_turn = Game { players: game.players.clone(), dice: game.dice.clone(), captured: game.captured.clone(), current_player: (game.current_player + 1) % 2, ..Default::default() };
+                if !options.is_empty() || !attack_options(&other_turn).is_empty() {
+                    return Err("attack possible after the game ended".to_string());
+                }
+                return Ok(());
+            }
+            if passes == 2 {
+                return Ok(());
+            }
+            // Passing is only allowed when there's no attack, so either side of this decides what the player may do
+            if options.is_empty() != (find_power_attack(&game).is_none() && find_skill_attack(&game, u32::MAX).is_none()) {
+                return Err(format!("{} attack options, but the searches disagree", options.len()));
+            }
+
+            if options.is_empty() {
+                moves.push(format!("player {} passes", game.current_player));
+                passes += 1;
+            } else {
+                let option = &options[rng.roll(options.len().min(u8::MAX as usize) as u8) as usize - 1];
+                moves.push(format!("player {} attacks {:?}", game.current_player, option));
+                let attacker = game.current_player as usize;
+                let defender_size = game.dice[(attacker + 1) % 2][option.defender_die_index as usize].size as u32;
+                let total_before = scores(&game).iter().sum::<u32>();
+                resolve_attack(&mut game, &option.attacker_die_indices, option.defender_die_index, Some(option.attack_type), &mut rng);
+                // The capturer gains the size twice over and the defender loses it once, in doubled points
+                if scores(&game).iter().sum::<u32>() != total_before + defender_size {
+                    return Err(format!("scores went from {} to {} for a d{}", total_before, scores(&game).iter().sum::<u32>(), defender_size));
+                }
+                passes = 0;
+            }
+            advance_turn(&mut game);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn every_game_keeps_invariants() {
+        for seed in 0..SELF_PLAY_GAMES {
+            let mut moves = vec![];
+            if let Err(error) = self_play(seed, &mut moves) {
+                panic!("Self-play game with seed {} broke: {}\n{}", seed, error, moves.join("\n"));
+            }
+        }
+    }
+
+    #[test]
+    fn d1_rolls_one() {
+        let mut rng = Rng::new(&[3; 32].to_vec());
+        assert!((0..100).all(|_| rng.roll(1) == 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid die size: d0")]
+    fn d0_not_rolled() {
+        Rng::new(&[3; 32].to_vec()).roll(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid die size: d1")]
+    fn d1_not_allowed() {
+        roll_die(&mut Rng::new(&[3; 32].to_vec()), 1);
+    }
+
+    #[test]
+    fn initiative_to_lowest_die() {
+        let dice = |values: &[u8]| values.iter().map(|value| Die { size: 20, value: *value }).collect::<Vec<Die>>();
+        assert_eq!(initiative(&[dice(&[5, 3]), dice(&[2, 4])]), Some(1));
+        assert_eq!(initiative(&[dice(&[2, 9]), dice(&[2, 4])]), Some(1));
+        assert_eq!(initiative(&[dice(&[2, 4]), dice(&[4, 2])]), None);
+    }
+
+    /// Rolls the same number every time, counting the rolls
+    struct FixedRng {
+        value: u8,
+        rolls: u32,
+    }
+
+    impl DiceRng for FixedRng {
+        fn roll(&mut self, _size: u8) -> u8 {
+            self.rolls += 1;
+            self.value
+        }
+    }
+
+    #[test]
+    fn tied_initiative_falls_back_to_creator() {
+        let mut dice = vec![vec![Die { size: 6, value: 3 }, Die { size: 8, value: 5 }], vec![Die { size: 10, value: 5 }, Die { size: 4, value: 3 }]];
+        let mut rng = FixedRng { value: 2, rolls: 0 };
+
+        assert_eq!(settle_initiative(&mut dice, &mut rng, 10), 0);
+        // Ten re-rolls of all four dice, then no more
+        assert_eq!(rng.rolls, 40);
+        assert_eq!(dice, vec![vec![Die { size: 6, value: 2 }, Die { size: 8, value: 2 }], vec![Die { size: 10, value: 2 }, Die { size: 4, value: 2 }]]);
+    }
+
+    #[test]
+    fn tie_broken_by_reroll() {
+        let mut dice = vec![vec![Die { size: 6, value: 3 }], vec![Die { size: 10, value: 3 }]];
+        let mut rng = Rng::new(&[0; 32].to_vec());
+
+        let first = settle_initiative(&mut dice, &mut rng, 10);
+        assert_ne!(dice[0][0].value, dice[1][0].value);
+        assert_eq!(Some(first), initiative(&dice));
+    }
+
+    #[test]
+    #[should_panic(expected = "Attack failed")]
+    fn weak_attack_panics() {
+        let mut game = Game { players: vec!["bob.near".to_string(), "alice.near".to_string()], dice: vec![vec![Die { size: 6, value: 2 }], vec![Die { size: 6, value: 3 }]], captured: vec![vec![], vec![]], ..Default::default() };
+        resolve_attack(&mut game, &[0], 0, None, &mut Rng::new(&[0; 32].to_vec()));
+    }
+
+    fn dice_game(own: Vec<Die>, targets: Vec<Die>) -> Game {
+        Game { players: vec!["bob.near".to_string(), "alice.near".to_string()], dice: vec![own, targets], captured: vec![vec![], vec![]], ..Default::default() }
+    }
+
+    #[test]
+    fn many_dice_options_from_table() {
+        // 2^40 sets of dice per defender die, too many to list, so each defender gets the one the table finds
+        let mut game = dice_game(vec![Die { size: 4, value: 1 }; 40], vec![Die { size: 6, value: 5 }, Die { size: 4, value: 1 }]);
+        let options = attack_options(&game);
+        assert_eq!(options.iter().filter(|option| option.attack_type == AttackType::Power).count(), 40);
+        let skill = options.into_iter().filter(|option| option.attack_type == AttackType::Skill).collect::<Vec<AttackOption>>();
+        assert_eq!(skill.len(), 1);
+        assert_eq!((skill[0].attacker_die_indices.len(), skill[0].defender_die_index), (5, 0));
+
+        resolve_attack(&mut game, &skill[0].attacker_die_indices, 0, Some(AttackType::Skill), &mut Rng::new(&[1; 32].to_vec()));
+        assert_eq!(game.captured[0], vec![6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Die 0 can only attack once")]
+    fn same_die_twice_rejected() {
+        let mut game = dice_game(vec![Die { size: 6, value: 5 }, Die { size: 4, value: 1 }], vec![Die { size: 20, value: 10 }, Die { size: 20, value: 20 }]);
+        resolve_attack(&mut game, &[0, 0], 0, None, &mut Rng::new(&[0; 32].to_vec()));
+    }
+
+    #[test]
+    #[should_panic(expected = "You have no die 2")]
+    fn missing_attacker_rejected() {
+        let mut game = dice_game(vec![Die { size: 6, value: 5 }, Die { size: 4, value: 1 }], vec![Die { size: 20, value: 6 }]);
+        resolve_attack(&mut game, &[0, 2], 0, None, &mut Rng::new(&[0; 32].to_vec()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Your opponent has no die 1")]
+    fn missing_defender_rejected() {
+        let mut game = dice_game(vec![Die { size: 6, value: 5 }], vec![Die { size: 4, value: 1 }]);
+        resolve_attack(&mut game, &[0], 1, None, &mut Rng::new(&[0; 32].to_vec()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Attack failed")]
+    fn attack_value_past_u8() {
+        // Ten d30 showing 30 add up to 300, which fails the skill attack instead of overflowing
+        let mut game = dice_game(vec![Die { size: 30, value: 30 }; 10], vec![Die { size: 30, value: 30 }]);
+        resolve_attack(&mut game, &(0..10).collect::<Vec<u8>>(), 0, Some(AttackType::Skill), &mut Rng::new(&[0; 32].to_vec()));
+    }
+}
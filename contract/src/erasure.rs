@@ -0,0 +1,183 @@
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+type Eraser = fn(&mut Contract, &str);
+
+/// Every piece of per-account data, remove_my_data goes through all of them in order.
+/// Anything new stored per account has to be added here.
+const USER_DATA: &[(&str, Eraser)] = &[
+    ("notifications", |_, account_id| { env::storage_remove(&notifications_key(account_id)); }),
+    ("following", |_, account_id| { env::storage_remove(&follow::following_key(account_id)); }),
+    ("blocks", |_, account_id| { env::storage_remove(&blocks::blocks_key(account_id)); }),
+    ("games awaiting a move", |_, account_id| { env::storage_remove(&awaiting::awaiting_key(account_id)); }),
+    ("daily games", |contract, account_id| { contract.daily_games.remove(&account_id.to_string()); }),
+    ("first finished game", |contract, account_id| { contract.first_finished_at.remove(&account_id.to_string()); }),
+    ("rating", |contract, account_id| { contract.ratings.remove(&account_id.to_string()); }),
+    ("last ranked game", |contract, account_id| { contract.last_ranked_game_at.remove(&account_id.to_string()); }),
+    ("leaderboard", |contract, account_id| contract.remove_from_leaderboard(account_id)),
+    ("record", |contract, account_id| { contract.records.remove(&account_id.to_string()); }),
+    ("season history", |contract, account_id| { contract.season_history.remove(&account_id.to_string()); }),
+    ("achievements", |contract, account_id| { contract.achievements.remove(&account_id.to_string()); }),
+    ("streak", |contract, account_id| { contract.streaks.remove(&account_id.to_string()); }),
+    ("streak leaderboard", |contract, account_id| contract.remove_from_streak_leaderboard(account_id)),
+    ("replay votes", |contract, account_id| { contract.replay_votes.remove(&account_id.to_string()); }),
+    ("club", |contract, account_id| contract.remove_from_club(account_id)),
+    ("puzzle solves", |contract, account_id| contract.remove_puzzle_solves(account_id, &anonymized_id(account_id))),
+    // Must stay last, anonymizing games goes through this index
+    ("games", |contract, account_id| contract.anonymize_games(account_id)),
+];
+
+/// Stand-in for an erased account in games it played, stable so opponents' records still line up.
+fn anonymized_id(account_id: &str) -> String {
+    let hash = env::sha256(account_id.as_bytes());
+    format!("deleted-{}", hash[..8].iter().map(|byte| format!("{:02x}", byte)).collect::<String>())
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Erases the caller's per-account data and replaces their account id in past games and tournaments.
+    /// Fails while any of their games is still open.
+    pub fn remove_my_data(&mut self) -> () {
+        let account_id = env::predecessor_account_id().to_string();
+
+        let open_games = get_user_games(account_id.clone()).into_iter()
+            .filter(|game_id| self.games.get(game_id).is_some_and(|game| !game.is_finished() && game.status != GameStatus::Cancelled))
+            .collect::<Vec<String>>();
+        if !open_games.is_empty() {
+            fail(Error::OpenGamesLeft { game_ids: open_games.join(", ") });
+        }
+
+        for (_, erase) in USER_DATA.iter() {
+            erase(self, &account_id);
+        }
+
+        emit_event("user_data_removed", serde_json::json!({
+            "account_id": anonymized_id(&account_id),
+            "erased": USER_DATA.iter().map(|(name, _)| *name).collect::<Vec<&str>>(),
+        }));
+    }
+
+    fn anonymize_games(&mut self, account_id: &str) {
+        let replacement = anonymized_id(account_id);
+        let rename = |id: &mut String| if id == account_id { *id = replacement.clone(); };
+
+        for game_id in get_user_games(account_id.to_string()) {
+            let mut game = match self.games.get(&game_id) {
+                Some(game) => game,
+                None => continue,
+            };
+            game.players.iter_mut().for_each(rename);
+            self.save_game(&game);
+            if game.daily.is_some() {
+                self.anonymize_daily(&game, account_id, &replacement);
+            }
+
+            if let Some(mut tournament) = game.tournament_id.as_ref().and_then(|id| self.tournaments.get(id)) {
+                tournament.players.iter_mut().for_each(rename);
+                tournament.winner.iter_mut().for_each(rename);
+                tournament.payouts.iter_mut().for_each(|payout| rename(&mut payout.account_id));
+                for m in tournament.rounds.iter_mut().flatten() {
+                    m.players.iter_mut().for_each(rename);
+                    m.winner.iter_mut().for_each(rename);
+                }
+                self.tournaments.insert(&tournament.id.clone(), &tournament);
+            }
+        }
+        env::storage_remove(&user_games_key(account_id.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::login_as;
+
+    fn finished_game_with_data(contract: &mut Contract) -> String {
+        login_as("bob.near");
+        let game_id = contract.create_game(None);
+        contract.follow("carol.near".parse().unwrap());
+        login_as("alice.near");
+        contract.join_game(game_id.clone(), None);
+        login_as("bob.near");
+        contract.resign(game_id.clone());
+        notify("bob.near", "your_turn", &game_id, "alice.near");
+        return game_id;
+    }
+
+    #[test]
+    fn remove_my_data() {
+        let mut contract = Contract::default();
+        let game_id = finished_game_with_data(&mut contract);
+
+        login_as("bob.near");
+        contract.remove_my_data();
+
+        assert_eq!(env::storage_read(&notifications_key("bob.near")), None);
+        assert_eq!(env::storage_read(b"follow:bob.near"), None);
+        assert_eq!(env::storage_read(&user_games_key("bob.near".to_string())), None);
+        assert!(!contract.daily_games.contains_key(&"bob.near".to_string()));
+        assert!(!contract.first_finished_at.contains_key(&"bob.near".to_string()));
+        assert!(!contract.ratings.contains_key(&"bob.near".to_string()));
+        assert!(!contract.records.contains_key(&"bob.near".to_string()));
+        assert!(!contract.achievements.contains_key(&"bob.near".to_string()));
+        assert!(!contract.streaks.contains_key(&"bob.near".to_string()));
+        assert!(!contract.replay_votes.contains_key(&"bob.near".to_string()));
+        assert!(!contract.club_memberships.contains_key(&"bob.near".to_string()));
+        assert!(contract.get_streak_leaderboard(10).iter().all(|entry| entry.account_id != "bob.near"));
+        assert!(!leaderboard::read_leaderboard(None).iter().any(|entry| entry.account_id == "bob.near"));
+        assert!(!contract.season_history.contains_key(&"bob.near".to_string()));
+
+        // The opponent keeps the game and its result
+        let game = contract.games.get(&game_id).unwrap();
+        assert_eq!(game.players, vec![anonymized_id("bob.near"), "alice.near".to_string()]);
+        assert!(game.players[0].starts_with("deleted-"));
+        assert_eq!(game.winner, Some(1));
+        assert_eq!(get_user_games("alice.near".to_string()), vec![game_id]);
+        assert!(contract.first_finished_at.contains_key(&"alice.near".to_string()));
+    }
+
+    #[test]
+    fn remove_my_data_tournament() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(tournament::CreateTournamentOptions {
+            name: "Cup".to_string(),
+            max_players: 2,
+            entry_fee: None,
+            match_length: None,
+            starts_at: None,
+            swiss_rounds: None,
+            prize_split: None,
+        });
+        login_as("bob.near");
+        contract.register(tournament_id.clone());
+        login_as("alice.near");
+        contract.register(tournament_id.clone());
+        let game_id = contract.get_tournament(tournament_id.clone()).unwrap().rounds[0][0].game_id.clone();
+        login_as("alice.near");
+        contract.resign(game_id);
+
+        login_as("bob.near");
+        contract.remove_my_data();
+
+        let tournament = contract.get_tournament(tournament_id).unwrap();
+        assert_eq!(tournament.players, vec![anonymized_id("bob.near"), "alice.near".to_string()]);
+        assert_eq!(tournament.winner, Some(anonymized_id("bob.near")));
+        assert_eq!(tournament.rounds[0][0].winner, Some(anonymized_id("bob.near")));
+    }
+
+    #[test]
+    #[should_panic(expected = "Finish or resign these games first: 1, 2")]
+    fn remove_my_data_open_games() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.create_game(None);
+        contract.create_game(None);
+        login_as("alice.near");
+        contract.join_game("1".to_string(), None);
+
+        login_as("bob.near");
+        contract.remove_my_data();
+    }
+}
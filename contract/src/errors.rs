@@ -0,0 +1,1350 @@
+use near_sdk::near_bindgen;
+use near_sdk::serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// A failure the contract reports, either by panicking with its message through fail or as the body of a web4
+/// error response through error_response
+#[derive(Debug, PartialEq, Clone)]
+pub enum Error {
+    ContractPaused,
+    NotOwner { action: String },
+    NotLoggedIn,
+    InvalidAccountId { account_id: String },
+    GameNotFound { game_id: String },
+    NotJoined { player_id: String, game_id: String },
+    NotYourTurn,
+    GameNotActive { game_id: String },
+    GameNotStarted { game_id: String },
+    GameAlreadyStarted { game_id: String },
+    GameCancelled { game_id: String },
+    GameFinished { game_id: String },
+    GameNotFinished { game_id: String },
+    GamePaused { game_id: String },
+    VerifyingButtons { game_id: String },
+    TooManyAttackers,
+    AttackerNotFound { index: usize },
+    DuplicateAttacker { index: usize },
+    DefenderNotFound { index: usize },
+    NoHint { game_id: String },
+    Fog { game_id: String },
+    NoWager { game_id: String },
+    InvalidDay { day: String },
+    ButtonNotFound { button: String },
+    ClubNotFound { club_id: String },
+    PuzzleNotFound { puzzle_id: String },
+    TournamentNotFound { tournament_id: String },
+    InvalidDieSize { size: u8 },
+    AttackFailed,
+    PowerAttackDice,
+    SkillAttackDice,
+    AttackPossible { attack: String },
+    NotUndoable,
+    NotRequested { request: String, game_id: String },
+    AlreadyJoined { player_id: String, game_id: String },
+    NotInvited { game_id: String, player_id: String },
+    GameFull { game_id: String },
+    GameNotOver { game_id: String },
+    NoAuxiliaryDice { game_id: String },
+    TooEarlyToResign { game_id: String, turns: u32 },
+    CorruptedGame { game_id: String, expected: usize, players: usize, dice: usize, captured: usize },
+    NotCreator { action: String },
+    NotAccountOwner { action: String },
+    NotOrganizer { tournament_id: String },
+    WrongDeposit { amount: Balance, action: String },
+    DepositTooLow { amount: Balance, action: String },
+    NoDeposit { action: String },
+    FeeTooHigh,
+    InsufficientTreasury { treasury: Balance },
+    DailyLimitReached { free_games: u32, fee: Balance },
+    SelfBlock,
+    TooManyBlocked { max: usize },
+    SelfFollow,
+    TooManyFollowed { max: usize },
+    SelfPair,
+    EmptyButtonName,
+    NoButtonDice,
+    TooManyDice { max: usize },
+    ButtonExists { button: String },
+    PresetAndTimeControl,
+    InvalidBank { min: u64, max: u64 },
+    InvalidIncrement { max: u64 },
+    NoClock { game_id: String },
+    OutOfTime { player_id: String, game_id: String },
+    TimeLeft { player_id: String, game_id: String },
+    OwnTurnTimeout,
+    AlreadyFinalized { game_id: String },
+    InvalidExtension { max: u64 },
+    ExtensionPending { game_id: String },
+    TooManyExtensions { player_id: String, max: usize, game_id: String },
+    PausePending { game_id: String },
+    PausesUsedUp { game_id: String, max_pause_ns: u64 },
+    GameNotPaused { game_id: String },
+    EmptyClubName,
+    ClubNameTooLong { max: usize },
+    ClubFull { club_id: String },
+    NotInClub { account_id: String },
+    AlreadyInClub { account_id: String, club_id: String },
+    DailyPlayed { player_id: String, date: String },
+    OpenGamesLeft { game_ids: String },
+    FixedSeats { game_id: String },
+    YourTurn,
+    AlreadyMoved { player_id: String, game_id: String },
+    NotInactive { player_id: String },
+    HasSideBets { game_id: String },
+    NoFireSkill,
+    TurnDownCount,
+    TurnDownAttacker,
+    TurnDownTooFar { index: usize },
+    NoTurnDown,
+    BoostTooFar { index: usize },
+    WagerRequired { game_id: String, stake: Balance },
+    SessionKeyNotEd25519,
+    SessionKeyRegistered { game_id: String },
+    SessionKeyNotRegistered { game_id: String },
+    PartialSignature,
+    InvalidSessionKey,
+    InvalidSessionSignature,
+    InvalidSeed,
+    ZeroLobbyTtl,
+    ZeroWindowWiden,
+    NoStateToMigrate,
+    NoMigration,
+    MigrationResumesAt { game_id: String },
+    EmptyPuzzle,
+    InvalidDieValue,
+    InvalidSolution,
+    ForfeitPenaltyTooLow,
+    AlreadyNominated { game_id: String },
+    NotNominated { game_id: String },
+    NoFinishedGame,
+    AlreadyVoted { account_id: String, game_id: String },
+    InvalidRoomName { name: String },
+    RoomExists { room_id: String },
+    NoRoomCode,
+    InvalidPlayerIndex { player_index: u8 },
+    OwnGameBet,
+    ContractGameBet { game_id: String },
+    SideBetsLocked { game_id: String },
+    NoSideBet { game_id: String },
+    SideBetClaimed { game_id: String },
+    SideBetLost { game_id: String },
+    HasWager { game_id: String },
+    NotExpired { game_id: String },
+    NotSwiss { tournament_id: String },
+    TournamentNotRunning { tournament_id: String },
+    RoundInProgress { round: usize, tournament_id: String },
+    TitlesOutOfOrder,
+    InvalidMaxPlayers { max: u32 },
+    InvalidMatchLength,
+    NoSwissRounds,
+    InvalidPrizeSplit,
+    TooManyPrizePlaces,
+    RegistrationClosed { tournament_id: String },
+    AlreadyRegistered { player_id: String, tournament_id: String },
+    TooEarlyToStart { tournament_id: String },
+    TournamentStarted { tournament_id: String },
+    NoPayout { tournament_id: String },
+    PayoutClaimed { tournament_id: String },
+    NoWinnings { account_id: String },
+    TooEarlyToReclaim { game_id: String },
+    NotStalled { game_id: String },
+    NotAbandoned { game_id: String },
+    StakeTooLow { stake: Balance, min_stake: Balance },
+    WagerSettled { game_id: String },
+}
+
+/// An entry of list_error_codes
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ErrorCode {
+    pub code: String,
+    pub status: u16,
+    /// The message with its values left as `{name}` placeholders
+    pub message: String,
+}
+
+impl Error {
+    /// One of each variant, numbers at 0 and text empty, see template for the messages they make
+    pub fn variants() -> Vec<Error> {
+        let variants = vec![
+            Error::ContractPaused,
+            Error::NotOwner { action: String::new() },
+            Error::NotLoggedIn,
+            Error::InvalidAccountId { account_id: String::new() },
+            Error::GameNotFound { game_id: String::new() },
+            Error::NotJoined { player_id: String::new(), game_id: String::new() },
+            Error::NotYourTurn,
+            Error::GameNotActive { game_id: String::new() },
+            Error::GameNotStarted { game_id: String::new() },
+            Error::GameAlreadyStarted { game_id: String::new() },
+            Error::GameCancelled { game_id: String::new() },
+            Error::GameFinished { game_id: String::new() },
+            Error::GameNotFinished { game_id: String::new() },
+            Error::GamePaused { game_id: String::new() },
+            Error::VerifyingButtons { game_id: String::new() },
+            Error::TooManyAttackers,
+            Error::AttackerNotFound { index: 0 },
+            Error::DuplicateAttacker { index: 0 },
+            Error::DefenderNotFound { index: 0 },
+            Error::NoHint { game_id: String::new() },
+            Error::Fog { game_id: String::new() },
+            Error::NoWager { game_id: String::new() },
+            Error::InvalidDay { day: String::new() },
+            Error::ButtonNotFound { button: String::new() },
+            Error::ClubNotFound { club_id: String::new() },
+            Error::PuzzleNotFound { puzzle_id: String::new() },
+            Error::TournamentNotFound { tournament_id: String::new() },
+            Error::InvalidDieSize { size: 0 },
+            Error::AttackFailed,
+            Error::PowerAttackDice,
+            Error::SkillAttackDice,
+            Error::AttackPossible { attack: String::new() },
+            Error::NotUndoable,
+            Error::NotRequested { request: String::new(), game_id: String::new() },
+            Error::AlreadyJoined { player_id: String::new(), game_id: String::new() },
+            Error::NotInvited { game_id: String::new(), player_id: String::new() },
+            Error::GameFull { game_id: String::new() },
+            Error::GameNotOver { game_id: String::new() },
+            Error::NoAuxiliaryDice { game_id: String::new() },
+            Error::TooEarlyToResign { game_id: String::new(), turns: 0 },
+            Error::CorruptedGame { game_id: String::new(), expected: 0, players: 0, dice: 0, captured: 0 },
+            Error::NotCreator { action: String::new() },
+            Error::NotAccountOwner { action: String::new() },
+            Error::NotOrganizer { tournament_id: String::new() },
+            Error::WrongDeposit { amount: 0, action: String::new() },
+            Error::DepositTooLow { amount: 0, action: String::new() },
+            Error::NoDeposit { action: String::new() },
+            Error::FeeTooHigh,
+            Error::InsufficientTreasury { treasury: 0 },
+            Error::DailyLimitReached { free_games: 0, fee: 0 },
+            Error::SelfBlock,
+            Error::TooManyBlocked { max: 0 },
+            Error::SelfFollow,
+            Error::TooManyFollowed { max: 0 },
+            Error::SelfPair,
+            Error::EmptyButtonName,
+            Error::NoButtonDice,
+            Error::TooManyDice { max: 0 },
+            Error::ButtonExists { button: String::new() },
+            Error::PresetAndTimeControl,
+            Error::InvalidBank { min: 0, max: 0 },
+            Error::InvalidIncrement { max: 0 },
+            Error::NoClock { game_id: String::new() },
+            Error::OutOfTime { player_id: String::new(), game_id: String::new() },
+            Error::TimeLeft { player_id: String::new(), game_id: String::new() },
+            Error::OwnTurnTimeout,
+            Error::AlreadyFinalized { game_id: String::new() },
+            Error::InvalidExtension { max: 0 },
+            Error::ExtensionPending { game_id: String::new() },
+            Error::TooManyExtensions { player_id: String::new(), max: 0, game_id: String::new() },
+            Error::PausePending { game_id: String::new() },
+            Error::PausesUsedUp { game_id: String::new(), max_pause_ns: 0 },
+            Error::GameNotPaused { game_id: String::new() },
+            Error::EmptyClubName,
+            Error::ClubNameTooLong { max: 0 },
+            Error::ClubFull { club_id: String::new() },
+            Error::NotInClub { account_id: String::new() },
+            Error::AlreadyInClub { account_id: String::new(), club_id: String::new() },
+            Error::DailyPlayed { player_id: String::new(), date: String::new() },
+            Error::OpenGamesLeft { game_ids: String::new() },
+            Error::FixedSeats { game_id: String::new() },
+            Error::YourTurn,
+            Error::AlreadyMoved { player_id: String::new(), game_id: String::new() },
+            Error::NotInactive { player_id: String::new() },
+            Error::HasSideBets { game_id: String::new() },
+            Error::NoFireSkill,
+            Error::TurnDownCount,
+            Error::TurnDownAttacker,
+            Error::TurnDownTooFar { index: 0 },
+            Error::NoTurnDown,
+            Error::BoostTooFar { index: 0 },
+            Error::WagerRequired { game_id: String::new(), stake: 0 },
+            Error::SessionKeyNotEd25519,
+            Error::SessionKeyRegistered { game_id: String::new() },
+            Error::SessionKeyNotRegistered { game_id: String::new() },
+            Error::PartialSignature,
+            Error::InvalidSessionKey,
+            Error::InvalidSessionSignature,
+            Error::InvalidSeed,
+            Error::ZeroLobbyTtl,
+            Error::ZeroWindowWiden,
+            Error::NoStateToMigrate,
+            Error::NoMigration,
+            Error::MigrationResumesAt { game_id: String::new() },
+            Error::EmptyPuzzle,
+            Error::InvalidDieValue,
+            Error::InvalidSolution,
+            Error::ForfeitPenaltyTooLow,
+            Error::AlreadyNominated { game_id: String::new() },
+            Error::NotNominated { game_id: String::new() },
+            Error::NoFinishedGame,
+            Error::AlreadyVoted { account_id: String::new(), game_id: String::new() },
+            Error::InvalidRoomName { name: String::new() },
+            Error::RoomExists { room_id: String::new() },
+            Error::NoRoomCode,
+            Error::InvalidPlayerIndex { player_index: 0 },
+            Error::OwnGameBet,
+            Error::ContractGameBet { game_id: String::new() },
+            Error::SideBetsLocked { game_id: String::new() },
+            Error::NoSideBet { game_id: String::new() },
+            Error::SideBetClaimed { game_id: String::new() },
+            Error::SideBetLost { game_id: String::new() },
+            Error::HasWager { game_id: String::new() },
+            Error::NotExpired { game_id: String::new() },
+            Error::NotSwiss { tournament_id: String::new() },
+            Error::TournamentNotRunning { tournament_id: String::new() },
+            Error::RoundInProgress { round: 0, tournament_id: String::new() },
+            Error::TitlesOutOfOrder,
+            Error::InvalidMaxPlayers { max: 0 },
+            Error::InvalidMatchLength,
+            Error::NoSwissRounds,
+            Error::InvalidPrizeSplit,
+            Error::TooManyPrizePlaces,
+            Error::RegistrationClosed { tournament_id: String::new() },
+            Error::AlreadyRegistered { player_id: String::new(), tournament_id: String::new() },
+            Error::TooEarlyToStart { tournament_id: String::new() },
+            Error::TournamentStarted { tournament_id: String::new() },
+            Error::NoPayout { tournament_id: String::new() },
+            Error::PayoutClaimed { tournament_id: String::new() },
+            Error::NoWinnings { account_id: String::new() },
+            Error::TooEarlyToReclaim { game_id: String::new() },
+            Error::NotStalled { game_id: String::new() },
+            Error::NotAbandoned { game_id: String::new() },
+            Error::StakeTooLow { stake: 0, min_stake: 0 },
+            Error::WagerSettled { game_id: String::new() },
+        ];
+        // No wildcard, so a new variant doesn't compile until it's named here too, next to the list it belongs in
+        for error in &variants {
+            match error {
+                Error::ContractPaused | Error::NotOwner { .. } | Error::NotLoggedIn | Error::InvalidAccountId { .. }
+                    | Error::GameNotFound { .. } | Error::NotJoined { .. } | Error::NotYourTurn
+                    | Error::GameNotActive { .. } | Error::GameNotStarted { .. } | Error::GameAlreadyStarted { .. }
+                    | Error::GameCancelled { .. } | Error::GameFinished { .. } | Error::GameNotFinished { .. }
+                    | Error::GamePaused { .. } | Error::VerifyingButtons { .. } | Error::TooManyAttackers
+                    | Error::AttackerNotFound { .. } | Error::DuplicateAttacker { .. }
+                    | Error::DefenderNotFound { .. } | Error::NoHint { .. } | Error::Fog { .. }
+                    | Error::NoWager { .. } | Error::InvalidDay { .. } | Error::ButtonNotFound { .. }
+                    | Error::ClubNotFound { .. } | Error::PuzzleNotFound { .. } | Error::TournamentNotFound { .. }
+                    | Error::InvalidDieSize { .. } | Error::AttackFailed | Error::PowerAttackDice
+                    | Error::SkillAttackDice | Error::AttackPossible { .. } | Error::NotUndoable
+                    | Error::NotRequested { .. } | Error::AlreadyJoined { .. } | Error::NotInvited { .. }
+                    | Error::GameFull { .. } | Error::GameNotOver { .. } | Error::NoAuxiliaryDice { .. }
+                    | Error::TooEarlyToResign { .. } | Error::CorruptedGame { .. } | Error::NotCreator { .. }
+                    | Error::NotAccountOwner { .. } | Error::NotOrganizer { .. } | Error::WrongDeposit { .. }
+                    | Error::DepositTooLow { .. } | Error::NoDeposit { .. } | Error::FeeTooHigh
+                    | Error::InsufficientTreasury { .. } | Error::DailyLimitReached { .. } | Error::SelfBlock
+                    | Error::TooManyBlocked { .. } | Error::SelfFollow | Error::TooManyFollowed { .. }
+                    | Error::SelfPair | Error::EmptyButtonName | Error::NoButtonDice | Error::TooManyDice { .. }
+                    | Error::ButtonExists { .. } | Error::PresetAndTimeControl | Error::InvalidBank { .. }
+                    | Error::InvalidIncrement { .. } | Error::NoClock { .. } | Error::OutOfTime { .. }
+                    | Error::TimeLeft { .. } | Error::OwnTurnTimeout | Error::AlreadyFinalized { .. }
+                    | Error::InvalidExtension { .. } | Error::ExtensionPending { .. }
+                    | Error::TooManyExtensions { .. } | Error::PausePending { .. } | Error::PausesUsedUp { .. }
+                    | Error::GameNotPaused { .. } | Error::EmptyClubName | Error::ClubNameTooLong { .. }
+                    | Error::ClubFull { .. } | Error::NotInClub { .. } | Error::AlreadyInClub { .. }
+                    | Error::DailyPlayed { .. } | Error::OpenGamesLeft { .. } | Error::FixedSeats { .. }
+                    | Error::YourTurn | Error::AlreadyMoved { .. } | Error::NotInactive { .. }
+                    | Error::HasSideBets { .. } | Error::NoFireSkill | Error::TurnDownCount | Error::TurnDownAttacker
+                    | Error::TurnDownTooFar { .. } | Error::NoTurnDown | Error::BoostTooFar { .. }
+                    | Error::WagerRequired { .. } | Error::SessionKeyNotEd25519 | Error::SessionKeyRegistered { .. }
+                    | Error::SessionKeyNotRegistered { .. } | Error::PartialSignature | Error::InvalidSessionKey
+                    | Error::InvalidSessionSignature | Error::InvalidSeed | Error::ZeroLobbyTtl
+                    | Error::ZeroWindowWiden | Error::NoStateToMigrate | Error::NoMigration
+                    | Error::MigrationResumesAt { .. } | Error::EmptyPuzzle | Error::InvalidDieValue
+                    | Error::InvalidSolution | Error::ForfeitPenaltyTooLow | Error::AlreadyNominated { .. }
+                    | Error::NotNominated { .. } | Error::NoFinishedGame | Error::AlreadyVoted { .. }
+                    | Error::InvalidRoomName { .. } | Error::RoomExists { .. } | Error::NoRoomCode
+                    | Error::InvalidPlayerIndex { .. } | Error::OwnGameBet | Error::ContractGameBet { .. }
+                    | Error::SideBetsLocked { .. }
+                    | Error::NoSideBet { .. } | Error::SideBetClaimed { .. } | Error::SideBetLost { .. }
+                    | Error::HasWager { .. } | Error::NotExpired { .. } | Error::NotSwiss { .. }
+                    | Error::TournamentNotRunning { .. } | Error::RoundInProgress { .. } | Error::TitlesOutOfOrder
+                    | Error::InvalidMaxPlayers { .. } | Error::InvalidMatchLength | Error::NoSwissRounds
+                    | Error::InvalidPrizeSplit | Error::TooManyPrizePlaces | Error::RegistrationClosed { .. }
+                    | Error::AlreadyRegistered { .. } | Error::TooEarlyToStart { .. }
+                    | Error::TournamentStarted { .. } | Error::NoPayout { .. } | Error::PayoutClaimed { .. }
+                    | Error::NoWinnings { .. } | Error::TooEarlyToReclaim { .. } | Error::NotStalled { .. }
+                    | Error::NotAbandoned { .. } | Error::StakeTooLow { .. } | Error::WagerSettled { .. } => {},
+            }
+        }
+        variants
+    }
+
+    /// Stable name of the failure, the `error` of a web4 error response
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::ContractPaused => "contract_paused",
+            Error::NotOwner { .. } => "not_owner",
+            Error::NotLoggedIn => "not_logged_in",
+            Error::InvalidAccountId { .. } => "invalid_account_id",
+            Error::GameNotFound { .. } => "game_not_found",
+            Error::NotJoined { .. } => "not_joined",
+            Error::NotYourTurn => "not_your_turn",
+            Error::GameNotActive { .. } => "game_not_active",
+            Error::GameNotStarted { .. } => "game_not_started",
+            Error::GameAlreadyStarted { .. } => "game_already_started",
+            Error::GameCancelled { .. } => "game_cancelled",
+            Error::GameFinished { .. } => "game_finished",
+            Error::GameNotFinished { .. } => "game_not_finished",
+            Error::GamePaused { .. } => "game_paused",
+            Error::VerifyingButtons { .. } => "verifying_buttons",
+            Error::TooManyAttackers => "too_many_attackers",
+            Error::AttackerNotFound { .. } => "attacker_not_found",
+            Error::DuplicateAttacker { .. } => "duplicate_attacker",
+            Error::DefenderNotFound { .. } => "defender_not_found",
+            Error::NoHint { .. } => "no_hint",
+            Error::Fog { .. } => "fog",
+            Error::NoWager { .. } => "no_wager",
+            Error::InvalidDay { .. } => "invalid_day",
+            Error::ButtonNotFound { .. } => "button_not_found",
+            Error::ClubNotFound { .. } => "club_not_found",
+            Error::PuzzleNotFound { .. } => "puzzle_not_found",
+            Error::TournamentNotFound { .. } => "tournament_not_found",
+            Error::InvalidDieSize { .. } => "invalid_die_size",
+            Error::AttackFailed => "attack_failed",
+            Error::PowerAttackDice => "power_attack_dice",
+            Error::SkillAttackDice => "skill_attack_dice",
+            Error::AttackPossible { .. } => "attack_possible",
+            Error::NotUndoable => "not_undoable",
+            Error::NotRequested { .. } => "not_requested",
+            Error::AlreadyJoined { .. } => "already_joined",
+            Error::NotInvited { .. } => "not_invited",
+            Error::GameFull { .. } => "game_full",
+            Error::GameNotOver { .. } => "game_not_over",
+            Error::NoAuxiliaryDice { .. } => "no_auxiliary_dice",
+            Error::TooEarlyToResign { .. } => "too_early_to_resign",
+            Error::CorruptedGame { .. } => "corrupted_game",
+            Error::NotCreator { .. } => "not_creator",
+            Error::NotAccountOwner { .. } => "not_account_owner",
+            Error::NotOrganizer { .. } => "not_organizer",
+            Error::WrongDeposit { .. } => "wrong_deposit",
+            Error::DepositTooLow { .. } => "deposit_too_low",
+            Error::NoDeposit { .. } => "no_deposit",
+            Error::FeeTooHigh => "fee_too_high",
+            Error::InsufficientTreasury { .. } => "insufficient_treasury",
+            Error::DailyLimitReached { .. } => "daily_limit_reached",
+            Error::SelfBlock => "self_block",
+            Error::TooManyBlocked { .. } => "too_many_blocked",
+            Error::SelfFollow => "self_follow",
+            Error::TooManyFollowed { .. } => "too_many_followed",
+            Error::SelfPair => "self_pair",
+            Error::EmptyButtonName => "empty_button_name",
+            Error::NoButtonDice => "no_button_dice",
+            Error::TooManyDice { .. } => "too_many_dice",
+            Error::ButtonExists { .. } => "button_exists",
+            Error::PresetAndTimeControl => "preset_and_time_control",
+            Error::InvalidBank { .. } => "invalid_bank",
+            Error::InvalidIncrement { .. } => "invalid_increment",
+            Error::NoClock { .. } => "no_clock",
+            Error::OutOfTime { .. } => "out_of_time",
+            Error::TimeLeft { .. } => "time_left",
+            Error::OwnTurnTimeout => "own_turn_timeout",
+            Error::AlreadyFinalized { .. } => "already_finalized",
+            Error::InvalidExtension { .. } => "invalid_extension",
+            Error::ExtensionPending { .. } => "extension_pending",
+            Error::TooManyExtensions { .. } => "too_many_extensions",
+            Error::PausePending { .. } => "pause_pending",
+            Error::PausesUsedUp { .. } => "pauses_used_up",
+            Error::GameNotPaused { .. } => "game_not_paused",
+            Error::EmptyClubName => "empty_club_name",
+            Error::ClubNameTooLong { .. } => "club_name_too_long",
+            Error::ClubFull { .. } => "club_full",
+            Error::NotInClub { .. } => "not_in_club",
+            Error::AlreadyInClub { .. } => "already_in_club",
+            Error::DailyPlayed { .. } => "daily_played",
+            Error::OpenGamesLeft { .. } => "open_games_left",
+            Error::FixedSeats { .. } => "fixed_seats",
+            Error::YourTurn => "your_turn",
+            Error::AlreadyMoved { .. } => "already_moved",
+            Error::NotInactive { .. } => "not_inactive",
+            Error::HasSideBets { .. } => "has_side_bets",
+            Error::NoFireSkill => "no_fire_skill",
+            Error::TurnDownCount => "turn_down_count",
+            Error::TurnDownAttacker => "turn_down_attacker",
+            Error::TurnDownTooFar { .. } => "turn_down_too_far",
+            Error::NoTurnDown => "no_turn_down",
+            Error::BoostTooFar { .. } => "boost_too_far",
+            Error::WagerRequired { .. } => "wager_required",
+            Error::SessionKeyNotEd25519 => "session_key_not_ed25519",
+            Error::SessionKeyRegistered { .. } => "session_key_registered",
+            Error::SessionKeyNotRegistered { .. } => "session_key_not_registered",
+            Error::PartialSignature => "partial_signature",
+            Error::InvalidSessionKey => "invalid_session_key",
+            Error::InvalidSessionSignature => "invalid_session_signature",
+            Error::InvalidSeed => "invalid_seed",
+            Error::ZeroLobbyTtl => "zero_lobby_ttl",
+            Error::ZeroWindowWiden => "zero_window_widen",
+            Error::NoStateToMigrate => "no_state_to_migrate",
+            Error::NoMigration => "no_migration",
+            Error::MigrationResumesAt { .. } => "migration_resumes_at",
+            Error::EmptyPuzzle => "empty_puzzle",
+            Error::InvalidDieValue => "invalid_die_value",
+            Error::InvalidSolution => "invalid_solution",
+            Error::ForfeitPenaltyTooLow => "forfeit_penalty_too_low",
+            Error::AlreadyNominated { .. } => "already_nominated",
+            Error::NotNominated { .. } => "not_nominated",
+            Error::NoFinishedGame => "no_finished_game",
+            Error::AlreadyVoted { .. } => "already_voted",
+            Error::InvalidRoomName { .. } => "invalid_room_name",
+            Error::RoomExists { .. } => "room_exists",
+            Error::NoRoomCode => "no_room_code",
+            Error::InvalidPlayerIndex { .. } => "invalid_player_index",
+            Error::OwnGameBet => "own_game_bet",
+            Error::ContractGameBet { .. } => "contract_game_bet",
+            Error::SideBetsLocked { .. } => "side_bets_locked",
+            Error::NoSideBet { .. } => "no_side_bet",
+            Error::SideBetClaimed { .. } => "side_bet_claimed",
+            Error::SideBetLost { .. } => "side_bet_lost",
+            Error::HasWager { .. } => "has_wager",
+            Error::NotExpired { .. } => "not_expired",
+            Error::NotSwiss { .. } => "not_swiss",
+            Error::TournamentNotRunning { .. } => "tournament_not_running",
+            Error::RoundInProgress { .. } => "round_in_progress",
+            Error::TitlesOutOfOrder => "titles_out_of_order",
+            Error::InvalidMaxPlayers { .. } => "invalid_max_players",
+            Error::InvalidMatchLength => "invalid_match_length",
+            Error::NoSwissRounds => "no_swiss_rounds",
+            Error::InvalidPrizeSplit => "invalid_prize_split",
+            Error::TooManyPrizePlaces => "too_many_prize_places",
+            Error::RegistrationClosed { .. } => "registration_closed",
+            Error::AlreadyRegistered { .. } => "already_registered",
+            Error::TooEarlyToStart { .. } => "too_early_to_start",
+            Error::TournamentStarted { .. } => "tournament_started",
+            Error::NoPayout { .. } => "no_payout",
+            Error::PayoutClaimed { .. } => "payout_claimed",
+            Error::NoWinnings { .. } => "no_winnings",
+            Error::TooEarlyToReclaim { .. } => "too_early_to_reclaim",
+            Error::NotStalled { .. } => "not_stalled",
+            Error::NotAbandoned { .. } => "not_abandoned",
+            Error::StakeTooLow { .. } => "stake_too_low",
+            Error::WagerSettled { .. } => "wager_settled",
+        }
+    }
+
+    /// HTTP status of a web4 error response
+    pub fn status(&self) -> u16 {
+        match self {
+            Error::InvalidAccountId { .. } | Error::InvalidDay { .. } | Error::TooManyAttackers | Error::AttackerNotFound { .. } | Error::DuplicateAttacker { .. }
+                | Error::DefenderNotFound { .. } | Error::InvalidDieSize { .. } | Error::AttackFailed
+                | Error::PowerAttackDice | Error::SkillAttackDice | Error::WrongDeposit { .. } | Error::DepositTooLow { .. }
+                | Error::NoDeposit { .. } | Error::FeeTooHigh | Error::DailyLimitReached { .. } | Error::SelfBlock
+                | Error::SelfFollow | Error::SelfPair | Error::EmptyButtonName | Error::NoButtonDice | Error::TooManyDice { .. }
+                | Error::PresetAndTimeControl | Error::InvalidBank { .. } | Error::InvalidIncrement { .. }
+                | Error::InvalidExtension { .. } | Error::EmptyClubName | Error::ClubNameTooLong { .. } | Error::NoFireSkill
+                | Error::TurnDownCount | Error::TurnDownAttacker | Error::TurnDownTooFar { .. } | Error::NoTurnDown
+                | Error::BoostTooFar { .. } | Error::SessionKeyNotEd25519 | Error::PartialSignature
+                | Error::InvalidSessionKey | Error::InvalidSeed | Error::ZeroLobbyTtl | Error::ZeroWindowWiden
+                | Error::EmptyPuzzle | Error::InvalidDieValue | Error::InvalidSolution | Error::ForfeitPenaltyTooLow
+                | Error::InvalidRoomName { .. } | Error::InvalidPlayerIndex { .. } | Error::TitlesOutOfOrder
+                | Error::InvalidMaxPlayers { .. } | Error::InvalidMatchLength | Error::NoSwissRounds
+                | Error::InvalidPrizeSplit | Error::TooManyPrizePlaces | Error::StakeTooLow { .. } => 400,
+            Error::NotLoggedIn => 401,
+            Error::NotOwner { .. } | Error::NotJoined { .. } | Error::GameNotActive { .. } | Error::NoHint { .. }
+                | Error::Fog { .. } | Error::NotInvited { .. } | Error::NotCreator { .. } | Error::NotAccountOwner { .. }
+                | Error::NotOrganizer { .. } | Error::InvalidSessionSignature | Error::NoFinishedGame | Error::OwnGameBet
+                | Error::ContractGameBet { .. } => 403,
+            Error::GameNotFound { .. } | Error::NoWager { .. } | Error::ButtonNotFound { .. }
+                | Error::ClubNotFound { .. } | Error::PuzzleNotFound { .. } | Error::TournamentNotFound { .. }
+                | Error::SessionKeyNotRegistered { .. } | Error::NotNominated { .. } | Error::NoSideBet { .. }
+                | Error::NoPayout { .. } | Error::NoWinnings { .. } => 404,
+            Error::NotYourTurn | Error::GameNotStarted { .. } | Error::GameAlreadyStarted { .. }
+                | Error::GameCancelled { .. } | Error::GameFinished { .. } | Error::GameNotFinished { .. }
+                | Error::GamePaused { .. } | Error::VerifyingButtons { .. } | Error::AttackPossible { .. }
+                | Error::NotUndoable | Error::NotRequested { .. } | Error::AlreadyJoined { .. } | Error::GameFull { .. }
+                | Error::GameNotOver { .. } | Error::NoAuxiliaryDice { .. } | Error::TooEarlyToResign { .. }
+                | Error::InsufficientTreasury { .. } | Error::TooManyBlocked { .. } | Error::TooManyFollowed { .. }
+                | Error::ButtonExists { .. } | Error::NoClock { .. } | Error::OutOfTime { .. } | Error::TimeLeft { .. }
+                | Error::OwnTurnTimeout | Error::AlreadyFinalized { .. } | Error::ExtensionPending { .. }
+                | Error::TooManyExtensions { .. } | Error::PausePending { .. } | Error::PausesUsedUp { .. }
+                | Error::GameNotPaused { .. } | Error::ClubFull { .. } | Error::NotInClub { .. }
+                | Error::AlreadyInClub { .. } | Error::DailyPlayed { .. } | Error::OpenGamesLeft { .. }
+                | Error::FixedSeats { .. } | Error::YourTurn | Error::AlreadyMoved { .. } | Error::NotInactive { .. }
+                | Error::HasSideBets { .. } | Error::WagerRequired { .. } | Error::SessionKeyRegistered { .. }
+                | Error::AlreadyNominated { .. } | Error::AlreadyVoted { .. } | Error::RoomExists { .. }
+                | Error::SideBetsLocked { .. } | Error::SideBetClaimed { .. } | Error::SideBetLost { .. }
+                | Error::HasWager { .. } | Error::NotExpired { .. } | Error::NotSwiss { .. }
+                | Error::TournamentNotRunning { .. } | Error::RoundInProgress { .. } | Error::RegistrationClosed { .. }
+                | Error::AlreadyRegistered { .. } | Error::TooEarlyToStart { .. } | Error::TournamentStarted { .. }
+                | Error::PayoutClaimed { .. } | Error::TooEarlyToReclaim { .. } | Error::NotStalled { .. }
+                | Error::NotAbandoned { .. } | Error::WagerSettled { .. } | Error::NoMigration
+                | Error::MigrationResumesAt { .. } => 409,
+            Error::CorruptedGame { .. } | Error::NoStateToMigrate | Error::NoRoomCode => 500,
+            Error::ContractPaused => 503,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        self.render(|_, value| value)
+    }
+
+    /// The message with its values left as `{name}` placeholders
+    pub fn template(&self) -> String {
+        self.render(|name, _| format!("{{{}}}", name))
+    }
+
+    /// The message, with each value written out by value(name, value)
+    fn render(&self, value: impl Fn(&str, String) -> String) -> String {
+        match self {
+            Error::ContractPaused => "Contract is paused".to_string(),
+            Error::NotOwner { action } => format!("Only owner can {}", value("action", action.to_string())),
+            Error::NotLoggedIn => "Log in first".to_string(),
+            Error::InvalidAccountId { account_id } => format!("Invalid account id: {}", value("account_id", account_id.to_string())),
+            Error::GameNotFound { game_id } => format!("Game not found: {}", value("game_id", game_id.to_string())),
+            Error::NotJoined { player_id, game_id } => format!("Player {} has not joined game {}", value("player_id", player_id.to_string()), value("game_id", game_id.to_string())),
+            Error::NotYourTurn => "It is not your turn".to_string(),
+            Error::GameNotActive { game_id } => format!("Game {} is not being played", value("game_id", game_id.to_string())),
+            Error::GameNotStarted { game_id } => format!("Game {} has not started", value("game_id", game_id.to_string())),
+            Error::GameAlreadyStarted { game_id } => format!("Game {} has already started", value("game_id", game_id.to_string())),
+            Error::GameCancelled { game_id } => format!("Game {} is cancelled", value("game_id", game_id.to_string())),
+            Error::GameFinished { game_id } => format!("Game {} is finished", value("game_id", game_id.to_string())),
+            Error::GameNotFinished { game_id } => format!("Game {} is not finished", value("game_id", game_id.to_string())),
+            Error::GamePaused { game_id } => format!("Game {} is paused", value("game_id", game_id.to_string())),
+            Error::VerifyingButtons { game_id } => format!("Game {} is verifying button ownership", value("game_id", game_id.to_string())),
+            Error::TooManyAttackers => "More attacking dice than you have".to_string(),
+            Error::AttackerNotFound { index } => format!("You have no die {}", value("index", index.to_string())),
+            Error::DuplicateAttacker { index } => format!("Die {} can only attack once", value("index", index.to_string())),
+            Error::DefenderNotFound { index } => format!("Your opponent has no die {}", value("index", index.to_string())),
+            Error::NoHint { game_id } => format!("Hints are off in game {}", value("game_id", game_id.to_string())),
+            Error::Fog { game_id } => format!("Game {} keeps its moves hidden until it's finished", value("game_id", game_id.to_string())),
+            Error::NoWager { game_id } => format!("Game {} has no wager", value("game_id", game_id.to_string())),
+            Error::InvalidDay { day } => format!("Day must be YYYYMMDD, not {}", value("day", day.to_string())),
+            Error::ButtonNotFound { button } => format!("Button not found: {}", value("button", button.to_string())),
+            Error::ClubNotFound { club_id } => format!("Club not found: {}", value("club_id", club_id.to_string())),
+            Error::PuzzleNotFound { puzzle_id } => format!("Puzzle not found: {}", value("puzzle_id", puzzle_id.to_string())),
+            Error::TournamentNotFound { tournament_id } => format!("Tournament not found: {}", value("tournament_id", tournament_id.to_string())),
+            Error::InvalidDieSize { size } => format!("Invalid die size: d{}", value("size", size.to_string())),
+            Error::AttackFailed => "Attack failed".to_string(),
+            Error::PowerAttackDice => "Power attack takes exactly one die".to_string(),
+            Error::SkillAttackDice => "Skill attack takes at least one die".to_string(),
+            Error::AttackPossible { attack } => format!("{} attack is possible", value("attack", attack.to_string())),
+            Error::NotUndoable => "Only the last move can be undone, by the player who made it".to_string(),
+            Error::NotRequested { request, game_id } => format!("No {} requested by the opponent in game {}", value("request", request.to_string()), value("game_id", game_id.to_string())),
+            Error::AlreadyJoined { player_id, game_id } => format!("Player {} has already joined game {}", value("player_id", player_id.to_string()), value("game_id", game_id.to_string())),
+            Error::NotInvited { game_id, player_id } => format!("Game {} is not open to {}", value("game_id", game_id.to_string()), value("player_id", player_id.to_string())),
+            Error::GameFull { game_id } => format!("Game is full: {}", value("game_id", game_id.to_string())),
+            Error::GameNotOver { game_id } => format!("Game {} is not over yet", value("game_id", game_id.to_string())),
+            Error::NoAuxiliaryDice { game_id } => format!("Game {} has no auxiliary dice to agree on", value("game_id", game_id.to_string())),
+            Error::TooEarlyToResign { game_id, turns } => format!("Game {} can't be resigned before {} turns have been played", value("game_id", game_id.to_string()), value("turns", turns.to_string())),
+            Error::CorruptedGame { game_id, expected, players, dice, captured } => format!("Game {} is corrupted: expected {} players, dice and captured lists, found {}, {} and {}", value("game_id", game_id.to_string()), value("expected", expected.to_string()), value("players", players.to_string()), value("dice", dice.to_string()), value("captured", captured.to_string())),
+            Error::NotCreator { action } => format!("Only the creator can {}", value("action", action.to_string())),
+            Error::NotAccountOwner { action } => format!("Only the account owner can {}", value("action", action.to_string())),
+            Error::NotOrganizer { tournament_id } => format!("Only the organizer can cancel tournament {}", value("tournament_id", tournament_id.to_string())),
+            Error::WrongDeposit { amount, action } => format!("Attach exactly {} yoctoNEAR to {}", value("amount", amount.to_string()), value("action", action.to_string())),
+            Error::DepositTooLow { amount, action } => format!("Attach at least {} yoctoNEAR to {}", value("amount", amount.to_string()), value("action", action.to_string())),
+            Error::NoDeposit { action } => format!("Attach a deposit to {}", value("action", action.to_string())),
+            Error::FeeTooHigh => "Fee can't exceed 100%".to_string(),
+            Error::InsufficientTreasury { treasury } => format!("Treasury only holds {} yoctoNEAR", value("treasury", treasury.to_string())),
+            Error::DailyLimitReached { free_games, fee } => format!("Free daily limit of {} games reached, attach {} yoctoNEAR to create a game", value("free_games", free_games.to_string()), value("fee", fee.to_string())),
+            Error::SelfBlock => "Can't block yourself".to_string(),
+            Error::TooManyBlocked { max } => format!("Can't block more than {} accounts", value("max", max.to_string())),
+            Error::SelfFollow => "Can't follow yourself".to_string(),
+            Error::TooManyFollowed { max } => format!("Can't follow more than {} accounts", value("max", max.to_string())),
+            Error::SelfPair => "Can't pair an account with itself".to_string(),
+            Error::EmptyButtonName => "Button name can't be empty".to_string(),
+            Error::NoButtonDice => "Button needs at least one die".to_string(),
+            Error::TooManyDice { max } => format!("At most {} dice a side", value("max", max.to_string())),
+            Error::ButtonExists { button } => format!("Button already exists: {}", value("button", button.to_string())),
+            Error::PresetAndTimeControl => "Pick a time preset or a custom time control, not both".to_string(),
+            Error::InvalidBank { min, max } => format!("Bank must be between {} and {} ns", value("min", min.to_string()), value("max", max.to_string())),
+            Error::InvalidIncrement { max } => format!("Increment can't be over {} ns", value("max", max.to_string())),
+            Error::NoClock { game_id } => format!("Game {} has no clock", value("game_id", game_id.to_string())),
+            Error::OutOfTime { player_id, game_id } => format!("{} ran out of time in game {}", value("player_id", player_id.to_string()), value("game_id", game_id.to_string())),
+            Error::TimeLeft { player_id, game_id } => format!("{} still has time left in game {}", value("player_id", player_id.to_string()), value("game_id", game_id.to_string())),
+            Error::OwnTurnTimeout => "Can't claim a timeout on your own turn".to_string(),
+            Error::AlreadyFinalized { game_id } => format!("Game {} is already finalized", value("game_id", game_id.to_string())),
+            Error::InvalidExtension { max } => format!("Extension must be between 1 and {} ns", value("max", max.to_string())),
+            Error::ExtensionPending { game_id } => format!("An extension is already pending in game {}", value("game_id", game_id.to_string())),
+            Error::TooManyExtensions { player_id, max, game_id } => format!("{} already asked for {} extensions in game {}", value("player_id", player_id.to_string()), value("max", max.to_string()), value("game_id", game_id.to_string())),
+            Error::PausePending { game_id } => format!("A pause is already pending in game {}", value("game_id", game_id.to_string())),
+            Error::PausesUsedUp { game_id, max_pause_ns } => format!("Game {} has used up its {} ns of pauses", value("game_id", game_id.to_string()), value("max_pause_ns", max_pause_ns.to_string())),
+            Error::GameNotPaused { game_id } => format!("Game {} is not paused", value("game_id", game_id.to_string())),
+            Error::EmptyClubName => "Club name can't be empty".to_string(),
+            Error::ClubNameTooLong { max } => format!("Club name can't be longer than {} bytes", value("max", max.to_string())),
+            Error::ClubFull { club_id } => format!("Club {} is full", value("club_id", club_id.to_string())),
+            Error::NotInClub { account_id } => format!("{} is not in a club", value("account_id", account_id.to_string())),
+            Error::AlreadyInClub { account_id, club_id } => format!("{} is already in club {}", value("account_id", account_id.to_string()), value("club_id", club_id.to_string())),
+            Error::DailyPlayed { player_id, date } => format!("{} already played the daily challenge for {}", value("player_id", player_id.to_string()), value("date", date.to_string())),
+            Error::OpenGamesLeft { game_ids } => format!("Finish or resign these games first: {}", value("game_ids", game_ids.to_string())),
+            Error::FixedSeats { game_id } => format!("Game {} has fixed seats", value("game_id", game_id.to_string())),
+            Error::YourTurn => "It is your turn".to_string(),
+            Error::AlreadyMoved { player_id, game_id } => format!("{} has already moved in game {}", value("player_id", player_id.to_string()), value("game_id", game_id.to_string())),
+            Error::NotInactive { player_id } => format!("{} is not inactive yet", value("player_id", player_id.to_string())),
+            Error::HasSideBets { game_id } => format!("Game {} has side bets on it", value("game_id", game_id.to_string())),
+            Error::NoFireSkill => "Fire attack needs a button with the Fire skill".to_string(),
+            Error::TurnDownCount => "Give a turn down amount for each of your dice".to_string(),
+            Error::TurnDownAttacker => "The attacking die can't be turned down".to_string(),
+            Error::TurnDownTooFar { index } => format!("Die {} can't be turned down below 1", value("index", index.to_string())),
+            Error::NoTurnDown => "Turn down at least one die".to_string(),
+            Error::BoostTooFar { index } => format!("Die {} can't be boosted above its size", value("index", index.to_string())),
+            Error::WagerRequired { game_id, stake } => format!("Game {} requires a wager of {} yoctoNEAR, use join_wagered_game", value("game_id", game_id.to_string()), value("stake", stake.to_string())),
+            Error::SessionKeyNotEd25519 => "Session key must be an ed25519 key".to_string(),
+            Error::SessionKeyRegistered { game_id } => format!("Session key is already registered for game {}", value("game_id", game_id.to_string())),
+            Error::SessionKeyNotRegistered { game_id } => format!("Session key is not registered for game {}", value("game_id", game_id.to_string())),
+            Error::PartialSignature => "signature and signer_pk must be provided together".to_string(),
+            Error::InvalidSessionKey => "Invalid session key".to_string(),
+            Error::InvalidSessionSignature => "Invalid session signature".to_string(),
+            Error::InvalidSeed => "Seed must be 32 bytes".to_string(),
+            Error::ZeroLobbyTtl => "Lobby TTL can't be 0".to_string(),
+            Error::ZeroWindowWiden => "Window can't widen every 0 ns".to_string(),
+            Error::NoStateToMigrate => "No state to migrate".to_string(),
+            Error::NoMigration => "No migration in progress".to_string(),
+            Error::MigrationResumesAt { game_id } => format!("Migration resumes at game {}", value("game_id", game_id.to_string())),
+            Error::EmptyPuzzle => "Puzzle needs dice on both sides".to_string(),
+            Error::InvalidDieValue => "Die values must be between 1 and the die's size".to_string(),
+            Error::InvalidSolution => "Solution doesn't capture every die".to_string(),
+            Error::ForfeitPenaltyTooLow => "Forfeit penalty can't be smaller than a normal loss".to_string(),
+            Error::AlreadyNominated { game_id } => format!("Game {} is already nominated", value("game_id", game_id.to_string())),
+            Error::NotNominated { game_id } => format!("Game {} is not nominated", value("game_id", game_id.to_string())),
+            Error::NoFinishedGame => "Finish a game before voting".to_string(),
+            Error::AlreadyVoted { account_id, game_id } => format!("{} already voted for game {}", value("account_id", account_id.to_string()), value("game_id", game_id.to_string())),
+            Error::InvalidRoomName { name } => format!("Invalid room name: {}", value("name", name.to_string())),
+            Error::RoomExists { room_id } => format!("Room {} already exists", value("room_id", room_id.to_string())),
+            Error::NoRoomCode => "Room code is not set".to_string(),
+            Error::InvalidPlayerIndex { player_index } => format!("Invalid player index: {}", value("player_index", player_index.to_string())),
+            Error::OwnGameBet => "Players can't bet on their own game".to_string(),
+            Error::ContractGameBet { game_id } => format!("Game {} is against the contract, so it takes no bets", value("game_id", game_id.to_string())),
+            Error::SideBetsLocked { game_id } => format!("Side bets on game {} are locked", value("game_id", game_id.to_string())),
+            Error::NoSideBet { game_id } => format!("No side bet on game {}", value("game_id", game_id.to_string())),
+            Error::SideBetClaimed { game_id } => format!("Side bet on game {} is already claimed", value("game_id", game_id.to_string())),
+            Error::SideBetLost { game_id } => format!("Side bet on game {} lost", value("game_id", game_id.to_string())),
+            Error::HasWager { game_id } => format!("Game {} has a wager, use reclaim_wager", value("game_id", game_id.to_string())),
+            Error::NotExpired { game_id } => format!("Game {} has not expired yet", value("game_id", game_id.to_string())),
+            Error::NotSwiss { tournament_id } => format!("Tournament {} isn't a Swiss tournament", value("tournament_id", tournament_id.to_string())),
+            Error::TournamentNotRunning { tournament_id } => format!("Tournament {} is not running", value("tournament_id", tournament_id.to_string())),
+            Error::RoundInProgress { round, tournament_id } => format!("Round {} of tournament {} is still being played", value("round", round.to_string()), value("tournament_id", tournament_id.to_string())),
+            Error::TitlesOutOfOrder => "Titles must go up in rating".to_string(),
+            Error::InvalidMaxPlayers { max } => format!("Tournament needs 2 to {} players", value("max", max.to_string())),
+            Error::InvalidMatchLength => "Matches need at least one game".to_string(),
+            Error::NoSwissRounds => "Swiss tournament needs at least one round".to_string(),
+            Error::InvalidPrizeSplit => "Prize split must add up to 100%".to_string(),
+            Error::TooManyPrizePlaces => "Prize split can't have more places than players".to_string(),
+            Error::RegistrationClosed { tournament_id } => format!("Registration for tournament {} is closed", value("tournament_id", tournament_id.to_string())),
+            Error::AlreadyRegistered { player_id, tournament_id } => format!("{} is already registered for tournament {}", value("player_id", player_id.to_string()), value("tournament_id", tournament_id.to_string())),
+            Error::TooEarlyToStart { tournament_id } => format!("Tournament {} can't start before its start time", value("tournament_id", tournament_id.to_string())),
+            Error::TournamentStarted { tournament_id } => format!("Tournament {} has already started", value("tournament_id", tournament_id.to_string())),
+            Error::NoPayout { tournament_id } => format!("Nothing to claim from tournament {}", value("tournament_id", tournament_id.to_string())),
+            Error::PayoutClaimed { tournament_id } => format!("Payout from tournament {} is already claimed", value("tournament_id", tournament_id.to_string())),
+            Error::NoWinnings { account_id } => format!("No winnings to withdraw for {}", value("account_id", account_id.to_string())),
+            Error::TooEarlyToReclaim { game_id } => format!("Wager for game {} can't be reclaimed yet", value("game_id", game_id.to_string())),
+            Error::NotStalled { game_id } => format!("Game {} is not stalled yet", value("game_id", game_id.to_string())),
+            Error::NotAbandoned { game_id } => format!("Game {} is not abandoned yet", value("game_id", game_id.to_string())),
+            Error::StakeTooLow { stake, min_stake } => format!("Stake of {} yoctoNEAR is below the minimum of {} yoctoNEAR", value("stake", stake.to_string()), value("min_stake", min_stake.to_string())),
+            Error::WagerSettled { game_id } => format!("Wager for game {} is already settled", value("game_id", game_id.to_string())),
+        }
+    }
+}
+
+/// Aborts the call with the error's message
+pub(crate) fn fail(error: Error) -> ! {
+    panic!("{}", error.message())
+}
+
+/// Web4 response carrying the error's code and status
+pub(crate) fn error_response(error: Error) -> Web4Response {
+    Web4Response::Body {
+        content_type: "application/json".to_owned(),
+        body: serde_json::to_vec(&serde_json::json!({ "error": error.code() })).unwrap().into(),
+        status: Some(error.status()),
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Every error the contract reports, for clients to map codes to their own messages
+    pub fn list_error_codes(&self) -> Vec<ErrorCode> {
+        Error::variants().iter().map(|error| ErrorCode {
+            code: error.code().to_string(),
+            status: error.status(),
+            message: error.template(),
+        }).collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_owner(&self, action: &str) {
+        if !self.is_owner() {
+            fail(Error::NotOwner { action: action.to_string() });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_as, call_at, login_as, request_path};
+    use crate::tournament::{CreateTournamentOptions, Payout};
+    use std::collections::HashSet;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    type Call = fn(&mut Contract);
+
+    const ROOM_DEPOSIT: Balance = 5_000_000_000_000_000_000_000_000;
+    const GAME_CREATION_FEE: Balance = 10_000_000_000_000_000_000_000;
+    /// Compressed ed25519 base point, a valid public key nobody signs with here
+    const ED25519_BASEPOINT: [u8; 32] = [
+        0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+        0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    ];
+
+    fn game(id: &str, status: GameStatus) -> Game {
+        Game {
+            id: id.to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status,
+            dice: vec![vec![Die { size: 20, value: 20 }; 3], vec![Die { size: 20, value: 1 }; 3]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        }
+    }
+
+    /// 1 bob.near to move against alice.near, 2 waiting for alice.near's auxiliary choice, 3 cancelled, 4 finished,
+    /// 5 holding a seat while bob.near's button is checked, 6 fogged
+    fn prepared_contract() -> Contract {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &game("1", GameStatus::Active));
+        contract.games.insert(&"2".to_string(), &Game { auxiliary_choices: vec![Some(true), None], ..game("2", GameStatus::Waiting) });
+        contract.games.insert(&"3".to_string(), &game("3", GameStatus::Cancelled));
+        contract.games.insert(&"4".to_string(), &game("4", GameStatus::Finished));
+        contract.games.insert(&"5".to_string(), &Game {
+            players: vec!["bob.near".to_string(), "".to_string()],
+            verifying: true,
+            ..game("5", GameStatus::Waiting)
+        });
+        contract.games.insert(&"6".to_string(), &Game { fog: true, ..game("6", GameStatus::Active) });
+        contract
+    }
+
+    fn update_game(contract: &mut Contract, game_id: &str, update: impl FnOnce(&mut Game)) {
+        let mut game = contract.games.get(&game_id.to_string()).unwrap();
+        update(&mut game);
+        contract.games.insert(&game.id, &game);
+    }
+
+    fn time_control(bank_ns: u64, increment_ns: u64) -> TimeControl {
+        TimeControl { bank_ns, increment_ns, per_move: false }
+    }
+
+    /// Puts game 1 on a clock with bob.near, on turn since 0, having remaining_ns left
+    fn clocked(game: &mut Game, remaining_ns: u64) {
+        game.time_control = Some(time_control(DAY_NS, 0));
+        game.remaining_ns = vec![remaining_ns, DAY_NS];
+    }
+
+    /// Gives bob.near a button with the Fire skill in game 1
+    fn fire_button(contract: &mut Contract) {
+        login_as("alice.near");
+        contract.register_button("Blaze".to_string(), vec![20, 20, 20], vec![fire::FIRE_SKILL.to_string()]);
+        update_game(contract, "1", |game| game.buttons = vec!["Blaze".to_string(), DEFAULT_BUTTON.to_string()]);
+        login_as("bob.near");
+    }
+
+    fn session_key(curve: CurveType, key: Vec<u8>) -> PublicKey {
+        let mut data = vec![curve as u8];
+        data.extend(key);
+        PublicKey::try_from(data).unwrap()
+    }
+
+    fn tournament_options(max_players: u32) -> CreateTournamentOptions {
+        CreateTournamentOptions {
+            name: "Cup".to_string(),
+            max_players,
+            entry_fee: None,
+            match_length: None,
+            starts_at: None,
+            swiss_rounds: None,
+            prize_split: None,
+        }
+    }
+
+    /// Tournament 1 for two players organized by bob.near, registered by the given players. Leaves the caller
+    /// logged in as it was.
+    fn tournament(contract: &mut Contract, swiss_rounds: Option<u32>, players: &[&str]) {
+        let caller = env::predecessor_account_id();
+        login_as("bob.near");
+        contract.create_tournament(CreateTournamentOptions { swiss_rounds, ..tournament_options(2) });
+        for player_id in players {
+            login_as(player_id);
+            contract.register("1".to_string());
+        }
+        login_as(caller.as_str());
+    }
+
+    fn panic_message(call: impl FnOnce()) -> String {
+        let payload = catch_unwind(AssertUnwindSafe(call)).expect_err("The call should have failed");
+        match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => payload.downcast::<&str>().unwrap().to_string(),
+        }
+    }
+
+    fn game_id(id: &str) -> String {
+        id.to_string()
+    }
+
+    #[test]
+    fn every_error_reachable() {
+        let panics: Vec<(Error, &str, Call)> = vec![
+            (Error::ContractPaused, "bob.near", |contract| {
+                contract.config.paused = true;
+                contract.create_game(None);
+            }),
+            (Error::NotOwner { action: "set the lobby TTL".to_string() }, "bob.near", |contract| contract.set_lobby_ttl(1)),
+            (Error::GameNotFound { game_id: game_id("9") }, "bob.near", |contract| { contract.attack(game_id("9"), vec![0], 0, None, None, None); }),
+            (Error::NotJoined { player_id: "carol.near".to_string(), game_id: game_id("1") }, "carol.near", |contract| { contract.attack(game_id("1"), vec![0], 0, None, None, None); }),
+            (Error::NotYourTurn, "alice.near", |contract| { contract.attack(game_id("1"), vec![0], 0, None, None, None); }),
+            (Error::TooManyAttackers, "bob.near", |contract| { contract.attack(game_id("1"), vec![0; 4], 0, None, None, None); }),
+            (Error::AttackerNotFound { index: 3 }, "bob.near", |contract| { contract.attack(game_id("1"), vec![3], 0, None, None, None); }),
+            (Error::DuplicateAttacker { index: 0 }, "bob.near", |contract| { contract.attack(game_id("1"), vec![0, 0], 0, None, None, None); }),
+            (Error::DefenderNotFound { index: 3 }, "bob.near", |contract| { contract.attack(game_id("1"), vec![0], 3, None, None, None); }),
+            (Error::GameNotStarted { game_id: game_id("2") }, "bob.near", |contract| { contract.attack(game_id("2"), vec![0], 0, None, None, None); }),
+            (Error::GameAlreadyStarted { game_id: game_id("1") }, "bob.near", |contract| contract.accept_auxiliary(game_id("1"), true)),
+            (Error::GameCancelled { game_id: game_id("3") }, "bob.near", |contract| { contract.attack(game_id("3"), vec![0], 0, None, None, None); }),
+            (Error::GameFinished { game_id: game_id("4") }, "bob.near", |contract| { contract.attack(game_id("4"), vec![0], 0, None, None, None); }),
+            (Error::GamePaused { game_id: game_id("1") }, "bob.near", |contract| {
+                let mut game = contract.games.get(&game_id("1")).unwrap();
+                game.pause = Some(Pause { started_at: 0, ends_at: DAY_NS });
+                contract.games.insert(&game.id, &game);
+                contract.attack(game_id("1"), vec![0], 0, None, None, None);
+            }),
+            (Error::VerifyingButtons { game_id: game_id("5") }, "alice.near", |contract| contract.join_game(game_id("5"), None)),
+            (Error::NoHint { game_id: game_id("1") }, "bob.near", |contract| { contract.get_hint(game_id("1")); }),
+            (Error::NoWager { game_id: game_id("1") }, "alice.near", |contract| contract.join_wagered_game(game_id("1"), None)),
+            (Error::InvalidDay { day: "today".to_string() }, "bob.near", |contract| { contract.get_games_by_day("today".to_string()); }),
+            (Error::ButtonNotFound { button: "Nobody".to_string() }, "alice.near", |contract| contract.set_button_nft_gate("Nobody".to_string(), None)),
+            (Error::ClubNotFound { club_id: "9".to_string() }, "bob.near", |contract| contract.join_club("9".to_string())),
+            (Error::PuzzleNotFound { puzzle_id: "9".to_string() }, "bob.near", |contract| { contract.solve_puzzle("9".to_string(), vec![]); }),
+            (Error::TournamentNotFound { tournament_id: "9".to_string() }, "bob.near", |contract| contract.register("9".to_string())),
+            (Error::InvalidDieSize { size: 7 }, "alice.near", |contract| contract.register_button("Odd".to_string(), vec![7], vec![])),
+            (Error::AttackFailed, "bob.near", |contract| { contract.attack(game_id("1"), vec![0], 0, None, None, Some(AttackType::Skill)); }),
+            (Error::PowerAttackDice, "bob.near", |contract| { contract.attack(game_id("1"), vec![0, 1], 0, None, None, Some(AttackType::Power)); }),
+            (Error::SkillAttackDice, "bob.near", |contract| { contract.attack(game_id("1"), vec![], 0, None, None, Some(AttackType::Skill)); }),
+            (Error::AttackPossible { attack: "Power".to_string() }, "bob.near", |contract| contract.pass(game_id("1"), None, None)),
+            (Error::NotUndoable, "bob.near", |contract| contract.request_undo(game_id("1"))),
+            (Error::NotRequested { request: "undo".to_string(), game_id: game_id("1") }, "bob.near", |contract| contract.approve_undo(game_id("1"))),
+            (Error::AlreadyJoined { player_id: "bob.near".to_string(), game_id: game_id("1") }, "bob.near", |contract| contract.join_game(game_id("1"), None)),
+            (Error::NotInvited { game_id: game_id("5"), player_id: "carol.near".to_string() }, "carol.near", |contract| {
+                login_as("bob.near");
+                contract.block_player("carol.near".parse().unwrap());
+                login_as("carol.near");
+                contract.join_game(game_id("5"), None);
+            }),
+            (Error::GameFull { game_id: game_id("1") }, "carol.near", |contract| contract.join_game(game_id("1"), None)),
+            (Error::GameNotOver { game_id: game_id("1") }, "carol.near", |contract| contract.claim_side_bet(game_id("1"))),
+            (Error::NoAuxiliaryDice { game_id: game_id("5") }, "bob.near", |contract| contract.accept_auxiliary(game_id("5"), true)),
+            (Error::TooEarlyToResign { game_id: game_id("1"), turns: 1 }, "bob.near", |contract| {
+                contract.config.min_turns_before_resign = 1;
+                contract.resign(game_id("1"));
+            }),
+            (Error::CorruptedGame { game_id: game_id("1"), expected: 2, players: 2, dice: 1, captured: 2 }, "bob.near", |contract| {
+                update_game(contract, "1", |game| { game.dice.pop(); });
+                contract.attack(game_id("1"), vec![0], 0, None, None, None);
+            }),
+            (Error::NotCreator { action: "evict a player".to_string() }, "alice.near", |contract| contract.evict_inactive(game_id("1"))),
+            (Error::NotAccountOwner { action: "clear notifications".to_string() }, "bob.near", |contract| contract.clear_notifications("carol.near".to_string())),
+            (Error::NotOrganizer { tournament_id: "1".to_string() }, "carol.near", |contract| {
+                tournament(contract, None, &[]);
+                contract.cancel_tournament("1".to_string());
+            }),
+            (Error::WrongDeposit { amount: 0, action: "create a game".to_string() }, "bob.near", |contract| {
+                call_as("bob.near", 0, 1);
+                contract.create_game(None);
+            }),
+            (Error::DepositTooLow { amount: ROOM_DEPOSIT, action: "create a room".to_string() }, "alice.near", |contract| contract.create_room("lobby".to_string())),
+            (Error::NoDeposit { action: "bet on the game".to_string() }, "carol.near", |contract| contract.place_side_bet(game_id("1"), 0)),
+            (Error::FeeTooHigh, "alice.near", |contract| contract.set_wager_fee(10_001)),
+            (Error::InsufficientTreasury { treasury: 0 }, "alice.near", |contract| contract.withdraw_treasury("carol.near".parse().unwrap(), U128(1))),
+            (Error::DailyLimitReached { free_games: 0, fee: GAME_CREATION_FEE }, "bob.near", |contract| {
+                contract.config.free_games_per_day = 0;
+                contract.create_game(None);
+            }),
+            (Error::SelfBlock, "bob.near", |contract| contract.block_player("bob.near".parse().unwrap())),
+            (Error::TooManyBlocked { max: 100 }, "bob.near", |contract| {
+                for index in 0..=100 {
+                    contract.block_player(format!("player{}.near", index).parse().unwrap());
+                }
+            }),
+            (Error::SelfFollow, "bob.near", |contract| contract.follow("bob.near".parse().unwrap())),
+            (Error::TooManyFollowed { max: 100 }, "bob.near", |contract| {
+                for index in 0..=100 {
+                    contract.follow(format!("player{}.near", index).parse().unwrap());
+                }
+            }),
+            (Error::SelfPair, "alice.near", |contract| { contract.get_or_create_for_pair("bob.near".parse().unwrap(), "bob.near".parse().unwrap()); }),
+            (Error::EmptyButtonName, "alice.near", |contract| contract.register_button("".to_string(), vec![6], vec![])),
+            (Error::NoButtonDice, "alice.near", |contract| contract.register_button("Empty".to_string(), vec![], vec![])),
+            (Error::TooManyDice { max: 10 }, "alice.near", |contract| contract.register_button("Horde".to_string(), vec![6; 11], vec![])),
+            (Error::ButtonExists { button: DEFAULT_BUTTON.to_string() }, "alice.near", |contract| contract.register_button(DEFAULT_BUTTON.to_string(), vec![6], vec![])),
+            (Error::PresetAndTimeControl, "bob.near", |contract| {
+                contract.create_game(Some(CreateGameOptions { time_preset: Some(TimePreset::Blitz), time_control: Some(time_control(DAY_NS, 0)), ..Default::default() }));
+            }),
+            (Error::InvalidBank { min: 60000000000, max: 30 * DAY_NS }, "bob.near", |contract| {
+                contract.create_game(Some(CreateGameOptions { time_control: Some(time_control(0, 0)), ..Default::default() }));
+            }),
+            (Error::InvalidIncrement { max: DAY_NS }, "bob.near", |contract| {
+                contract.create_game(Some(CreateGameOptions { time_control: Some(time_control(DAY_NS, 2 * DAY_NS)), ..Default::default() }));
+            }),
+            (Error::NoClock { game_id: game_id("1") }, "bob.near", |contract| contract.request_extension(game_id("1"), 1)),
+            (Error::OutOfTime { player_id: "bob.near".to_string(), game_id: game_id("1") }, "bob.near", |contract| {
+                update_game(contract, "1", |game| clocked(game, 0));
+                contract.request_extension(game_id("1"), 1);
+            }),
+            (Error::TimeLeft { player_id: "bob.near".to_string(), game_id: game_id("1") }, "alice.near", |contract| {
+                update_game(contract, "1", |game| clocked(game, DAY_NS));
+                contract.claim_timeout_win(game_id("1"));
+            }),
+            (Error::OwnTurnTimeout, "bob.near", |contract| contract.claim_timeout_win(game_id("1"))),
+            (Error::AlreadyFinalized { game_id: game_id("4") }, "bob.near", |contract| contract.finalize_expired(game_id("4"))),
+            (Error::InvalidExtension { max: 7 * DAY_NS }, "bob.near", |contract| {
+                update_game(contract, "1", |game| clocked(game, DAY_NS));
+                contract.request_extension(game_id("1"), 0);
+            }),
+            (Error::ExtensionPending { game_id: game_id("1") }, "bob.near", |contract| {
+                update_game(contract, "1", |game| {
+                    clocked(game, DAY_NS);
+                    game.extension_requested = Some(1);
+                });
+                contract.request_extension(game_id("1"), 1);
+            }),
+            (Error::TooManyExtensions { player_id: "bob.near".to_string(), max: 2, game_id: game_id("1") }, "bob.near", |contract| {
+                update_game(contract, "1", |game| {
+                    clocked(game, DAY_NS);
+                    game.history = vec![MoveRecord::new(0, MoveKind::ExtensionRequest); 2];
+                });
+                contract.request_extension(game_id("1"), 1);
+            }),
+            (Error::PausePending { game_id: game_id("1") }, "bob.near", |contract| {
+                update_game(contract, "1", |game| game.pause_requested_by = Some(1));
+                contract.request_pause(game_id("1"));
+            }),
+            (Error::PausesUsedUp { game_id: game_id("1"), max_pause_ns: 0 }, "bob.near", |contract| {
+                contract.config.max_pause_ns = 0;
+                contract.request_pause(game_id("1"));
+            }),
+            (Error::GameNotPaused { game_id: game_id("1") }, "bob.near", |contract| contract.resume(game_id("1"))),
+            (Error::EmptyClubName, "bob.near", |contract| { contract.create_club("".to_string()); }),
+            (Error::ClubNameTooLong { max: 32 }, "bob.near", |contract| { contract.create_club("x".repeat(33)); }),
+            (Error::ClubFull { club_id: "1".to_string() }, "carol.near", |contract| {
+                login_as("bob.near");
+                contract.create_club("Full".to_string());
+                for index in 1..50 {
+                    login_as(&format!("member{}.near", index));
+                    contract.join_club("1".to_string());
+                }
+                login_as("carol.near");
+                contract.join_club("1".to_string());
+            }),
+            (Error::NotInClub { account_id: "bob.near".to_string() }, "bob.near", |contract| contract.leave_club()),
+            (Error::AlreadyInClub { account_id: "bob.near".to_string(), club_id: "1".to_string() }, "bob.near", |contract| {
+                contract.create_club("First".to_string());
+                contract.create_club("Second".to_string());
+            }),
+            (Error::DailyPlayed { player_id: "bob.near".to_string(), date: "1970-01-01".to_string() }, "bob.near", |contract| {
+                contract.start_daily();
+                contract.start_daily();
+            }),
+            (Error::OpenGamesLeft { game_ids: game_id("1") }, "bob.near", |contract| {
+                contract.create_game(None);
+                contract.remove_my_data();
+            }),
+            (Error::FixedSeats { game_id: game_id("1") }, "bob.near", |contract| {
+                update_game(contract, "1", |game| game.tournament_id = Some("1".to_string()));
+                contract.evict_inactive(game_id("1"));
+            }),
+            (Error::YourTurn, "bob.near", |contract| contract.evict_inactive(game_id("1"))),
+            (Error::AlreadyMoved { player_id: "alice.near".to_string(), game_id: game_id("1") }, "bob.near", |contract| {
+                update_game(contract, "1", |game| {
+                    game.current_player = 1;
+                    game.history = vec![MoveRecord::new(1, MoveKind::Pass)];
+                });
+                contract.evict_inactive(game_id("1"));
+            }),
+            (Error::NotInactive { player_id: "alice.near".to_string() }, "bob.near", |contract| {
+                update_game(contract, "1", |game| game.current_player = 1);
+                contract.evict_inactive(game_id("1"));
+            }),
+            (Error::HasSideBets { game_id: game_id("1") }, "bob.near", |contract| {
+                update_game(contract, "1", |game| game.current_player = 1);
+                call_as("carol.near", 0, 1);
+                contract.place_side_bet(game_id("1"), 0);
+                call_at("bob.near", DAY_NS);
+                contract.evict_inactive(game_id("1"));
+            }),
+            (Error::NoFireSkill, "bob.near", |contract| {
+                update_game(contract, "1", |game| game.buttons = vec![DEFAULT_BUTTON.to_string(); 2]);
+                contract.fire_attack(game_id("1"), 0, 0, vec![0, 1, 0]);
+            }),
+            (Error::TurnDownCount, "bob.near", |contract| {
+                fire_button(contract);
+                contract.fire_attack(game_id("1"), 0, 0, vec![]);
+            }),
+            (Error::TurnDownAttacker, "bob.near", |contract| {
+                fire_button(contract);
+                contract.fire_attack(game_id("1"), 0, 0, vec![1, 0, 0]);
+            }),
+            (Error::TurnDownTooFar { index: 1 }, "bob.near", |contract| {
+                fire_button(contract);
+                contract.fire_attack(game_id("1"), 0, 0, vec![0, 20, 0]);
+            }),
+            (Error::NoTurnDown, "bob.near", |contract| {
+                fire_button(contract);
+                contract.fire_attack(game_id("1"), 0, 0, vec![0, 0, 0]);
+            }),
+            (Error::BoostTooFar { index: 0 }, "bob.near", |contract| {
+                fire_button(contract);
+                contract.fire_attack(game_id("1"), 0, 0, vec![0, 1, 0]);
+            }),
+            (Error::WagerRequired { game_id: game_id("5"), stake: 5 }, "carol.near", |contract| {
+                contract.wagers.insert(&game_id("5"), &Wager { stake: U128(5), settled: false });
+                contract.join_game(game_id("5"), None);
+            }),
+            (Error::SessionKeyNotEd25519, "bob.near", |contract| contract.register_session_key(game_id("1"), session_key(CurveType::SECP256K1, vec![1; 64]))),
+            (Error::SessionKeyRegistered { game_id: game_id("1") }, "bob.near", |contract| {
+                contract.register_session_key(game_id("1"), session_key(CurveType::ED25519, ED25519_BASEPOINT.to_vec()));
+                contract.register_session_key(game_id("1"), session_key(CurveType::ED25519, ED25519_BASEPOINT.to_vec()));
+            }),
+            (Error::SessionKeyNotRegistered { game_id: game_id("1") }, "carol.near", |contract| {
+                contract.attack(game_id("1"), vec![0], 0, Some(vec![0; 64].into()), Some(session_key(CurveType::ED25519, ED25519_BASEPOINT.to_vec())), None);
+            }),
+            (Error::PartialSignature, "carol.near", |contract| { contract.attack(game_id("1"), vec![0], 0, Some(vec![0; 64].into()), None, None); }),
+            (Error::InvalidSessionKey, "carol.near", |contract| {
+                login_as("bob.near");
+                contract.register_session_key(game_id("1"), session_key(CurveType::ED25519, vec![2; 32]));
+                login_as("carol.near");
+                contract.attack(game_id("1"), vec![0], 0, Some(vec![0; 64].into()), Some(session_key(CurveType::ED25519, vec![2; 32])), None);
+            }),
+            (Error::InvalidSessionSignature, "carol.near", |contract| {
+                login_as("bob.near");
+                contract.register_session_key(game_id("1"), session_key(CurveType::ED25519, ED25519_BASEPOINT.to_vec()));
+                login_as("carol.near");
+                contract.attack(game_id("1"), vec![0], 0, Some(vec![0; 64].into()), Some(session_key(CurveType::ED25519, ED25519_BASEPOINT.to_vec())), None);
+            }),
+            (Error::InvalidSeed, "alice.near", |contract| contract.reseed_game(game_id("1"), vec![0; 3].into())),
+            (Error::ZeroLobbyTtl, "alice.near", |contract| contract.set_lobby_ttl(0)),
+            (Error::ZeroWindowWiden, "alice.near", |contract| contract.set_matchmaking_config(100, 0)),
+            (Error::NoStateToMigrate, "alice.near", |_| { Contract::migrate(); }),
+            (Error::NoMigration, "alice.near", |contract| { contract.migrate_games(1, 10); }),
+            (Error::MigrationResumesAt { game_id: game_id("1") }, "alice.near", |contract| {
+                contract.migration = Some(Migration { next_game_id: 1, last_game_id: 3, started_at: 0 });
+                contract.migrate_games(2, 10);
+            }),
+            (Error::EmptyPuzzle, "alice.near", |contract| { contract.add_puzzle(vec![], vec![], "".to_string()); }),
+            (Error::InvalidDieValue, "alice.near", |contract| {
+                contract.add_puzzle(vec![vec![Die { size: 6, value: 0 }], vec![Die { size: 6, value: 1 }]], vec![], "".to_string());
+            }),
+            (Error::InvalidSolution, "alice.near", |contract| {
+                contract.add_puzzle(vec![vec![Die { size: 6, value: 1 }], vec![Die { size: 6, value: 1 }]], vec![], "".to_string());
+            }),
+            (Error::ForfeitPenaltyTooLow, "alice.near", |contract| contract.set_forfeit_penalty_multiplier(99)),
+            (Error::AlreadyNominated { game_id: game_id("4") }, "bob.near", |contract| {
+                contract.nominate_replay(game_id("4"));
+                contract.nominate_replay(game_id("4"));
+            }),
+            (Error::NotNominated { game_id: game_id("4") }, "bob.near", |contract| contract.vote_replay(game_id("4"))),
+            (Error::NoFinishedGame, "carol.near", |contract| {
+                login_as("bob.near");
+                contract.nominate_replay(game_id("4"));
+                login_as("carol.near");
+                contract.vote_replay(game_id("4"));
+            }),
+            (Error::AlreadyVoted { account_id: "bob.near".to_string(), game_id: game_id("4") }, "bob.near", |contract| {
+                contract.first_finished_at.insert(&"bob.near".to_string(), &0);
+                contract.nominate_replay(game_id("4"));
+                contract.vote_replay(game_id("4"));
+                contract.vote_replay(game_id("4"));
+            }),
+            (Error::InvalidRoomName { name: "Lobby!".to_string() }, "alice.near", |contract| {
+                call_as("alice.near", 0, ROOM_DEPOSIT);
+                contract.create_room("Lobby!".to_string());
+            }),
+            (Error::RoomExists { room_id: "lobby.alice.near".to_string() }, "alice.near", |contract| {
+                contract.rooms.push(&"lobby.alice.near".parse().unwrap());
+                call_as("alice.near", 0, ROOM_DEPOSIT);
+                contract.create_room("lobby".to_string());
+            }),
+            (Error::NoRoomCode, "alice.near", |contract| {
+                call_as("alice.near", 0, ROOM_DEPOSIT);
+                contract.create_room("lobby".to_string());
+            }),
+            (Error::InvalidPlayerIndex { player_index: 2 }, "carol.near", |contract| {
+                call_as("carol.near", 0, 1);
+                contract.place_side_bet(game_id("1"), 2);
+            }),
+            (Error::OwnGameBet, "bob.near", |contract| {
+                call_as("bob.near", 0, 1);
+                contract.place_side_bet(game_id("1"), 0);
+            }),
+            (Error::ContractGameBet { game_id: game_id("1") }, "carol.near", |contract| {
+                update_game(contract, "1", |game| game.daily = Some("2026-10-17".to_string()));
+                call_as("carol.near", 0, 1);
+                contract.place_side_bet(game_id("1"), 0);
+            }),
+            (Error::SideBetsLocked { game_id: game_id("1") }, "carol.near", |contract| {
+                contract.config.side_bet_lock_after_moves = 0;
+                call_as("carol.near", 0, 1);
+                contract.place_side_bet(game_id("1"), 0);
+            }),
+            (Error::NoSideBet { game_id: game_id("4") }, "carol.near", |contract| contract.claim_side_bet(game_id("4"))),
+            (Error::SideBetClaimed { game_id: game_id("1") }, "carol.near", |contract| {
+                call_as("carol.near", 0, 1);
+                contract.place_side_bet(game_id("1"), 0);
+                update_game(contract, "1", |game| {
+                    game.status = GameStatus::Finished;
+                    game.winner = Some(0);
+                });
+                contract.claim_side_bet(game_id("1"));
+                contract.claim_side_bet(game_id("1"));
+            }),
+            (Error::SideBetLost { game_id: game_id("1") }, "carol.near", |contract| {
+                call_as("dave.near", 0, 1);
+                contract.place_side_bet(game_id("1"), 0);
+                call_as("carol.near", 0, 1);
+                contract.place_side_bet(game_id("1"), 1);
+                update_game(contract, "1", |game| {
+                    game.status = GameStatus::Finished;
+                    game.winner = Some(0);
+                });
+                contract.claim_side_bet(game_id("1"));
+            }),
+            (Error::HasWager { game_id: game_id("2") }, "bob.near", |contract| {
+                contract.wagers.insert(&game_id("2"), &Wager { stake: U128(5), settled: false });
+                contract.expire_game(game_id("2"));
+            }),
+            (Error::NotExpired { game_id: game_id("2") }, "bob.near", |contract| contract.expire_game(game_id("2"))),
+            (Error::NotSwiss { tournament_id: "1".to_string() }, "bob.near", |contract| {
+                tournament(contract, None, &["bob.near", "carol.near"]);
+                contract.start_next_round("1".to_string());
+            }),
+            (Error::TournamentNotRunning { tournament_id: "1".to_string() }, "bob.near", |contract| {
+                tournament(contract, Some(1), &[]);
+                contract.start_next_round("1".to_string());
+            }),
+            (Error::RoundInProgress { round: 1, tournament_id: "1".to_string() }, "bob.near", |contract| {
+                tournament(contract, Some(1), &["bob.near", "carol.near"]);
+                contract.start_next_round("1".to_string());
+            }),
+            (Error::TitlesOutOfOrder, "alice.near", |contract| {
+                contract.set_rating_titles(vec![
+                    RatingTitle { min_rating: 1600, title: "Expert".to_string() },
+                    RatingTitle { min_rating: 1400, title: "Novice".to_string() },
+                ]);
+            }),
+            (Error::InvalidMaxPlayers { max: 64 }, "bob.near", |contract| { contract.create_tournament(tournament_options(1)); }),
+            (Error::InvalidMatchLength, "bob.near", |contract| { contract.create_tournament(CreateTournamentOptions { match_length: Some(0), ..tournament_options(2) }); }),
+            (Error::NoSwissRounds, "bob.near", |contract| { contract.create_tournament(CreateTournamentOptions { swiss_rounds: Some(0), ..tournament_options(2) }); }),
+            (Error::InvalidPrizeSplit, "bob.near", |contract| { contract.create_tournament(CreateTournamentOptions { prize_split: Some(vec![50]), ..tournament_options(2) }); }),
+            (Error::TooManyPrizePlaces, "bob.near", |contract| { contract.create_tournament(CreateTournamentOptions { prize_split: Some(vec![50, 30, 20]), ..tournament_options(2) }); }),
+            (Error::RegistrationClosed { tournament_id: "1".to_string() }, "dave.near", |contract| {
+                tournament(contract, None, &["bob.near", "carol.near"]);
+                contract.register("1".to_string());
+            }),
+            (Error::AlreadyRegistered { player_id: "bob.near".to_string(), tournament_id: "1".to_string() }, "bob.near", |contract| {
+                tournament(contract, None, &["bob.near"]);
+                contract.register("1".to_string());
+            }),
+            (Error::TooEarlyToStart { tournament_id: "1".to_string() }, "bob.near", |contract| {
+                tournament(contract, None, &[]);
+                contract.start_tournament("1".to_string());
+            }),
+            (Error::TournamentStarted { tournament_id: "1".to_string() }, "bob.near", |contract| {
+                tournament(contract, None, &["bob.near", "carol.near"]);
+                contract.cancel_tournament("1".to_string());
+            }),
+            (Error::NoPayout { tournament_id: "1".to_string() }, "bob.near", |contract| {
+                tournament(contract, None, &[]);
+                contract.claim_tournament_payout("1".to_string());
+            }),
+            (Error::PayoutClaimed { tournament_id: "1".to_string() }, "bob.near", |contract| {
+                tournament(contract, None, &[]);
+                let mut tournament = contract.tournaments.get(&"1".to_string()).unwrap();
+                tournament.payouts.push(Payout { account_id: "bob.near".to_string(), amount: U128(1), claimed: true });
+                contract.tournaments.insert(&tournament.id, &tournament);
+                contract.claim_tournament_payout("1".to_string());
+            }),
+            (Error::NoWinnings { account_id: "bob.near".to_string() }, "bob.near", |contract| { contract.withdraw_winnings(); }),
+            (Error::TooEarlyToReclaim { game_id: game_id("5") }, "bob.near", |contract| {
+                update_game(contract, "5", |game| game.verifying = false);
+                contract.wagers.insert(&game_id("5"), &Wager { stake: U128(5), settled: false });
+                contract.reclaim_wager(game_id("5"));
+            }),
+            (Error::NotStalled { game_id: game_id("1") }, "bob.near", |contract| {
+                contract.wagers.insert(&game_id("1"), &Wager { stake: U128(5), settled: false });
+                contract.refund_stalled_wager(game_id("1"));
+            }),
+            (Error::NotAbandoned { game_id: game_id("1") }, "alice.near", |contract| {
+                contract.wagers.insert(&game_id("1"), &Wager { stake: U128(5), settled: false });
+                contract.sweep_abandoned_wager(game_id("1"), "carol.near".parse().unwrap());
+            }),
+            (Error::StakeTooLow { stake: 5, min_stake: 10 }, "bob.near", |contract| {
+                contract.config.min_stake = U128(10);
+                call_as("bob.near", 0, 5);
+                contract.create_wagered_game(None);
+            }),
+            (Error::WagerSettled { game_id: game_id("1") }, "bob.near", |contract| {
+                contract.wagers.insert(&game_id("1"), &Wager { stake: U128(5), settled: true });
+                contract.refund_stalled_wager(game_id("1"));
+            }),
+        ];
+        let responses = vec![
+            (Error::NotLoggedIn, "/api/my/feed"),
+            (Error::InvalidAccountId { account_id: "Bob".to_string() }, "/api/users/Bob/awaiting"),
+            (Error::GameNotActive { game_id: game_id("2") }, "/api/games/2/odds"),
+            (Error::Fog { game_id: game_id("6") }, "/api/games/6/moves"),
+            (Error::GameNotFinished { game_id: game_id("1") }, "/api/games/1/result"),
+        ];
+
+        let mut reached = HashSet::new();
+        for (error, account_id, call) in panics {
+            // Each call starts from empty storage, whatever the calls before it left there
+            near_sdk::mock::with_mocked_blockchain(|blockchain| blockchain.take_storage());
+            login_as(account_id);
+            let mut contract = prepared_contract();
+            assert_eq!(panic_message(|| call(&mut contract)), error.message(), "{}", error.code());
+            reached.insert(error.code());
+        }
+        for (error, path) in responses {
+            let contract = prepared_contract();
+            assert_eq!(contract.web4_get(request_path(path)), error_response(error.clone()), "{}", error.code());
+            reached.insert(error.code());
+        }
+
+        let codes = Error::variants().iter().map(|error| error.code()).collect::<HashSet<&str>>();
+        assert_eq!(codes.len(), Error::variants().len());
+        assert_eq!(reached, codes);
+    }
+
+    /// Failures have to go through fail with an Error, so each has a code clients can rely on. Only the test
+    /// modules, from the first `#[cfg(test)]` of a file on, can panic on their own. Unwrapping and indexing into a
+    /// request path's parts panic just the same; the one unwrap allowed is of serializing, which can't fail.
+    #[test]
+    fn no_free_form_failures() {
+        const PANICKING: [&str; 8] = ["panic!(", "require!(", "assert!(", "assert_eq!(", "unreachable!(", "panic_str(", ".expect(", "parts["];
+        const SERIALIZING: [&str; 2] = ["to_vec(", "try_from_slice("];
+        let src = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        for entry in std::fs::read_dir(src).unwrap() {
+            let path = entry.unwrap().path();
+            let source = std::fs::read_to_string(&path).unwrap();
+            let code = source.split("#[cfg(test)]").next().unwrap();
+            for (index, line) in code.lines().enumerate() {
+                let serializing = SERIALIZING.iter().map(|serializing| line.matches(serializing).count()).sum::<usize>();
+                let free_form = (PANICKING.iter().any(|panicking| line.contains(panicking)) && line.trim() != "panic!(\"{}\", error.message())")
+                    || line.matches(".unwrap()").count() > serializing;
+                assert!(!free_form, "{}:{} fails without an Error: {}", path.display(), index + 1, line.trim());
+            }
+        }
+    }
+
+    #[test]
+    fn list_error_codes() {
+        let contract = Contract::default();
+        let codes = contract.list_error_codes();
+        assert_eq!(codes.len(), Error::variants().len());
+        assert_eq!(codes[5], ErrorCode {
+            code: "not_joined".to_string(),
+            status: 403,
+            message: "Player {player_id} has not joined game {game_id}".to_string(),
+        });
+        assert_eq!(codes.iter().find(|code| code.code == "too_many_dice").unwrap().message, "At most {max} dice a side");
+    }
+}
@@ -0,0 +1,141 @@
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Frees the seat of a player who joined but never moved, once they kept the game waiting for
+    /// ContractConfig.evict_after_ns. The round is undone and the game goes back to waiting for an opponent.
+    pub fn evict_inactive(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let account_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+        pauses::assert_running(&mut game);
+        if game.players[0] != account_id {
+            fail(Error::NotCreator { action: "evict a player".to_string() });
+        }
+        if game.tournament_id.is_some() || game.against_contract() {
+            fail(Error::FixedSeats { game_id: game_id.to_string() });
+        }
+        if game.current_player != 1 {
+            fail(Error::YourTurn);
+        }
+        if game.history.iter().any(|record| record.player == 1) {
+            fail(Error::AlreadyMoved { player_id: game.players[1].to_string(), game_id: game_id.to_string() });
+        }
+        if env::block_timestamp() < game.last_move_at + self.config.evict_after_ns {
+            fail(Error::NotInactive { player_id: game.players[1].to_string() });
+        }
+        if self.side_bets.get(&game_id).is_some() {
+            fail(Error::HasSideBets { game_id: game_id.to_string() });
+        }
+
+        let evicted = std::mem::take(&mut game.players[1]);
+        remove_user_game(evicted.clone(), &game_id);
+        self.refund_released_seat(&game_id, &evicted);
+
+        let button = self.find_button(&game.buttons[0]).unwrap_or_else(|| fail(Error::ButtonNotFound { button: game.buttons[0].to_string() }));
+        game.buttons[1] = "".to_string();
+        game.dice = vec![button.dice.iter().map(|size| Die { size: *size, value: 0 }).collect(), vec![]];
+        game.captured = vec![vec![], vec![]];
+        game.history = vec![];
+        game.auxiliary_choices = vec![];
+        game.undo_requested_by = None;
+        game.round_seed = vec![];
+        game.current_player = 0;
+        game.status = GameStatus::Waiting;
+        self.save_game(&game);
+        if !game.unlisted {
+            self.add_open_game(&game_id);
+        }
+
+        emit_event("player_evicted", serde_json::json!({
+            "game_id": game_id,
+            "account_id": evicted,
+        }));
+    }
+
+    pub fn set_evict_after(&mut self, evict_after_ns: u64) -> () {
+        self.assert_owner("set the eviction timeout");
+
+        self.config.evict_after_ns = evict_after_ns;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_at, login_as};
+
+    /// bob.near's game, joined by carol.near who is to move
+    fn joined_game(contract: &mut Contract) -> String {
+        call_at("bob.near", 0);
+        let game_id = contract.create_game(None);
+        call_at("carol.near", 0);
+        contract.join_game(game_id.clone(), None);
+
+        let mut game = contract.games.get(&game_id).unwrap();
+        game.current_player = 1;
+        contract.games.insert(&game_id, &game);
+        return game_id;
+    }
+
+    #[test]
+    fn evicted_seat_can_be_joined() {
+        let mut contract = Contract::default();
+        let game_id = joined_game(&mut contract);
+
+        call_at("bob.near", DAY_NS);
+        contract.evict_inactive(game_id.clone());
+
+        let game = contract.games.get(&game_id).unwrap();
+        assert_eq!(game.players, vec!["bob.near".to_string(), "".to_string()]);
+        assert_eq!(game.buttons, vec!["Standard".to_string(), "".to_string()]);
+        assert_eq!(game.dice[1], vec![]);
+        assert_eq!(game.status, GameStatus::Waiting);
+        assert!(get_user_games("carol.near".to_string()).is_empty());
+        assert_eq!(contract.get_open_games().last().unwrap().game_id, game_id);
+
+        call_at("dave.near", DAY_NS);
+        contract.join_game(game_id.clone(), None);
+        let game = contract.games.get(&game_id).unwrap();
+        assert_eq!(game.players, vec!["bob.near".to_string(), "dave.near".to_string()]);
+        assert_eq!(game.status, GameStatus::Active);
+        assert_eq!(game.dice[1].len(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "carol.near is not inactive yet")]
+    fn evict_too_early() {
+        let mut contract = Contract::default();
+        let game_id = joined_game(&mut contract);
+
+        call_at("bob.near", DAY_NS - 1);
+        contract.evict_inactive(game_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "carol.near has already moved in game 1")]
+    fn evict_after_moving() {
+        let mut contract = Contract::default();
+        let game_id = joined_game(&mut contract);
+        let mut game = contract.games.get(&game_id).unwrap();
+        game.history.push(MoveRecord::new(1, MoveKind::Pass));
+        contract.games.insert(&game_id, &game);
+
+        call_at("bob.near", DAY_NS);
+        contract.evict_inactive(game_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the creator can evict a player")]
+    fn evict_not_creator() {
+        let mut contract = Contract::default();
+        let game_id = joined_game(&mut contract);
+
+        login_as("dave.near");
+        contract.evict_inactive(game_id);
+    }
+}
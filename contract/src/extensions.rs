@@ -0,0 +1,187 @@
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+/// Most time one extension can add
+const MAX_EXTENSION_NS: u64 = 7 * DAY_NS;
+/// Extensions each player can ask for in a game
+const MAX_EXTENSION_REQUESTS: usize = 2;
+
+#[near_bindgen]
+impl Contract {
+    /// Asks the opponent for extra_ns more on the caller's clock, see grant_extension. Only on the caller's turn,
+    /// and it lapses when they move. Until it's granted their clock keeps running and the opponent can still claim the timeout.
+    pub fn request_extension(&mut self, game_id: String, extra_ns: u64) -> () {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+        let player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() }));
+        pauses::assert_running(&mut game);
+        if game.time_control.is_none() {
+            fail(Error::NoClock { game_id: game_id.to_string() });
+        }
+        if game.current_player as usize != player_index {
+            fail(Error::NotYourTurn);
+        }
+        if extra_ns == 0 || extra_ns > MAX_EXTENSION_NS {
+            fail(Error::InvalidExtension { max: MAX_EXTENSION_NS });
+        }
+        if game.extension_requested.is_some() {
+            fail(Error::ExtensionPending { game_id: game_id.to_string() });
+        }
+        let requests = game.history.iter().filter(|record| record.player == player_index as u8 && record.kind == MoveKind::ExtensionRequest).count();
+        if requests >= MAX_EXTENSION_REQUESTS {
+            fail(Error::TooManyExtensions { player_id: player_id.to_string(), max: MAX_EXTENSION_REQUESTS, game_id: game_id.to_string() });
+        }
+        if clocks::time_left(&game, player_index) == 0 {
+            fail(Error::OutOfTime { player_id: player_id.to_string(), game_id: game_id.to_string() });
+        }
+
+        game.extension_requested = Some(extra_ns);
+        game.history.push(MoveRecord::new(player_index as u8, MoveKind::ExtensionRequest));
+        self.save_game(&game);
+
+        notify(&game.players[(player_index + 1) % 2], "extension_requested", &game_id, &player_id);
+    }
+
+    /// Adds the time the player on turn asked for with request_extension to their clock
+    pub fn grant_extension(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+        let player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() }));
+        pauses::assert_running(&mut game);
+        let extra_ns = match game.extension_requested {
+            Some(extra_ns) if game.current_player as usize != player_index => extra_ns,
+            _ => fail(Error::NotRequested { request: "extension".to_string(), game_id: game_id.to_string() }),
+        };
+
+        let requester = game.current_player as usize;
+        game.remaining_ns[requester] += extra_ns;
+        game.extension_requested = None;
+        game.history.push(MoveRecord::new(player_index as u8, MoveKind::ExtensionGrant));
+        self.save_game(&game);
+
+        emit_event("extension_granted", serde_json::json!({
+            "game_id": game_id,
+            "player": requester,
+            "extra_ns": extra_ns,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::call_at;
+
+    const HOUR_NS: u64 = 60 * 60 * 1_000_000_000;
+
+    /// bob.near to move on a day's bank, with a d20 that can take alice.near's d4s
+    fn clocked_game(contract: &mut Contract) {
+        call_at("bob.near", 0);
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 20 }], vec![Die { size: 4, value: 1 }, Die { size: 4, value: 1 }]],
+            captured: vec![vec![], vec![]],
+            time_control: Some(TimeControl { bank_ns: DAY_NS, increment_ns: 0, per_move: false }),
+            remaining_ns: vec![DAY_NS, DAY_NS],
+            ..Default::default()
+        });
+    }
+
+    fn kinds(contract: &Contract) -> Vec<(u8, MoveKind)> {
+        contract.games.get(&"1".to_string()).unwrap().history.iter().map(|record| (record.player, record.kind)).collect()
+    }
+
+    #[test]
+    fn extension_granted() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        call_at("bob.near", 20 * HOUR_NS);
+        contract.request_extension("1".to_string(), 2 * DAY_NS);
+        let view = contract.game_view(&contract.games.get(&"1".to_string()).unwrap(), None);
+        assert_eq!(view.extension_requested, Some(2 * DAY_NS));
+
+        call_at("alice.near", 21 * HOUR_NS);
+        contract.grant_extension("1".to_string());
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.extension_requested, None);
+        assert_eq!(game.remaining_ns, vec![3 * DAY_NS, DAY_NS]);
+        assert_eq!(kinds(&contract), vec![(0, MoveKind::ExtensionRequest), (1, MoveKind::ExtensionGrant)]);
+
+        // Past the day bob.near started with, still on the clock
+        call_at("bob.near", 30 * HOUR_NS);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().remaining_ns, vec![3 * DAY_NS - 30 * HOUR_NS, DAY_NS]);
+    }
+
+    #[test]
+    fn ignored_then_timed_out() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        call_at("bob.near", 20 * HOUR_NS);
+        contract.request_extension("1".to_string(), DAY_NS);
+
+        call_at("alice.near", DAY_NS);
+        contract.claim_timeout_win("1".to_string());
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!((game.status, game.winner), (GameStatus::Finished, Some(1)));
+    }
+
+    #[test]
+    fn request_lapses_on_move() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        call_at("bob.near", HOUR_NS);
+        contract.request_extension("1".to_string(), DAY_NS);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().extension_requested, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "bob.near already asked for 2 extensions in game 1")]
+    fn extension_cap() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        for _ in 0..2 {
+            call_at("bob.near", HOUR_NS);
+            contract.request_extension("1".to_string(), HOUR_NS);
+            call_at("alice.near", HOUR_NS);
+            contract.grant_extension("1".to_string());
+        }
+        call_at("bob.near", HOUR_NS);
+        contract.request_extension("1".to_string(), HOUR_NS);
+    }
+
+    #[test]
+    #[should_panic(expected = "Extension must be between 1 and 604800000000000 ns")]
+    fn extension_too_long() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        contract.request_extension("1".to_string(), 8 * DAY_NS);
+    }
+
+    #[test]
+    #[should_panic(expected = "No extension requested by the opponent in game 1")]
+    fn grant_own_request() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        contract.request_extension("1".to_string(), DAY_NS);
+        contract.grant_extension("1".to_string());
+    }
+}
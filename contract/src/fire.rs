@@ -0,0 +1,153 @@
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+/// Button skill whose dice can be turned down to boost the die making a power attack
+pub(crate) const FIRE_SKILL: &str = "Fire";
+
+#[near_bindgen]
+impl Contract {
+    /// Power attack boosted by turning down the caller's other dice. turn_down has an amount for each of the caller's
+    /// dice: each die loses that much, to no less than 1, and the attacking die gains the total, to no more than its size.
+    /// Only buttons with the Fire skill can, skills are per button so every one of its dice is a fire die.
+    pub fn fire_attack(&mut self, game_id: String, attacker_die_index: u8, defender_die_index: u8, turn_down: Vec<u8>) -> AttackView {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+        let player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() }));
+        pauses::assert_running(&mut game);
+        if game.current_player != player_index as u8 {
+            fail(Error::NotYourTurn);
+        }
+        let has_fire = self.find_button(&game.buttons[player_index]).is_some_and(|button| button.skills.iter().any(|skill| skill == FIRE_SKILL));
+        if !has_fire {
+            fail(Error::NoFireSkill);
+        }
+
+        let dice = &game.dice[player_index];
+        if turn_down.len() != dice.len() {
+            fail(Error::TurnDownCount);
+        }
+        if turn_down[attacker_die_index as usize] != 0 {
+            fail(Error::TurnDownAttacker);
+        }
+        for (index, amount) in turn_down.iter().enumerate() {
+            if *amount > 0 && *amount >= dice[index].value {
+                fail(Error::TurnDownTooFar { index });
+            }
+        }
+        let boost = turn_down.iter().map(|amount| *amount as u32).sum::<u32>();
+        if boost == 0 {
+            fail(Error::NoTurnDown);
+        }
+        let attacker = &dice[attacker_die_index as usize];
+        if attacker.value as u32 + boost > attacker.size as u32 {
+            fail(Error::BoostTooFar { index: attacker_die_index as usize });
+        }
+
+        let dice_before = game.dice.clone();
+        for (index, amount) in turn_down.iter().enumerate() {
+            game.dice[player_index][index].value -= amount;
+        }
+        game.dice[player_index][attacker_die_index as usize].value += boost as u8;
+        let attack_view = self.apply_attack(&mut game, vec![attacker_die_index], defender_die_index, Some(AttackType::Power));
+        // Undo goes back to before the dice were turned down
+        if let Some(record) = game.history.last_mut() {
+            record.dice_before = dice_before;
+        }
+        self.play_forced_moves(&mut game);
+        self.save_game(&game);
+
+        if !game.is_finished() {
+            notify(&game.players[game.current_player as usize], "your_turn", &game_id, &player_id);
+        }
+        return attack_view;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::login_as;
+
+    /// bob.near plays a Fire button with a d6 showing 3 and a d8 showing 4 against a d10 showing 6
+    fn fire_game(contract: &mut Contract) {
+        login_as("alice.near");
+        contract.register_button("Blaze".to_string(), vec![6, 8], vec![FIRE_SKILL.to_string()]);
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            buttons: vec!["Blaze".to_string(), DEFAULT_BUTTON.to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![
+                vec![Die { size: 6, value: 3 }, Die { size: 8, value: 4 }],
+                vec![Die { size: 10, value: 6 }, Die { size: 4, value: 4 }],
+            ],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+        login_as("bob.near");
+    }
+
+    #[test]
+    fn fire_assisted_capture() {
+        let mut contract = Contract::default();
+        fire_game(&mut contract);
+        // Neither die beats the d10 alone and together they overshoot it
+        assert!(contract.get_move_options("1".to_string(), None).attacks.iter().all(|attack| attack.defender_die_index != 0));
+
+        contract.fire_attack("1".to_string(), 1, 0, vec![2, 0]);
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.captured[0], vec![10]);
+        assert_eq!(game.dice[0][0], Die { size: 6, value: 1 });
+        assert_eq!(game.dice[1], vec![Die { size: 4, value: 4 }]);
+        assert_eq!(game.current_player, 1);
+        assert_eq!(game.history.last().unwrap().dice_before[0], vec![Die { size: 6, value: 3 }, Die { size: 8, value: 4 }]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attack failed")]
+    fn fire_boost_too_small() {
+        let mut contract = Contract::default();
+        fire_game(&mut contract);
+
+        contract.fire_attack("1".to_string(), 1, 0, vec![1, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Die 0 can't be turned down below 1")]
+    fn fire_turn_down_below_one() {
+        let mut contract = Contract::default();
+        fire_game(&mut contract);
+
+        contract.fire_attack("1".to_string(), 1, 0, vec![3, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Die 0 can't be boosted above its size")]
+    fn fire_boost_above_size() {
+        let mut contract = Contract::default();
+        fire_game(&mut contract);
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        game.dice[0][1].value = 8;
+        contract.games.insert(&"1".to_string(), &game);
+
+        contract.fire_attack("1".to_string(), 0, 0, vec![0, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fire attack needs a button with the Fire skill")]
+    fn fire_without_skill() {
+        let mut contract = Contract::default();
+        fire_game(&mut contract);
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        game.buttons[0] = DEFAULT_BUTTON.to_string();
+        contract.games.insert(&"1".to_string(), &game);
+
+        contract.fire_attack("1".to_string(), 1, 0, vec![2, 0]);
+    }
+}
@@ -0,0 +1,156 @@
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::*;
+
+const MAX_FOLLOWING: usize = 100;
+
+pub(crate) fn following_key(account_id: &str) -> Vec<u8> {
+    format!("follow:{}", account_id).as_bytes().to_vec()
+}
+
+fn get_following(account_id: &str) -> Vec<String> {
+    match env::storage_read(&following_key(account_id)) {
+        Some(following_vec) => Vec::<String>::try_from_slice(&following_vec).unwrap(),
+        None => vec![],
+    }
+}
+
+fn set_following(account_id: &str, following: &Vec<String>) {
+    if following.is_empty() {
+        env::storage_remove(&following_key(account_id));
+    } else {
+        env::storage_write(&following_key(account_id), &following.try_to_vec().unwrap());
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Adds the account to the caller's follow list. Following an account twice is a no-op.
+    pub fn follow(&mut self, account_id: AccountId) -> () {
+        let follower_id = env::predecessor_account_id().to_string();
+        if follower_id == account_id.to_string() {
+            fail(Error::SelfFollow);
+        }
+
+        let mut following = get_following(&follower_id);
+        if following.contains(&account_id.to_string()) {
+            return;
+        }
+        if following.len() >= MAX_FOLLOWING {
+            fail(Error::TooManyFollowed { max: MAX_FOLLOWING });
+        }
+        following.push(account_id.to_string());
+        set_following(&follower_id, &following);
+    }
+
+    pub fn unfollow(&mut self, account_id: AccountId) -> () {
+        let follower_id = env::predecessor_account_id().to_string();
+        let mut following = get_following(&follower_id);
+        following.retain(|id| id != account_id.as_str());
+        set_following(&follower_id, &following);
+    }
+
+    pub fn get_following(&self, account_id: AccountId) -> Vec<String> {
+        get_following(account_id.as_str())
+    }
+
+    /// Games of the accounts the given account follows, most recently active first. Cancelled games are left out.
+    pub fn get_following_feed(&self, account_id: AccountId, limit: u64) -> Vec<Game> {
+        let mut game_ids = get_following(account_id.as_str()).into_iter()
+            .flat_map(get_user_games)
+            .collect::<Vec<String>>();
+        game_ids.sort();
+        game_ids.dedup();
+
+        let mut games = game_ids.iter()
+            .filter_map(|game_id| self.games.get(game_id))
+            .filter(|game| game.status != GameStatus::Cancelled)
+            .collect::<Vec<Game>>();
+        games.sort_by_key(|game| std::cmp::Reverse(game.last_move_at));
+        games.truncate(limit as usize);
+        return games;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_at, login_as, request_path};
+
+    fn feed_ids(contract: &Contract, account_id: &str) -> Vec<String> {
+        contract.get_following_feed(account_id.parse().unwrap(), 10).into_iter().map(|game| game.id).collect()
+    }
+
+    #[test]
+    fn following_feed() {
+        let mut contract = Contract::default();
+        login_as("eve.near");
+        contract.follow("bob.near".parse().unwrap());
+        contract.follow("carol.near".parse().unwrap());
+        contract.follow("bob.near".parse().unwrap());
+        assert_eq!(contract.get_following("eve.near".parse().unwrap()), vec!["bob.near", "carol.near"]);
+
+        call_at("bob.near", 1);
+        let bob_game = contract.create_game(None);
+        call_at("carol.near", 2);
+        let carol_game = contract.create_game(None);
+        call_at("dave.near", 3);
+        contract.create_game(None);
+        call_at("carol.near", 4);
+        contract.join_game(bob_game.clone(), None);
+
+        // bob.near's game is shared with carol.near and listed once, it moved last
+        assert_eq!(feed_ids(&contract, "eve.near"), vec![bob_game.clone(), carol_game.clone()]);
+        assert_eq!(contract.get_following_feed("eve.near".parse().unwrap(), 1).len(), 1);
+
+        login_as("eve.near");
+        contract.unfollow("bob.near".parse().unwrap());
+        contract.unfollow("carol.near".parse().unwrap());
+        assert_eq!(feed_ids(&contract, "eve.near"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn web4_get_my_feed() {
+        let mut contract = Contract::default();
+        login_as("eve.near");
+        contract.follow("bob.near".parse().unwrap());
+        login_as("bob.near");
+        let game_id = contract.create_game(None);
+
+        let mut request = request_path("/api/my/feed");
+        request.account_id = Some("eve.near".to_string());
+        match contract.web4_get(request) {
+            Web4Response::Body { content_type, body, status } => {
+                assert_eq!(content_type, "application/json".to_owned());
+                assert_eq!(status, None);
+                assert_eq!(String::from_utf8(body.into()).unwrap(),
+                    serde_json::to_string(&vec![contract.games.get(&game_id).unwrap()]).unwrap());
+            },
+            _ => panic!("Unexpected response"),
+        }
+
+        assert_eq!(contract.web4_get(request_path("/api/my/feed")), Web4Response::Body {
+            content_type: "application/json".to_owned(),
+            body: r#"{"error":"not_logged_in"}"#.as_bytes().to_owned().into(),
+            status: Some(401),
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't follow more than 100 accounts")]
+    fn follow_limit() {
+        let mut contract = Contract::default();
+        login_as("eve.near");
+        for i in 0..=MAX_FOLLOWING {
+            contract.follow(format!("player{}.near", i).parse().unwrap());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't follow yourself")]
+    fn follow_yourself() {
+        let mut contract = Contract::default();
+        login_as("eve.near");
+        contract.follow("eve.near".parse().unwrap());
+    }
+}
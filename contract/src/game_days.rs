@@ -0,0 +1,103 @@
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+/// `YYYYMMDD` of a block timestamp in UTC, the key of a day in Contract.games_by_day
+pub(crate) fn utc_day(timestamp: u64) -> String {
+    daily::utc_date(timestamp).replace('-', "")
+}
+
+fn is_day(day: &str) -> bool {
+    day.len() == 8 && day.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Ids of the games created on the UTC day, given as `YYYYMMDD`, in the order they were created
+    pub fn get_games_by_day(&self, day: String) -> Vec<String> {
+        if !is_day(&day) {
+            fail(Error::InvalidDay { day: day.to_string() });
+        }
+        self.games_by_day.get(&day).unwrap_or_default()
+    }
+}
+
+impl Contract {
+    /// Takes the id for a new game and files it under the day it's created on
+    pub(crate) fn next_game_id(&mut self) -> String {
+        self.last_game_id += 1;
+        let game_id = format!("{}", self.last_game_id);
+
+        let day = utc_day(env::block_timestamp());
+        let mut game_ids = self.games_by_day.get(&day).unwrap_or_default();
+        game_ids.push(game_id.clone());
+        self.games_by_day.insert(&day, &game_ids);
+
+        game_id
+    }
+
+    /// Drops a deleted game from the day it was created on
+    pub(crate) fn remove_from_day(&mut self, game: &Game) {
+        let day = utc_day(game.created_at);
+        if let Some(mut game_ids) = self.games_by_day.get(&day) {
+            game_ids.retain(|id| id != &game.id);
+            if game_ids.is_empty() {
+                self.games_by_day.remove(&day);
+            } else {
+                self.games_by_day.insert(&day, &game_ids);
+            }
+        }
+    }
+
+    pub(crate) fn games_by_day_response(&self, day: &str) -> Web4Response {
+        if !is_day(day) {
+            return error_response(Error::InvalidDay { day: day.to_string() });
+        }
+        Web4Response::Body {
+            content_type: "application/json".to_owned(),
+            body: serde_json::to_vec(&self.get_games_by_day(day.to_string())).unwrap().into(),
+            status: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_at, request_path};
+
+    /// 2024-03-01 00:00 UTC
+    const MARCH_1: u64 = 1_709_251_200 * 1_000_000_000;
+
+    fn by_day_response(contract: &Contract, day: &str) -> (Option<u16>, serde_json::Value) {
+        match contract.web4_get(request_path(&format!("/api/games/by-day/{}", day))) {
+            Web4Response::Body { body, status, .. } => (status, serde_json::from_slice(&Vec::<u8>::from(body)).unwrap()),
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    fn games_land_in_their_day() {
+        let mut contract = Contract::default();
+        call_at("bob.near", MARCH_1 - 1);
+        contract.create_game(None);
+        call_at("bob.near", MARCH_1);
+        contract.create_game(None);
+        call_at("carol.near", MARCH_1 + DAY_NS - 1);
+        contract.create_game_vs_bot(BotDifficulty::Random);
+        call_at("carol.near", MARCH_1 + DAY_NS);
+        contract.create_game(None);
+
+        assert_eq!(contract.get_games_by_day("20240229".to_string()), vec!["1"]);
+        assert_eq!(contract.get_games_by_day("20240301".to_string()), vec!["2", "3"]);
+        assert_eq!(contract.get_games_by_day("20240302".to_string()), vec!["4"]);
+        assert_eq!(contract.get_games_by_day("20240303".to_string()), Vec::<String>::new());
+        assert_eq!(by_day_response(&contract, "20240301"), (None, serde_json::json!(["2", "3"])));
+    }
+
+    #[test]
+    fn invalid_day() {
+        let contract = Contract::default();
+        assert_eq!(by_day_response(&contract, "2024-03-01"), (Some(400), serde_json::json!({ "error": "invalid_day" })));
+    }
+}
@@ -0,0 +1,290 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+/// Version tag prefixed to the canonical encoding. Bump it whenever the layout below changes.
+const GAME_HASH_DOMAIN: &[u8] = b"buttonmen:game:v1";
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum MoveKind {
+    Attack,
+    Pass,
+    Resign,
+    /// Not moves, the player on turn asking for more time and the opponent giving it, see request_extension
+    ExtensionRequest,
+    ExtensionGrant,
+}
+
+impl MoveKind {
+    /// Whether the record is a move rather than a request between the players
+    pub fn is_move(self) -> bool {
+        matches!(self, MoveKind::Attack | MoveKind::Pass | MoveKind::Resign)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MoveRecord {
+    pub player: u8,
+    pub kind: MoveKind,
+    pub attacker_die_indices: Vec<u8>,
+    pub defender_die_index: Option<u8>,
+    /// Size of the captured die
+    pub captured: Option<u8>,
+    /// Values the attacker dice were re-rolled to, in attacker_die_indices order
+    pub rerolled: Vec<u8>,
+    pub timestamp: u64,
+    /// Both players' dice right before the move, so it can be undone. Not part of the game hash.
+    pub dice_before: Vec<Vec<Die>>,
+    /// Played by the contract for a player who opted in with set_auto_forced. Not part of the game hash.
+    pub auto: bool,
+    /// Time the player took over an attack or pass, from the start of their turn and leaving out pauses.
+    /// Not part of the game hash.
+    #[serde(default)]
+    pub elapsed_ns: u64,
+    /// Both players' clocks right before the move, so undoing it gives the time back. Empty in games without a
+    /// time control. Not part of the game hash.
+    #[serde(default)]
+    pub remaining_before: Vec<u64>,
+}
+
+impl MoveRecord {
+    pub fn new(player: u8, kind: MoveKind) -> Self {
+        Self {
+            player,
+            kind,
+            attacker_die_indices: vec![],
+            defender_die_index: None,
+            captured: None,
+            rerolled: vec![],
+            timestamp: env::block_timestamp(),
+            dice_before: vec![],
+            auto: false,
+            elapsed_ns: 0,
+            remaining_before: vec![],
+        }
+    }
+}
+
+fn write_u8_list(bytes: &mut Vec<u8>, values: &[u8]) {
+    bytes.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(values);
+}
+
+fn write_str(bytes: &mut Vec<u8>, value: &str) {
+    write_u8_list(bytes, value.as_bytes());
+}
+
+/// Canonical byte layout of a game, independent of how the contract stores it.
+/// All integers are little-endian, lists are prefixed with a u32 length, absent values are 0xFF:
+///
+/// ```text
+/// domain tag "buttonmen:game:v1"
+/// id: str
+/// players: u32 count, then str per player
+/// round_seed: u8 list
+/// history: u32 count, then per move:
+///     player u8, kind u8 (0 attack, 1 pass, 2 resign, 3 extension request, 4 extension grant), attacker_die_indices u8 list,
+///     defender_die_index u8, captured u8, rerolled u8 list, timestamp u64
+/// status: u8 (0 waiting, 1 active, 2 finished, 3 cancelled)
+/// winner: u8
+/// per player: dice as u32 count then (size u8, value u8) pairs, captured u8 list
+/// ```
+pub fn canonical_bytes(game: &Game) -> Vec<u8> {
+    let mut bytes = GAME_HASH_DOMAIN.to_vec();
+    write_str(&mut bytes, &game.id);
+
+    bytes.extend_from_slice(&(game.players.len() as u32).to_le_bytes());
+    for player_id in game.players.iter() {
+        write_str(&mut bytes, player_id);
+    }
+    write_u8_list(&mut bytes, &game.round_seed);
+
+    bytes.extend_from_slice(&(game.history.len() as u32).to_le_bytes());
+    for record in game.history.iter() {
+        bytes.push(record.player);
+        bytes.push(match record.kind {
+            MoveKind::Attack => 0,
+            MoveKind::Pass => 1,
+            MoveKind::Resign => 2,
+            MoveKind::ExtensionRequest => 3,
+            MoveKind::ExtensionGrant => 4,
+        });
+        write_u8_list(&mut bytes, &record.attacker_die_indices);
+        bytes.push(record.defender_die_index.unwrap_or(0xFF));
+        bytes.push(record.captured.unwrap_or(0xFF));
+        write_u8_list(&mut bytes, &record.rerolled);
+        bytes.extend_from_slice(&record.timestamp.to_le_bytes());
+    }
+
+    bytes.push(match game.status {
+        GameStatus::Waiting => 0,
+        GameStatus::Active => 1,
+        GameStatus::Finished => 2,
+        GameStatus::Cancelled => 3,
+        GameStatus::Expired => 4,
+    });
+    bytes.push(game.winner.unwrap_or(0xFF));
+    for player_index in 0..game.players.len() {
+        bytes.extend_from_slice(&(game.dice[player_index].len() as u32).to_le_bytes());
+        for die in game.dice[player_index].iter() {
+            bytes.push(die.size);
+            bytes.push(die.value);
+        }
+        write_u8_list(&mut bytes, &game.captured[player_index]);
+    }
+
+    return bytes;
+}
+
+/// Hex-encoded sha256 of the canonical encoding.
+pub fn game_hash(game: &Game) -> String {
+    env::sha256(&canonical_bytes(game)).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Hash of the game's move history and current state, see canonical_bytes for the exact layout.
+    pub fn get_game_hash(&self, game_id: String) -> String {
+        let game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        game_hash(&game)
+    }
+}
+
+impl Contract {
+    /// /api/games/{id}/moves, the game's history. Records carry the dice as they were before each move,
+    /// so a fogged game's moves are only shown once it's finished.
+    pub(crate) fn moves_response(&self, game_id: &str) -> Web4Response {
+        match self.games.get(&game_id.to_string()) {
+            Some(game) if game.fog && !game.is_finished() => error_response(Error::Fog { game_id: game_id.to_string() }),
+            Some(game) => Web4Response::Body {
+                content_type: "application/json".to_owned(),
+                body: serde_json::to_vec(&game.history).unwrap().into(),
+                status: None,
+            },
+            None => error_response(Error::GameNotFound { game_id: game_id.to_string() }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_at, request_path};
+
+    const SECOND_NS: u64 = 1_000_000_000;
+
+    fn finished_game() -> Game {
+        let mut attack = MoveRecord::new(0, MoveKind::Attack);
+        attack.attacker_die_indices = vec![0, 1];
+        attack.defender_die_index = Some(0);
+        attack.captured = Some(10);
+        attack.rerolled = vec![3, 2];
+
+        Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 1,
+            dice: vec![vec![Die { size: 4, value: 3 }, Die { size: 6, value: 2 }], vec![]],
+            captured: vec![vec![10], vec![]],
+            round_seed: vec![1; 32],
+            status: GameStatus::Finished,
+            winner: Some(0),
+            history: vec![MoveRecord::new(1, MoveKind::Pass), attack],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn game_hash_changes_with_moves() {
+        let game = finished_game();
+        let hash = game_hash(&game);
+        assert_eq!(hash.len(), 64);
+
+        let mut other = finished_game();
+        other.history[1].attacker_die_indices = vec![1, 0];
+        assert_ne!(game_hash(&other), hash);
+
+        let mut other = finished_game();
+        other.history[1].rerolled = vec![3, 1];
+        assert_ne!(game_hash(&other), hash);
+
+        let mut other = finished_game();
+        other.history.remove(0);
+        assert_ne!(game_hash(&other), hash);
+    }
+
+    #[test]
+    fn game_hash_round_trip() {
+        let game = finished_game();
+        let hash = game_hash(&game);
+
+        let borsh_game = Game::try_from_slice(&game.try_to_vec().unwrap()).unwrap();
+        assert_eq!(game_hash(&borsh_game), hash);
+
+        let json_game: Game = serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+        assert_eq!(game_hash(&json_game), hash);
+    }
+
+    #[test]
+    fn game_finished_event_hash() {
+        let mut game = finished_game();
+        game.status = GameStatus::Active;
+        finish_game(&mut game, Some(0), FinishReason::Resignation);
+
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![format!(
+            r#"EVENT_JSON:{{"standard":"buttonmen","version":"1.0.0","event":"game_finished","data":[{{"game_id":"1","players":["bob.near","alice.near"],"winner":0,"hash":"{}"}}]}}"#,
+            game_hash(&game))]);
+    }
+
+    #[test]
+    fn moves_timed() {
+        let mut contract = Contract::default();
+        call_at("bob.near", 0);
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 20 }, Die { size: 4, value: 1 }], vec![Die { size: 4, value: 1 }, Die { size: 20, value: 20 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        call_at("bob.near", 40 * SECOND_NS);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        call_at("alice.near", 100 * SECOND_NS);
+        contract.attack("1".to_string(), vec![0], 1, None, None, None);
+        call_at("bob.near", 110 * SECOND_NS);
+        contract.resign("1".to_string());
+
+        let moves = match contract.web4_get(request_path("/api/games/1/moves")) {
+            Web4Response::Body { body, .. } => serde_json::from_slice::<Vec<MoveRecord>>(&Vec::<u8>::from(body)).unwrap(),
+            _ => panic!("Unexpected response"),
+        };
+        assert_eq!(moves.iter().map(|record| record.elapsed_ns).collect::<Vec<u64>>(), vec![40 * SECOND_NS, 60 * SECOND_NS, 0]);
+        assert_eq!(contract.get_average_move_time("bob.near".parse().unwrap()), Some(40 * SECOND_NS));
+        assert_eq!(contract.get_average_move_time("alice.near".parse().unwrap()), Some(60 * SECOND_NS));
+        assert_eq!(contract.get_average_move_time("carol.near".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn fogged_moves_hidden() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            status: GameStatus::Active,
+            fog: true,
+            ..Default::default()
+        });
+
+        match contract.web4_get(request_path("/api/games/1/moves")) {
+            Web4Response::Body { status, .. } => assert_eq!(status, Some(403)),
+            _ => panic!("Unexpected response"),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+/// Storage key of a season's leaderboard, kept sorted so reading it is a single storage_read
+fn leaderboard_key(season_id: Option<u32>) -> Vec<u8> {
+    match season_id {
+        Some(season_id) => format!("leaderboard:{}", season_id).as_bytes().to_vec(),
+        None => b"leaderboard".to_vec(),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LeaderboardEntry {
+    pub account_id: String,
+    pub rating: u32,
+}
+
+/// Leaderboard entry as read, with the title and badges the account shows
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RankedEntry {
+    pub account_id: String,
+    pub rating: u32,
+    pub title: Option<String>,
+    pub badges: Vec<Badge>,
+}
+
+pub(crate) fn read_leaderboard(season_id: Option<u32>) -> Vec<LeaderboardEntry> {
+    match env::storage_read(&leaderboard_key(season_id)) {
+        Some(leaderboard_vec) => Vec::<LeaderboardEntry>::try_from_slice(&leaderboard_vec).unwrap(),
+        None => vec![],
+    }
+}
+
+fn write_leaderboard(season_id: Option<u32>, leaderboard: &Vec<LeaderboardEntry>) {
+    env::storage_write(&leaderboard_key(season_id), &leaderboard.try_to_vec().unwrap());
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Highest rated accounts of the current season, best first
+    pub fn get_leaderboard(&self, from: u64, limit: u64) -> Vec<RankedEntry> {
+        self.ranked(read_leaderboard(self.current_season_id()).into_iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    pub fn set_leaderboard_size(&mut self, size: u32) -> () {
+        self.assert_owner("set the leaderboard size");
+
+        self.config.leaderboard_size = size;
+        let mut leaderboard = read_leaderboard(self.current_season_id());
+        leaderboard.truncate(size as usize);
+        write_leaderboard(self.current_season_id(), &leaderboard);
+    }
+
+    pub(crate) fn ranked(&self, leaderboard: Vec<LeaderboardEntry>) -> Vec<RankedEntry> {
+        leaderboard.into_iter().map(|entry| RankedEntry {
+            title: self.title_for(entry.rating),
+            badges: self.badges_of(&entry.account_id),
+            account_id: entry.account_id,
+            rating: entry.rating,
+        }).collect()
+    }
+
+    /// Moves the account to its new place on the leaderboard. Accounts not on it yet
+    /// only get in when there's room or they beat the lowest rating.
+    pub(crate) fn update_leaderboard(&mut self, account_id: &str, rating: u32) {
+        let size = self.config.leaderboard_size as usize;
+        let season_id = self.current_season_id();
+        let mut leaderboard = read_leaderboard(season_id);
+
+        match leaderboard.iter().position(|entry| entry.account_id == account_id) {
+            Some(index) => leaderboard[index].rating = rating,
+            None => {
+                if leaderboard.len() >= size && leaderboard.last().is_none_or(|lowest| rating <= lowest.rating) {
+                    return;
+                }
+                leaderboard.push(LeaderboardEntry { account_id: account_id.to_string(), rating });
+            },
+        }
+        // Entries only change when written, so this is when the others catch up on their rating decay
+        for entry in leaderboard.iter_mut().filter(|entry| entry.account_id != account_id && self.last_ranked_game_at.contains_key(&entry.account_id)) {
+            entry.rating = self.rating_of(&entry.account_id);
+        }
+        // Stable, so accounts with equal ratings keep their order
+        leaderboard.sort_by_key(|entry| std::cmp::Reverse(entry.rating));
+        leaderboard.truncate(size);
+        write_leaderboard(season_id, &leaderboard);
+    }
+
+    /// Takes the account off the leaderboards of every season
+    pub(crate) fn remove_from_leaderboard(&mut self, account_id: &str) {
+        let season_ids = std::iter::once(None).chain((1..=self.seasons.len() as u32).map(Some));
+        for season_id in season_ids {
+            let mut leaderboard = read_leaderboard(season_id);
+            leaderboard.retain(|entry| entry.account_id != account_id);
+            write_leaderboard(season_id, &leaderboard);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{login_as, request_path};
+
+    fn ranking(contract: &Contract) -> Vec<(String, u32)> {
+        contract.get_leaderboard(0, 100).into_iter().map(|entry| (entry.account_id, entry.rating)).collect()
+    }
+
+    fn contract_with_size(size: u32) -> Contract {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_leaderboard_size(size);
+        return contract;
+    }
+
+    #[test]
+    fn leaderboard_threshold() {
+        let mut contract = contract_with_size(3);
+        contract.update_leaderboard("a.near", 1500);
+        contract.update_leaderboard("b.near", 1520);
+        contract.update_leaderboard("c.near", 1480);
+        assert_eq!(ranking(&contract), vec![("b.near".to_string(), 1520), ("a.near".to_string(), 1500), ("c.near".to_string(), 1480)]);
+
+        // Matching the lowest rating isn't enough to get in once it's full
+        contract.update_leaderboard("d.near", 1480);
+        assert_eq!(ranking(&contract).len(), 3);
+        assert!(!ranking(&contract).iter().any(|(account_id, _)| account_id == "d.near"));
+
+        contract.update_leaderboard("d.near", 1481);
+        assert_eq!(ranking(&contract), vec![("b.near".to_string(), 1520), ("a.near".to_string(), 1500), ("d.near".to_string(), 1481)]);
+    }
+
+    #[test]
+    fn leaderboard_update_resorts() {
+        let mut contract = contract_with_size(3);
+        contract.update_leaderboard("a.near", 1500);
+        contract.update_leaderboard("b.near", 1520);
+        contract.update_leaderboard("c.near", 1480);
+
+        contract.update_leaderboard("c.near", 1530);
+        contract.update_leaderboard("b.near", 1470);
+        assert_eq!(ranking(&contract), vec![("c.near".to_string(), 1530), ("a.near".to_string(), 1500), ("b.near".to_string(), 1470)]);
+        assert_eq!(contract.get_leaderboard(1, 1), vec![RankedEntry { account_id: "a.near".to_string(), rating: 1500, title: Some("Novice".to_string()), badges: vec![] }]);
+
+        contract.set_leaderboard_size(2);
+        assert_eq!(ranking(&contract), vec![("c.near".to_string(), 1530), ("a.near".to_string(), 1500)]);
+    }
+
+    #[test]
+    fn leaderboard_after_game() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        let game_id = contract.create_game(None);
+        login_as("carol.near");
+        contract.join_game(game_id.clone(), None);
+        contract.resign(game_id);
+
+        assert_eq!(ranking(&contract), vec![("bob.near".to_string(), 1516), ("carol.near".to_string(), 1484)]);
+        match contract.web4_get(request_path("/api/leaderboard")) {
+            Web4Response::Body { content_type, body, .. } => {
+                assert_eq!(content_type, "application/json".to_owned());
+                assert_eq!(String::from_utf8(body.into()).unwrap(),
+                    concat!(r#"[{"account_id":"bob.near","rating":1516,"title":"Novice","badges":[]},"#,
+                        r#"{"account_id":"carol.near","rating":1484,"title":"Novice","badges":[]}]"#));
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can set the leaderboard size")]
+    fn leaderboard_size_not_owner() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.set_leaderboard_size(10);
+    }
+}
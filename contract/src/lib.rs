@@ -1,11 +1,115 @@
+// web4_* method names follow the web4 gateway convention
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return, clippy::unused_unit)]
+
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::LookupMap;
-use near_sdk::{env, serde_json, near_bindgen, require};
+use near_sdk::collections::{LookupMap, Vector};
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::{env, ext_contract, serde_json, near_bindgen, AccountId, Balance, CurveType, Gas, PromiseError, PublicKey};
 use near_sdk::serde::{Deserialize, Serialize};
 
 use near_rng::Rng;
 
+mod achievements;
+mod allowance;
+mod auto_forced;
+mod awaiting;
+mod blocks;
+mod bot;
+mod buttons;
+mod clocks;
+mod clubs;
+mod daily;
+mod engine;
+mod erasure;
+mod errors;
+mod eviction;
+mod extensions;
+mod fire;
+mod follow;
+mod game_days;
+mod history;
+mod leaderboard;
+mod lobby;
+mod matchmaking;
+mod migration;
+mod odds;
+mod pauses;
+mod policies;
+mod puzzles;
+mod ratings;
+mod records;
+mod replays;
+mod report;
+mod results;
+mod rooms;
+mod rules;
+mod seasons;
+mod side_bet;
+mod swiss;
+mod titles;
+mod tournament;
+mod undo;
+mod spam_deposit;
+mod stats;
+mod streaks;
+mod wager;
+
+use achievements::EarnedAchievement;
+use allowance::DailyGames;
+use buttons::ButtonDef;
+use clocks::{TimeControl, TimePreset};
+use clubs::{Club, ClubEntry};
+use daily::DailyScore;
+use engine::{advance_turn, assert_valid_die_size, attack_options, find_power_attack, leader, roll_dice, score, scores, settle_initiative, DiceRng};
+use errors::{error_response, fail, Error};
+use history::{MoveKind, MoveRecord};
+use matchmaking::OpenGame;
+use migration::Migration;
+use pauses::Pause;
+use policies::{AttackEvaluation, BotDifficulty};
+use puzzles::{Puzzle, PuzzleSolve};
+use records::{FinishReason, PlayerRecord};
+use replays::Replay;
+use seasons::{Season, SeasonRating};
+use side_bet::{SideBet, SideBetPool};
+use stats::WeekStats;
+use streaks::Streak;
+use titles::{Badge, RatingTitle};
+use tournament::Tournament;
+use wager::Wager;
+
 const MAX_LATEST_GAMES: usize = 10;
+/// Every game is between two players, see Game::assert_two_players
+const PLAYER_COUNT: usize = 2;
+const DAY_NS: u64 = 24 * 60 * 60 * 1_000_000_000;
+const MAX_NOTIFICATIONS: usize = 50;
+
+/// How many of a player's NFTs are checked when verifying a premium button
+const NFT_TOKENS_LIMIT: u64 = 100;
+const GAS_FOR_NFT_TOKENS: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_BUTTON_VERIFIED: Gas = Gas(10_000_000_000_000);
+
+/// Built-in buttons (characters) players can play as, the owner can add more with register_button.
+/// Game logic rolls dice from these, and /api/buttons serves them all to the frontend.
+const BUTTONS: &[Button] = &[
+    Button { name: "Standard", dice: &[4, 6, 8, 10, 20], auxiliary: &[], skills: &[] },
+    Button { name: "Brawler", dice: &[6, 8, 12, 12, 20], auxiliary: &[], skills: &[] },
+    Button { name: "Trickster", dice: &[4, 4, 6, 10, 20], auxiliary: &[8], skills: &[] },
+];
+
+const DEFAULT_BUTTON: &str = "Standard";
+
+/// Token ids are either the series id itself or `<series>:<edition>`.
+fn token_in_series(token_id: &str, token_series: &str) -> bool {
+    token_id == token_series || token_id.starts_with(&format!("{}:", token_series))
+}
+
+#[ext_contract(ext_nft)]
+#[allow(dead_code)]
+trait NonFungibleToken {
+    fn nft_tokens_for_owner(&self, account_id: AccountId, from_index: Option<U128>, limit: Option<u64>) -> Vec<NftToken>;
+}
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -14,6 +118,70 @@ pub struct Contract {
     pub last_game_id: u64,
     pub latest_games: Vec<String>,
     pub web4_static_url: String,
+    pub config: ContractConfig,
+    /// Account allowed to call owner-only methods
+    pub owner_id: AccountId,
+    pub session_keys: LookupMap<String, SessionKey>,
+    pub wagers: LookupMap<String, Wager>,
+    pub audit_log: Vector<AuditEntry>,
+    pub side_bets: LookupMap<String, SideBetPool>,
+    /// Each spectator's stakes, keyed by `<game_id>:<account_id>`
+    pub side_bet_stakes: LookupMap<String, SideBet>,
+    /// Fees collected by the contract, in yoctoNEAR
+    pub treasury: Balance,
+    pub daily_games: LookupMap<String, DailyGames>,
+    /// Block timestamp of each account's first finished game
+    pub first_finished_at: LookupMap<String, u64>,
+    /// Anti-spam deposits held for games created by new accounts, in yoctoNEAR
+    pub spam_deposits: LookupMap<String, Balance>,
+    /// Room contracts deployed by create_room
+    pub rooms: Vector<AccountId>,
+    pub tournaments: LookupMap<String, Tournament>,
+    pub last_tournament_id: u64,
+    /// Elo rating of every account that finished a game, as of the last season it played in.
+    /// Others are at ratings::DEFAULT_RATING
+    pub ratings: LookupMap<String, SeasonRating>,
+    /// When each account last finished a rated game, ratings decay from there, see ContractConfig.rating_decay_after_ns
+    pub last_ranked_game_at: LookupMap<String, u64>,
+    pub records: LookupMap<String, PlayerRecord>,
+    pub seasons: Vector<Season>,
+    /// Each account's final ratings of past seasons, other than the one in `ratings`
+    pub season_history: LookupMap<String, Vec<SeasonRating>>,
+    pub achievements: LookupMap<String, Vec<EarnedAchievement>>,
+    /// Game each account played in a daily challenge, keyed by `<date>:<account_id>`
+    pub daily_attempts: LookupMap<String, String>,
+    pub daily_leaderboards: LookupMap<String, Vec<DailyScore>>,
+    /// Games created with create_game that may still be waiting for an opponent, oldest first
+    pub open_games: Vec<OpenGame>,
+    /// Buttons added with register_button
+    pub buttons: LookupMap<String, ButtonDef>,
+    /// Names of registered buttons, in the order they were registered
+    pub button_names: Vec<String>,
+    pub streaks: LookupMap<String, Streak>,
+    /// Vote tally of every game nominated for the hall of fame
+    pub replays: LookupMap<String, Replay>,
+    /// Games each account voted for in the hall of fame
+    pub replay_votes: LookupMap<String, Vec<String>>,
+    /// Most voted replays, best first
+    pub top_replays: Vec<Replay>,
+    pub clubs: LookupMap<String, Club>,
+    pub last_club_id: u64,
+    /// Club each account is in
+    pub club_memberships: LookupMap<String, String>,
+    /// Clubs with the most wins, best first
+    pub club_leaderboard: Vec<ClubEntry>,
+    /// Activity of the last 52 weeks, each in the slot of its week index modulo 52
+    pub weekly_stats: Vec<WeekStats>,
+    /// Won wager pots waiting for withdraw_winnings, in yoctoNEAR
+    pub winnings: LookupMap<String, Balance>,
+    pub puzzles: LookupMap<String, Puzzle>,
+    pub last_puzzle_id: u64,
+    /// Each account's solves of a puzzle, keyed by `<puzzle_id>:<account_id>`
+    pub puzzle_solves: LookupMap<String, PuzzleSolve>,
+    /// Ids of the games created on each UTC day, keyed by `YYYYMMDD`
+    pub games_by_day: LookupMap<String, Vec<String>>,
+    /// Games still in the old layout while migrate_games works through them, the contract is paused until it's done
+    pub migration: Option<Migration>,
 }
 
 impl Default for Contract {
@@ -25,6 +193,46 @@ impl Default for Contract {
             // NOTE: This points to web4.near.page static by default
             // TODO: Point to default deployment of this game frontend
             web4_static_url: "ipfs://bafkreig74di4midqzggkjfmtfu4c7gei3u6scihgkvig2k4mjrovcjl4ri".to_string(),
+            config: ContractConfig::default(),
+            owner_id: env::current_account_id(),
+            session_keys: LookupMap::new(b"s".to_vec()),
+            wagers: LookupMap::new(b"w".to_vec()),
+            audit_log: Vector::new(b"a".to_vec()),
+            side_bets: LookupMap::new(b"b".to_vec()),
+            side_bet_stakes: LookupMap::new(b"k".to_vec()),
+            treasury: 0,
+            daily_games: LookupMap::new(b"d".to_vec()),
+            first_finished_at: LookupMap::new(b"f".to_vec()),
+            spam_deposits: LookupMap::new(b"p".to_vec()),
+            rooms: Vector::new(b"R".to_vec()),
+            tournaments: LookupMap::new(b"t".to_vec()),
+            last_tournament_id: 0,
+            ratings: LookupMap::new(b"r".to_vec()),
+            last_ranked_game_at: LookupMap::new(b"l".to_vec()),
+            records: LookupMap::new(b"c".to_vec()),
+            seasons: Vector::new(b"e".to_vec()),
+            season_history: LookupMap::new(b"h".to_vec()),
+            achievements: LookupMap::new(b"v".to_vec()),
+            daily_attempts: LookupMap::new(b"y".to_vec()),
+            daily_leaderboards: LookupMap::new(b"z".to_vec()),
+            open_games: vec![],
+            buttons: LookupMap::new(b"u".to_vec()),
+            button_names: vec![],
+            streaks: LookupMap::new(b"S".to_vec()),
+            replays: LookupMap::new(b"n".to_vec()),
+            replay_votes: LookupMap::new(b"o".to_vec()),
+            top_replays: vec![],
+            clubs: LookupMap::new(b"C".to_vec()),
+            last_club_id: 0,
+            club_memberships: LookupMap::new(b"m".to_vec()),
+            club_leaderboard: vec![],
+            weekly_stats: vec![],
+            winnings: LookupMap::new(b"W".to_vec()),
+            puzzles: LookupMap::new(b"P".to_vec()),
+            last_puzzle_id: 0,
+            puzzle_solves: LookupMap::new(b"Q".to_vec()),
+            games_by_day: LookupMap::new(b"D".to_vec()),
+            migration: None,
         }
     }
 }
@@ -36,96 +244,371 @@ fn user_games_key(player_id: String) -> Vec<u8> {
 fn get_user_games(player_id: String) -> Vec<String> {
     match env::storage_read(&user_games_key(player_id)) {
         Some(user_games_vec) => {
-            let user_games_str = String::from_utf8(user_games_vec).unwrap();
+            let user_games_str = String::from_utf8_lossy(&user_games_vec);
             user_games_str.split(",").map(|s| s.to_string()).collect::<Vec<String>>()
         },
         None => vec![],
     }
 }
 
+fn session_key_id(game_id: &str, public_key: &PublicKey) -> String {
+    format!("{}:{}", game_id, String::from(public_key))
+}
+
+/// Message a session key signs to authorize a move, e.g. `buttonmen.near:1:0:attack:0,1:2`.
+/// The nonce is tracked by the contract, so a signature can only be used once.
+fn session_message(game_id: &str, nonce: u64, action: &str) -> String {
+    format!("{}:{}:{}:{}", env::current_account_id(), game_id, nonce, action)
+}
+
+fn remove_user_game(player_id: String, game_id: &str) -> () {
+    let user_games_ids = get_user_games(player_id.to_string()).into_iter()
+        .filter(|id| id != game_id)
+        .collect::<Vec<String>>();
+    if user_games_ids.is_empty() {
+        env::storage_remove(&user_games_key(player_id));
+    } else {
+        env::storage_write(&user_games_key(player_id), user_games_ids.join(",").as_bytes());
+    }
+}
+
+fn notifications_key(account_id: &str) -> Vec<u8> {
+    format!("notify:{}", account_id).as_bytes().to_vec()
+}
+
+fn get_user_notifications(account_id: &str) -> Vec<Notification> {
+    match env::storage_read(&notifications_key(account_id)) {
+        Some(notifications_vec) => Vec::<Notification>::try_from_slice(&notifications_vec).unwrap(),
+        None => vec![],
+    }
+}
+
+/// Logs a NEP-297 event, e.g. `EVENT_JSON:{"standard":"buttonmen","version":"1.0.0","event":"notification","data":[...]}`
+fn emit_event(event: &str, data: serde_json::Value) {
+    env::log_str(&format!("EVENT_JSON:{}", serde_json::json!({
+        "standard": "buttonmen",
+        "version": "1.0.0",
+        "event": event,
+        "data": [data],
+    })));
+}
+
+/// Emits a notification event for indexers and appends it to the account's bounded queue.
+fn notify(account_id: &str, kind: &str, game_id: &str, actor: &str) {
+    let notification = Notification {
+        kind: kind.to_string(),
+        game_id: game_id.to_string(),
+        actor: actor.to_string(),
+    };
+    emit_event("notification", serde_json::json!({
+        "account_id": account_id,
+        "type": notification.kind,
+        "game_id": notification.game_id,
+        "actor": notification.actor,
+    }));
+
+    let mut notifications = get_user_notifications(account_id);
+    notifications.push(notification);
+    if notifications.len() > MAX_NOTIFICATIONS {
+        notifications.remove(0);
+    }
+    env::storage_write(&notifications_key(account_id), &notifications.try_to_vec().unwrap());
+}
+
 fn add_user_game(player_id: String, game_id: String) -> () {
     let mut user_games_ids = get_user_games(player_id.to_string());
     user_games_ids.push(game_id);
-    env::storage_write(&user_games_key(player_id), &user_games_ids.join(",").as_bytes());
+    env::storage_write(&user_games_key(player_id), user_games_ids.join(",").as_bytes());
 
     // TODO: Limit the number of games per user
 }
 
 #[near_bindgen]
 impl Contract {
+    /// Without an explicit init the contract account itself is the owner, see Default.
+    #[init]
+    pub fn new(owner_id: AccountId, web4_static_url: String) -> Self {
+        Self {
+            owner_id,
+            web4_static_url,
+            ..Default::default()
+        }
+    }
+
     /// Learn more about web4 here: https://web4.near.page
     pub fn web4_get(&self, request: Web4Request) -> Web4Response {
         if request.path == "/" || request.path.starts_with("/games/") {
             return self.serve_static("/index.html");
         }
 
+        if request.path == "/api/buttons" {
+            return Web4Response::Body {
+                content_type: "application/json".to_owned(),
+                body: serde_json::to_vec(&self.get_buttons()).unwrap().into(),
+                status: None,
+            }
+        }
+
         if request.path == "/config.js" {
             return Web4Response::Body {
                 content_type: "application/javascript".to_owned(),
                 body: format!("window._web4Config = {{ contractName: '{}' }};", env::current_account_id()).as_bytes().to_owned().into(),
+                status: None,
             }
         }
 
         // check path starts with /games/
         if request.path.starts_with("/api/games") {
             if request.path == "/api/games" {
+                let games = self.latest_games.iter()
+                    .filter_map(|game_id| self.games.get(game_id))
+                    .filter(|game| !self.is_lobby_expired(game))
+                    // TODO: Track games you joined separately
+                    // .filter(|game| { game.players.contains(&"".to_string()) })
+                    .map(|game| LobbyGame { ratings: game.players.iter().map(|player_id| self.rating_of(player_id)).collect(), game })
+                    .collect::<Vec<LobbyGame>>();
                 return Web4Response::Body {
                     content_type: "application/json".to_owned(),
-                    body: serde_json::to_vec(&self.latest_games.iter()
-                        .map(|game_id| { self.games.get(&game_id.to_string()).unwrap() })
-                        // TODO: Track games you joined separately
-                        // .filter(|game| { game.players.contains(&"".to_string()) })
-                        .collect::<Vec<Game>>()).unwrap().into(),
+                    body: serde_json::to_vec(&games).unwrap().into(),
+                    status: None,
                 }
             }
 
             let parts = request.path.split("/").collect::<Vec<&str>>();
-            let game_id = parts[3];
+            let game_id = parts.get(3).copied().unwrap_or_default();
+            if game_id == "by-day" {
+                return self.games_by_day_response(parts.get(4).unwrap_or(&""));
+            }
+
+            if parts.get(4) == Some(&"attacks") {
+                if self.games.get(&game_id.to_string()).is_none() {
+                    return error_response(Error::GameNotFound { game_id: game_id.to_string() });
+                }
+                let evaluate = request.query.get("evaluate").is_some_and(|values| values.iter().any(|value| value == "1"));
+                return Web4Response::Body {
+                    content_type: "application/json".to_owned(),
+                    body: serde_json::to_vec(&self.get_move_options(game_id.to_string(), Some(evaluate)).attacks).unwrap().into(),
+                    status: None,
+                }
+            }
+
+            if parts.get(4) == Some(&"odds") {
+                return match self.games.get(&game_id.to_string()) {
+                    Some(game) if game.status == GameStatus::Active => Web4Response::Body {
+                        content_type: "application/json".to_owned(),
+                        body: serde_json::to_vec(&self.estimate_win_probability(game_id.to_string(), odds::DEFAULT_ROLLOUTS)).unwrap().into(),
+                        status: None,
+                    },
+                    Some(_) => error_response(Error::GameNotActive { game_id: game_id.to_string() }),
+                    None => error_response(Error::GameNotFound { game_id: game_id.to_string() }),
+                }
+            }
+
+            if parts.get(4) == Some(&"moves") {
+                return self.moves_response(game_id);
+            }
+
+            if parts.get(4) == Some(&"result") {
+                return self.game_result_response(game_id);
+            }
+
+            if parts.get(4) == Some(&"rules") {
+                return match self.get_game_rules(game_id.to_string()) {
+                    Some(rules) => Web4Response::Body {
+                        content_type: "application/json".to_owned(),
+                        body: serde_json::to_vec(&rules).unwrap().into(),
+                        status: None,
+                    },
+                    None => error_response(Error::GameNotFound { game_id: game_id.to_string() }),
+                }
+            }
+
+            if parts.get(4) == Some(&"hint") {
+                return match self.games.get(&game_id.to_string()) {
+                    Some(game) if game.status == GameStatus::Active && (game.bot.is_some() || game.hints) => Web4Response::Body {
+                        content_type: "application/json".to_owned(),
+                        body: serde_json::to_vec(&self.get_hint(game_id.to_string())).unwrap().into(),
+                        status: None,
+                    },
+                    Some(_) => error_response(Error::NoHint { game_id: game_id.to_string() }),
+                    None => error_response(Error::GameNotFound { game_id: game_id.to_string() }),
+                }
+            }
 
             match self.games.get(&game_id.to_string()) {
                 Some(game) => {
-                    let game_view = GameView {
-                        id: game.id.clone(),
-                        players: game.players.clone(),
-                        current_player: game.current_player,
-                        dice: game.dice.clone(),
-                        captured: game.captured.clone(),
-                        is_pass_allowed: self.is_pass_allowed(&game),
-                    };
+                    let game_view = self.game_view(&game, request.account_id.as_deref());
                     return Web4Response::Body {
                         content_type: "application/json".to_owned(),
                         body: serde_json::to_vec(&game_view).unwrap().into(),
+                        status: None,
                     }
                 },
                 None => {
-                    // if game does not exist, return 404
-                    // TODO: Support HTTP error codes in boilerplate
-                    return Web4Response::Body {
-                        content_type: "text/html; charset=UTF-8".to_owned(),
-                        body: "<h1>Game not found</h1>".as_bytes().to_owned().into(),
-                    }
+                    return error_response(Error::GameNotFound { game_id: game_id.to_string() });
                 }
             }
         }
 
+        if request.path == "/api/my/feed" {
+            return match &request.account_id {
+                Some(account_id) => match account_id.parse() {
+                    Ok(account_id) => Web4Response::Body {
+                        content_type: "application/json".to_owned(),
+                        body: serde_json::to_vec(&self.get_following_feed(account_id, MAX_LATEST_GAMES as u64)).unwrap().into(),
+                        status: None,
+                    },
+                    Err(_) => error_response(Error::InvalidAccountId { account_id: account_id.to_string() }),
+                },
+                None => error_response(Error::NotLoggedIn),
+            }
+        }
+
+        if request.path == "/api/my/blocks" {
+            return match &request.account_id {
+                Some(account_id) => Web4Response::Body {
+                    content_type: "application/json".to_owned(),
+                    body: serde_json::to_vec(&blocks::get_blocks(account_id)).unwrap().into(),
+                    status: None,
+                },
+                None => error_response(Error::NotLoggedIn),
+            }
+        }
+
+        if request.path == "/api/daily" || request.path.starts_with("/api/daily/") {
+            let date = match request.path.split("/").nth(3) {
+                Some(date) => date.to_string(),
+                None => daily::utc_date(env::block_timestamp()),
+            };
+            return Web4Response::Body {
+                content_type: "application/json".to_owned(),
+                body: serde_json::json!({
+                    "date": date,
+                    "leaderboard": self.get_daily_leaderboard(date.clone()),
+                }).to_string().into_bytes().into(),
+                status: None,
+            }
+        }
+
+        if request.path == "/api/replays/top" {
+            return Web4Response::Body {
+                content_type: "application/json".to_owned(),
+                body: serde_json::to_vec(&self.get_top_replays(MAX_LATEST_GAMES as u64)).unwrap().into(),
+                status: None,
+            }
+        }
+
+        if request.path == "/api/clubs" {
+            return Web4Response::Body {
+                content_type: "application/json".to_owned(),
+                body: serde_json::to_vec(&self.get_club_leaderboard(MAX_LATEST_GAMES as u64)).unwrap().into(),
+                status: None,
+            }
+        }
+
+        if let Some(club_id) = request.path.strip_prefix("/api/clubs/") {
+            return match self.get_club(club_id.to_string()) {
+                Some(club) => Web4Response::Body {
+                    content_type: "application/json".to_owned(),
+                    body: serde_json::to_vec(&club).unwrap().into(),
+                    status: None,
+                },
+                None => error_response(Error::ClubNotFound { club_id: club_id.to_string() }),
+            }
+        }
+
+        if request.path == "/api/puzzles" {
+            return Web4Response::Body {
+                content_type: "application/json".to_owned(),
+                body: serde_json::to_vec(&self.list_puzzles()).unwrap().into(),
+                status: None,
+            }
+        }
+
+        if let Some(puzzle_id) = request.path.strip_prefix("/api/puzzles/") {
+            return match self.get_puzzle(puzzle_id.to_string()) {
+                Some(puzzle) => Web4Response::Body {
+                    content_type: "application/json".to_owned(),
+                    body: serde_json::to_vec(&puzzle).unwrap().into(),
+                    status: None,
+                },
+                None => error_response(Error::PuzzleNotFound { puzzle_id: puzzle_id.to_string() }),
+            }
+        }
+
+        if request.path == "/api/stats/weekly" {
+            return Web4Response::Body {
+                content_type: "application/json".to_owned(),
+                body: serde_json::to_vec(&self.get_weekly_stats(52)).unwrap().into(),
+                status: None,
+            }
+        }
+
+        if request.path == "/api/tournaments" {
+            return Web4Response::Body {
+                content_type: "application/json".to_owned(),
+                body: serde_json::to_vec(&self.get_latest_tournaments(MAX_LATEST_GAMES as u64)).unwrap().into(),
+                status: None,
+            }
+        }
+
+        if request.path.starts_with("/api/tournaments/") {
+            let parts = request.path.split("/").collect::<Vec<&str>>();
+            let tournament_id = parts.get(3).copied().unwrap_or_default();
+            return match self.tournaments.get(&tournament_id.to_string()) {
+                Some(tournament) if parts.get(4) == Some(&"standings") => Web4Response::Body {
+                    content_type: "application/json".to_owned(),
+                    body: serde_json::to_vec(&swiss::standings(&tournament)).unwrap().into(),
+                    status: None,
+                },
+                Some(tournament) => Web4Response::Body {
+                    content_type: "application/json".to_owned(),
+                    body: serde_json::to_vec(&tournament).unwrap().into(),
+                    status: None,
+                },
+                None => error_response(Error::TournamentNotFound { tournament_id: tournament_id.to_string() }),
+            }
+        }
+
+        if request.path == "/api/leaderboard" {
+            return Web4Response::Body {
+                content_type: "application/json".to_owned(),
+                body: serde_json::to_vec(&self.ranked(leaderboard::read_leaderboard(self.current_season_id()))).unwrap().into(),
+                status: None,
+            }
+        }
+
+        if request.path == "/api/rooms" {
+            return Web4Response::Body {
+                content_type: "application/json".to_owned(),
+                body: serde_json::to_vec(&self.list_rooms()).unwrap().into(),
+                status: None,
+            }
+        }
+
         if request.path.starts_with("/api/users") {
             let parts = request.path.split("/").collect::<Vec<&str>>();
-            let user_id = parts[3];
+            let user_id = parts.get(3).copied().unwrap_or_default();
 
-            if parts[4] == "games" {
-                let user_games_ids = match env::storage_read(format!("ug:{}", user_id).as_bytes()) {
-                    Some(user_games_vec) => {
-                        let user_games_str = String::from_utf8(user_games_vec).unwrap();
-                        user_games_str.split(",").map(|s| s.to_string()).collect::<Vec<String>>()
+            if parts.get(4) == Some(&"awaiting") {
+                return match user_id.parse() {
+                    Ok(account_id) => Web4Response::Body {
+                        content_type: "application/json".to_owned(),
+                        body: serde_json::to_vec(&self.get_games_awaiting_move(account_id)).unwrap().into(),
+                        status: None,
                     },
-                    None => vec![],
-                };
+                    Err(_) => error_response(Error::InvalidAccountId { account_id: user_id.to_string() }),
+                }
+            }
 
+            if parts.get(4) == Some(&"games") {
+                let games = get_user_games(user_id.to_string()).iter()
+                    .filter_map(|game_id| self.games.get(game_id))
+                    .collect::<Vec<Game>>();
                 return Web4Response::Body {
                     content_type: "application/json".to_owned(),
-                    body: serde_json::to_vec(&user_games_ids.iter()
-                        .map(|game_id| { self.games.get(&game_id.to_string()).unwrap() })
-                        .collect::<Vec<Game>>()).unwrap().into(),
+                    body: serde_json::to_vec(&games).unwrap().into(),
+                    status: None,
                 }
             }
 
@@ -142,25 +625,146 @@ impl Contract {
         }
     }
 
-    pub fn create_game(&mut self) -> String {
-        self.last_game_id += 1;
-        let game_id = format!("{}", self.last_game_id);
+    fn is_owner(&self) -> bool {
+        env::predecessor_account_id() == self.owner_id
+    }
+
+    /// Every state-changing game method must call this first, so that the owner
+    /// can freeze gameplay while views and web4_get keep serving existing games.
+    /// Games are also frozen while a migration is under way, some of them are still in the old layout.
+    fn assert_not_paused(&self) {
+        if self.config.paused || self.migration.is_some() {
+            fail(Error::ContractPaused);
+        }
+    }
+
+    /// Free up to ContractConfig.free_games_per_day games a day, after that the game creation fee has to be attached.
+    /// Accounts which never finished a game also attach the refundable ContractConfig.new_account_deposit.
+    #[payable]
+    pub fn create_game(&mut self, options: Option<CreateGameOptions>) -> String {
+        self.assert_not_paused();
+
+        let fee = self.charge_game_creation();
+        let spam_deposit = self.required_spam_deposit(&env::predecessor_account_id());
+        if env::attached_deposit() != fee + spam_deposit {
+            fail(Error::WrongDeposit { amount: fee + spam_deposit, action: "create a game".to_string() });
+        }
+
+        let listed = options.as_ref().is_none_or(|options| options.public != Some(false));
+        let game_id = self.internal_create_game(options);
+        self.hold_spam_deposit(&game_id, spam_deposit);
+        if listed {
+            self.add_open_game(&game_id);
+        }
+        return game_id;
+    }
+
+    /// Game as seen by the given account, the public view without one. In a fogged game a player who's waiting for their turn
+    /// only sees the sizes of the opponent's dice, values are zeroed until it's their turn or the game ends.
+    fn game_view(&self, game: &Game, account_id: Option<&str>) -> GameView {
+        let mut dice = game.dice.clone();
+        let reader_index = account_id.and_then(|account_id| game.players.iter().position(|p| p == account_id));
+        if let Some(reader_index) = reader_index {
+            if game.fog && game.status == GameStatus::Active && game.current_player as usize != reader_index {
+                for (player_index, player_dice) in dice.iter_mut().enumerate() {
+                    if player_index != reader_index {
+                        player_dice.iter_mut().for_each(|die| die.value = 0);
+                    }
+                }
+            }
+        }
+
+        GameView {
+            id: game.id.clone(),
+            players: game.players.clone(),
+            current_player: match game.current_player {
+                0xFF => None,
+                current_player => Some(current_player),
+            },
+            dice,
+            captured: game.captured.clone(),
+            captured_value: game.captured.iter().map(|sizes| sizes.iter().map(|size| *size as u32).sum()).collect(),
+            is_pass_allowed: self.is_pass_allowed(game),
+            round_seed: game.round_seed.clone().into(),
+            buttons: game.buttons.clone(),
+            verifying: game.verifying,
+            status: if self.is_lobby_expired(game) { GameStatus::Expired } else { game.status },
+            winner: game.winner,
+            auxiliary_choices: game.auxiliary_choices.clone(),
+            report_to: game.report_to.clone(),
+            result_reported: game.result_reported,
+            undo_requested_by: game.undo_requested_by,
+            fog: game.fog,
+            ratings: game.players.iter().map(|player_id| self.rating_of(player_id)).collect(),
+            rating_deltas: game.rating_deltas.clone(),
+            titles: game.players.iter().map(|player_id| self.title_for(self.rating_of(player_id))).collect(),
+            badges: game.players.iter().map(|player_id| self.badges_of(player_id)).collect(),
+            clubs: game.players.iter().map(|player_id| self.club_memberships.get(player_id)).collect(),
+            your_turn: account_id.map(|_| game.status == GameStatus::Active
+                && reader_index.is_some_and(|reader_index| game.current_player as usize == reader_index)),
+            time_control: game.time_control,
+            remaining_ns: (0..game.remaining_ns.len()).map(|player_index| clocks::time_left(game, player_index)).collect(),
+            extension_requested: game.extension_requested,
+            pause_requested_by: game.pause_requested_by,
+            paused_until: game.pause.filter(|_| pauses::is_paused(game)).map(|pause| pause.ends_at),
+        }
+    }
+
+    fn internal_create_game(&mut self, options: Option<CreateGameOptions>) -> String {
+        let options = options.unwrap_or_default();
+        let button_name = options.button.unwrap_or(DEFAULT_BUTTON.to_string());
+        let button = self.find_button(&button_name).unwrap_or_else(|| fail(Error::ButtonNotFound { button: button_name.to_string() }));
+
+        let game_id = self.next_game_id();
         let player_id = env::predecessor_account_id();
 
+        let time_control = clocks::resolve_time_control(options.time_preset, options.time_control);
+
         let mut rng = Rng::new(&env::random_seed());
         let game = Game {
             id: game_id.clone(),
             players: vec![player_id.to_string(), "".to_string()],
             current_player: 0xFF,
-            // TODO: Roll dice according to character sheet
-            dice: vec![roll_dice(&mut rng, vec![4, 6, 8, 10, 20]), vec![]],
+            dice: vec![roll_dice(&mut rng, button.dice.to_vec()), vec![]],
             captured: vec![vec![], vec![]],
+            round_seed: vec![],
+            buttons: vec![button_name.clone(), "".to_string()],
+            verifying: self.verify_button(&game_id, &player_id, &button_name),
+            status: GameStatus::Waiting,
+            winner: None,
+            created_at: env::block_timestamp(),
+            last_move_at: env::block_timestamp(),
+            auxiliary_choices: vec![],
+            history: vec![],
+            report_to: options.report_to,
+            result_reported: None,
+            undo_requested_by: None,
+            tournament_id: None,
+            fog: options.fog,
+            rating_deltas: vec![],
+            finish_reason: None,
+            season_id: None,
+            roll_seq: 0,
+            daily: None,
+            unlisted: options.public == Some(false),
+            bot: None,
+            hints: options.hints,
+            auto_forced: vec![],
+            time_control,
+            remaining_ns: vec![],
+            turn_started_at: 0,
+            extension_requested: None,
+            pause_requested_by: None,
+            pause: None,
+            paused_ns: 0,
         };
 
-        self.games.insert(&game_id, &game);
-        self.latest_games.push(game_id.clone());
-        if self.latest_games.len() > MAX_LATEST_GAMES {
-            self.latest_games.remove(0);
+        self.save_game(&game);
+        if !game.unlisted {
+            self.latest_games.push(game_id.clone());
+            if self.latest_games.len() > MAX_LATEST_GAMES {
+                self.latest_games.remove(0);
+            }
         }
 
         add_user_game(player_id.to_string(), game_id.clone());
@@ -168,245 +772,750 @@ impl Contract {
         return game_id;
     }
 
-    pub fn join_game(&mut self, game_id: String) -> () {
+    /// Drops a cancelled or deleted game from the lobby, which reads every game it lists
+    pub(crate) fn remove_from_latest(&mut self, game_id: &str) {
+        self.latest_games.retain(|id| id != game_id);
+    }
+
+    /// Returns the active game between the two accounts, or seats them in a new one.
+    /// Lets a matchmaker make sure a pair has a game without tracking game ids itself.
+    pub fn get_or_create_for_pair(&mut self, a: AccountId, b: AccountId) -> String {
+        self.assert_owner("pair players");
+        if a == b {
+            fail(Error::SelfPair);
+        }
+        self.assert_not_paused();
+
+        let existing = get_user_games(a.to_string()).into_iter().find(|game_id| {
+            self.games.get(game_id).is_some_and(|game| game.status == GameStatus::Active && game.players.contains(&b.to_string()))
+        });
+        match existing {
+            Some(game_id) => game_id,
+            None => self.create_seated_game(&[a.to_string(), b.to_string()], None),
+        }
+    }
+
+    /// Games the player is in that have started and aren't over yet, the dashboard's main query.
+    /// Only the player's own games are read, and they come back as summaries without dice or history.
+    pub fn my_active_games(&self, player_id: AccountId) -> Vec<GameSummary> {
+        get_user_games(player_id.to_string()).into_iter()
+            .filter_map(|game_id| self.games.get(&game_id))
+            .filter(|game| game.status == GameStatus::Active)
+            .map(|game| GameSummary {
+                your_turn: game.players.get(game.current_player as usize) == Some(&player_id.to_string()),
+                paused: pauses::is_paused(&game),
+                id: game.id,
+                players: game.players,
+                buttons: game.buttons,
+                current_player: game.current_player,
+                last_move_at: game.last_move_at,
+                tournament_id: game.tournament_id,
+            })
+            .collect()
+    }
+
+    /// Ids of the player's games that wait on them: active ones where it's their turn, and ones that haven't started
+    /// because they still have to accept or decline the auxiliary dice. Reserve dice aren't in the game yet.
+    /// Most urgent first, see get_games_awaiting_move.
+    pub fn games_awaiting_me(&self, player_id: AccountId) -> Vec<String> {
+        self.get_games_awaiting_move(player_id).into_iter().map(|awaiting| awaiting.game_id).collect()
+    }
+
+    /// Creates a game with every seat already taken, playing the default button, and starts the round.
+    /// Used where someone else pairs the players up, e.g. tournaments. It's not listed in the lobby.
+    pub(crate) fn create_seated_game(&mut self, players: &[String], tournament_id: Option<String>) -> String {
+        let button = self.find_button(DEFAULT_BUTTON).unwrap_or_else(|| fail(Error::ButtonNotFound { button: DEFAULT_BUTTON.to_string() }));
+
+        let game_id = self.next_game_id();
+
+        let mut game = Game {
+            id: game_id.clone(),
+            players: players.to_vec(),
+            current_player: 0xFF,
+            dice: players.iter().map(|_| button.dice.iter().map(|size| Die { size: *size, value: 0 }).collect()).collect(),
+            captured: players.iter().map(|_| vec![]).collect(),
+            buttons: players.iter().map(|_| DEFAULT_BUTTON.to_string()).collect(),
+            created_at: env::block_timestamp(),
+            tournament_id,
+            ..Default::default()
+        };
+        for player_id in players.iter() {
+            add_user_game(player_id.clone(), game_id.clone());
+        }
+        self.start_round(&mut game, env::predecessor_account_id().as_str());
+
+        return game_id;
+    }
+
+    pub fn join_game(&mut self, game_id: String, button: Option<String>) -> () {
+        self.assert_not_paused();
+
+        if let Some(wager) = self.wagers.get(&game_id) {
+            fail(Error::WagerRequired { game_id: game_id.to_string(), stake: wager.stake.0 });
+        }
+
+        self.internal_join_game(game_id, button);
+    }
+
+    /// Takes an empty seat for the caller. Finding the seat and writing the player into it happen in this one call,
+    /// with no promise in between, and calls run one after another, so a second join in the same block reads the
+    /// game with the seat taken. The only promise, verify_button's, is made once the seat is written, and
+    /// game.verifying keeps other joins and cancellation out until on_button_verified is back.
+    fn internal_join_game(&mut self, game_id: String, button: Option<String>) -> () {
         let player_id = env::predecessor_account_id().to_string();
+        let button_name = button.unwrap_or(DEFAULT_BUTTON.to_string());
+        let button = self.find_button(&button_name).unwrap_or_else(|| fail(Error::ButtonNotFound { button: button_name.to_string() }));
 
         match self.games.get(&game_id) {
             Some(mut game) => {
-                // Check if the player has already joined
+                game.assert_two_players();
+
+                // Check if the player has already joined. This must happen before looking for
+                // an empty slot, whichever slot the player is in, so nobody can take both seats.
                 if game.players.contains(&player_id) {
-                    panic!("Player {} has already joined game {}", player_id, game_id);
+                    fail(Error::AlreadyJoined { player_id: player_id.to_string(), game_id: game_id.to_string() });
+                }
+
+                if blocks::is_blocked(&game.players[0], &player_id) {
+                    fail(Error::NotInvited { game_id: game_id.to_string(), player_id: player_id.to_string() });
+                }
+
+                if game.verifying {
+                    fail(Error::VerifyingButtons { game_id: game_id.to_string() });
+                }
+
+                if game.status == GameStatus::Cancelled {
+                    fail(Error::GameCancelled { game_id: game_id.to_string() });
                 }
 
                 // Find an empty slot for the player
-                match game.players.iter().position(|p| p == "") {
+                match game.players.iter().position(|p| p.is_empty()) {
                     Some(player_index) => {
                         // Assign the player to the game
                         game.players[player_index] = player_id.to_string();
-                        // TODO: Roll dice according to character sheet
-                        let mut rng = Rng::new(&env::random_seed());
-                        game.dice[player_index] = roll_dice(&mut rng, vec![4, 6, 8, 10, 20]);
-
-                        // Sorted dice from lowest to highest for every player
-                        let sorted_dice = game.dice.iter().cloned().map(|mut dice| {
-                            dice.sort_by(|a, b| a.value.cmp(&b.value));
-                            dice
-                        }).collect::<Vec<Vec<Die>>>();
-
-                        // Whoever rolled the single lowest number will go first.
-                        // If the lowest dice are tied, compare the next lowest dice,
-                        // and so on until a starting player is determined.
-                        // TODO: If all numbers are tied, the round is a draw.
-                        game.current_player = 0;
-                        'outer: for i in 0..sorted_dice[0].len() {
-                            for player in 0..sorted_dice.len() {
-                                if sorted_dice[player][i].value < sorted_dice[game.current_player as usize][i].value {
-                                    game.current_player = player as u8;
-                                    break 'outer;
-                                }
-                            }
-                        }
-
-                        // Update the game state
-                        self.games.insert(&game_id, &game);
+                        game.buttons[player_index] = button_name.clone();
+                        game.dice[player_index] = button.dice.iter().map(|size| Die { size: *size, value: 0 }).collect();
 
                         add_user_game(player_id.to_string(), game_id.clone());
+
+                        // Auxiliary dice are only added if every player accepts them before the round starts
+                        if game.buttons.iter().any(|name| self.find_button(name).is_some_and(|button| !button.auxiliary.is_empty())) {
+                            game.auxiliary_choices = vec![None; game.players.len()];
+                        }
+
+                        // The round starts only once the player is known to own the button
+                        game.verifying = self.verify_button(&game_id, &env::predecessor_account_id(), &button_name);
+                        self.try_start_round(&mut game, &player_id);
                     },
                     None => {
-                        panic!("Game is full: {}", game_id);
+                        fail(Error::GameFull { game_id: game_id.to_string() });
                     }
                 }
             }
             None => {
-                panic!("Game not found: {}", game_id);
+                fail(Error::GameNotFound { game_id: game_id.to_string() });
             }
         }
     }
 
-    pub fn attack(&mut self, game_id: String, attacker_die_indices: Vec<u8>, defender_die_index: u8) -> () {
+    /// Starts the round once every seat is taken, buttons are verified and auxiliary dice are agreed on.
+    fn try_start_round(&mut self, game: &mut Game, player_id: &str) {
+        let ready = !game.verifying
+            && game.players.iter().all(|p| !p.is_empty())
+            && game.auxiliary_choices.iter().all(|choice| choice.is_some());
+        if ready {
+            self.start_round(game, player_id);
+        } else {
+            self.save_game(game);
+        }
+    }
+
+    /// Accepts or declines the auxiliary dice before the round starts.
+    /// They are added to every player's dice only if all players accept.
+    pub fn accept_auxiliary(&mut self, game_id: String, accept: bool) -> () {
+        self.assert_not_paused();
+
         let player_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        let player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() }));
+        if game.is_started() {
+            fail(Error::GameAlreadyStarted { game_id: game_id.to_string() });
+        }
+        if game.auxiliary_choices.is_empty() {
+            fail(Error::NoAuxiliaryDice { game_id: game_id.to_string() });
+        }
 
-        match self.games.get(&game_id) {
-            Some(mut game) => {
-                let current_player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| panic!("Player {} has not joined game {}", player_id, game_id));
-                if game.current_player != current_player_index as u8 {
-                    panic!("It is not your turn");
-                }
+        game.auxiliary_choices[player_index] = Some(accept);
+        if game.auxiliary_choices.iter().all(|choice| *choice == Some(true)) {
+            for player_index in 0..game.players.len() {
+                let button_name = &game.buttons[player_index];
+                let button = self.find_button(button_name).unwrap_or_else(|| fail(Error::ButtonNotFound { button: button_name.to_string() }));
+                game.dice[player_index].extend(button.auxiliary.iter().map(|size| Die { size: *size, value: 0 }));
+            }
+        }
 
-                let attacker_dice_idx = game.current_player as usize;
-                let defender_dice_idx = (game.current_player + 1) as usize % 2;
-
-                // Perform power attack or skill attack based on the number of attacker dice indices
-                let attack_value = attacker_die_indices.iter().fold(0, |acc, index| acc + game.dice[attacker_dice_idx][*index as usize].value);
-                let attack_success = if attacker_die_indices.len() == 1 {
-                    // Power attack
-                    attack_value >= game.dice[defender_dice_idx][defender_die_index as usize].value
-                } else {
-                    // Skill attack
-                    attack_value == game.dice[defender_dice_idx][defender_die_index as usize].value
-                };
-
-                if !attack_success {
-                    panic!("Attack failed");
-                }
+        self.try_start_round(&mut game, &player_id);
+    }
 
-                // Capture the die
-                game.captured[current_player_index].push(game.dice[defender_dice_idx][defender_die_index as usize].size);
-                game.dice[defender_dice_idx].remove(defender_die_index as usize);
-                // Re-roll attacker dice
-                let mut rng = Rng::new(&env::random_seed());
-                attacker_die_indices.iter().for_each(|index| {
-                    game.dice[attacker_dice_idx][*index as usize] = roll_die(&mut rng, game.dice[attacker_dice_idx][*index as usize].size);
-                });
-                // Switch to the next player
-                game.current_player = (game.current_player + 1) % 2;
-
-                // Check win condition
-                if game.dice[defender_dice_idx].len() == 0 {
-                    // TODO: End game
-                }
+    fn start_round(&mut self, game: &mut Game, player_id: &str) {
+        // All starting dice are rolled from the stored round seed,
+        // so anyone can reproduce them with roll_round_dice.
+        // Re-rolls for a tied initiative come from the seed's hash.
+        game.round_seed = env::random_seed();
+        game.dice = roll_round_dice(&game.round_seed, &game.dice);
+        game.status = GameStatus::Active;
+        game.last_move_at = env::block_timestamp();
+
+        let mut rng = Rng::new(&env::sha256(&game.round_seed));
+        game.current_player = settle_initiative(&mut game.dice, &mut rng, self.config.max_initiative_rerolls);
+        clocks::start_clocks(game);
+
+        // Update the game state
+        self.save_game(game);
+
+        let first_player_id = &game.players[game.current_player as usize];
+        if first_player_id != player_id && game.bot.is_none() {
+            notify(first_player_id, "your_turn", &game.id, player_id);
+        }
+    }
 
-                // Update the game state
-                self.games.insert(&game_id, &game);
+    /// Starts an NFT ownership check if the button is gated by ContractConfig.premium_buttons.
+    /// Returns true when the game has to wait for on_button_verified.
+    fn verify_button(&self, game_id: &str, player_id: &AccountId, button_name: &str) -> bool {
+        match self.config.premium_buttons.get(button_name) {
+            Some(gate) => {
+                ext_nft::ext(gate.nft_contract_id.clone())
+                    .with_static_gas(GAS_FOR_NFT_TOKENS)
+                    .nft_tokens_for_owner(player_id.clone(), None, Some(NFT_TOKENS_LIMIT))
+                    .then(Self::ext(env::current_account_id())
+                        .with_static_gas(GAS_FOR_BUTTON_VERIFIED)
+                        .on_button_verified(game_id.to_string(), player_id.to_string(), gate.token_series.clone()));
+                true
             },
-            None => {
-                panic!("Game not found: {}", game_id);
-            }
+            None => false,
         }
     }
 
-    fn find_power_attack(game: &Game) -> Option<(usize, usize)> {
-        let current_player_index = game.current_player as usize;
-        let other_player_index = (game.current_player as usize + 1) % 2;
+    #[private]
+    pub fn on_button_verified(&mut self, game_id: String, player_id: String, token_series: String, #[callback_result] tokens: Result<Vec<NftToken>, PromiseError>) -> bool {
+        let mut game = match self.games.get(&game_id) {
+            Some(game) => game,
+            None => return false,
+        };
+        let player_index = match game.players.iter().position(|p| p == &player_id) {
+            Some(player_index) => player_index,
+            None => return false,
+        };
+        // Nothing is changed in a game that moved on while the call was out
+        if game.status != GameStatus::Waiting {
+            return false;
+        }
 
-        // Verify that power attack is not possible
-        for attacker_die_index in 0..game.dice[current_player_index].len() {
-            for defender_die_index in 0..game.dice[other_player_index].len() {
-                if game.dice[current_player_index][attacker_die_index].value >= game.dice[other_player_index][defender_die_index].value {
-                    return Some((attacker_die_index, defender_die_index));
-                }
-            }
+        let owned = match tokens {
+            Ok(tokens) => tokens.iter().any(|token| token_in_series(&token.token_id, &token_series)),
+            Err(_) => false,
+        };
+
+        game.verifying = false;
+        if owned {
+            self.try_start_round(&mut game, &player_id);
+            return true;
         }
 
-        return None;
+        emit_event("button_verification_failed", serde_json::json!({
+            "game_id": game_id,
+            "player_id": player_id,
+            "button": game.buttons[player_index],
+        }));
+        remove_user_game(player_id.clone(), &game_id);
+        awaiting::remove_awaiting(&player_id, &game_id);
+        self.refund_released_seat(&game_id, &player_id);
+        if player_index == 0 {
+            self.refund_spam_deposit(&game);
+        }
+        if game.players.iter().all(|p| p.is_empty() || p == &player_id) {
+            // Nobody else is in the game, so it's dropped altogether
+            self.games.remove(&game_id);
+            self.remove_from_latest(&game_id);
+            self.remove_from_day(&game);
+            self.wagers.remove(&game_id);
+        } else {
+            game.players[player_index] = "".to_string();
+            game.buttons[player_index] = "".to_string();
+            game.dice[player_index] = vec![];
+            self.save_game(&game);
+        }
+        return false;
     }
 
-    fn find_skill_attack(game: &Game) -> Option<(Vec<u8>, u8)> {
-        let current_player_index = game.current_player as usize;
-        let other_player_index = (game.current_player as usize + 1) % 2;
-
-        fn find_skill_attack_recursive(attacker_dice_values: &[u8], defender_die_value: u8, selected_attacker_dice: Vec<u8>) -> Option<Vec<u8>> {
-            if attacker_dice_values.len() == 0 {
-                if defender_die_value == 0 {
-                    return Some(selected_attacker_dice);
-                } else {
-                    return None;
-                }
-            }
+    /// Registers an ed25519 key which a relayer can use to submit moves on behalf of the caller in the given game.
+    pub fn register_session_key(&mut self, game_id: String, public_key: PublicKey) -> () {
+        self.assert_not_paused();
 
-            if let Some(result) = find_skill_attack_recursive(&attacker_dice_values[1..], defender_die_value, selected_attacker_dice.clone()) {
-                return Some(result);
-            }
+        let player_id = env::predecessor_account_id().to_string();
+        let game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        if !game.players.contains(&player_id) {
+            fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() });
+        }
+        if public_key.curve_type() != CurveType::ED25519 {
+            fail(Error::SessionKeyNotEd25519);
+        }
+        // A key is registered once: taking over another player's key would let it move for them, and registering
+        // it again would reset its nonce so signatures already relayed could be replayed
+        let key_id = session_key_id(&game_id, &public_key);
+        if self.session_keys.get(&key_id).is_some() {
+            fail(Error::SessionKeyRegistered { game_id: game_id.to_string() });
+        }
 
-            if defender_die_value >= attacker_dice_values[0] {
-                if let Some(result) = find_skill_attack_recursive(&attacker_dice_values[1..], defender_die_value - attacker_dice_values[0], {
-                    let mut selected_attacker_dice = selected_attacker_dice.clone();
-                    selected_attacker_dice.push(0);
-                    selected_attacker_dice
-                }) {
-                    return Some(result);
-                }
-            }
+        self.session_keys.insert(&key_id, &SessionKey {
+            player_id,
+            nonce: 0,
+        });
+    }
 
-            return None;
-        }
+    pub fn get_session_nonce(&self, game_id: String, public_key: PublicKey) -> Option<u64> {
+        self.session_keys.get(&session_key_id(&game_id, &public_key)).map(|session_key| session_key.nonce)
+    }
+
+    /// Returns the account making the move: either the predecessor or, for relayed moves,
+    /// the player who registered `signer_pk` as a session key for this game.
+    fn resolve_player(&mut self, game_id: &str, action: &str, signature: Option<Base64VecU8>, signer_pk: Option<PublicKey>) -> String {
+        let (signature, signer_pk) = match (signature, signer_pk) {
+            (None, None) => return env::predecessor_account_id().to_string(),
+            (Some(signature), Some(signer_pk)) => (signature, signer_pk),
+            _ => fail(Error::PartialSignature),
+        };
 
-        // Verify that skill attack is not possible
-        for defender_die_index in 0..game.dice[other_player_index].len() {
-            let defender_die_value = game.dice[other_player_index][defender_die_index].value;
-            let attacker_dice_values = game.dice[current_player_index].iter().map(|die| die.value).collect::<Vec<u8>>();
+        let key_id = session_key_id(game_id, &signer_pk);
+        let mut session_key = self.session_keys.get(&key_id).unwrap_or_else(|| fail(Error::SessionKeyNotRegistered { game_id: game_id.to_string() }));
 
-            let result = find_skill_attack_recursive(&attacker_dice_values, defender_die_value, vec![]);
-            if result.is_some() {
-                return Some((result.unwrap(), defender_die_value));
-            }
+        let message = session_message(game_id, session_key.nonce, action);
+        let public_key = ed25519_dalek::PublicKey::from_bytes(&signer_pk.as_bytes()[1..]).unwrap_or_else(|_| fail(Error::InvalidSessionKey));
+        let signature = ed25519_dalek::Signature::from_bytes(&Vec::<u8>::from(signature)).unwrap_or_else(|_| fail(Error::InvalidSessionSignature));
+        if public_key.verify_strict(message.as_bytes(), &signature).is_err() {
+            fail(Error::InvalidSessionSignature);
         }
 
-        return None;
+        // Consume the nonce so the same signature can't be replayed
+        session_key.nonce += 1;
+        self.session_keys.insert(&key_id, &session_key);
+
+        return session_key.player_id;
     }
 
-    fn is_pass_allowed(&self, game: &Game) -> bool {
-        if game.current_player as usize > game.players.len() {
-            // Game not started yet
-            return false;
-        }
+    /// Whether the given account is the one expected to move in a game in progress.
+    pub fn is_player_turn(&self, game_id: String, player_id: AccountId) -> bool {
+        let game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        game.status == GameStatus::Active
+            && game.players.get(game.current_player as usize) == Some(&player_id.to_string())
+    }
 
-        let current_player_index = game.current_player as usize;
-        let other_player_index = (game.current_player as usize + 1) % 2;
+    /// Scores and the player who'd win if the game ended now, without ending it.
+    /// Scores are doubled like score(), a resignation can overrule them once the game is finished.
+    pub fn projected_result(&self, game_id: String) -> ProjectedResult {
+        let game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        let scores = scores(&game);
+        ProjectedResult { leader: leader(&scores), scores }
+    }
 
-        let power_attack = Self::find_power_attack(game);
-        if power_attack.is_some() {
-            return false;
+    /// Everything the player to move can do, in one call: the attacks, whether passing is allowed and whose turn it is.
+    /// With evaluate each attack comes with its policies::evaluate_attack, off by default to keep the view cheap.
+    pub fn get_move_options(&self, game_id: String, evaluate: Option<bool>) -> MoveOptions {
+        let game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        if game.status != GameStatus::Active {
+            return MoveOptions { attacks: vec![], can_pass: false, your_turn_for: None };
         }
 
-        let skill_attack = Self::find_skill_attack(game);
-        if skill_attack.is_some() {
-            return false;
+        let mut attacks = attack_options(&game);
+        if evaluate == Some(true) {
+            for attack in attacks.iter_mut() {
+                attack.evaluation = Some(policies::evaluate_attack(&game, attack));
+            }
+        }
+        MoveOptions {
+            attacks,
+            can_pass: self.is_pass_allowed(&game),
+            your_turn_for: Some(game.players[game.current_player as usize].clone()),
         }
-
-        return true;
     }
 
-    pub fn pass(&mut self, game_id: String) -> () {
-        let player_id = env::predecessor_account_id().to_string();
+    pub fn attack(&mut self, game_id: String, attacker_die_indices: Vec<u8>, defender_die_index: u8, signature: Option<Base64VecU8>, signer_pk: Option<PublicKey>, attack_type: Option<AttackType>) -> AttackView {
+        self.assert_not_paused();
+
+        let mut action = format!("attack:{}:{}", attacker_die_indices.iter().map(|index| index.to_string()).collect::<Vec<String>>().join(","), defender_die_index);
+        if let Some(attack_type) = attack_type {
+            action = format!("{}:{}", action, attack_type.as_str());
+        }
+        let player_id = self.resolve_player(&game_id, &action, signature, signer_pk);
 
         match self.games.get(&game_id) {
             Some(mut game) => {
-                let current_player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| panic!("Player {} has not joined game {}", player_id, game_id));
+                assert_in_progress(&game);
+                let current_player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() }));
+                pauses::assert_running(&mut game);
                 if game.current_player != current_player_index as u8 {
-                    panic!("It is not your turn");
+                    fail(Error::NotYourTurn);
                 }
-
-                let power_attack = Self::find_power_attack(&game);
-                if power_attack.is_some() {
-                    panic!("Power attack is possible");
+                // Bounds the work of resolving the attack by the dice in play rather than by what the caller sends
+                if attacker_die_indices.len() > game.dice[current_player_index].len() {
+                    fail(Error::TooManyAttackers);
                 }
 
-                let skill_attack = Self::find_skill_attack(&game);
-                if skill_attack.is_some() {
-                    panic!("Skill attack is possible");
+                let attack_view = self.apply_attack(&mut game, attacker_die_indices, defender_die_index, attack_type);
+                if game.daily.is_some() {
+                    self.play_daily_opponent(&mut game);
+                }
+                if game.bot.is_some() {
+                    self.play_bot(&mut game);
                 }
+                self.play_forced_moves(&mut game);
 
-                // Switch to the next player
-                game.current_player = (game.current_player + 1) % 2;
+                // Update the game state
+                self.save_game(&game);
+
+                if !game.is_finished() && !game.against_contract() {
+                    notify(&game.players[game.current_player as usize], "your_turn", &game_id, &player_id);
+                }
+
+                return attack_view;
+            },
+            None => {
+                fail(Error::GameNotFound { game_id: game_id.to_string() });
+            }
+        }
+    }
+
+    /// Makes the current player's attack, finishing the game if it captured the last die
+    pub(crate) fn apply_attack(&mut self, game: &mut Game, attacker_die_indices: Vec<u8>, defender_die_index: u8, attack_type: Option<AttackType>) -> AttackView {
+        let current_player_index = game.current_player as usize;
+        let defender_dice_idx = (game.current_player + 1) as usize % 2;
+        let elapsed_ns = clocks::on_clock(game);
+        let remaining_before = game.remaining_ns.clone();
+        clocks::charge_clock(game);
+
+        let dice_before = game.dice.clone();
+        let mut rolls = GameRolls::new(game);
+        let attack_view = engine::resolve_attack(game, &attacker_die_indices, defender_die_index, attack_type, &mut rolls);
+        game.roll_seq = rolls.roll_seq;
+        let mut record = MoveRecord::new(current_player_index as u8, MoveKind::Attack);
+        record.rerolled = attack_view.rerolled.clone();
+        record.attacker_die_indices = attacker_die_indices;
+        record.defender_die_index = Some(defender_die_index);
+        record.captured = game.captured[current_player_index].last().copied();
+        record.dice_before = dice_before;
+        record.elapsed_ns = elapsed_ns;
+        record.remaining_before = remaining_before;
+        game.history.push(record);
+        self.count_weekly(|week| week.attacks += 1);
+        if !game.against_contract() {
+            self.check_capture_achievements(game);
+        }
+        game.undo_requested_by = None;
+        advance_turn(game);
+        game.last_move_at = env::block_timestamp();
+
+        // Check win condition
+        if game.dice[defender_dice_idx].is_empty() {
+            finish_game(game, None, FinishReason::CaptureOut);
+            self.after_game_finished(game);
+        }
+
+        return attack_view;
+    }
+
+    fn find_skill_attack(&self, game: &Game) -> Option<(Vec<u8>, u8)> {
+        engine::find_skill_attack(game, self.config.skill_search_dp_threshold)
+    }
+
+    fn is_pass_allowed(&self, game: &Game) -> bool {
+        if !game.is_started() {
+            return false;
+        }
+
+        let power_attack = find_power_attack(game);
+        if power_attack.is_some() {
+            return false;
+        }
+
+        let skill_attack = self.find_skill_attack(game);
+        if skill_attack.is_some() {
+            return false;
+        }
+
+        return true;
+    }
+
+    pub fn pass(&mut self, game_id: String, signature: Option<Base64VecU8>, signer_pk: Option<PublicKey>) -> () {
+        self.assert_not_paused();
+
+        let player_id = self.resolve_player(&game_id, "pass", signature, signer_pk);
+
+        match self.games.get(&game_id) {
+            Some(mut game) => {
+                assert_in_progress(&game);
+                let current_player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() }));
+                pauses::assert_running(&mut game);
+                if game.current_player != current_player_index as u8 {
+                    fail(Error::NotYourTurn);
+                }
+
+                let power_attack = find_power_attack(&game);
+                if power_attack.is_some() {
+                    fail(Error::AttackPossible { attack: "Power".to_string() });
+                }
+
+                let skill_attack = self.find_skill_attack(&game);
+                if skill_attack.is_some() {
+                    fail(Error::AttackPossible { attack: "Skill".to_string() });
+                }
+
+                apply_pass(&mut game);
+                if game.daily.is_some() {
+                    self.play_daily_opponent(&mut game);
+                }
+                if game.bot.is_some() {
+                    self.play_bot(&mut game);
+                }
+                self.play_forced_moves(&mut game);
 
                 // Update the game state
-                self.games.insert(&game_id, &game);
+                self.save_game(&game);
+
+                if !game.is_finished() && !game.against_contract() {
+                    notify(&game.players[game.current_player as usize], "your_turn", &game_id, &player_id);
+                }
             },
             None => {
-                panic!("Game not found: {}", game_id);
+                fail(Error::GameNotFound { game_id: game_id.to_string() });
             }
         }
     }
 
+    /// Concedes the game, the opponent wins regardless of score.
+    pub fn resign(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+        let player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() }));
+        // Only rated games, nothing rides on one against the contract
+        if !game.against_contract() && game.moves_played() < self.config.min_turns_before_resign as usize {
+            fail(Error::TooEarlyToResign { game_id: game_id.to_string(), turns: self.config.min_turns_before_resign });
+        }
+
+        game.last_move_at = env::block_timestamp();
+        game.history.push(MoveRecord::new(player_index as u8, MoveKind::Resign));
+        finish_game(&mut game, Some((player_index as u8 + 1) % 2), FinishReason::Resignation);
+        self.after_game_finished(&mut game);
+        self.save_game(&game);
+    }
+
+    /// Settles everything riding on a game that just finished.
+    fn after_game_finished(&mut self, game: &mut Game) {
+        self.count_weekly(|week| week.games_finished += 1);
+        // Nothing rides on a daily challenge but the day's leaderboard
+        if game.daily.is_some() {
+            self.record_daily_score(game);
+            return;
+        }
+        // Nor on a game against the bot
+        if game.bot.is_some() {
+            return;
+        }
+        self.update_ratings(game);
+        self.update_records(game);
+        self.update_streaks(game);
+        self.update_club_results(game);
+        self.check_finish_achievements(game);
+        self.settle_finished_wager(game);
+        self.settle_side_bets(game);
+        self.refund_spam_deposit(game);
+        self.mark_players_finished(game);
+        self.report_finished_game(game);
+        self.advance_tournament(game);
+    }
+
     // TODO: Move this to a separate trait together with serve_static
     pub fn web4_setStaticUrl(&mut self, url: String) -> () {
-        // TODO: Allow to set owner like in https://github.com/near/near-sdk-rs/blob/00226858199419aaa8c99f756bd192851666fb36/near-contract-standards/src/upgrade/mod.rs#L7
-        require!(env::predecessor_account_id() == env::current_account_id(), "Only owner can set static URL");
+        self.assert_owner("set static URL");
 
         self.web4_static_url = url;
     }
+
+    pub fn set_paused(&mut self, paused: bool) -> () {
+        self.assert_owner("pause the contract");
+
+        self.config.paused = paused;
+    }
+
+    /// Turns, counting both players', a game must have before either can resign. 0 allows resigning right away.
+    pub fn set_min_turns_before_resign(&mut self, turns: u32) -> () {
+        self.assert_owner("set the minimum turns before resigning");
+
+        self.config.min_turns_before_resign = turns;
+    }
+
+    pub fn set_max_initiative_rerolls(&mut self, rerolls: u32) -> () {
+        self.assert_owner("set the initiative re-rolls");
+
+        self.config.max_initiative_rerolls = rerolls;
+    }
+
+    pub fn set_skill_search_dp_threshold(&mut self, threshold: u32) -> () {
+        self.assert_owner("set the skill search threshold");
+
+        self.config.skill_search_dp_threshold = threshold;
+    }
+
+    /// Requires players to own an NFT from the given series to play the button, or removes the requirement.
+    pub fn set_button_nft_gate(&mut self, button: String, gate: Option<NftGate>) -> () {
+        self.assert_owner("gate buttons");
+        if self.find_button(&button).is_none() {
+            fail(Error::ButtonNotFound { button });
+        }
+
+        match gate {
+            Some(gate) => self.config.premium_buttons.insert(button, gate),
+            None => self.config.premium_buttons.remove(&button),
+        };
+    }
+
+    /// Re-rolls the dice currently in play from the given seed, for games whose RNG state is suspected compromised.
+    /// Captures and turn are kept, the seed is published in an event and the audit log.
+    pub fn reseed_game(&mut self, game_id: String, new_seed: Base64VecU8) -> () {
+        self.assert_owner("reseed games");
+        if new_seed.0.len() != 32 {
+            fail(Error::InvalidSeed);
+        }
+
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+
+        game.dice = roll_round_dice(&new_seed.0, &game.dice);
+        self.save_game(&game);
+
+        let seed = serde_json::to_value(&new_seed).unwrap_or_default();
+        self.audit("reseed_game", format!("game {}: seed {}", game_id, seed.as_str().unwrap_or_default()));
+        emit_event("game_reseeded", serde_json::json!({
+            "game_id": game_id,
+            "seed": seed,
+        }));
+    }
+
+    pub fn get_notifications(&self, account_id: String, from: u64, limit: u64) -> Vec<Notification> {
+        get_user_notifications(&account_id).into_iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    pub fn clear_notifications(&mut self, account_id: String) -> () {
+        if env::predecessor_account_id().to_string() != account_id {
+            fail(Error::NotAccountOwner { action: "clear notifications".to_string() });
+        }
+
+        env::storage_remove(&notifications_key(&account_id));
+    }
+
+    fn audit(&mut self, action: &str, details: String) {
+        self.audit_log.push(&AuditEntry {
+            timestamp: env::block_timestamp(),
+            actor: env::predecessor_account_id().to_string(),
+            action: action.to_string(),
+            details,
+        });
+    }
+
+    pub fn get_audit_log(&self, from: u64, limit: u64) -> Vec<AuditEntry> {
+        (from..std::cmp::min(from + limit, self.audit_log.len()))
+            .filter_map(|index| self.audit_log.get(index))
+            .collect()
+    }
+
+    pub fn get_config(&self) -> ContractConfig {
+        self.config.clone()
+    }
+
+    pub fn get_treasury(&self) -> U128 {
+        self.treasury.into()
+    }
+}
+
+fn assert_in_progress(game: &Game) {
+    game.assert_two_players();
+    if game.status == GameStatus::Cancelled {
+        fail(Error::GameCancelled { game_id: game.id.to_string() });
+    }
+    if !game.is_started() {
+        fail(Error::GameNotStarted { game_id: game.id.to_string() });
+    }
+    if game.is_finished() {
+        fail(Error::GameFinished { game_id: game.id.to_string() });
+    }
+}
+
+/// Passes the current player's turn
+fn apply_pass(game: &mut Game) {
+    let mut record = MoveRecord::new(game.current_player, MoveKind::Pass);
+    record.elapsed_ns = clocks::on_clock(game);
+    record.remaining_before = game.remaining_ns.clone();
+    clocks::charge_clock(game);
+    record.dice_before = game.dice.clone();
+    game.history.push(record);
+    game.undo_requested_by = None;
+    advance_turn(game);
+    game.last_move_at = env::block_timestamp();
+}
+
+/// Ends the game. Without an explicit winner the higher score wins, equal scores are a draw.
+fn finish_game(game: &mut Game, winner: Option<u8>, reason: FinishReason) {
+    game.status = GameStatus::Finished;
+    game.finish_reason = Some(reason);
+    game.winner = winner.or_else(|| leader(&scores(game)));
+
+    emit_event("game_finished", serde_json::json!({
+        "game_id": game.id,
+        "players": game.players,
+        "winner": game.winner,
+        "hash": history::game_hash(game),
+    }));
+}
+
+/// Re-rolls of a game. Each roll mixes the block seed with the game id and the game's roll counter,
+/// so rolls in the same block, or in blocks that happen to reuse a seed, don't come out the same.
+/// A daily challenge rolls from its round seed instead, so it plays out the same for everyone.
+/// Counts on from the game's roll_seq, which has to be set to the one it ends at.
+struct GameRolls {
+    seed: Vec<u8>,
+    game_id: String,
+    roll_seq: u64,
+}
+
+impl GameRolls {
+    fn new(game: &Game) -> Self {
+        Self {
+            seed: if game.daily.is_some() { game.round_seed.clone() } else { env::random_seed() },
+            game_id: game.id.clone(),
+            roll_seq: game.roll_seq,
+        }
+    }
 }
 
-fn roll_die(rng: &mut Rng, size: u8) -> Die {
-    Die {
-        size,
-        value: rng.rand_range_u32(1, size.into()) as u8,
+impl DiceRng for GameRolls {
+    fn roll(&mut self, size: u8) -> u8 {
+        self.roll_seq += 1;
+        let mut seed = self.seed.clone();
+        seed.extend(self.game_id.as_bytes());
+        seed.extend(self.roll_seq.to_le_bytes());
+        Rng::new(&env::sha256(&seed)).roll(size)
     }
 }
 
-fn roll_dice(rng: &mut Rng, sizes: Vec<u8>) -> Vec<Die> {
-    sizes.iter().map(|size| roll_die(rng, *size)).collect()
+/// Rolls every player's dice (in player order) from the round seed.
+pub fn roll_round_dice(round_seed: &[u8], dice: &[Vec<Die>]) -> Vec<Vec<Die>> {
+    let mut rng = Rng::new(&round_seed.to_vec());
+    dice.iter().map(|player_dice| roll_dice(&mut rng, player_dice.iter().map(|die| die.size).collect())).collect()
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -428,7 +1537,10 @@ pub enum Web4Response {
     Body {
         #[serde(rename = "contentType")]
         content_type: String,
-        body: near_sdk::json_types::Base64VecU8,
+        body: Base64VecU8,
+        /// HTTP status code, the gateway responds with 200 when it's omitted
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        status: Option<u16>,
     },
     BodyUrl {
         #[serde(rename = "bodyUrl")]
@@ -440,6 +1552,185 @@ pub enum Web4Response {
     },
 }
 
+#[derive(Debug, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Button {
+    name: &'static str,
+    dice: &'static [u8],
+    /// Optional extra dice, used only when both players agree at the start
+    auxiliary: &'static [u8],
+    skills: &'static [&'static str],
+}
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractConfig {
+    /// When set, all state-changing game methods are rejected
+    pub paused: bool,
+    /// Buttons which can only be played by holders of an NFT from the given series
+    pub premium_buttons: std::collections::HashMap<String, NftGate>,
+    /// Creator of a wagered game can reclaim the stake if nobody joins within this time
+    pub wager_start_expiry_ns: u64,
+    /// Either player can split the pot back if nobody moved within this time
+    pub wager_stall_refund_ns: u64,
+    /// Owner can sweep the escrow of a game nobody touched within this time
+    pub wager_sweep_after_ns: u64,
+    /// Cut of the losing side bets taken into the treasury, in basis points
+    pub side_bet_fee_bps: u16,
+    /// Side bets close once a game has had this many moves
+    pub side_bet_lock_after_moves: u32,
+    /// Cut of a won wager's pot taken into the treasury, in basis points. Draws are refunded in full.
+    pub wager_fee_bps: u16,
+    /// Smallest stake a wagered game can be created or joined with, in yoctoNEAR
+    pub min_stake: U128,
+    /// Games an account can create per day before game_creation_fee applies
+    pub free_games_per_day: u32,
+    pub game_creation_fee: U128,
+    /// Refundable deposit accounts without a finished game attach when creating a game, 0 to disable
+    pub new_account_deposit: U128,
+    /// Anyone can expire an unjoined game after this time
+    pub game_join_expiry_ns: u64,
+    /// Minimum deposit to fund a room account, covering storage of the deployed wasm
+    pub room_deposit: U128,
+    /// Largest rating change a single game can cause
+    pub elo_k_factor: u32,
+    /// Rating loss for resigning, in percent of what losing the game would cost, 100 treats it as a normal loss
+    pub forfeit_penalty_multiplier: u32,
+    /// Number of accounts kept on the leaderboard
+    pub leaderboard_size: u32,
+    /// Share of a rating's distance from ratings::DEFAULT_RATING kept into a new season, 0 resets everyone
+    pub season_carryover_pct: u32,
+    /// Cut of a tournament's entry fees taken into the treasury, in basis points
+    pub tournament_fee_bps: u16,
+    /// Rating distance quick_match accepts between players, widened by as much again every match_window_widen_ns an open game waits
+    pub match_rating_window: u32,
+    pub match_window_widen_ns: u64,
+    /// Titles shown for ratings, from the lowest rating up
+    pub rating_titles: Vec<RatingTitle>,
+    /// Attacker dice count above which skill attacks are searched with a subset-sum table, keeping pass within gas
+    pub skill_search_dp_threshold: u32,
+    /// A joined player who hasn't moved can have their seat freed by the creator after keeping the game waiting this long
+    pub evict_after_ns: u64,
+    /// Ratings lose rating_decay_per_week for every full week without a rated game beyond rating_decay_after_ns,
+    /// down to no less than rating_decay_floor
+    pub rating_decay_after_ns: u64,
+    pub rating_decay_per_week: u32,
+    pub rating_decay_floor: u32,
+    /// Turns a game must have before either player can resign, see Contract::resign
+    pub min_turns_before_resign: u32,
+    /// Times a round's dice are re-rolled while initiative is tied before the creator goes first
+    pub max_initiative_rerolls: u32,
+    /// Paid from the treasury to whoever calls finalize_expired, 0 for nothing
+    pub finalize_bounty: U128,
+    /// Longest a game can spend paused in all, see Contract::request_pause
+    pub max_pause_ns: u64,
+    /// How long after a clock runs out a game nobody ended is scored as it stands rather than lost on time,
+    /// 0 to always award the timeout
+    pub adjudicate_after_ns: u64,
+    /// Unjoined games older than this drop out of the lobby and can be swept with sweep_expired
+    pub lobby_ttl_ns: u64,
+}
+
+impl Default for ContractConfig {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            premium_buttons: std::collections::HashMap::new(),
+            wager_start_expiry_ns: 7 * DAY_NS,
+            wager_stall_refund_ns: 30 * DAY_NS,
+            wager_sweep_after_ns: 365 * DAY_NS,
+            side_bet_fee_bps: 200,
+            wager_fee_bps: 0,
+            min_stake: U128(0),
+            side_bet_lock_after_moves: 2,
+            free_games_per_day: 10,
+            // 0.01 NEAR
+            game_creation_fee: U128(10_000_000_000_000_000_000_000),
+            new_account_deposit: U128(0),
+            game_join_expiry_ns: 7 * DAY_NS,
+            // 5 NEAR
+            room_deposit: U128(5_000_000_000_000_000_000_000_000),
+            elo_k_factor: 32,
+            forfeit_penalty_multiplier: 100,
+            leaderboard_size: 100,
+            season_carryover_pct: 50,
+            tournament_fee_bps: 500,
+            match_rating_window: 150,
+            match_window_widen_ns: 10 * 60 * 1_000_000_000,
+            rating_titles: titles::default_rating_titles(),
+            skill_search_dp_threshold: 12,
+            evict_after_ns: DAY_NS,
+            rating_decay_after_ns: 30 * DAY_NS,
+            rating_decay_per_week: 10,
+            rating_decay_floor: 1400,
+            min_turns_before_resign: 0,
+            max_initiative_rerolls: 10,
+            lobby_ttl_ns: 14 * DAY_NS,
+            max_pause_ns: 7 * DAY_NS,
+            adjudicate_after_ns: 30 * DAY_NS,
+            finalize_bounty: U128(0),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuditEntry {
+    timestamp: u64,
+    actor: String,
+    action: String,
+    details: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftGate {
+    pub nft_contract_id: AccountId,
+    pub token_series: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftToken {
+    pub token_id: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CreateGameOptions {
+    /// Button to play as, defaults to DEFAULT_BUTTON
+    pub button: Option<String>,
+    /// Contract (e.g. a tournament) that gets on_game_result once the game finishes, can't be changed later
+    pub report_to: Option<AccountId>,
+    /// Hide the opponent's die values from each player until it's their turn
+    #[serde(default)]
+    pub fog: bool,
+    /// List the game in the lobby and for quick_match, defaults to true. Unlisted games are joined by their id.
+    pub public: Option<bool>,
+    /// Let both players ask for get_hint, joining the game agrees to it
+    #[serde(default)]
+    pub hints: bool,
+    /// Clocks for both players, joining the game agrees to them. Without one there's no time limit on moves.
+    pub time_control: Option<TimeControl>,
+    /// Named clock to use instead of a custom time_control
+    pub time_preset: Option<TimePreset>,
+}
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Notification {
+    #[serde(rename = "type")]
+    kind: String,
+    game_id: String,
+    actor: String,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct SessionKey {
+    player_id: String,
+    nonce: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Die {
@@ -447,14 +1738,121 @@ pub struct Die {
     value: u8
 }
 
-#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+#[derive(Debug, Default, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum GameStatus {
+    /// Waiting for the second player (or for button verification)
+    #[default]
+    Waiting,
+    Active,
+    Finished,
+    /// Closed without a result, e.g. when an unjoined or stalled wager was refunded
+    Cancelled,
+    /// Waiting for longer than ContractConfig.lobby_ttl_ns. Only shown in views, the game stays Waiting until sweep_expired
+    Expired,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Game {
     id: String,
     players: Vec<String>,
     current_player: u8,
     dice: Vec<Vec<Die>>,
+    /// Sizes of the dice each player captured, in the order they were captured. Undo takes back the last one,
+    /// so anything wanting them sorted, for scoring or display, sorts a copy.
     captured: Vec<Vec<u8>>,
+    /// Random seed the current round's starting dice were rolled from, empty until the round starts
+    round_seed: Vec<u8>,
+    /// Button played by each player
+    buttons: Vec<String>,
+    /// Set while a premium button's NFT ownership check is in flight
+    verifying: bool,
+    status: GameStatus,
+    /// Index of the winning player once the game is finished, None for a draw
+    winner: Option<u8>,
+    /// Block timestamps (in nanoseconds) of game creation and of the last move
+    created_at: u64,
+    last_move_at: u64,
+    /// Each player's answer on using auxiliary dice, empty when no button has any
+    auxiliary_choices: Vec<Option<bool>>,
+    /// Every move made in the game, in order
+    history: Vec<MoveRecord>,
+    /// Account notified of the result when the game finishes
+    report_to: Option<AccountId>,
+    /// Whether report_to accepted the result, None until its on_game_result call resolves
+    result_reported: Option<bool>,
+    /// Player who asked to take back their last move, until the opponent approves or someone moves
+    undo_requested_by: Option<u8>,
+    /// Tournament the game is a match of
+    tournament_id: Option<String>,
+    /// Opponent's die values are hidden from the waiting player, see Contract::game_view
+    fog: bool,
+    /// Change of each player's rating from this game, empty until it's rated
+    rating_deltas: Vec<i32>,
+    /// How the game ended, None until it's finished
+    finish_reason: Option<FinishReason>,
+    /// Season the game was rated in, None for games finished before the first season
+    season_id: Option<u32>,
+    /// Number of re-rolls made in the game so far, see GameRolls
+    roll_seq: u64,
+    /// UTC date of the daily challenge the game is, played against the contract
+    daily: Option<String>,
+    /// Kept out of the lobby and quick_match, only reachable by its id
+    unlisted: bool,
+    /// Bot in the second seat, the contract moves for it
+    bot: Option<BotDifficulty>,
+    /// Both players can ask for get_hint
+    hints: bool,
+    /// Whether each player has their forced moves played for them, see set_auto_forced. Empty until someone opts in.
+    auto_forced: Vec<bool>,
+    time_control: Option<TimeControl>,
+    /// Time left in each player's bank as of their last move, empty without a time control
+    remaining_ns: Vec<u64>,
+    /// Block timestamp the current player's turn started at, their clock too in games with a time control
+    turn_started_at: u64,
+    /// Time the current player asked the opponent to add to their clock, until it's granted or they move
+    extension_requested: Option<u64>,
+    /// Player who asked to pause the game, see Contract::request_pause
+    pause_requested_by: Option<u8>,
+    pause: Option<Pause>,
+    /// Time the game spent paused in pauses that are over, counted against ContractConfig.max_pause_ns
+    paused_ns: u64,
+}
+
+impl Game {
+    /// Whether the opponent is played by the contract, as in daily challenges and games against the bot
+    fn against_contract(&self) -> bool {
+        self.daily.is_some() || self.bot.is_some()
+    }
+
+    fn is_started(&self) -> bool {
+        self.status != GameStatus::Waiting
+    }
+
+    fn is_finished(&self) -> bool {
+        self.status == GameStatus::Finished
+    }
+
+    /// Moves made so far, leaving out the records of requests between the players
+    fn moves_played(&self) -> usize {
+        self.history.iter().filter(|record| record.kind.is_move()).count()
+    }
+
+    /// Games are for exactly two players, with a seat, dice and captured dice for each. Much of the code
+    /// relies on it (e.g. the opponent being `(index + 1) % 2`), so a game that breaks it fails here
+    /// instead of deep in some indexing.
+    fn assert_two_players(&self) {
+        if self.players.len() != PLAYER_COUNT || self.dice.len() != PLAYER_COUNT || self.captured.len() != PLAYER_COUNT {
+            fail(Error::CorruptedGame {
+                game_id: self.id.to_string(),
+                expected: PLAYER_COUNT,
+                players: self.players.len(),
+                dice: self.dice.len(),
+                captured: self.captured.len(),
+            });
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -462,28 +1860,174 @@ pub struct Game {
 pub struct GameView {
     id: String,
     players: Vec<String>,
-    current_player: u8,
+    /// None until the round starts, rather than the 0xFF games keep
+    current_player: Option<u8>,
     dice: Vec<Vec<Die>>,
+    /// In capture order, as the game stores them
     captured: Vec<Vec<u8>>,
+    /// Sum of the sizes each player captured
+    captured_value: Vec<u32>,
     is_pass_allowed: bool,
+    round_seed: Base64VecU8,
+    buttons: Vec<String>,
+    verifying: bool,
+    status: GameStatus,
+    winner: Option<u8>,
+    auxiliary_choices: Vec<Option<bool>>,
+    report_to: Option<AccountId>,
+    result_reported: Option<bool>,
+    undo_requested_by: Option<u8>,
+    fog: bool,
+    /// Current rating of each player
+    ratings: Vec<u32>,
+    rating_deltas: Vec<i32>,
+    /// Each player's rating title and badges, see titles.rs
+    titles: Vec<Option<String>>,
+    badges: Vec<Vec<Badge>>,
+    /// Club each player is in
+    clubs: Vec<Option<String>>,
+    /// Whether the reading account is to move, only set when the request has an account
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    your_turn: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_control: Option<TimeControl>,
+    /// Time each player has left as of the view, the current player's clock counting down
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    remaining_ns: Vec<u64>,
+    /// Time the player on turn is asking the opponent for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    extension_requested: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pause_requested_by: Option<u8>,
+    /// When the pause the game is on runs out, None unless it's paused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    paused_until: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum AttackType {
+    /// One die captures a die showing the same or a lower value
+    Power,
+    /// Dice whose values add up exactly to the defender's value capture it
+    Skill,
+}
+
+impl AttackType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AttackType::Power => "power",
+            AttackType::Skill => "skill",
+        }
+    }
+}
+
+/// Outcome of an attack, for the frontend to animate
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AttackView {
+    /// New values of the attacking dice, in the order they were given
+    rerolled: Vec<u8>,
+    /// What the attacking dice showed before they were re-rolled
+    previous_values: Option<Vec<u8>>,
+}
+
+/// What a dashboard lists for a game, without dice or history
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GameSummary {
+    id: String,
+    players: Vec<String>,
+    buttons: Vec<String>,
+    current_player: u8,
+    /// Whether the player the summary was made for is to move
+    your_turn: bool,
+    last_move_at: u64,
+    tournament_id: Option<String>,
+    /// Whether the players agreed on a pause that's still holding
+    paused: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AttackOption {
+    attacker_die_indices: Vec<u8>,
+    defender_die_index: u8,
+    attack_type: AttackType,
+    /// Only filled in when asked for, see get_move_options
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    evaluation: Option<AttackEvaluation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MoveOptions {
+    /// Attacks open to the player to move, empty unless the game is active
+    attacks: Vec<AttackOption>,
+    can_pass: bool,
+    /// Account to move, None unless the game is active
+    your_turn_for: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProjectedResult {
+    scores: Vec<u32>,
+    leader: Option<u8>,
+}
+
+/// Lobby entry, the game along with its players' ratings
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LobbyGame {
+    #[serde(flatten)]
+    game: Game,
+    ratings: Vec<u32>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::engine::roll_die;
     use near_sdk::testing_env;
     use near_sdk::test_utils::VMContextBuilder;
 
-    fn login_as(player_id: &str) {
+    pub(crate) fn login_as(player_id: &str) {
         testing_env!(VMContextBuilder::new()
             .predecessor_account_id(player_id.parse().unwrap())
             .build());
     }
 
+    /// Calls as the account at the block timestamp
+    pub(crate) fn call_at(account_id: &str, timestamp: u64) {
+        call_as(account_id, timestamp, 0);
+    }
+
+    /// Calls as the account at the block timestamp with the deposit attached. The contract holds enough to pay
+    /// out whatever the tests transfer.
+    pub(crate) fn call_as(account_id: &str, timestamp: u64, deposit: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(account_id.parse().unwrap())
+            .block_timestamp(timestamp)
+            .attached_deposit(deposit)
+            .account_balance(10u128.pow(33))
+            .build());
+    }
+
+    /// The receiver and amount of every transfer the last call made
+    pub(crate) fn transfers() -> Vec<(String, Balance)> {
+        near_sdk::test_utils::get_created_receipts().into_iter().flat_map(|receipt| {
+            receipt.actions.into_iter().filter_map(move |action| match action {
+                near_sdk::mock::VmAction::Transfer { deposit } => Some((receipt.receiver_id.to_string(), deposit)),
+                _ => None,
+            })
+        }).collect()
+    }
+
     #[test]
     fn create_game() {
         let mut contract = Contract::default();
-        contract.create_game();
+        contract.create_game(None);
 
         assert_eq!(contract.last_game_id, 1);
         let game = contract.games.get(&"1".to_string()).unwrap();
@@ -503,18 +2047,35 @@ mod tests {
     #[should_panic(expected = "Player bob.near has already joined game 1")]
     fn join_game_same_player() {
         let mut contract = Contract::default();
-        contract.create_game();
-        contract.join_game("1".to_string());
+        contract.create_game(None);
+        contract.join_game("1".to_string(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Player bob.near has already joined game 1")]
+    fn join_game_creator_empty_slot() {
+        let mut contract = Contract::default();
+        // Creator sits in the second slot with the first one still open
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["".to_string(), "bob.near".to_string()],
+            current_player: 0xFF,
+            dice: vec![vec![], vec![Die { size: 4, value: 1 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        contract.join_game("1".to_string(), None);
     }
 
     #[test]
     fn join_game_other_player() {
         let mut contract = Contract::default();
-        contract.create_game();
+        contract.create_game(None);
 
         testing_env!(VMContextBuilder::new()
             // 32 bytes of random seed
-            // used just so that current_player is not 0 because of the same roll
+            // picked so that alice.near wins initiative
             .random_seed([
                 1, 0, 0, 0, 0, 0, 0, 0,
                 0, 0, 0, 0, 0, 0, 0, 0,
@@ -523,224 +2084,1440 @@ mod tests {
             ])
             .predecessor_account_id("alice.near".parse().unwrap())
             .build());
-        contract.join_game("1".to_string());
+        contract.join_game("1".to_string(), None);
 
         assert_eq!(contract.last_game_id, 1);
         let game = contract.games.get(&"1".to_string()).unwrap();
         assert_eq!(game.players, vec!["bob.near".to_string(), "alice.near".to_string()]);
         assert_eq!(game.current_player, 1);
         assert_eq!(game.dice, vec![
-            vec![Die { size: 4, value: 2 }, Die { size: 6, value: 4 }, Die { size: 8, value: 5 }, Die { size: 10, value: 5}, Die { size: 20, value: 5 }],
-            vec![Die { size: 4, value: 2 }, Die { size: 6, value: 3 }, Die { size: 8, value: 2 }, Die { size: 10, value: 1 }, Die { size: 20, value: 5 }]]);
+            vec![Die { size: 4, value: 3 }, Die { size: 6, value: 2 }, Die { size: 8, value: 8 }, Die { size: 10, value: 4 }, Die { size: 20, value: 15 }],
+            vec![Die { size: 4, value: 2 }, Die { size: 6, value: 6 }, Die { size: 8, value: 4 }, Die { size: 10, value: 6 }, Die { size: 20, value: 9 }]]);
         assert_eq!(game.captured, vec![vec![], vec![]] as Vec<Vec<u8>>);
     }
 
     #[test]
-    #[should_panic(expected = "Game is full: 1")]
-    fn join_game_full() {
+    fn join_game_round_seed() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+
+        testing_env!(VMContextBuilder::new()
+            .random_seed([7; 32])
+            .predecessor_account_id("alice.near".parse().unwrap())
+            .build());
+        contract.join_game("1".to_string(), None);
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.round_seed, vec![7; 32]);
+        // Starting dice can be reproduced from the stored seed alone
+        assert_eq!(roll_round_dice(&game.round_seed, &game.dice), game.dice);
+        assert_ne!(roll_round_dice(&[8; 32], &game.dice), game.dice);
+    }
+
+    fn join_with_auxiliary(contract: &mut Contract) {
+        contract.create_game(None);
+        login_as("alice.near");
+        contract.join_game("1".to_string(), Some("Trickster".to_string()));
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.status, GameStatus::Waiting);
+        assert_eq!(game.auxiliary_choices, vec![None, None]);
+    }
+
+    #[test]
+    fn accept_auxiliary_both() {
+        let mut contract = Contract::default();
+        join_with_auxiliary(&mut contract);
+
+        contract.accept_auxiliary("1".to_string(), true);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().status, GameStatus::Waiting);
+
+        login_as("bob.near");
+        contract.accept_auxiliary("1".to_string(), true);
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.status, GameStatus::Active);
+        assert_eq!(game.dice.iter().map(|dice| dice.iter().map(|die| die.size).collect()).collect::<Vec<Vec<u8>>>(), vec![
+            vec![4, 6, 8, 10, 20],
+            vec![4, 4, 6, 10, 20, 8],
+        ]);
+    }
+
+    #[test]
+    fn accept_auxiliary_declined() {
+        let mut contract = Contract::default();
+        join_with_auxiliary(&mut contract);
+
+        contract.accept_auxiliary("1".to_string(), true);
+        login_as("bob.near");
+        contract.accept_auxiliary("1".to_string(), false);
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.status, GameStatus::Active);
+        assert_eq!(game.auxiliary_choices, vec![Some(false), Some(true)]);
+        assert_eq!(game.dice[1].iter().map(|die| die.size).collect::<Vec<u8>>(), vec![4, 4, 6, 10, 20]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 has no auxiliary dice to agree on")]
+    fn accept_auxiliary_none() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+        contract.accept_auxiliary("1".to_string(), true);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game is full: 1")]
+    fn join_game_full() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+
+        login_as("alice.near");
+        contract.join_game("1".to_string(), None);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id("eve.near".parse().unwrap())
+            .build());
+        contract.join_game("1".to_string(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game not found: 1")]
+    fn join_game_not_found() {
+        let mut contract = Contract::default();
+        contract.join_game("1".to_string(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "It is not your turn")]
+    fn attack_not_your_turn() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+
+        login_as("alice.near");
+        contract.join_game("1".to_string(), None);
+        // alice.near rolled lower and goes first
+        login_as("bob.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Player eve.near has not joined game 1")]
+    fn attack_not_joined() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+
+        login_as("alice.near");
+        contract.join_game("1".to_string(), None);
+
+        login_as("eve.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attack failed")]
+    fn attack_failed() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 4, value: 2 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        login_as("bob.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+    }
+
+    #[test]
+    fn attack_power_success() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 4 }, Die { size: 6, value: 1} ], vec![Die { size: 4, value: 2 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.players, vec!["bob.near".to_string(), "alice.near".to_string()]);
+        assert_eq!(game.current_player, 1);
+        // NOTE: The attacker's die is re-rolled. It's deterministic in tests
+        assert_eq!(game.dice, vec![vec![Die { size: 4, value: 4 }, Die { size: 6, value: 1 }], vec![]]);
+        assert_eq!(game.captured, vec![vec![4], vec![]]);
+    }
+
+    #[test]
+    fn attack_skill_success() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 2 }, Die { size: 6, value: 4 }], vec![Die { size: 10, value: 6 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        contract.attack("1".to_string(), vec![0, 1], 0, None, None, None);
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.players, vec!["bob.near".to_string(), "alice.near".to_string()]);
+        assert_eq!(game.current_player, 1);
+        assert_eq!(game.dice, vec![vec![Die { size: 4, value: 4 }, Die { size: 6, value: 2 }], vec![]]);
+        assert_eq!(game.captured, vec![vec![10], vec![]]);
+    }
+
+    #[test]
+    fn captured_in_capture_order() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 20 }], vec![Die { size: 12, value: 1 }, Die { size: 4, value: 1 }, Die { size: 8, value: 1 }, Die { size: 6, value: 1 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        // Takes the d12, then the d4, then the d8, with the turn handed back to bob.near each time
+        for _ in 0..3 {
+            contract.attack("1".to_string(), vec![0], 0, None, None, None);
+            let mut game = contract.games.get(&"1".to_string()).unwrap();
+            game.current_player = 0;
+            contract.games.insert(&"1".to_string(), &game);
+        }
+
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().captured, vec![vec![12, 4, 8], vec![]]);
+        match contract.web4_get(request_path("/api/games/1")) {
+            Web4Response::Body { body, .. } => {
+                let view: serde_json::Value = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert_eq!(view["captured"], serde_json::json!([[12, 4, 8], []]));
+                assert_eq!(view["captured_value"], serde_json::json!([24, 0]));
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    fn insert_attack_game(contract: &mut Contract) {
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 2 }, Die { size: 6, value: 4 }], vec![Die { size: 10, value: 4 }, Die { size: 8, value: 1 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn attack_single_die_skill() {
+        let mut contract = Contract::default();
+        insert_attack_game(&mut contract);
+
+        contract.attack("1".to_string(), vec![1], 0, None, None, Some(AttackType::Skill));
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.captured, vec![vec![10], vec![]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attack failed")]
+    fn attack_single_die_skill_not_exact() {
+        let mut contract = Contract::default();
+        insert_attack_game(&mut contract);
+
+        // A power attack would capture the d8 showing 1, a skill attack needs an exact match
+        contract.attack("1".to_string(), vec![1], 1, None, None, Some(AttackType::Skill));
+    }
+
+    #[test]
+    #[should_panic(expected = "Power attack takes exactly one die")]
+    fn attack_power_two_dice() {
+        let mut contract = Contract::default();
+        insert_attack_game(&mut contract);
+
+        contract.attack("1".to_string(), vec![0, 1], 1, None, None, Some(AttackType::Power));
+    }
+
+    #[test]
+    #[should_panic(expected = "Skill attack takes at least one die")]
+    fn attack_skill_no_dice() {
+        let mut contract = Contract::default();
+        insert_attack_game(&mut contract);
+
+        contract.attack("1".to_string(), vec![], 0, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "More attacking dice than you have")]
+    fn attack_oversized_indices() {
+        let mut contract = Contract::default();
+        insert_attack_game(&mut contract);
+
+        contract.attack("1".to_string(), vec![0; 10_000], 0, None, None, None);
+    }
+
+    #[test]
+    fn rerolls_differ_with_same_block_seed() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 19 }, Die { size: 20, value: 19 }], vec![Die { size: 4, value: 1 }, Die { size: 4, value: 1 }, Die { size: 4, value: 1 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        // Both attacks run with the same block seed, bob.near is given the turn back in between
+        let first = contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.roll_seq, 1);
+        game.current_player = 0;
+        contract.games.insert(&"1".to_string(), &game);
+        let second = contract.attack("1".to_string(), vec![1], 0, None, None, None);
+
+        assert_ne!(first.rerolled, second.rerolled);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().roll_seq, 2);
+    }
+
+    #[test]
+    fn attack_previous_values() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 1 }, Die { size: 6, value: 3 }, Die { size: 8, value: 7 }], vec![Die { size: 10, value: 8 }, Die { size: 12, value: 9 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        let view = contract.attack("1".to_string(), vec![2, 0], 0, None, None, None);
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(view.previous_values, Some(vec![7, 1]));
+        assert_eq!(view.rerolled, vec![game.dice[0][2].value, game.dice[0][0].value]);
+    }
+
+    #[test]
+    fn attack_power_alice() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 1,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 4 }, Die { size: 6, value: 1} ], vec![Die { size: 4, value: 3 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        login_as("alice.near");
+        contract.attack("1".to_string(), vec![0], 1, None, None, None);
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.players, vec!["bob.near".to_string(), "alice.near".to_string()]);
+        assert_eq!(game.current_player, 0);
+        // NOTE: The attacker's die is re-rolled. It's deterministic in tests
+        assert_eq!(game.dice, vec![vec![Die { size: 4, value: 4 }], vec![Die { size: 4, value: 4 }]]);
+        assert_eq!(game.captured, vec![vec![], vec![6]]);
+    }
+
+    fn insert_finished_game(contract: &mut Contract) {
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 1,
+            dice: vec![vec![Die { size: 4, value: 2 }], vec![]],
+            captured: vec![vec![4], vec![]],
+            status: GameStatus::Finished,
+            winner: Some(0),
+            ..Default::default()
+        });
+    }
+
+    fn insert_corrupted_game(contract: &mut Contract, players: usize, dice: usize, captured: usize) {
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string(), "carol.near".to_string()][..players].to_vec(),
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 19 }]; dice],
+            captured: vec![vec![]; captured],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is corrupted: expected 2 players, dice and captured lists, found 2, 1 and 2")]
+    fn attack_corrupted_dice() {
+        let mut contract = Contract::default();
+        insert_corrupted_game(&mut contract, 2, 1, 2);
+        login_as("bob.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is corrupted: expected 2 players, dice and captured lists, found 2, 2 and 3")]
+    fn pass_corrupted_captured() {
+        let mut contract = Contract::default();
+        insert_corrupted_game(&mut contract, 2, 2, 3);
+        login_as("bob.near");
+        contract.pass("1".to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is corrupted: expected 2 players, dice and captured lists, found 3, 3 and 3")]
+    fn resign_corrupted_players() {
+        let mut contract = Contract::default();
+        insert_corrupted_game(&mut contract, 3, 3, 3);
+        login_as("bob.near");
+        contract.resign("1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is corrupted: expected 2 players, dice and captured lists, found 1, 2 and 2")]
+    fn join_corrupted_game() {
+        let mut contract = Contract::default();
+        insert_corrupted_game(&mut contract, 1, 2, 2);
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        game.status = GameStatus::Waiting;
+        contract.games.insert(&"1".to_string(), &game);
+        login_as("alice.near");
+        contract.join_game("1".to_string(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 has not started")]
+    fn attack_not_started() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is finished")]
+    fn attack_finished() {
+        let mut contract = Contract::default();
+        insert_finished_game(&mut contract);
+
+        login_as("alice.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+    }
+
+    #[test]
+    fn attack_last_die_finishes_game() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 4 }], vec![Die { size: 20, value: 2 }]],
+            captured: vec![vec![], vec![12]],
+            ..Default::default()
+        });
+
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+
+        // bob.near: 20 captured + half of 4 left, alice.near: 12 captured
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, Some(0));
+        assert_eq!((score(&game, 0), score(&game, 1)), (44, 24));
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 has not started")]
+    fn pass_not_started() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+        contract.pass("1".to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is finished")]
+    fn pass_finished() {
+        let mut contract = Contract::default();
+        insert_finished_game(&mut contract);
+
+        login_as("alice.near");
+        contract.pass("1".to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 has not started")]
+    fn resign_not_started() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+        contract.resign("1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is finished")]
+    fn resign_finished() {
+        let mut contract = Contract::default();
+        insert_finished_game(&mut contract);
+        contract.resign("1".to_string());
+    }
+
+    #[test]
+    fn resign_success() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+        login_as("alice.near");
+        contract.join_game("1".to_string(), None);
+
+        login_as("bob.near");
+        contract.resign("1".to_string());
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.status, GameStatus::Finished);
+        assert_eq!(game.winner, Some(1));
+    }
+
+    fn game_with_turns(contract: &mut Contract, turns: u8) {
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 20, value: 9 }]],
+            captured: vec![vec![], vec![]],
+            history: (0..turns).map(|turn| MoveRecord::new(turn % 2, MoveKind::Pass)).collect(),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 can't be resigned before 2 turns have been played")]
+    fn resign_too_early() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_min_turns_before_resign(2);
+        game_with_turns(&mut contract, 1);
+
+        login_as("bob.near");
+        contract.resign("1".to_string());
+    }
+
+    #[test]
+    fn resign_after_min_turns() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_min_turns_before_resign(2);
+        game_with_turns(&mut contract, 2);
+
+        login_as("bob.near");
+        contract.resign("1".to_string());
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().winner, Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can set the minimum turns before resigning")]
+    fn min_turns_before_resign_not_owner() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.set_min_turns_before_resign(2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can set the initiative re-rolls")]
+    fn max_initiative_rerolls_not_owner() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.set_max_initiative_rerolls(0);
+    }
+
+    #[test]
+    fn is_player_turn() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+        assert!(!contract.is_player_turn("1".to_string(), "bob.near".parse().unwrap()));
+
+        login_as("alice.near");
+        contract.join_game("1".to_string(), None);
+
+        // alice.near rolled lower and goes first
+        assert!(contract.is_player_turn("1".to_string(), "alice.near".parse().unwrap()));
+        assert!(!contract.is_player_turn("1".to_string(), "bob.near".parse().unwrap()));
+        assert!(!contract.is_player_turn("1".to_string(), "eve.near".parse().unwrap()));
+    }
+
+    #[test]
+    fn games_awaiting_me() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+        login_as("alice.near");
+        contract.join_game("1".to_string(), None);
+
+        // alice.near rolled lower and goes first
+        assert_eq!(contract.games_awaiting_me("alice.near".parse().unwrap()), vec!["1"]);
+        assert_eq!(contract.games_awaiting_me("bob.near".parse().unwrap()), Vec::<String>::new());
+
+        // Both players owe a choice on the auxiliary dice
+        login_as("bob.near");
+        contract.create_game(None);
+        login_as("alice.near");
+        contract.join_game("2".to_string(), Some("Trickster".to_string()));
+        assert_eq!(contract.games_awaiting_me("bob.near".parse().unwrap()), vec!["2"]);
+        contract.accept_auxiliary("2".to_string(), true);
+        assert_eq!(contract.games_awaiting_me("alice.near".parse().unwrap()), vec!["1"]);
+
+        // Nobody to play against yet
+        login_as("carol.near");
+        contract.create_game(None);
+        assert_eq!(contract.games_awaiting_me("carol.near".parse().unwrap()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn get_or_create_for_pair() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        let game_id = contract.get_or_create_for_pair("bob.near".parse().unwrap(), "carol.near".parse().unwrap());
+        let game = contract.games.get(&game_id).unwrap();
+        assert_eq!(game.players, vec!["bob.near".to_string(), "carol.near".to_string()]);
+        assert_eq!(game.status, GameStatus::Active);
+
+        // Either order finds the same game
+        assert_eq!(contract.get_or_create_for_pair("bob.near".parse().unwrap(), "carol.near".parse().unwrap()), game_id);
+        assert_eq!(contract.get_or_create_for_pair("carol.near".parse().unwrap(), "bob.near".parse().unwrap()), game_id);
+        assert_ne!(contract.get_or_create_for_pair("bob.near".parse().unwrap(), "dave.near".parse().unwrap()), game_id);
+
+        login_as("bob.near");
+        contract.resign(game_id.clone());
+        login_as("alice.near");
+        assert_ne!(contract.get_or_create_for_pair("bob.near".parse().unwrap(), "carol.near".parse().unwrap()), game_id);
+    }
+
+    #[test]
+    fn my_active_games() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        let unjoined_id = contract.create_game(None);
+        let active_id = contract.create_game(None);
+        let finished_id = contract.create_game(None);
+        login_as("alice.near");
+        contract.join_game(active_id.clone(), None);
+        contract.join_game(finished_id.clone(), None);
+        contract.resign(finished_id.clone());
+
+        let games = contract.my_active_games("bob.near".parse().unwrap());
+        assert_eq!(games.iter().map(|summary| summary.id.clone()).collect::<Vec<String>>(), vec![active_id.clone()]);
+        let game = contract.games.get(&active_id).unwrap();
+        assert_eq!(games[0], GameSummary {
+            id: active_id.clone(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            buttons: game.buttons.clone(),
+            current_player: game.current_player,
+            your_turn: game.current_player == 0,
+            last_move_at: game.last_move_at,
+            tournament_id: None,
+            paused: false,
+        });
+        assert_eq!(contract.my_active_games("alice.near".parse().unwrap())[0].your_turn, game.current_player == 1);
+
+        assert!(contract.games.get(&unjoined_id).is_some());
+        assert_eq!(contract.my_active_games("carol.near".parse().unwrap()), vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can pair players")]
+    fn get_or_create_for_pair_not_owner() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.get_or_create_for_pair("bob.near".parse().unwrap(), "carol.near".parse().unwrap());
+    }
+
+    #[test]
+    fn move_options() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 1,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 6, value: 5 }, Die { size: 20, value: 10 }], vec![Die { size: 4, value: 2 }, Die { size: 8, value: 3 }, Die { size: 12, value: 5 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        let options = contract.get_move_options("1".to_string(), None);
+        assert_eq!(options.attacks, vec![
+            AttackOption { attacker_die_indices: vec![2], defender_die_index: 0, attack_type: AttackType::Power, evaluation: None },
+            AttackOption { attacker_die_indices: vec![0, 1], defender_die_index: 0, attack_type: AttackType::Skill, evaluation: None },
+            AttackOption { attacker_die_indices: vec![0, 1, 2], defender_die_index: 1, attack_type: AttackType::Skill, evaluation: None },
+        ]);
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(options.can_pass, contract.is_pass_allowed(&game));
+        assert!(!options.can_pass);
+        assert_eq!(options.your_turn_for, Some("alice.near".to_string()));
+        assert!(contract.is_player_turn("1".to_string(), "alice.near".parse().unwrap()));
+
+        // Every option is an attack the contract accepts
+        for option in options.attacks {
+            let mut game = contract.games.get(&"1".to_string()).unwrap();
+            contract.apply_attack(&mut game, option.attacker_die_indices, option.defender_die_index, Some(option.attack_type));
+        }
+    }
+
+    #[test]
+    fn move_options_evaluated() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 1,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 6, value: 5 }, Die { size: 20, value: 10 }], vec![Die { size: 4, value: 2 }, Die { size: 8, value: 3 }, Die { size: 12, value: 5 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        // The d12 showing 5 is below its average of 6.5, so re-rolling it gains 1.5 points on top of the 6 for the d6
+        let power = AttackEvaluation { points_gained: 12, reroll_cost: -3, net_swing: 15 };
+        // The d4 showing 2 and the d8 showing 3 are each half a point and a point and a half below their averages
+        let skill = AttackEvaluation { points_gained: 12, reroll_cost: -4, net_swing: 16 };
+        let all_dice = AttackEvaluation { points_gained: 40, reroll_cost: -7, net_swing: 47 };
+        let evaluations = |attacks: Vec<AttackOption>| attacks.into_iter().map(|attack| attack.evaluation).collect::<Vec<Option<AttackEvaluation>>>();
+        assert_eq!(evaluations(contract.get_move_options("1".to_string(), Some(true)).attacks), vec![Some(power), Some(skill), Some(all_dice)]);
+
+        match contract.web4_get(request_path("/api/games/1/attacks")) {
+            Web4Response::Body { body, .. } => {
+                assert!(!String::from_utf8(body.into()).unwrap().contains("evaluation"));
+            },
+            _ => panic!("Unexpected response"),
+        }
+        let mut request = request_path("/api/games/1/attacks");
+        request.query.insert("evaluate".to_string(), vec!["1".to_string()]);
+        match contract.web4_get(request) {
+            Web4Response::Body { body, .. } => {
+                let attacks: Vec<AttackOption> = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert_eq!(evaluations(attacks), vec![Some(power), Some(skill), Some(all_dice)]);
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    fn advance_turn_wraps_around() {
+        let mut game = Game {
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 1,
+            dice: vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 6, value: 2 }]],
+            ..Default::default()
+        };
+        advance_turn(&mut game);
+        assert_eq!(game.current_player, 0);
+
+        // The move that took the last die still hands over the turn before the game ends
+        game.dice[1] = vec![];
+        advance_turn(&mut game);
+        assert_eq!(game.current_player, 1);
+    }
+
+    #[test]
+    fn advance_turn_skips_eliminated() {
+        let mut game = Game {
+            players: vec!["bob.near".to_string(), "alice.near".to_string(), "carol.near".to_string()],
+            current_player: 0,
+            dice: vec![vec![Die { size: 4, value: 1 }], vec![], vec![Die { size: 6, value: 2 }]],
+            ..Default::default()
+        };
+        advance_turn(&mut game);
+        assert_eq!(game.current_player, 2);
+        advance_turn(&mut game);
+        assert_eq!(game.current_player, 0);
+    }
+
+    #[test]
+    fn projected_result_mid_game() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 6, value: 5 }, Die { size: 20, value: 10 }], vec![Die { size: 4, value: 2 }, Die { size: 12, value: 5 }]],
+            captured: vec![vec![8], vec![10, 4]],
+            ..Default::default()
+        });
+
+        // bob.near: 2 * 8 + 6 + 20 = 42, alice.near: 2 * (10 + 4) + 4 + 12 = 44
+        assert_eq!(contract.projected_result("1".to_string()), ProjectedResult { scores: vec![42, 44], leader: Some(1) });
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().status, GameStatus::Active);
+
+        // Capturing the d4 puts bob.near ahead: 2 * 12 + 26 = 50 against 2 * 14 + 12 = 40
+        login_as("bob.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        assert_eq!(contract.projected_result("1".to_string()), ProjectedResult { scores: vec![50, 40], leader: Some(0) });
+    }
+
+    #[test]
+    fn projected_result_even() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 1 }], vec![]],
+            captured: vec![vec![], vec![10]],
+            ..Default::default()
+        });
+
+        assert_eq!(contract.projected_result("1".to_string()), ProjectedResult { scores: vec![20, 20], leader: None });
+    }
+
+    #[test]
+    fn move_options_pass_only() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 20, value: 9 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        let options = contract.get_move_options("1".to_string(), None);
+        assert_eq!(options, MoveOptions { attacks: vec![], can_pass: true, your_turn_for: Some("bob.near".to_string()) });
+        assert_eq!(options.can_pass, contract.is_pass_allowed(&contract.games.get(&"1".to_string()).unwrap()));
+    }
+
+    #[test]
+    fn move_options_finished() {
+        let mut contract = Contract::default();
+        insert_finished_game(&mut contract);
+
+        assert_eq!(contract.get_move_options("1".to_string(), None), MoveOptions { attacks: vec![], can_pass: false, your_turn_for: None });
+    }
+
+    #[test]
+    fn is_player_turn_finished() {
+        let mut contract = Contract::default();
+        insert_finished_game(&mut contract);
+
+        assert!(!contract.is_player_turn("1".to_string(), "alice.near".parse().unwrap()));
+    }
+
+    #[test]
+    #[should_panic(expected = "It is not your turn")]
+    fn pass_not_your_turn() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+
+        login_as("alice.near");
+        contract.join_game("1".to_string(), None);
+        // alice.near rolled lower and goes first
+        login_as("bob.near");
+        contract.pass("1".to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Player eve.near has not joined game 1")]
+    fn pass_not_joined() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+
+        login_as("alice.near");
+        contract.join_game("1".to_string(), None);
+
+        login_as("eve.near");
+        contract.pass("1".to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Power attack is possible")]
+    fn pass_power_attack_possible() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 1 }, Die { size: 6, value: 4} ], vec![Die { size: 4, value: 2 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        contract.pass("1".to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Skill attack is possible")]
+    fn pass_skill_attack_possible() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![
+                vec![Die { size: 4, value: 1 }, Die { size: 6, value: 1 }, Die { size: 10, value: 2 }],
+                vec![Die { size: 4, value: 3 }, Die { size: 8, value: 6 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        contract.pass("1".to_string(), None, None);
+    }
+
+    #[test]
+    fn pass_success() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![
+                vec![Die { size: 4, value: 1 }, Die { size: 6, value: 1} ],
+                vec![Die { size: 4, value: 3 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        contract.pass("1".to_string(), None, None);
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.players, vec!["bob.near".to_string(), "alice.near".to_string()]);
+        assert_eq!(game.current_player, 1);
+        assert_eq!(game.dice, vec![
+            vec![Die { size: 4, value: 1 }, Die { size: 6, value: 1} ],
+            vec![Die { size: 4, value: 3 }]]);
+        assert_eq!(game.captured, vec![vec![], vec![]] as Vec<Vec<u8>>);
+    }
+
+    #[test]
+    fn pass_many_dice() {
+        let mut contract = Contract::default();
+        // 40 even dice can't add up to an odd value, the recursive search would try billions of sets to find out
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![
+                vec![Die { size: 4, value: 2 }; 40],
+                vec![Die { size: 30, value: 29 }, Die { size: 30, value: 27 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        contract.pass("1".to_string(), None, None);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().current_player, 1);
+    }
+
+    #[test]
+    fn skill_attack_many_dice() {
+        let contract = Contract::default();
+        let mut attacker_dice = vec![Die { size: 4, value: 2 }; 30];
+        attacker_dice.extend([Die { size: 6, value: 5 }, Die { size: 8, value: 7 }]);
+        let game = Game {
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![attacker_dice, vec![Die { size: 20, value: 19 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        };
+
+        let (attacker_die_indices, defender_die_value) = contract.find_skill_attack(&game).unwrap();
+        assert_eq!(defender_die_value, 19);
+        // The table names the actual dice, each once
+        assert_eq!(attacker_die_indices.iter().map(|index| game.dice[0][*index as usize].value as u32).sum::<u32>(), 19);
+        assert!(attacker_die_indices.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    fn set_paused(contract: &mut Contract, paused: bool) {
+        // alice.near is the contract account in the default test context
+        login_as("alice.near");
+        contract.set_paused(paused);
+        login_as("bob.near");
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can pause the contract")]
+    fn set_paused_not_owner() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.set_paused(true);
+    }
+
+    fn insert_reseed_game(contract: &mut Contract) {
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 1,
+            status: GameStatus::Active,
+            dice: vec![
+                vec![Die { size: 20, value: 1 }, Die { size: 20, value: 1 }, Die { size: 20, value: 1 }],
+                vec![Die { size: 20, value: 1 }, Die { size: 12, value: 1 }]],
+            captured: vec![vec![8], vec![4, 6]],
+            round_seed: vec![0; 32],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn reseed_game() {
+        let mut contract = Contract::default();
+        insert_reseed_game(&mut contract);
+
+        login_as("alice.near");
+        contract.reseed_game("1".to_string(), Base64VecU8(vec![7; 32]));
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.dice.iter().map(|dice| dice.iter().map(|die| die.size).collect()).collect::<Vec<Vec<u8>>>(), vec![vec![20, 20, 20], vec![20, 12]]);
+        assert!(game.dice.iter().flatten().any(|die| die.value != 1));
+        assert_eq!(game.captured, vec![vec![8], vec![4, 6]]);
+        assert_eq!(game.current_player, 1);
+        assert_eq!(game.round_seed, vec![0; 32]);
+
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![
+            r#"EVENT_JSON:{"standard":"buttonmen","version":"1.0.0","event":"game_reseeded","data":[{"game_id":"1","seed":"BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc="}]}"#,
+        ]);
+        assert_eq!(contract.get_audit_log(0, 10)[0].details, "game 1: seed BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=");
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can reseed games")]
+    fn reseed_game_not_owner() {
+        let mut contract = Contract::default();
+        insert_reseed_game(&mut contract);
+
+        login_as("bob.near");
+        contract.reseed_game("1".to_string(), Base64VecU8(vec![7; 32]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is finished")]
+    fn reseed_game_finished() {
+        let mut contract = Contract::default();
+        insert_finished_game(&mut contract);
+
+        login_as("alice.near");
+        contract.reseed_game("1".to_string(), Base64VecU8(vec![7; 32]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn create_game_paused() {
+        let mut contract = Contract::default();
+        set_paused(&mut contract, true);
+        contract.create_game(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn join_game_paused() {
+        let mut contract = Contract::default();
+        contract.create_game(None);
+        set_paused(&mut contract, true);
+
+        login_as("eve.near");
+        contract.join_game("1".to_string(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn attack_paused() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 4 }], vec![Die { size: 4, value: 2 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+        set_paused(&mut contract, true);
+
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn pass_paused() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 4, value: 3 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+        set_paused(&mut contract, true);
+
+        contract.pass("1".to_string(), None, None);
+    }
+
+    #[test]
+    fn unpause_restores_moves() {
+        let mut contract = Contract::default();
+        let game_id = contract.create_game(None);
+        set_paused(&mut contract, true);
+        assert!(contract.get_config().paused);
+
+        // Views keep working while paused
+        assert!(matches!(contract.web4_get(request_path(&format!("/api/games/{}", game_id))), Web4Response::Body { status: None, .. }));
+
+        set_paused(&mut contract, false);
+        assert!(!contract.get_config().paused);
+        assert_eq!(contract.create_game(None), "2");
+    }
+
+    fn session_secret(seed: u8) -> (ed25519_dalek::ExpandedSecretKey, ed25519_dalek::PublicKey) {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[seed; 32]).unwrap();
+        (ed25519_dalek::ExpandedSecretKey::from(&secret), ed25519_dalek::PublicKey::from(&secret))
+    }
+
+    fn session_public_key(seed: u8) -> PublicKey {
+        let (_, public_key) = session_secret(seed);
+        let mut data = vec![CurveType::ED25519 as u8];
+        data.extend_from_slice(public_key.as_bytes());
+        PublicKey::try_from(data).unwrap()
+    }
+
+    fn session_sign(seed: u8, message: &str) -> Option<Base64VecU8> {
+        let (secret, public_key) = session_secret(seed);
+        Some(secret.sign(message.as_bytes(), &public_key).to_bytes().to_vec().into())
+    }
+
+    fn insert_session_game(contract: &mut Contract) {
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 4 }, Die { size: 6, value: 1 }], vec![Die { size: 4, value: 2 }, Die { size: 4, value: 3 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn attack_session_key() {
+        let mut contract = Contract::default();
+        insert_session_game(&mut contract);
+        contract.register_session_key("1".to_string(), session_public_key(1));
+        assert_eq!(contract.get_session_nonce("1".to_string(), session_public_key(1)), Some(0));
+
+        // Relayer submits the move on behalf of bob.near
+        login_as("eve.near");
+        contract.attack("1".to_string(), vec![0], 1, session_sign(1, "alice.near:1:0:attack:0:1"), Some(session_public_key(1)), None);
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.current_player, 1);
+        assert_eq!(game.captured, vec![vec![4], vec![]]);
+        assert_eq!(contract.get_session_nonce("1".to_string(), session_public_key(1)), Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid session signature")]
+    fn attack_session_key_replay() {
         let mut contract = Contract::default();
-        contract.create_game();
+        insert_session_game(&mut contract);
+        contract.register_session_key("1".to_string(), session_public_key(1));
 
-        login_as("alice.near");
-        contract.join_game("1".to_string());
-
-        testing_env!(VMContextBuilder::new()
-            .predecessor_account_id("eve.near".parse().unwrap())
-            .build());
-        contract.join_game("1".to_string());
+        login_as("eve.near");
+        contract.attack("1".to_string(), vec![0], 1, session_sign(1, "alice.near:1:0:attack:0:1"), Some(session_public_key(1)), None);
+        // Nonce has moved on, so the same signature no longer verifies
+        contract.attack("1".to_string(), vec![0], 1, session_sign(1, "alice.near:1:0:attack:0:1"), Some(session_public_key(1)), None);
     }
 
     #[test]
-    #[should_panic(expected = "Game not found: 1")]
-    fn join_game_not_found() {
+    #[should_panic(expected = "Session key is not registered for game 1")]
+    fn attack_session_key_not_registered() {
         let mut contract = Contract::default();
-        contract.join_game("1".to_string());
+        insert_session_game(&mut contract);
+        contract.register_session_key("1".to_string(), session_public_key(1));
+
+        login_as("eve.near");
+        contract.attack("1".to_string(), vec![0], 1, session_sign(2, "alice.near:1:0:attack:0:1"), Some(session_public_key(2)), None);
     }
 
     #[test]
-    #[should_panic(expected = "It is not your turn")]
-    fn attack_not_your_turn() {
+    #[should_panic(expected = "Invalid session signature")]
+    fn attack_session_key_wrong_signer() {
         let mut contract = Contract::default();
-        contract.create_game();
+        insert_session_game(&mut contract);
+        contract.register_session_key("1".to_string(), session_public_key(1));
 
-        login_as("alice.near");
-        contract.join_game("1".to_string());
-        contract.attack("1".to_string(), vec![0], 0);
+        login_as("eve.near");
+        contract.attack("1".to_string(), vec![0], 1, session_sign(2, "alice.near:1:0:attack:0:1"), Some(session_public_key(1)), None);
     }
 
     #[test]
-    #[should_panic(expected = "Player eve.near has not joined game 1")]
-    fn attack_not_joined() {
+    #[should_panic(expected = "Session key is already registered for game 1")]
+    fn register_session_key_hijack() {
         let mut contract = Contract::default();
-        contract.create_game();
+        insert_session_game(&mut contract);
+        contract.register_session_key("1".to_string(), session_public_key(1));
 
+        // The opponent claiming bob.near's key would have it sign moves for them
         login_as("alice.near");
-        contract.join_game("1".to_string());
+        contract.register_session_key("1".to_string(), session_public_key(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Session key is already registered for game 1")]
+    fn register_session_key_again() {
+        let mut contract = Contract::default();
+        insert_session_game(&mut contract);
+        contract.register_session_key("1".to_string(), session_public_key(1));
 
         login_as("eve.near");
-        contract.attack("1".to_string(), vec![0], 0);
+        contract.attack("1".to_string(), vec![0], 1, session_sign(1, "alice.near:1:0:attack:0:1"), Some(session_public_key(1)), None);
+        assert_eq!(contract.get_session_nonce("1".to_string(), session_public_key(1)), Some(1));
+
+        // Registering again would put the nonce back to 0 and let the relayed attack be replayed
+        login_as("bob.near");
+        contract.register_session_key("1".to_string(), session_public_key(1));
     }
 
     #[test]
-    #[should_panic(expected = "Attack failed")]
-    fn attack_failed() {
+    #[should_panic(expected = "Player eve.near has not joined game 1")]
+    fn register_session_key_not_joined() {
         let mut contract = Contract::default();
-        contract.games.insert(&"1".to_string(), &Game {
-            id: "1".to_string(),
-            players: vec!["bob.near".to_string(), "alice.near".to_string()],
-            current_player: 0,
-            dice: vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 4, value: 2 }]],
-            captured: vec![vec![], vec![]],
-        });
+        insert_session_game(&mut contract);
 
+        login_as("eve.near");
+        contract.register_session_key("1".to_string(), session_public_key(1));
+    }
+
+    fn gate_brawler(contract: &mut Contract) {
+        login_as("alice.near");
+        contract.set_button_nft_gate("Brawler".to_string(), Some(NftGate {
+            nft_contract_id: "nft.near".parse().unwrap(),
+            token_series: "brawler".to_string(),
+        }));
         login_as("bob.near");
-        contract.attack("1".to_string(), vec![0], 0);
+    }
+
+    fn brawler_options() -> Option<CreateGameOptions> {
+        Some(CreateGameOptions { button: Some("Brawler".to_string()), ..Default::default() })
+    }
+
+    fn nft_tokens(token_ids: &[&str]) -> Result<Vec<NftToken>, PromiseError> {
+        Ok(token_ids.iter().map(|token_id| NftToken { token_id: token_id.to_string() }).collect())
     }
 
     #[test]
-    fn attack_power_success() {
+    fn create_game_premium_button_owned() {
         let mut contract = Contract::default();
-        contract.games.insert(&"1".to_string(), &Game {
-            id: "1".to_string(),
-            players: vec!["bob.near".to_string(), "alice.near".to_string()],
-            current_player: 0,
-            dice: vec![vec![Die { size: 4, value: 4 }, Die { size: 6, value: 1} ], vec![Die { size: 4, value: 2 }]],
-            captured: vec![vec![], vec![]],
-        });
+        gate_brawler(&mut contract);
+
+        let game_id = contract.create_game(brawler_options());
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, "nft.near".parse::<AccountId>().unwrap());
+        match &receipts[0].actions[0] {
+            near_sdk::mock::VmAction::FunctionCall { function_name, .. } => assert_eq!(function_name, "nft_tokens_for_owner"),
+            _ => panic!("Unexpected action"),
+        }
+        let game = contract.games.get(&game_id).unwrap();
+        assert!(game.verifying);
+        assert_eq!(game.buttons, vec!["Brawler".to_string(), "".to_string()]);
+        assert_eq!(game.dice[0].iter().map(|die| die.size).collect::<Vec<u8>>(), vec![6, 8, 12, 12, 20]);
 
-        contract.attack("1".to_string(), vec![0], 0);
+        assert!(contract.on_button_verified(game_id.clone(), "bob.near".to_string(), "brawler".to_string(), nft_tokens(&["other:1", "brawler:3"])));
+        assert!(!contract.games.get(&game_id).unwrap().verifying);
 
-        let game = contract.games.get(&"1".to_string()).unwrap();
-        assert_eq!(game.players, vec!["bob.near".to_string(), "alice.near".to_string()]);
-        assert_eq!(game.current_player, 1);
-        // NOTE: The attacker's die is re-rolled. It's deterministic in tests
-        assert_eq!(game.dice, vec![vec![Die { size: 4, value: 2 }, Die { size: 6, value: 1 }], vec![]]);
-        assert_eq!(game.captured, vec![vec![4], vec![]]);
+        login_as("alice.near");
+        contract.join_game(game_id.clone(), None);
+        let game = contract.games.get(&game_id).unwrap();
+        assert_eq!(game.buttons, vec!["Brawler".to_string(), "Standard".to_string()]);
+        assert_ne!(game.current_player, 0xFF);
     }
 
     #[test]
-    fn attack_skill_success() {
+    fn create_game_premium_button_not_owned() {
         let mut contract = Contract::default();
-        contract.games.insert(&"1".to_string(), &Game {
-            id: "1".to_string(),
-            players: vec!["bob.near".to_string(), "alice.near".to_string()],
-            current_player: 0,
-            dice: vec![vec![Die { size: 4, value: 2 }, Die { size: 6, value: 4 }], vec![Die { size: 10, value: 6 }]],
-            captured: vec![vec![], vec![]],
-        });
+        gate_brawler(&mut contract);
 
-        contract.attack("1".to_string(), vec![0, 1], 0);
+        let game_id = contract.create_game(brawler_options());
+        assert!(!contract.on_button_verified(game_id.clone(), "bob.near".to_string(), "brawler".to_string(), nft_tokens(&["brawlers:1"])));
 
-        let game = contract.games.get(&"1".to_string()).unwrap();
-        assert_eq!(game.players, vec!["bob.near".to_string(), "alice.near".to_string()]);
-        assert_eq!(game.current_player, 1);
-        assert_eq!(game.dice, vec![vec![Die { size: 4, value: 2 }, Die { size: 6, value: 4 }], vec![]]);
-        assert_eq!(game.captured, vec![vec![10], vec![]]);
+        assert!(contract.games.get(&game_id).is_none());
+        assert_eq!(contract.latest_games, vec![] as Vec<String>);
+        assert_eq!(get_user_games("bob.near".to_string()), vec![] as Vec<String>);
     }
 
     #[test]
-    fn attack_power_alice() {
+    fn join_game_premium_button_call_failed() {
         let mut contract = Contract::default();
-        contract.games.insert(&"1".to_string(), &Game {
-            id: "1".to_string(),
-            players: vec!["bob.near".to_string(), "alice.near".to_string()],
-            current_player: 1,
-            dice: vec![vec![Die { size: 4, value: 4 }, Die { size: 6, value: 1} ], vec![Die { size: 4, value: 3 }]],
-            captured: vec![vec![], vec![]],
-        });
+        gate_brawler(&mut contract);
+        let game_id = contract.create_game(None);
 
         login_as("alice.near");
-        contract.attack("1".to_string(), vec![0], 1);
+        contract.join_game(game_id.clone(), Some("Brawler".to_string()));
+        let game = contract.games.get(&game_id).unwrap();
+        assert!(game.verifying);
+        assert_eq!(game.current_player, 0xFF);
+
+        assert!(!contract.on_button_verified(game_id.clone(), "alice.near".to_string(), "brawler".to_string(), Err(PromiseError::Failed)));
+        let game = contract.games.get(&game_id).unwrap();
+        assert!(!game.verifying);
+        assert_eq!(game.players, vec!["bob.near".to_string(), "".to_string()]);
+        assert_eq!(game.dice[1], vec![]);
+        assert_eq!(get_user_games("alice.near".to_string()), vec![] as Vec<String>);
 
-        let game = contract.games.get(&"1".to_string()).unwrap();
-        assert_eq!(game.players, vec!["bob.near".to_string(), "alice.near".to_string()]);
-        assert_eq!(game.current_player, 0);
-        // NOTE: The attacker's die is re-rolled. It's deterministic in tests
-        assert_eq!(game.dice, vec![vec![Die { size: 4, value: 4 }], vec![Die { size: 4, value: 2 }]]);
-        assert_eq!(game.captured, vec![vec![], vec![6]]);
+        // The slot is open again
+        login_as("eve.near");
+        contract.join_game(game_id.clone(), None);
+        assert_ne!(contract.games.get(&game_id).unwrap().current_player, 0xFF);
     }
 
     #[test]
-    #[should_panic(expected = "It is not your turn")]
-    fn pass_not_your_turn() {
+    fn button_verified_after_cancel() {
         let mut contract = Contract::default();
-        contract.create_game();
-
+        gate_brawler(&mut contract);
+        let game_id = contract.create_game(None);
         login_as("alice.near");
-        contract.join_game("1".to_string());
-        contract.pass("1".to_string());
+        contract.join_game(game_id.clone(), Some("Brawler".to_string()));
+
+        // As if something had cancelled the game while the ownership check was out
+        let mut game = contract.games.get(&game_id).unwrap();
+        game.status = GameStatus::Cancelled;
+        contract.games.insert(&game_id, &game);
+
+        assert!(!contract.on_button_verified(game_id.clone(), "alice.near".to_string(), "brawler".to_string(), nft_tokens(&["brawler:3"])));
+        let game = contract.games.get(&game_id).unwrap();
+        assert_eq!(game.status, GameStatus::Cancelled);
+        assert_eq!(game.current_player, 0xFF);
     }
 
     #[test]
-    #[should_panic(expected = "Player eve.near has not joined game 1")]
-    fn pass_not_joined() {
+    #[should_panic(expected = "Game is full: 1")]
+    fn second_join_sees_first() {
         let mut contract = Contract::default();
-        contract.create_game();
-
+        contract.create_game(None);
         login_as("alice.near");
-        contract.join_game("1".to_string());
+        contract.join_game("1".to_string(), None);
 
         login_as("eve.near");
-        contract.pass("1".to_string());
+        contract.join_game("1".to_string(), None);
     }
 
     #[test]
-    #[should_panic(expected = "Power attack is possible")]
-    fn pass_power_attack_possible() {
+    #[should_panic(expected = "Game 1 is verifying button ownership")]
+    fn join_game_while_verifying() {
         let mut contract = Contract::default();
-        contract.games.insert(&"1".to_string(), &Game {
-            id: "1".to_string(),
-            players: vec!["bob.near".to_string(), "alice.near".to_string()],
-            current_player: 0,
-            dice: vec![vec![Die { size: 4, value: 1 }, Die { size: 6, value: 4} ], vec![Die { size: 4, value: 2 }]],
-            captured: vec![vec![], vec![]],
-        });
+        gate_brawler(&mut contract);
+        contract.create_game(brawler_options());
 
-        contract.pass("1".to_string());
+        login_as("eve.near");
+        contract.join_game("1".to_string(), None);
+    }
+
+    fn your_turn(game_id: &str, actor: &str) -> Notification {
+        Notification { kind: "your_turn".to_string(), game_id: game_id.to_string(), actor: actor.to_string() }
     }
 
     #[test]
-    #[should_panic(expected = "Skill attack is possible")]
-    fn pass_skill_attack_possible() {
+    fn notifications_short_game() {
         let mut contract = Contract::default();
+        contract.create_game(None);
+
+        // alice.near rolls lower with the default seed and starts, so nobody else is notified
+        login_as("alice.near");
+        contract.join_game("1".to_string(), None);
+        assert_eq!(contract.get_notifications("bob.near".to_string(), 0, 10), vec![]);
+        assert_eq!(contract.get_notifications("alice.near".to_string(), 0, 10), vec![]);
+
         contract.games.insert(&"1".to_string(), &Game {
             id: "1".to_string(),
             players: vec!["bob.near".to_string(), "alice.near".to_string()],
-            current_player: 0,
-            dice: vec![
-                vec![Die { size: 4, value: 1 }, Die { size: 6, value: 1 }, Die { size: 10, value: 2 }],
-                vec![Die { size: 4, value: 3 }, Die { size: 8, value: 6 }]],
+            current_player: 1,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 1 }, Die { size: 6, value: 1 }], vec![Die { size: 4, value: 3 }]],
             captured: vec![vec![], vec![]],
+            ..Default::default()
         });
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        assert_eq!(contract.get_notifications("bob.near".to_string(), 0, 10), vec![your_turn("1", "alice.near")]);
+        assert_eq!(near_sdk::test_utils::get_logs(), vec![
+            r#"EVENT_JSON:{"standard":"buttonmen","version":"1.0.0","event":"notification","data":[{"account_id":"bob.near","type":"your_turn","game_id":"1","actor":"alice.near"}]}"#,
+        ]);
+
+        login_as("bob.near");
+        contract.pass("1".to_string(), None, None);
+        assert_eq!(contract.get_notifications("alice.near".to_string(), 0, 10), vec![your_turn("1", "bob.near")]);
+
+        // Capturing the last die finishes the game, so it's nobody's turn
+        login_as("alice.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        assert_eq!(contract.get_notifications("bob.near".to_string(), 0, 10), vec![your_turn("1", "alice.near")]);
 
-        contract.pass("1".to_string());
+        login_as("bob.near");
+        contract.clear_notifications("bob.near".to_string());
+        assert_eq!(contract.get_notifications("bob.near".to_string(), 0, 10), vec![]);
+        assert_eq!(contract.get_notifications("alice.near".to_string(), 0, 10).len(), 1);
     }
 
     #[test]
-    fn pass_success() {
+    fn notifications_pass_finishes_game() {
         let mut contract = Contract::default();
         contract.games.insert(&"1".to_string(), &Game {
             id: "1".to_string(),
             players: vec!["bob.near".to_string(), "alice.near".to_string()],
             current_player: 0,
-            dice: vec![
-                vec![Die { size: 4, value: 1 }, Die { size: 6, value: 1} ],
-                vec![Die { size: 4, value: 3 }]],
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 4, value: 3 }]],
             captured: vec![vec![], vec![]],
+            buttons: vec![DEFAULT_BUTTON.to_string(), DEFAULT_BUTTON.to_string()],
+            auto_forced: vec![false, true],
+            ..Default::default()
         });
 
-        contract.pass("1".to_string());
+        // alice.near's only attack is played for her and takes bob.near's last die
+        login_as("bob.near");
+        contract.pass("1".to_string(), None, None);
+        assert!(contract.games.get(&"1".to_string()).unwrap().is_finished());
+        assert_eq!(contract.get_notifications("bob.near".to_string(), 0, 10), vec![]);
+    }
+
+    #[test]
+    fn notifications_bounded() {
+        login_as("bob.near");
+        for i in 0..MAX_NOTIFICATIONS + 5 {
+            notify("bob.near", "your_turn", &i.to_string(), "alice.near");
+        }
 
-        let game = contract.games.get(&"1".to_string()).unwrap();
-        assert_eq!(game.players, vec!["bob.near".to_string(), "alice.near".to_string()]);
-        assert_eq!(game.current_player, 1);
-        assert_eq!(game.dice, vec![
-            vec![Die { size: 4, value: 1 }, Die { size: 6, value: 1} ],
-            vec![Die { size: 4, value: 3 }]]);
-        assert_eq!(game.captured, vec![vec![], vec![]] as Vec<Vec<u8>>);
+        let contract = Contract::default();
+        let notifications = contract.get_notifications("bob.near".to_string(), 0, 100);
+        assert_eq!(notifications.len(), MAX_NOTIFICATIONS);
+        assert_eq!(notifications[0], your_turn("5", "alice.near"));
+        assert_eq!(contract.get_notifications("bob.near".to_string(), 1, 2), vec![your_turn("6", "alice.near"), your_turn("7", "alice.near")]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the account owner can clear notifications")]
+    fn clear_notifications_other_account() {
+        let mut contract = Contract::default();
+        login_as("eve.near");
+        contract.clear_notifications("bob.near".to_string());
     }
 
-    fn request_path(path: &str) -> Web4Request {
+    pub(crate) fn request_path(path: &str) -> Web4Request {
         Web4Request {
             account_id: None,
             path: path.to_string(),
@@ -770,26 +3547,61 @@ mod tests {
         });
     }
 
+    #[test]
+    fn web4_get_buttons() {
+        let contract = Contract::default();
+
+        match contract.web4_get(request_path("/api/buttons")) {
+            Web4Response::Body { content_type, body, .. } => {
+                assert_eq!(content_type, "application/json".to_owned());
+                let buttons: serde_json::Value = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert!(buttons.as_array().unwrap().contains(&serde_json::json!({
+                    "name": "Standard",
+                    "dice": [4, 6, 8, 10, 20],
+                    "auxiliary": [],
+                    "skills": [],
+                })));
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
     #[test]
     fn web4_get_game_state() {
         let mut contract = Contract::default();
-        let game_id = contract.create_game();
+        let game_id = contract.create_game(None);
 
         let response = contract.web4_get(request_path(&format!("/api/games/{}", game_id)));
         match response {
-            Web4Response::Body { content_type, body } => {
+            Web4Response::Body { content_type, body, .. } => {
                 assert_eq!(content_type, "application/json".to_owned());
                 assert_eq!(String::from_utf8(body.into()).unwrap(),
                     serde_json::to_string(&serde_json::json!({
                         "id": game_id,
                         "players": ["bob.near", ""],
-                        "current_player": 0xff,
+                        "current_player": null,
                         "dice": [
-                            [{"size": 4, "value": 2}, {"size": 6, "value": 4}, {"size": 8, "value": 5}, {"size": 10, "value": 5}, {"size": 20, "value": 5}],
+                            [{"size": 4, "value": 3}, {"size": 6, "value": 2}, {"size": 8, "value": 8}, {"size": 10, "value": 9}, {"size": 20, "value": 11}],
                             []
                         ],
                         "captured": [[], []],
+                        "captured_value": [0, 0],
                         "is_pass_allowed": false,
+                        "round_seed": "",
+                        "buttons": ["Standard", ""],
+                        "verifying": false,
+                        "status": "waiting",
+                        "winner": null,
+                        "auxiliary_choices": [],
+                        "report_to": null,
+                        "result_reported": null,
+                        "undo_requested_by": null,
+                        "fog": false,
+                        "ratings": [1500, 1500],
+                        "rating_deltas": [],
+                        "titles": ["Novice", "Novice"],
+                        "badges": [[], []],
+                        "clubs": [null, null],
                     })).unwrap());
 
             },
@@ -797,6 +3609,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn die_size_allowed() {
+        assert_valid_die_size(12);
+        let die = roll_die(&mut Rng::new(&vec![0; 32]), 12);
+        assert_eq!(die.size, 12);
+    }
+
+    #[test]
+    fn roll_die_zero_seed_in_range() {
+        let mut rng = Rng::new(&vec![0; 32]);
+        for size in engine::ALLOWED_DIE_SIZES {
+            let values = (0..1000).map(|_| roll_die(&mut rng, *size).value).collect::<std::collections::HashSet<u8>>();
+            // Every face comes up, the highest included
+            assert_eq!(values, (1..=*size).collect(), "d{}", size);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid die size: d7")]
+    fn die_size_not_allowed() {
+        roll_die(&mut Rng::new(&vec![0; 32]), 7);
+    }
+
+    #[test]
+    fn buttons_use_allowed_die_sizes() {
+        for button in BUTTONS {
+            button.dice.iter().chain(button.auxiliary.iter()).for_each(|size| assert_valid_die_size(*size));
+        }
+    }
+
+    #[test]
+    fn is_pass_allowed_not_started() {
+        let contract = Contract::default();
+        let game = Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "".to_string()],
+            current_player: 0,
+            dice: vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 4, value: 3 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        };
+        assert!(!contract.is_pass_allowed(&game));
+
+        let game = Game { status: GameStatus::Active, ..game };
+        assert!(contract.is_pass_allowed(&game));
+    }
+
     #[test]
     fn web4_get_game_state_is_pass_allowed() {
         let mut contract = Contract::default();
@@ -804,15 +3663,17 @@ mod tests {
             id: "1".to_string(),
             players: vec!["bob.near".to_string(), "alice.near".to_string()],
             current_player: 0,
+            status: GameStatus::Active,
             dice: vec![
                 vec![Die { size: 4, value: 1 }, Die { size: 6, value: 1} ],
                 vec![Die { size: 4, value: 3 }]],
             captured: vec![vec![], vec![]],
+            ..Default::default()
         });
 
-        let response = contract.web4_get(request_path(&format!("/api/games/1")));
+        let response = contract.web4_get(request_path("/api/games/1"));
         match response {
-            Web4Response::Body { content_type, body } => {
+            Web4Response::Body { content_type, body, .. } => {
                 assert_eq!(content_type, "application/json".to_owned());
                 assert_eq!(String::from_utf8(body.into()).unwrap(),
                     serde_json::to_string(&serde_json::json!({
@@ -824,23 +3685,170 @@ mod tests {
                             [{"size": 4, "value": 3}]
                         ],
                         "captured": [[], []],
+                        "captured_value": [0, 0],
                         "is_pass_allowed": true,
+                        "round_seed": "",
+                        "buttons": [],
+                        "verifying": false,
+                        "status": "active",
+                        "winner": null,
+                        "auxiliary_choices": [],
+                        "report_to": null,
+                        "result_reported": null,
+                        "undo_requested_by": null,
+                        "fog": false,
+                        "ratings": [1500, 1500],
+                        "rating_deltas": [],
+                        "titles": ["Novice", "Novice"],
+                        "badges": [[], []],
+                        "clubs": [null, null],
                     })).unwrap());
             },
             _ => panic!("Unexpected response"),
         }
     }
 
+    #[test]
+    fn web4_get_unstarted_current_player() {
+        let mut contract = Contract::default();
+        let game_id = contract.create_game(None);
+
+        let view_of = |contract: &Contract| match contract.web4_get(request_path(&format!("/api/games/{}", game_id))) {
+            Web4Response::Body { body, .. } => serde_json::from_slice::<serde_json::Value>(&Vec::<u8>::from(body)).unwrap(),
+            _ => panic!("Unexpected response"),
+        };
+        assert_eq!(view_of(&contract)["current_player"], serde_json::Value::Null);
+
+        login_as("alice.near");
+        contract.join_game(game_id.clone(), None);
+        assert!(view_of(&contract)["current_player"].is_u64());
+    }
+
+    #[test]
+    fn web4_get_captured_value() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 4, value: 3 }]],
+            captured: vec![vec![20, 6, 4], vec![]],
+            ..Default::default()
+        });
+
+        match contract.web4_get(request_path("/api/games/1")) {
+            Web4Response::Body { body, .. } => {
+                let view: GameView = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert_eq!(view.captured_value, vec![30, 0]);
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    fn fogged_dice_as(contract: &Contract, account_id: &str) -> Vec<Vec<Die>> {
+        let mut request = request_path("/api/games/1");
+        request.account_id = Some(account_id.to_string());
+        match contract.web4_get(request) {
+            Web4Response::Body { body, .. } => serde_json::from_slice::<GameView>(&Vec::<u8>::from(body)).unwrap().dice,
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    fn web4_get_fogged_game() {
+        let mut contract = Contract::default();
+        let dice = vec![
+            vec![Die { size: 4, value: 3 }, Die { size: 6, value: 5 }],
+            vec![Die { size: 8, value: 7 }]];
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 1,
+            status: GameStatus::Active,
+            dice: dice.clone(),
+            captured: vec![vec![], vec![]],
+            fog: true,
+            ..Default::default()
+        });
+
+        // bob.near waits for alice.near and only sees the sizes of her dice
+        assert_eq!(fogged_dice_as(&contract, "bob.near"), vec![dice[0].clone(), vec![Die { size: 8, value: 0 }]]);
+        assert_eq!(fogged_dice_as(&contract, "alice.near"), dice);
+
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        game.current_player = 0;
+        contract.games.insert(&"1".to_string(), &game);
+        assert_eq!(fogged_dice_as(&contract, "bob.near"), dice);
+        assert_eq!(fogged_dice_as(&contract, "alice.near"), vec![vec![Die { size: 4, value: 0 }, Die { size: 6, value: 0 }], dice[1].clone()]);
+
+        game.fog = false;
+        contract.games.insert(&"1".to_string(), &game);
+        assert_eq!(fogged_dice_as(&contract, "alice.near"), dice);
+    }
+
+    #[test]
+    fn web4_get_game_your_turn() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 1,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 3 }], vec![Die { size: 8, value: 7 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+
+        let your_turn = |contract: &Contract, account_id: Option<&str>| {
+            let mut request = request_path("/api/games/1");
+            request.account_id = account_id.map(|account_id| account_id.to_string());
+            match contract.web4_get(request) {
+                Web4Response::Body { body, .. } => serde_json::from_slice::<serde_json::Value>(&Vec::<u8>::from(body)).unwrap()["your_turn"].clone(),
+                _ => panic!("Unexpected response"),
+            }
+        };
+        assert_eq!(your_turn(&contract, Some("alice.near")), serde_json::json!(true));
+        assert_eq!(your_turn(&contract, Some("bob.near")), serde_json::json!(false));
+        assert_eq!(your_turn(&contract, Some("eve.near")), serde_json::json!(false));
+        // Public view has no reader to tell
+        assert_eq!(your_turn(&contract, None), serde_json::Value::Null);
+
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        game.status = GameStatus::Finished;
+        contract.games.insert(&"1".to_string(), &game);
+        assert_eq!(your_turn(&contract, Some("alice.near")), serde_json::json!(false));
+    }
+
+    #[test]
+    fn create_fogged_game() {
+        let mut contract = Contract::default();
+        let game_id = contract.create_game(Some(CreateGameOptions { fog: true, ..Default::default() }));
+        assert!(contract.games.get(&game_id).unwrap().fog);
+    }
+
     #[test]
     fn web4_get_game_state_not_found() {
         let contract = Contract::default();
 
         let response = contract.web4_get(request_path("/api/games/1"));
-        // TODO: JSON error?
         assert_eq!(response, Web4Response::Body {
-            content_type: "text/html; charset=UTF-8".to_owned(),
-            body: "<h1>Game not found</h1>".as_bytes().to_owned().into(),
+            content_type: "application/json".to_owned(),
+            body: r#"{"error":"game_not_found"}"#.as_bytes().to_owned().into(),
+            status: Some(404),
         });
+        assert_eq!(serde_json::to_value(&response).unwrap()["status"], 404);
+    }
+
+    #[test]
+    fn web4_get_path_cut_short() {
+        let contract = Contract::default();
+
+        assert_eq!(contract.web4_get(request_path("/api/gamesx")), error_response(Error::GameNotFound { game_id: "".to_string() }));
+        assert_eq!(contract.web4_get(request_path("/api/users")), contract.serve_static("/api/users"));
+        let mut request = request_path("/api/my/feed");
+        request.account_id = Some("Not An Account".to_string());
+        assert_eq!(contract.web4_get(request), error_response(Error::InvalidAccountId { account_id: "Not An Account".to_string() }));
     }
 
     #[test]
@@ -851,32 +3859,62 @@ mod tests {
         assert_eq!(response, Web4Response::Body {
             content_type: "application/json".to_owned(),
             body: "[]".as_bytes().to_owned().into(),
+            status: None,
         });
     }
 
     #[test]
     fn web4_get_latest_games() {
         let mut contract = Contract::default();
-        let game1 = contract.create_game();
-        let game2 = contract.create_game();
+        let game1 = contract.create_game(None);
+        let game2 = contract.create_game(None);
 
         login_as("alice.near");
-        contract.join_game(game2.clone());
+        contract.join_game(game2.clone(), None);
 
         let response = contract.web4_get(request_path("/api/games"));
         match response {
-            Web4Response::Body { content_type, body } => {
+            Web4Response::Body { content_type, body, .. } => {
                 assert_eq!(content_type, "application/json".to_owned());
                 assert_eq!(String::from_utf8(body.into()).unwrap(),
                     serde_json::to_string(&vec![
-                        contract.games.get(&game1).unwrap(),
-                        contract.games.get(&game2).unwrap(),
+                        LobbyGame { game: contract.games.get(&game1).unwrap(), ratings: vec![1500, 1500] },
+                        LobbyGame { game: contract.games.get(&game2).unwrap(), ratings: vec![1500, 1500] },
                     ]).unwrap());
             },
             _ => panic!("Unexpected response"),
         }
     }
 
+    #[test]
+    fn web4_get_unlisted_game() {
+        let mut contract = Contract::default();
+        let listed = contract.create_game(None);
+        let unlisted = contract.create_game(Some(CreateGameOptions { public: Some(false), ..Default::default() }));
+        match contract.web4_get(request_path("/api/games")) {
+            Web4Response::Body { body, .. } => {
+                let games: serde_json::Value = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert_eq!(games.as_array().unwrap().iter().map(|game| game["id"].clone()).collect::<Vec<serde_json::Value>>(), vec![serde_json::json!(listed)]);
+            },
+            _ => panic!("Unexpected response"),
+        }
+        assert_eq!(contract.get_open_games().iter().map(|open_game| open_game.game_id.clone()).collect::<Vec<String>>(), vec![listed]);
+
+        match contract.web4_get(request_path(&format!("/api/games/{}", unlisted))) {
+            Web4Response::Body { body, status, .. } => {
+                assert_eq!(status, None);
+                let game: serde_json::Value = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert_eq!(game["id"], unlisted);
+            },
+            _ => panic!("Unexpected response"),
+        }
+
+        // Playable by whoever has the link
+        login_as("alice.near");
+        contract.join_game(unlisted.clone(), None);
+        assert_eq!(contract.games.get(&unlisted).unwrap().status, GameStatus::Active);
+    }
+
     #[test]
     fn web4_get_your_games_empty() {
         let contract = Contract::default();
@@ -885,20 +3923,21 @@ mod tests {
         assert_eq!(response, Web4Response::Body {
             content_type: "application/json".to_owned(),
             body: "[]".as_bytes().to_owned().into(),
+            status: None,
         });
     }
 
     #[test]
     fn web4_get_your_games() {
         let mut contract = Contract::default();
-        let game1 = contract.create_game();
-        let game2 = contract.create_game();
+        let game1 = contract.create_game(None);
+        let game2 = contract.create_game(None);
 
         login_as("alice.near");
-        contract.join_game(game2.clone());
+        contract.join_game(game2.clone(), None);
 
         match contract.web4_get(request_path("/api/users/alice.near/games")) {
-            Web4Response::Body { content_type, body } => {
+            Web4Response::Body { content_type, body, .. } => {
                 assert_eq!(content_type, "application/json".to_owned());
                 assert_eq!(String::from_utf8(body.into()).unwrap(),
                     serde_json::to_string(&vec![
@@ -908,7 +3947,7 @@ mod tests {
         }
 
         match contract.web4_get(request_path("/api/users/bob.near/games")) {
-            Web4Response::Body { content_type, body } => {
+            Web4Response::Body { content_type, body, .. } => {
                 assert_eq!(content_type, "application/json".to_owned());
                 assert_eq!(String::from_utf8(body.into()).unwrap(),
                     serde_json::to_string(&vec![
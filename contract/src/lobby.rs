@@ -0,0 +1,147 @@
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Cancels the lobby's games nobody joined within ContractConfig.lobby_ttl_ns, keeping their creators' deposits
+    /// like expire_game does. Anyone can call it. Games with a wager are left to reclaim_wager. Returns the ids of the cancelled games.
+    pub fn sweep_expired(&mut self) -> Vec<String> {
+        self.assert_not_paused();
+
+        let mut candidates = self.latest_games.clone();
+        for open_game in self.open_games.iter() {
+            if !candidates.contains(&open_game.game_id) {
+                candidates.push(open_game.game_id.clone());
+            }
+        }
+
+        let mut swept = vec![];
+        for game_id in candidates {
+            let mut game = match self.games.get(&game_id) {
+                Some(game) if self.is_lobby_expired(&game) && !game.verifying && self.wagers.get(&game_id).is_none() => game,
+                _ => continue,
+            };
+            self.cancel_unjoined(&mut game);
+            swept.push(game_id);
+        }
+
+        if !swept.is_empty() {
+            emit_event("games_expired", serde_json::json!({ "game_ids": swept }));
+        }
+        swept
+    }
+
+    pub fn set_lobby_ttl(&mut self, ttl_ns: u64) -> () {
+        self.assert_owner("set the lobby TTL");
+        if ttl_ns == 0 {
+            fail(Error::ZeroLobbyTtl);
+        }
+
+        self.config.lobby_ttl_ns = ttl_ns;
+    }
+}
+
+impl Contract {
+    /// Whether the game is still waiting for a second player past ContractConfig.lobby_ttl_ns. A full game waiting
+    /// on the auxiliary dice choices isn't, it's up to the players.
+    pub(crate) fn is_lobby_expired(&self, game: &Game) -> bool {
+        game.status == GameStatus::Waiting
+            && game.players.iter().any(|p| p.is_empty())
+            && env::block_timestamp() >= game.created_at.saturating_add(self.config.lobby_ttl_ns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_as, login_as, request_path};
+    use near_sdk::json_types::U128;
+    use near_sdk::test_utils::get_created_receipts;
+
+    const DEPOSIT: Balance = 5_000;
+
+    fn lobby_ids(contract: &Contract) -> Vec<String> {
+        match contract.web4_get(request_path("/api/games")) {
+            Web4Response::Body { body, .. } => serde_json::from_slice::<Vec<serde_json::Value>>(&Vec::<u8>::from(body)).unwrap()
+                .into_iter().map(|game| game["id"].as_str().unwrap().to_string()).collect(),
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    fn expired_games_leave_the_lobby() {
+        let mut contract = Contract::default();
+        call_as("bob.near", 0, 0);
+        let old_game = contract.create_game(None);
+        call_as("carol.near", DAY_NS, 0);
+        let new_game = contract.create_game(None);
+
+        call_as("eve.near", 14 * DAY_NS - 1, 0);
+        assert_eq!(lobby_ids(&contract), vec![old_game.clone(), new_game.clone()]);
+
+        call_as("eve.near", 14 * DAY_NS, 0);
+        assert_eq!(lobby_ids(&contract), vec![new_game.clone()]);
+        assert_eq!(contract.get_open_games().iter().map(|open_game| open_game.game_id.clone()).collect::<Vec<String>>(), vec![new_game]);
+        let view = contract.game_view(&contract.games.get(&old_game).unwrap(), None);
+        assert_eq!(view.status, GameStatus::Expired);
+        assert_eq!(serde_json::to_value(view.status).unwrap(), "expired");
+    }
+
+    #[test]
+    fn sweep_forfeits_the_deposit() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_new_account_deposit(U128(DEPOSIT));
+        call_as("bob.near", 0, DEPOSIT);
+        let old_game = contract.create_game(None);
+        call_as("carol.near", DAY_NS, DEPOSIT);
+        let new_game = contract.create_game(None);
+
+        call_as("eve.near", 14 * DAY_NS, 0);
+        assert_eq!(contract.sweep_expired(), vec![old_game.clone()]);
+
+        let game = contract.games.get(&old_game).unwrap();
+        assert_eq!(game.status, GameStatus::Cancelled);
+        assert_eq!(contract.latest_games, vec![new_game.clone()]);
+        assert_eq!(contract.open_games.iter().map(|open_game| open_game.game_id.clone()).collect::<Vec<String>>(), vec![new_game.clone()]);
+        assert_eq!(contract.get_spam_deposit(old_game), None);
+        assert_eq!(contract.get_spam_deposit(new_game), Some(U128(DEPOSIT)));
+        assert_eq!(contract.treasury, DEPOSIT);
+        assert!(get_created_receipts().is_empty());
+    }
+
+    #[test]
+    fn sweep_skips_wagers() {
+        let mut contract = Contract::default();
+        call_as("bob.near", 0, 100);
+        let game_id = contract.create_wagered_game(None);
+
+        call_as("eve.near", 14 * DAY_NS, 0);
+        assert_eq!(contract.sweep_expired(), vec![] as Vec<String>);
+        assert_eq!(contract.games.get(&game_id).unwrap().status, GameStatus::Waiting);
+    }
+
+    #[test]
+    fn full_game_not_expired() {
+        let mut contract = Contract::default();
+        call_as("bob.near", 0, 0);
+        let game_id = contract.create_game(None);
+        let mut game = contract.games.get(&game_id).unwrap();
+        game.players[1] = "carol.near".to_string();
+        game.auxiliary_choices = vec![None, None];
+        contract.games.insert(&game_id, &game);
+
+        call_as("eve.near", 14 * DAY_NS, 0);
+        assert_eq!(contract.sweep_expired(), vec![] as Vec<String>);
+        assert_eq!(contract.game_view(&contract.games.get(&game_id).unwrap(), None).status, GameStatus::Waiting);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can set the lobby TTL")]
+    fn lobby_ttl_not_owner() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.set_lobby_ttl(DAY_NS);
+    }
+}
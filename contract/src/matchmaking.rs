@@ -0,0 +1,228 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, Promise};
+
+use crate::*;
+
+/// Open games kept for quick_match, the oldest are dropped beyond this
+const MAX_OPEN_GAMES: usize = 100;
+/// Open games quick_match reads per call, oldest first, so its gas stays bounded
+const MATCH_CANDIDATES: usize = 20;
+
+/// Game waiting for an opponent, with its creator's rating when it was created
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OpenGame {
+    pub game_id: String,
+    pub rating: u32,
+    pub created_at: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Joins the open game whose creator's rating is closest to the caller's, within the rating window, or creates
+    /// a game when none fits. Only games on the same clock as the time preset, no clock by default, are joined.
+    /// Attach what create_game needs, it's refunded when an open game is joined.
+    #[payable]
+    pub fn quick_match(&mut self, button: Option<String>, time_preset: Option<TimePreset>) -> String {
+        self.assert_not_paused();
+
+        let time_control = time_preset.and_then(TimePreset::time_control);
+        match self.find_match(env::predecessor_account_id().as_str(), time_control) {
+            Some(game_id) => {
+                self.open_games.retain(|open_game| open_game.game_id != game_id);
+                self.internal_join_game(game_id.clone(), button);
+                if env::attached_deposit() > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(env::attached_deposit());
+                }
+                game_id
+            },
+            None => self.create_game(Some(CreateGameOptions { button, time_preset, ..Default::default() })),
+        }
+    }
+
+    pub fn get_open_games(&self) -> Vec<OpenGame> {
+        self.open_games.iter()
+            .filter(|open_game| self.games.get(&open_game.game_id).is_none_or(|game| !self.is_lobby_expired(&game)))
+            .cloned()
+            .collect()
+    }
+
+    pub fn set_matchmaking_config(&mut self, rating_window: u32, window_widen_ns: u64) -> () {
+        self.assert_owner("configure matchmaking");
+        if window_widen_ns == 0 {
+            fail(Error::ZeroWindowWiden);
+        }
+
+        self.config.match_rating_window = rating_window;
+        self.config.match_window_widen_ns = window_widen_ns;
+    }
+
+    /// Adds a game created for anyone to join to the open games
+    pub(crate) fn add_open_game(&mut self, game_id: &str) {
+        self.open_games.push(OpenGame {
+            game_id: game_id.to_string(),
+            rating: self.rating_of(env::predecessor_account_id().as_str()),
+            created_at: env::block_timestamp(),
+        });
+        if self.open_games.len() > MAX_OPEN_GAMES {
+            self.open_games.remove(0);
+        }
+    }
+
+    /// Rating distance an open game accepts. It grows by ContractConfig.match_rating_window
+    /// every ContractConfig.match_window_widen_ns the game has been waiting.
+    fn match_window(&self, open_game: &OpenGame) -> u32 {
+        let waited = env::block_timestamp().saturating_sub(open_game.created_at);
+        let widenings = (waited / self.config.match_window_widen_ns).min(u32::MAX as u64) as u32;
+        self.config.match_rating_window.saturating_mul(widenings.saturating_add(1))
+    }
+
+    /// Closest rated of the oldest open games on the clock the account can join. Games that can't be joined anymore
+    /// are dropped on the way.
+    fn find_match(&mut self, account_id: &str, time_control: Option<TimeControl>) -> Option<String> {
+        let rating = self.rating_of(account_id);
+        let blocked = blocks::get_blocks(account_id);
+        let mut stale = vec![];
+        let mut best: Option<(u32, String)> = None;
+
+        for open_game in self.open_games.iter().take(MATCH_CANDIDATES) {
+            let game = match self.games.get(&open_game.game_id) {
+                Some(game) if game.status == GameStatus::Waiting && !game.verifying && game.players.iter().any(|p| p.is_empty()) && !self.is_lobby_expired(&game) => game,
+                _ => {
+                    stale.push(open_game.game_id.clone());
+                    continue;
+                }
+            };
+            if game.players.iter().any(|p| p == account_id) || game.time_control != time_control {
+                continue;
+            }
+            // Kept apart whichever of the two did the blocking
+            if blocked.contains(&game.players[0]) || blocks::is_blocked(&game.players[0], account_id) {
+                continue;
+            }
+
+            let distance = rating.abs_diff(open_game.rating);
+            if distance <= self.match_window(open_game) && best.as_ref().is_none_or(|(best_distance, _)| distance < *best_distance) {
+                best = Some((distance, open_game.game_id.clone()));
+            }
+        }
+
+        self.open_games.retain(|open_game| !stale.contains(&open_game.game_id));
+        best.map(|(_, game_id)| game_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_as, login_as, request_path, transfers};
+
+    const MINUTE_NS: u64 = 60 * 1_000_000_000;
+
+    fn rated(contract: &mut Contract, account_id: &str, rating: u32) {
+        contract.ratings.insert(&account_id.to_string(), &SeasonRating { season_id: None, rating });
+    }
+
+    fn open_game(contract: &mut Contract, account_id: &str, rating: u32, timestamp: u64) -> String {
+        rated(contract, account_id, rating);
+        call_as(account_id, timestamp, 0);
+        contract.create_game(None)
+    }
+
+    #[test]
+    fn closest_rating_in_window() {
+        let mut contract = Contract::default();
+        open_game(&mut contract, "carol.near", 1900, 0);
+        open_game(&mut contract, "dave.near", 1620, 0);
+        let erin_game = open_game(&mut contract, "erin.near", 1450, 0);
+        assert_eq!(contract.get_open_games()[2], OpenGame { game_id: erin_game.clone(), rating: 1450, created_at: 0 });
+
+        rated(&mut contract, "bob.near", 1500);
+        call_as("bob.near", 0, 0);
+        assert_eq!(contract.quick_match(None, None), erin_game);
+        let game = contract.games.get(&erin_game).unwrap();
+        assert_eq!(game.players, vec!["erin.near".to_string(), "bob.near".to_string()]);
+        assert_eq!(game.status, GameStatus::Active);
+        assert_eq!(contract.get_open_games().len(), 2);
+    }
+
+    #[test]
+    fn matches_on_the_same_clock() {
+        let mut contract = Contract::default();
+        rated(&mut contract, "carol.near", 1500);
+        call_as("carol.near", 0, 0);
+        let blitz_game = contract.create_game(Some(CreateGameOptions { time_preset: Some(TimePreset::Blitz), ..Default::default() }));
+
+        // Listed with its clock, so players know what they're joining
+        match contract.web4_get(request_path("/api/games")) {
+            Web4Response::Body { body, .. } => {
+                let lobby: serde_json::Value = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert_eq!(lobby[0]["time_control"], serde_json::json!({"bank_ns": 60 * MINUTE_NS, "increment_ns": 5 * MINUTE_NS, "per_move": false}));
+            },
+            _ => panic!("Unexpected response"),
+        }
+
+        call_as("bob.near", 0, 0);
+        let casual_game = contract.quick_match(None, None);
+        assert_ne!(casual_game, blitz_game);
+        assert_eq!(contract.games.get(&casual_game).unwrap().time_control, None);
+
+        call_as("dave.near", 0, 0);
+        assert_eq!(contract.quick_match(None, Some(TimePreset::Blitz)), blitz_game);
+    }
+
+    #[test]
+    fn creates_game_when_nothing_fits() {
+        let mut contract = Contract::default();
+        open_game(&mut contract, "carol.near", 1900, 0);
+
+        call_as("bob.near", 5 * MINUTE_NS, 0);
+        let game_id = contract.quick_match(Some("Standard".to_string()), None);
+        let game = contract.games.get(&game_id).unwrap();
+        assert_eq!(game.players, vec!["bob.near".to_string(), "".to_string()]);
+        assert_eq!(contract.get_open_games().iter().map(|open_game| open_game.game_id.clone()).collect::<Vec<String>>(), vec!["1".to_string(), game_id]);
+    }
+
+    #[test]
+    fn window_widens_while_waiting() {
+        let mut contract = Contract::default();
+        let carol_game = open_game(&mut contract, "carol.near", 1900, 0);
+
+        // 400 apart: 150 wide at first, 300 after 10 minutes, 450 after 20
+        call_as("bob.near", 19 * MINUTE_NS, 0);
+        assert!(contract.find_match("bob.near", None).is_none());
+        call_as("bob.near", 20 * MINUTE_NS, 10);
+        assert_eq!(contract.quick_match(None, None), carol_game);
+
+        // What was attached for creating a game goes back
+        assert_eq!(transfers(), vec![("bob.near".to_string(), 10)]);
+    }
+
+    #[test]
+    fn skips_own_and_taken_games() {
+        let mut contract = Contract::default();
+        let taken_game = open_game(&mut contract, "carol.near", 1500, 0);
+        login_as("dave.near");
+        contract.join_game(taken_game, None);
+        let own_game = open_game(&mut contract, "bob.near", 1500, 0);
+
+        call_as("bob.near", 0, 0);
+        let game_id = contract.quick_match(None, None);
+        assert_ne!(game_id, own_game);
+        // The joined game is dropped, bob.near's first one stays open
+        assert_eq!(contract.get_open_games().iter().map(|open_game| open_game.game_id.clone()).collect::<Vec<String>>(), vec![own_game, game_id]);
+    }
+
+    #[test]
+    fn scans_oldest_candidates_only() {
+        let mut contract = Contract::default();
+        for index in 0..MATCH_CANDIDATES {
+            open_game(&mut contract, &format!("far{}.near", index), 2500, 0);
+        }
+        open_game(&mut contract, "close.near", 1500, 0);
+
+        call_as("bob.near", 0, 0);
+        assert!(contract.find_match("bob.near", None).is_none());
+    }
+}
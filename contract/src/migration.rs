@@ -0,0 +1,249 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+/// Contract state as deployed before the config, ratings and economy were added
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldContract {
+    pub games: LookupMap<String, OldGame>,
+    pub last_game_id: u64,
+    pub latest_games: Vec<String>,
+    pub web4_static_url: String,
+}
+
+/// Game as stored by the old contract, under the same `g` prefix the games are kept under now
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldGame {
+    pub id: String,
+    pub players: Vec<String>,
+    pub current_player: u8,
+    pub dice: Vec<Vec<Die>>,
+    pub captured: Vec<Vec<u8>>,
+}
+
+/// Progress of migrate_games through the games the old contract left
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Migration {
+    /// Next game to rewrite, they're taken in order of id
+    pub next_game_id: u64,
+    pub last_game_id: u64,
+    /// When migrate ran, the migrated games are taken to start then
+    pub started_at: u64,
+}
+
+/// Old games were all played with the standard dice, a seat nobody joined yet is empty. The old contract never
+/// ended a game, so one where a player ran out of dice is finished here, scored as it stood.
+fn migrate_game(old: OldGame, started_at: u64) -> Game {
+    let mut game = Game {
+        buttons: old.players.iter().map(|player_id| if player_id.is_empty() { "" } else { DEFAULT_BUTTON }.to_string()).collect(),
+        id: old.id,
+        players: old.players,
+        current_player: old.current_player,
+        dice: old.dice,
+        captured: old.captured,
+        created_at: started_at,
+        last_move_at: started_at,
+        turn_started_at: started_at,
+        ..Default::default()
+    };
+    game.status = if game.players.iter().any(|player_id| player_id.is_empty()) {
+        GameStatus::Waiting
+    } else if game.dice.iter().any(|dice| dice.is_empty()) {
+        game.finish_reason = Some(FinishReason::CaptureOut);
+        game.winner = leader(&scores(&game));
+        GameStatus::Finished
+    } else {
+        GameStatus::Active
+    };
+    game
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Upgrades the state of the contract deployed before ContractConfig existed. Only the contract's own fields
+    /// are converted here, the games are rewritten by migrate_games, a batch at a time. Until the last one is,
+    /// the contract stays paused.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let old: OldContract = env::state_read().unwrap_or_else(|| fail(Error::NoStateToMigrate));
+        Contract {
+            last_game_id: old.last_game_id,
+            latest_games: old.latest_games,
+            web4_static_url: old.web4_static_url,
+            migration: (old.last_game_id > 0).then(|| Migration {
+                next_game_id: 1,
+                last_game_id: old.last_game_id,
+                started_at: env::block_timestamp(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Rewrites up to limit old games in the current layout, starting from from_id, which has to be where the last
+    /// call stopped: 1 first, then what it returned. Besides the game, it fills in the indexes the old contract
+    /// didn't keep: the day the game was created on, the lobby, what each player is awaiting and who finished a game.
+    /// Returns the id to go on from, None once every game is migrated and the contract is running again.
+    pub fn migrate_games(&mut self, from_id: u64, limit: u64) -> Option<u64> {
+        self.assert_owner("migrate games");
+        let mut migration = self.migration.take().unwrap_or_else(|| fail(Error::NoMigration));
+        if from_id != migration.next_game_id {
+            fail(Error::MigrationResumesAt { game_id: migration.next_game_id.to_string() });
+        }
+
+        let mut old_games: LookupMap<String, OldGame> = LookupMap::new(b"g".to_vec());
+        let day = game_days::utc_day(migration.started_at);
+        let mut day_game_ids = self.games_by_day.get(&day).unwrap_or_default();
+        let to_id = migration.last_game_id.min(from_id.saturating_add(limit).saturating_sub(1));
+        for game_id in from_id..=to_id {
+            let game_id = game_id.to_string();
+            // Taken out first, saving over it would read the old layout as a current game
+            if let Some(old_game) = old_games.remove(&game_id) {
+                let game = migrate_game(old_game, migration.started_at);
+                match game.status {
+                    GameStatus::Waiting => self.open_games.push(OpenGame {
+                        game_id: game.id.clone(),
+                        rating: self.rating_of(&game.players[0]),
+                        created_at: game.created_at,
+                    }),
+                    GameStatus::Finished => self.mark_players_finished(&game),
+                    _ => {},
+                }
+                day_game_ids.push(game.id.clone());
+                self.save_game(&game);
+            }
+        }
+        if !day_game_ids.is_empty() {
+            self.games_by_day.insert(&day, &day_game_ids);
+        }
+
+        migration.next_game_id = to_id + 1;
+        if migration.next_game_id > migration.last_game_id {
+            return None;
+        }
+        self.migration = Some(migration);
+        Some(to_id + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_at, login_as};
+
+    fn die(size: u8, value: u8) -> Die {
+        Die { size, value }
+    }
+
+    /// Writes the state the old contract would have left, with a waiting, an active and a played out game
+    fn write_old_state() {
+        let mut games = LookupMap::new(b"g".to_vec());
+        games.insert(&"1".to_string(), &OldGame {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "".to_string()],
+            current_player: 0xFF,
+            dice: vec![vec![die(4, 2), die(20, 11)], vec![]],
+            captured: vec![vec![], vec![]],
+        });
+        games.insert(&"2".to_string(), &OldGame {
+            id: "2".to_string(),
+            players: vec!["bob.near".to_string(), "carol.near".to_string()],
+            current_player: 1,
+            dice: vec![vec![die(4, 2)], vec![die(6, 5), die(8, 1)]],
+            captured: vec![vec![10], vec![]],
+        });
+        games.insert(&"3".to_string(), &OldGame {
+            id: "3".to_string(),
+            players: vec!["carol.near".to_string(), "dave.near".to_string()],
+            current_player: 0,
+            dice: vec![vec![die(6, 3)], vec![]],
+            captured: vec![vec![4, 8], vec![]],
+        });
+        env::state_write(&OldContract {
+            games,
+            last_game_id: 3,
+            latest_games: vec!["1".to_string(), "2".to_string(), "3".to_string()],
+            web4_static_url: "ipfs://old".to_string(),
+        });
+    }
+
+    #[test]
+    fn migrate_old_state() {
+        call_at("alice.near", 1_000);
+        write_old_state();
+        let mut contract = Contract::migrate();
+
+        assert_eq!(contract.last_game_id, 3);
+        assert_eq!(contract.latest_games, vec!["1", "2", "3"]);
+        assert_eq!(contract.web4_static_url, "ipfs://old");
+        assert_eq!(contract.owner_id.as_str(), "alice.near");
+        assert!(!contract.config.paused);
+
+        // A batch at a time, each picking up where the last one stopped
+        call_at("alice.near", 2 * DAY_NS);
+        assert_eq!(contract.migrate_games(1, 2), Some(3));
+        assert!(contract.migration.is_some());
+        assert_eq!(contract.migrate_games(3, 2), None);
+        assert!(contract.migration.is_none());
+
+        let waiting = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(waiting.status, GameStatus::Waiting);
+        assert_eq!(waiting.buttons, vec![DEFAULT_BUTTON, ""]);
+        assert_eq!(waiting.dice, vec![vec![die(4, 2), die(20, 11)], vec![]]);
+        assert_eq!(contract.open_games, vec![OpenGame { game_id: "1".to_string(), rating: ratings::DEFAULT_RATING, created_at: 1_000 }]);
+
+        let active = contract.games.get(&"2".to_string()).unwrap();
+        assert_eq!((active.status, active.current_player), (GameStatus::Active, 1));
+        assert_eq!(active.captured, vec![vec![10], vec![]]);
+        assert_eq!(active.turn_started_at, 1_000);
+
+        let finished = contract.games.get(&"3".to_string()).unwrap();
+        assert_eq!((finished.status, finished.winner, finished.finish_reason), (GameStatus::Finished, Some(0), Some(FinishReason::CaptureOut)));
+
+        // The indexes the old contract didn't keep
+        assert_eq!(contract.get_games_by_day("19700101".to_string()), vec!["1", "2", "3"]);
+        assert_eq!(contract.get_games_awaiting_move("carol.near".parse().unwrap()).iter().map(|game| game.game_id.clone()).collect::<Vec<String>>(), vec!["2"]);
+        assert!(contract.is_known_player("dave.near".parse().unwrap()));
+        assert!(!contract.is_known_player("bob.near".parse().unwrap()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn paused_until_migrated() {
+        login_as("alice.near");
+        write_old_state();
+        let mut contract = Contract::migrate();
+        contract.migrate_games(1, 2);
+
+        login_as("carol.near");
+        contract.attack("2".to_string(), vec![0], 0, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Migration resumes at game 3")]
+    fn migrate_games_out_of_order() {
+        login_as("alice.near");
+        write_old_state();
+        let mut contract = Contract::migrate();
+        contract.migrate_games(1, 2);
+        contract.migrate_games(1, 2);
+    }
+
+    #[test]
+    fn migrated_game_playable() {
+        login_as("alice.near");
+        write_old_state();
+        let mut contract = Contract::migrate();
+        contract.migrate_games(1, 10);
+
+        login_as("carol.near");
+        contract.attack("2".to_string(), vec![0], 0, None, None, None);
+        assert_eq!(contract.games.get(&"2".to_string()).unwrap().captured, vec![vec![10], vec![4]]);
+
+        login_as("dave.near");
+        contract.join_game("1".to_string(), None);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().status, GameStatus::Active);
+    }
+}
@@ -0,0 +1,117 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, Gas};
+
+use crate::*;
+
+/// Rollouts estimate_win_probability plays at most. With n of them a percentage is off by about 50 / sqrt(n) points
+/// (one standard error), so 3.5 points at 200.
+pub(crate) const MAX_ROLLOUTS: u32 = 200;
+/// Rollouts the odds route plays
+pub(crate) const DEFAULT_ROLLOUTS: u32 = 100;
+/// A rollout stops here and goes by the scores, as it would after both players passed
+const MAX_ROLLOUT_MOVES: usize = 100;
+/// No rollout starts once this much gas is used, so the view always returns
+const ROLLOUT_GAS_BUDGET: Gas = Gas(150_000_000_000_000);
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WinProbability {
+    /// Each player's share of rollouts won, in percent, rounded down
+    pub win_pct: Vec<u32>,
+    pub draw_pct: u32,
+    /// Rollouts played, fewer than asked for when the gas ran low
+    pub iterations: u32,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Estimates each player's chances by playing the game out from the current position up to MAX_ROLLOUTS times.
+    /// Rolls come from the position's own seed, so the same position always gets the same estimate.
+    pub fn estimate_win_probability(&self, game_id: String, iterations: u32) -> WinProbability {
+        let game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+
+        let mut rng = Rng::new(&env::sha256(format!("odds:{}:{}", game.id, game.roll_seq).as_bytes()));
+        let mut wins = vec![0u32; PLAYER_COUNT];
+        let mut draws = 0;
+        let mut completed = 0;
+        while completed < iterations.min(MAX_ROLLOUTS) && env::used_gas() < ROLLOUT_GAS_BUDGET {
+            match policies::play_out(&game, [BotDifficulty::Greedy; 2], &mut rng, MAX_ROLLOUT_MOVES) {
+                Some(winner) => wins[winner as usize] += 1,
+                None => draws += 1,
+            }
+            completed += 1;
+        }
+
+        let pct = |count: u32| (count * 100).checked_div(completed).unwrap_or(0);
+        WinProbability {
+            win_pct: wins.into_iter().map(pct).collect(),
+            draw_pct: pct(draws),
+            iterations: completed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::request_path;
+
+    fn insert_game(contract: &mut Contract, dice: Vec<Vec<Die>>) {
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice,
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn nearly_won() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract, vec![
+            vec![Die { size: 20, value: 19 }, Die { size: 20, value: 18 }, Die { size: 12, value: 11 }],
+            vec![Die { size: 4, value: 2 }, Die { size: 6, value: 1 }],
+        ]);
+
+        let odds = contract.estimate_win_probability("1".to_string(), 1_000);
+        assert_eq!(odds.iterations, MAX_ROLLOUTS);
+        assert!(odds.win_pct[0] >= 95, "{:?}", odds);
+        assert_eq!(odds.win_pct.iter().sum::<u32>() + odds.draw_pct, 100);
+    }
+
+    #[test]
+    fn same_position_same_odds() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract, vec![
+            vec![Die { size: 6, value: 3 }, Die { size: 8, value: 5 }, Die { size: 10, value: 2 }],
+            vec![Die { size: 6, value: 4 }, Die { size: 8, value: 1 }, Die { size: 12, value: 7 }],
+        ]);
+
+        let odds = contract.estimate_win_probability("1".to_string(), 50);
+        assert_eq!(odds.iterations, 50);
+        assert_eq!(contract.estimate_win_probability("1".to_string(), 50), odds);
+        match contract.web4_get(request_path("/api/games/1/odds")) {
+            Web4Response::Body { body, status, .. } => {
+                assert_eq!(status, None);
+                assert_eq!(serde_json::from_slice::<WinProbability>(&Vec::<u8>::from(body)).unwrap(), contract.estimate_win_probability("1".to_string(), DEFAULT_ROLLOUTS));
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is finished")]
+    fn finished_game_odds() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract, vec![vec![Die { size: 4, value: 1 }], vec![]]);
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        finish_game(&mut game, None, FinishReason::CaptureOut);
+        contract.games.insert(&"1".to_string(), &game);
+
+        contract.estimate_win_probability("1".to_string(), 10);
+    }
+}
@@ -0,0 +1,266 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+/// A pause both players agreed on, it holds until one of them resumes or it runs out at ends_at
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Pause {
+    pub started_at: u64,
+    /// When the game's allowance of ContractConfig.max_pause_ns runs out
+    pub ends_at: u64,
+}
+
+/// Whether the game is on a pause that hasn't run out
+pub(crate) fn is_paused(game: &Game) -> bool {
+    game.pause.is_some_and(|pause| env::block_timestamp() < pause.ends_at)
+}
+
+/// Time the current pause, if any, has held so far
+pub(crate) fn paused_for(game: &Game) -> u64 {
+    game.pause.map_or(0, |pause| env::block_timestamp().min(pause.ends_at).saturating_sub(pause.started_at))
+}
+
+/// Closes the pause, pushing the turn's clock and the game's deadlines back by the time it held
+fn end_pause(game: &mut Game) {
+    let paused_for = paused_for(game);
+    if game.pause.take().is_none() {
+        return;
+    }
+    game.paused_ns += paused_for;
+    game.last_move_at += paused_for;
+    game.turn_started_at += paused_for;
+}
+
+/// Stops whatever the game is asked to do while it's paused. A pause that has run out is closed on the way.
+pub(crate) fn assert_running(game: &mut Game) {
+    if is_paused(game) {
+        fail(Error::GamePaused { game_id: game.id.to_string() });
+    }
+    end_pause(game);
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Asks the opponent to pause the game, see accept_pause
+    pub fn request_pause(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+        let player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() }));
+        assert_running(&mut game);
+        if game.pause_requested_by.is_some() {
+            fail(Error::PausePending { game_id: game_id.to_string() });
+        }
+        if game.paused_ns >= self.config.max_pause_ns {
+            fail(Error::PausesUsedUp { game_id: game_id.to_string(), max_pause_ns: self.config.max_pause_ns });
+        }
+
+        game.pause_requested_by = Some(player_index as u8);
+        self.save_game(&game);
+
+        notify(&game.players[(player_index + 1) % 2], "pause_requested", &game_id, &player_id);
+    }
+
+    /// Pauses the game the opponent asked to, freezing the clock and deadlines until either player resumes it
+    /// or what's left of ContractConfig.max_pause_ns for the game runs out
+    pub fn accept_pause(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+        let player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() }));
+        match game.pause_requested_by {
+            Some(requested_by) if requested_by != player_index as u8 => {},
+            _ => fail(Error::NotRequested { request: "pause".to_string(), game_id: game_id.to_string() }),
+        }
+
+        let now = env::block_timestamp();
+        let ends_at = now + self.config.max_pause_ns.saturating_sub(game.paused_ns);
+        game.pause = Some(Pause { started_at: now, ends_at });
+        game.pause_requested_by = None;
+        self.save_game(&game);
+
+        emit_event("game_paused", serde_json::json!({
+            "game_id": game_id,
+            "ends_at": ends_at,
+        }));
+    }
+
+    /// Ends the pause, the player on turn's clock runs again from where it stopped
+    pub fn resume(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+        if !game.players.contains(&player_id) {
+            fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() });
+        }
+        if game.pause.is_none() {
+            fail(Error::GameNotPaused { game_id: game_id.to_string() });
+        }
+
+        end_pause(&mut game);
+        self.save_game(&game);
+
+        emit_event("game_resumed", serde_json::json!({
+            "game_id": game_id,
+            "paused_ns": game.paused_ns,
+        }));
+    }
+
+    pub fn set_max_pause(&mut self, max_pause_ns: u64) -> () {
+        self.assert_owner("set the pause limit");
+
+        self.config.max_pause_ns = max_pause_ns;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_at, login_as};
+
+    const HOUR_NS: u64 = 60 * 60 * 1_000_000_000;
+
+    /// bob.near to move on a day's bank, with d20s on both sides so every attack keeps the game going
+    fn clocked_game(contract: &mut Contract) {
+        call_at("bob.near", 0);
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 20 }; 3], vec![Die { size: 20, value: 1 }; 3]],
+            captured: vec![vec![], vec![]],
+            time_control: Some(TimeControl { bank_ns: DAY_NS, increment_ns: 0, per_move: false }),
+            remaining_ns: vec![DAY_NS, DAY_NS],
+            ..Default::default()
+        });
+        add_user_game("bob.near".to_string(), "1".to_string());
+    }
+
+    fn paused_game(contract: &mut Contract, at: u64) {
+        call_at("alice.near", at);
+        contract.request_pause("1".to_string());
+        call_at("bob.near", at);
+        contract.accept_pause("1".to_string());
+    }
+
+    fn game(contract: &Contract) -> Game {
+        contract.games.get(&"1".to_string()).unwrap()
+    }
+
+    #[test]
+    fn pause_handshake() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        call_at("alice.near", HOUR_NS);
+        contract.request_pause("1".to_string());
+        assert_eq!(game(&contract).pause_requested_by, Some(1));
+        assert_eq!(game(&contract).pause, None);
+
+        call_at("bob.near", 2 * HOUR_NS);
+        contract.accept_pause("1".to_string());
+        let view = contract.game_view(&game(&contract), None);
+        assert_eq!((view.pause_requested_by, view.paused_until), (None, Some(2 * HOUR_NS + 7 * DAY_NS)));
+        assert!(contract.my_active_games("bob.near".parse().unwrap())[0].paused);
+
+        call_at("alice.near", 5 * HOUR_NS);
+        contract.resume("1".to_string());
+        let view = contract.game_view(&game(&contract), None);
+        assert_eq!(view.paused_until, None);
+        assert_eq!(game(&contract).paused_ns, 3 * HOUR_NS);
+    }
+
+    #[test]
+    #[should_panic(expected = "No pause requested by the opponent in game 1")]
+    fn accept_own_request() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+
+        contract.request_pause("1".to_string());
+        contract.accept_pause("1".to_string());
+    }
+
+    #[test]
+    fn clock_frozen_while_paused() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+        paused_game(&mut contract, 2 * HOUR_NS);
+
+        // Paused for 3 days, far longer than bob.near's bank
+        call_at("bob.near", 3 * DAY_NS);
+        assert_eq!(contract.game_view(&game(&contract), None).remaining_ns, vec![DAY_NS - 2 * HOUR_NS, DAY_NS]);
+        contract.resume("1".to_string());
+
+        call_at("bob.near", 3 * DAY_NS + HOUR_NS);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        assert_eq!(game(&contract).remaining_ns, vec![DAY_NS - 3 * HOUR_NS, DAY_NS]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is paused")]
+    fn no_timeout_claim_while_paused() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+        paused_game(&mut contract, 0);
+
+        call_at("alice.near", 2 * DAY_NS);
+        contract.claim_timeout_win("1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is paused")]
+    fn no_move_while_paused() {
+        let mut contract = Contract::default();
+        clocked_game(&mut contract);
+        paused_game(&mut contract, 0);
+
+        call_at("bob.near", HOUR_NS);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+    }
+
+    #[test]
+    fn pause_runs_out() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_max_pause(DAY_NS);
+        clocked_game(&mut contract);
+        paused_game(&mut contract, HOUR_NS);
+
+        // The pause ran out a day after it started, bob.near's clock has run for 22 hours since
+        call_at("bob.near", 2 * DAY_NS - HOUR_NS);
+        assert_eq!(contract.game_view(&game(&contract), None).paused_until, None);
+        assert_eq!(contract.game_view(&game(&contract), None).remaining_ns, vec![HOUR_NS, DAY_NS]);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        assert_eq!(game(&contract).paused_ns, DAY_NS);
+        assert_eq!(game(&contract).remaining_ns, vec![HOUR_NS, DAY_NS]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 has used up its 86400000000000 ns of pauses")]
+    fn cumulative_cap() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_max_pause(DAY_NS);
+        clocked_game(&mut contract);
+
+        paused_game(&mut contract, 0);
+        call_at("bob.near", 20 * HOUR_NS);
+        contract.resume("1".to_string());
+        // Only 4 hours left of the day, this pause runs out early
+        paused_game(&mut contract, 21 * HOUR_NS);
+        assert_eq!(game(&contract).pause.unwrap().ends_at, 25 * HOUR_NS);
+
+        call_at("alice.near", 2 * DAY_NS);
+        contract.request_pause("1".to_string());
+    }
+}
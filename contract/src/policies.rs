@@ -0,0 +1,208 @@
+//! The bot's policies on their own: what an attack is worth and which one each difficulty picks.
+//! Like engine, nothing here touches the blockchain, Random's choice comes in through DiceRng. bot has the contract
+//! methods that seat the bot and play its moves, odds and puzzles reuse the same policies.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+
+use crate::engine::{attack_options, leader, roll_die, scores, DiceRng};
+use crate::{AttackOption, Game};
+
+/// Own captures the Lookahead bot looks past, the best by attack_swing. Bounds its search to that many
+/// of the opponent's replies, each a single pass over their attack options.
+const LOOKAHEAD_CANDIDATES: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum BotDifficulty {
+    /// Any legal attack, uniformly
+    Random,
+    /// The attack with the best attack_swing
+    Greedy,
+    /// The attack with the best attack_swing less the opponent's best reply to it
+    Lookahead,
+}
+
+/// The biggest capture the current player can make, with a single die if possible, otherwise the smallest set of dice.
+/// Returns attacker die indices and the defender die index, None when there's nothing to capture.
+/// Goes through every set of the player's dice, so it's only for buttons with a handful of dice.
+pub fn biggest_capture(game: &Game) -> Option<(Vec<u8>, u8)> {
+    let own = &game.dice[game.current_player as usize];
+    let targets = &game.dice[(game.current_player as usize + 1) % 2];
+    let mut by_size = (0..targets.len()).collect::<Vec<usize>>();
+    // Stable, so the first of equal dice is taken
+    by_size.sort_by_key(|index| std::cmp::Reverse(targets[*index].size));
+
+    for target in by_size {
+        let value = targets[target].value;
+        if let Some(attacker) = own.iter().position(|die| die.value >= value) {
+            return Some((vec![attacker as u8], target as u8));
+        }
+        // Smallest sets of dice first
+        let mut subsets = (1..1u32 << own.len()).collect::<Vec<u32>>();
+        subsets.sort_by_key(|subset| subset.count_ones());
+        for subset in subsets {
+            let attackers = (0..own.len()).filter(|index| subset & (1 << index) != 0).map(|index| index as u8).collect::<Vec<u8>>();
+            if attackers.iter().map(|index| own[*index as usize].value as u32).sum::<u32>() == value as u32 {
+                return Some((attackers, target as u8));
+            }
+        }
+    }
+    return None;
+}
+
+/// What an attack is worth to the current player, in half points like score()
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AttackEvaluation {
+    /// The captured die's size, counted double
+    pub points_gained: i32,
+    /// What the attacking dice give up by being re-rolled from their values to their average of (size + 1) / 2,
+    /// negative when they show less than that
+    pub reroll_cost: i32,
+    pub net_swing: i32,
+}
+
+pub fn evaluate_attack(game: &Game, option: &AttackOption) -> AttackEvaluation {
+    let own = &game.dice[game.current_player as usize];
+    let target = &game.dice[(game.current_player as usize + 1) % 2][option.defender_die_index as usize];
+    let points_gained = 2 * target.size as i32;
+    let reroll_cost = option.attacker_die_indices.iter()
+        .map(|index| 2 * own[*index as usize].value as i32 - own[*index as usize].size as i32 - 1)
+        .sum::<i32>();
+    AttackEvaluation { points_gained, reroll_cost, net_swing: points_gained - reroll_cost }
+}
+
+/// Net swing of evaluate_attack, what the bots go by
+pub fn attack_swing(game: &Game, option: &AttackOption) -> i32 {
+    evaluate_attack(game, option).net_swing
+}
+
+/// First of the items with the highest value, so ties go to the earlier one
+fn best_by<T, F: Fn(&T) -> i32>(items: Vec<T>, value: F) -> Option<(T, i32)> {
+    let mut best: Option<(T, i32)> = None;
+    for item in items {
+        let item_value = value(&item);
+        if best.as_ref().is_none_or(|(_, best_value)| item_value > *best_value) {
+            best = Some((item, item_value));
+        }
+    }
+    best
+}
+
+/// Position after the attack, with the attacking dice at their average instead of a roll, and the opponent to move
+pub fn after_attack(game: &Game, option: &AttackOption) -> Game {
+    let attacker = game.current_player as usize;
+    let mut dice = game.dice.clone();
+    for index in option.attacker_die_indices.iter() {
+        let die = &mut dice[attacker][*index as usize];
+        die.value = die.size.div_ceil(2);
+    }
+    dice[(attacker + 1) % 2].remove(option.defender_die_index as usize);
+    Game { dice, current_player: (game.current_player + 1) % 2, ..Default::default() }
+}
+
+/// Best of the options by attack_swing less the opponent's best reply, with that value
+pub fn lookahead(options: Vec<AttackOption>, game: &Game) -> Option<(AttackOption, i32)> {
+    let mut candidates = options.into_iter().map(|option| (attack_swing(game, &option), option)).collect::<Vec<(i32, AttackOption)>>();
+    // Stable, so equal swings keep their order
+    candidates.sort_by_key(|(swing, _)| std::cmp::Reverse(*swing));
+    candidates.truncate(LOOKAHEAD_CANDIDATES);
+    best_by(candidates, |(swing, option)| {
+        let reply = after_attack(game, option);
+        let best_reply = best_by(attack_options(&reply), |reply_option| attack_swing(&reply, reply_option));
+        swing - best_reply.map_or(0, |(_, reply_swing)| reply_swing)
+    }).map(|((_, option), value)| (option, value))
+}
+
+/// The bot's move for the current player, None to pass. Works on the game alone, Random picks with the rng,
+/// so the same position and rolls always give the same move.
+pub fn bot_move(game: &Game, difficulty: BotDifficulty, rng: &mut impl DiceRng) -> Option<(Vec<u8>, u8)> {
+    let options = attack_options(game);
+    if options.is_empty() {
+        return None;
+    }
+    let chosen = match difficulty {
+        BotDifficulty::Random => {
+            let index = rng.pick(options.len());
+            options.into_iter().nth(index)
+        },
+        BotDifficulty::Greedy => best_by(options, |option| attack_swing(game, option)).map(|(option, _)| option),
+        BotDifficulty::Lookahead => lookahead(options, game).map(|(option, _)| option),
+    }?;
+    Some((chosen.attacker_die_indices, chosen.defender_die_index))
+}
+
+/// Plays the game out with each player on their policy: attacks capture and re-roll with the rng, and the game ends
+/// once a player is out of dice, both pass in a row or max_moves are made. Returns the winner by the scores then,
+/// None for a draw.
+pub fn play_out(game: &Game, policies: [BotDifficulty; 2], rng: &mut impl DiceRng, max_moves: usize) -> Option<u8> {
+    let mut game = Game { players: game.players.clone(), dice: game.dice.clone(), captured: game.captured.clone(), current_player: game.current_player, ..Default::default() };
+    let mut passes = 0;
+    for _ in 0..max_moves {
+        if game.dice.iter().any(|dice| dice.is_empty()) || passes == 2 {
+            break;
+        }
+        let attacker = game.current_player as usize;
+        match bot_move(&game, policies[attacker], rng) {
+            Some((attackers, target)) => {
+                let defender = (attacker + 1) % 2;
+                let captured = game.dice[defender].remove(target as usize);
+                game.captured[attacker].push(captured.size);
+                for index in attackers {
+                    game.dice[attacker][index as usize] = roll_die(rng, game.dice[attacker][index as usize].size);
+                }
+                passes = 0;
+            },
+            None => passes += 1,
+        }
+        game.current_player = ((attacker + 1) % 2) as u8;
+    }
+    leader(&scores(&game))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::roll_dice;
+    use crate::Die;
+    use near_rng::Rng;
+
+    #[test]
+    fn lookahead_keeps_dice_out_of_reach() {
+        let game = Game {
+            current_player: 0,
+            dice: vec![
+                vec![Die { size: 4, value: 4 }, Die { size: 6, value: 4 }, Die { size: 8, value: 5 }],
+                vec![Die { size: 4, value: 3 }, Die { size: 10, value: 9 }, Die { size: 20, value: 3 }],
+            ],
+            ..Default::default()
+        };
+
+        // Taking the d20 with the d6 or the d8 is worth as much, but the d6 comes back at 3, in reach of the d4
+        assert_eq!(bot_move(&game, BotDifficulty::Greedy, &mut Rng::new(&vec![0; 32])), Some((vec![1], 2)));
+        assert_eq!(bot_move(&game, BotDifficulty::Lookahead, &mut Rng::new(&vec![0; 32])), Some((vec![2], 2)));
+        assert_eq!(bot_move(&game, BotDifficulty::Random, &mut Rng::new(&vec![1; 32])), bot_move(&game, BotDifficulty::Random, &mut Rng::new(&vec![1; 32])));
+    }
+
+    /// Plays the policies against each other from a position rolled from the index, returns the winning policy
+    fn bot_vs_bot(index: u8, policies: [BotDifficulty; 2]) -> Option<BotDifficulty> {
+        let mut rng = Rng::new(&vec![index; 32]);
+        let game = Game {
+            players: vec!["first.near".to_string(), "second.near".to_string()],
+            current_player: index % 2,
+            dice: vec![roll_dice(&mut rng, vec![4, 6, 8, 10, 20]), roll_dice(&mut rng, vec![4, 6, 8, 10, 20])],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        };
+        play_out(&game, policies, &mut rng, 200).map(|winner| policies[winner as usize])
+    }
+
+    #[test]
+    fn greedy_beats_random() {
+        let results = (0..20).map(|index| bot_vs_bot(index, [BotDifficulty::Greedy, BotDifficulty::Random])).collect::<Vec<_>>();
+        let greedy_wins = results.iter().filter(|winner| **winner == Some(BotDifficulty::Greedy)).count();
+        let random_wins = results.iter().filter(|winner| **winner == Some(BotDifficulty::Random)).count();
+        assert!(greedy_wins > random_wins, "{} to {}", greedy_wins, random_wins);
+    }
+}
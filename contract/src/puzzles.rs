@@ -0,0 +1,245 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::*;
+
+/// A position to clear: capture every one of the opponent's dice in as many of your own attacks as the solution takes.
+/// The opponent doesn't reply and re-rolled dice come back at their average of (size + 1) / 2, so a solution
+/// always plays out the same way.
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Puzzle {
+    pub id: String,
+    pub description: String,
+    /// Solver's dice, then the opponent's
+    pub position: Vec<Vec<Die>>,
+    /// Kept out of views, it would give the puzzle away
+    #[serde(skip)]
+    pub solution: Vec<AttackOption>,
+    /// Accounts that solved it
+    pub solvers: u32,
+    pub first_solver: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PuzzleSolve {
+    pub first_solved_at: u64,
+    /// Correct solutions the account submitted
+    pub solves: u32,
+}
+
+fn puzzle_solve_key(puzzle_id: &str, account_id: &str) -> String {
+    format!("{}:{}", puzzle_id, account_id)
+}
+
+/// Whether the moves capture all of the opponent's dice, each one a legal attack in the position the previous ones left
+fn clears_position(position: &[Vec<Die>], moves: &[AttackOption]) -> bool {
+    let mut game = Game { dice: position.to_vec(), current_player: 0, ..Default::default() };
+    for option in moves.iter() {
+        if game.dice[1].is_empty() || !attack_options(&game).iter().any(|legal| legal.attacker_die_indices == option.attacker_die_indices
+            && legal.defender_die_index == option.defender_die_index
+            && legal.attack_type == option.attack_type) {
+            return false;
+        }
+        game = Game { current_player: 0, ..policies::after_attack(&game, option) };
+    }
+    game.dice[1].is_empty()
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn add_puzzle(&mut self, position: Vec<Vec<Die>>, solution: Vec<AttackOption>, description: String) -> String {
+        self.assert_owner("add puzzles");
+        if position.len() != PLAYER_COUNT || position.iter().any(|dice| dice.is_empty()) {
+            fail(Error::EmptyPuzzle);
+        }
+        if position.iter().any(|dice| dice.len() > buttons::MAX_BUTTON_DICE) {
+            fail(Error::TooManyDice { max: buttons::MAX_BUTTON_DICE });
+        }
+        position.iter().flatten().for_each(|die| assert_valid_die_size(die.size));
+        if position.iter().flatten().any(|die| die.value < 1 || die.value > die.size) {
+            fail(Error::InvalidDieValue);
+        }
+        if !clears_position(&position, &solution) {
+            fail(Error::InvalidSolution);
+        }
+
+        self.last_puzzle_id += 1;
+        let puzzle_id = self.last_puzzle_id.to_string();
+        self.puzzles.insert(&puzzle_id, &Puzzle {
+            id: puzzle_id.clone(),
+            description,
+            position,
+            solution,
+            solvers: 0,
+            first_solver: None,
+        });
+        return puzzle_id;
+    }
+
+    pub fn get_puzzle(&self, puzzle_id: String) -> Option<Puzzle> {
+        self.puzzles.get(&puzzle_id)
+    }
+
+    /// Oldest first
+    pub fn list_puzzles(&self) -> Vec<Puzzle> {
+        (1..=self.last_puzzle_id).filter_map(|id| self.puzzles.get(&id.to_string())).collect()
+    }
+
+    /// Checks the caller's moves and, when they clear the puzzle in no more moves than its solution, counts the solve.
+    /// Wrong solutions aren't recorded.
+    pub fn solve_puzzle(&mut self, puzzle_id: String, moves: Vec<AttackOption>) -> bool {
+        self.assert_not_paused();
+
+        let mut puzzle = self.puzzles.get(&puzzle_id).unwrap_or_else(|| fail(Error::PuzzleNotFound { puzzle_id: puzzle_id.to_string() }));
+        if moves.len() > puzzle.solution.len() || !clears_position(&puzzle.position, &moves) {
+            return false;
+        }
+
+        let account_id = env::predecessor_account_id().to_string();
+        let key = puzzle_solve_key(&puzzle_id, &account_id);
+        let solve = match self.puzzle_solves.get(&key) {
+            Some(solve) => PuzzleSolve { solves: solve.solves + 1, ..solve },
+            None => {
+                puzzle.solvers += 1;
+                puzzle.first_solver.get_or_insert(account_id.clone());
+                self.puzzles.insert(&puzzle_id, &puzzle);
+                PuzzleSolve { first_solved_at: env::block_timestamp(), solves: 1 }
+            },
+        };
+        self.puzzle_solves.insert(&key, &solve);
+        self.check_puzzle_achievements(&account_id, &puzzle);
+
+        emit_event("puzzle_solved", serde_json::json!({
+            "puzzle_id": puzzle_id,
+            "account_id": account_id,
+        }));
+        return true;
+    }
+
+    pub fn get_puzzle_solve(&self, puzzle_id: String, account_id: AccountId) -> Option<PuzzleSolve> {
+        self.puzzle_solves.get(&puzzle_solve_key(&puzzle_id, account_id.as_str()))
+    }
+}
+
+impl Contract {
+    /// Forgets the account's solves, and credits its first solves to replacement
+    pub(crate) fn remove_puzzle_solves(&mut self, account_id: &str, replacement: &str) {
+        for id in 1..=self.last_puzzle_id {
+            let puzzle_id = id.to_string();
+            self.puzzle_solves.remove(&puzzle_solve_key(&puzzle_id, account_id));
+            if let Some(mut puzzle) = self.puzzles.get(&puzzle_id) {
+                if puzzle.first_solver.as_deref() == Some(account_id) {
+                    puzzle.first_solver = Some(replacement.to_string());
+                    self.puzzles.insert(&puzzle_id, &puzzle);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{login_as, request_path};
+
+    fn attack(attacker_die_indices: Vec<u8>, defender_die_index: u8, attack_type: AttackType) -> AttackOption {
+        AttackOption { attacker_die_indices, defender_die_index, attack_type, evaluation: None }
+    }
+
+    /// A d6 showing 2 and a d8 showing 3 against a d12 showing 5: neither die takes it alone, together they do
+    fn add_skill_puzzle(contract: &mut Contract) -> String {
+        login_as("alice.near");
+        contract.add_puzzle(
+            vec![vec![Die { size: 6, value: 2 }, Die { size: 8, value: 3 }], vec![Die { size: 12, value: 5 }]],
+            vec![attack(vec![0, 1], 0, AttackType::Skill)],
+            "Two small dice, one big target".to_string())
+    }
+
+    #[test]
+    fn one_move_puzzle() {
+        let mut contract = Contract::default();
+        let puzzle_id = add_skill_puzzle(&mut contract);
+
+        login_as("bob.near");
+        assert!(!contract.solve_puzzle(puzzle_id.clone(), vec![attack(vec![1], 0, AttackType::Power)]));
+        assert!(!contract.solve_puzzle(puzzle_id.clone(), vec![]));
+        assert_eq!(contract.get_puzzle_solve(puzzle_id.clone(), "bob.near".parse().unwrap()), None);
+
+        assert!(contract.solve_puzzle(puzzle_id.clone(), vec![attack(vec![0, 1], 0, AttackType::Skill)]));
+        assert!(contract.solve_puzzle(puzzle_id.clone(), vec![attack(vec![0, 1], 0, AttackType::Skill)]));
+        assert_eq!(contract.get_puzzle_solve(puzzle_id.clone(), "bob.near".parse().unwrap()), Some(PuzzleSolve { first_solved_at: 0, solves: 2 }));
+        assert_eq!(contract.get_achievements("bob.near".parse().unwrap()).into_iter().map(|achievement| achievement.id).collect::<Vec<String>>(), vec!["puzzle_solver"]);
+
+        login_as("carol.near");
+        assert!(contract.solve_puzzle(puzzle_id.clone(), vec![attack(vec![0, 1], 0, AttackType::Skill)]));
+        let puzzle = contract.get_puzzle(puzzle_id).unwrap();
+        assert_eq!((puzzle.solvers, puzzle.first_solver), (2, Some("bob.near".to_string())));
+    }
+
+    #[test]
+    fn solution_not_exposed() {
+        let mut contract = Contract::default();
+        add_skill_puzzle(&mut contract);
+
+        match contract.web4_get(request_path("/api/puzzles")) {
+            Web4Response::Body { body, .. } => {
+                let body = String::from_utf8(body.into()).unwrap();
+                assert!(body.contains("Two small dice, one big target"));
+                assert!(!body.contains("solution") && !body.contains("attack_type"));
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    fn two_move_puzzle() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        // The d20 takes the d12 and comes back showing 10, enough for the d10 showing 9
+        let puzzle_id = contract.add_puzzle(
+            vec![vec![Die { size: 20, value: 12 }], vec![Die { size: 10, value: 9 }, Die { size: 12, value: 11 }]],
+            vec![attack(vec![0], 1, AttackType::Power), attack(vec![0], 0, AttackType::Power)],
+            "Big die first".to_string());
+
+        login_as("bob.near");
+        // The other way around the d20 comes back showing 10, short of the d12 showing 11
+        assert!(!contract.solve_puzzle(puzzle_id.clone(), vec![attack(vec![0], 0, AttackType::Power), attack(vec![0], 0, AttackType::Power)]));
+        assert!(contract.solve_puzzle(puzzle_id, vec![attack(vec![0], 1, AttackType::Power), attack(vec![0], 0, AttackType::Power)]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Solution doesn't capture every die")]
+    fn wrong_solution_rejected() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.add_puzzle(
+            vec![vec![Die { size: 6, value: 2 }], vec![Die { size: 12, value: 5 }]],
+            vec![attack(vec![0], 0, AttackType::Power)],
+            "Impossible".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid die size: d0")]
+    fn zero_size_die_rejected() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.add_puzzle(
+            vec![vec![Die { size: 6, value: 6 }], vec![Die { size: 0, value: 0 }]],
+            vec![attack(vec![0], 0, AttackType::Power)],
+            "Broken".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can add puzzles")]
+    fn add_puzzle_not_owner() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.add_puzzle(
+            vec![vec![Die { size: 6, value: 6 }], vec![Die { size: 12, value: 5 }]],
+            vec![attack(vec![0], 0, AttackType::Power)],
+            "Easy".to_string());
+    }
+}
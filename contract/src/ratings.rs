@@ -0,0 +1,311 @@
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::*;
+use seasons::SeasonRating;
+
+pub const DEFAULT_RATING: u32 = 1500;
+
+/// Rating change of player A after a game against B, `score` being 1.0 for A's win, 0.5 for a draw and 0.0 for a loss.
+/// B's change is the same with the opposite sign.
+fn elo_delta(rating_a: u32, rating_b: u32, score: f64, k_factor: u32) -> i32 {
+    let expected = 1.0 / (1.0 + 10f64.powf((rating_b as f64 - rating_a as f64) / 400.0));
+    (k_factor as f64 * (score - expected)).round() as i32
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_rating(&self, account_id: AccountId) -> u32 {
+        self.rating_of(account_id.as_str())
+    }
+
+    pub fn set_elo_k_factor(&mut self, k_factor: u32) -> () {
+        self.assert_owner("set the K-factor");
+
+        self.config.elo_k_factor = k_factor;
+    }
+
+    /// Makes resigning cost more rating than losing the game would, e.g. 150 for half as much again
+    pub fn set_forfeit_penalty_multiplier(&mut self, multiplier: u32) -> () {
+        self.assert_owner("set the forfeit penalty");
+        if multiplier < 100 {
+            fail(Error::ForfeitPenaltyTooLow);
+        }
+
+        self.config.forfeit_penalty_multiplier = multiplier;
+    }
+
+    pub fn set_rating_decay(&mut self, after_ns: u64, per_week: u32, floor: u32) -> () {
+        self.assert_owner("set the rating decay");
+
+        self.config.rating_decay_after_ns = after_ns;
+        self.config.rating_decay_per_week = per_week;
+        self.config.rating_decay_floor = floor;
+    }
+
+    /// Rating in the current season, carried over from the account's last season if it hasn't played in this one,
+    /// less the decay for the time since its last rated game
+    pub(crate) fn rating_of(&self, account_id: &str) -> u32 {
+        let rating = match self.ratings.get(&account_id.to_string()) {
+            Some(entry) if entry.season_id == self.current_season_id() => entry.rating,
+            Some(entry) => self.carried_over_rating(entry.rating),
+            None => DEFAULT_RATING,
+        };
+        self.decayed(account_id, rating)
+    }
+
+    /// Worked out on every read rather than stored, the decayed rating is only persisted by the account's next
+    /// rated game, which restarts the clock so the same weeks never count twice
+    fn decayed(&self, account_id: &str, rating: u32) -> u32 {
+        let floor = self.config.rating_decay_floor;
+        let inactive = match self.last_ranked_game_at.get(&account_id.to_string()) {
+            Some(last_ranked_game_at) => env::block_timestamp().saturating_sub(last_ranked_game_at),
+            None => return rating,
+        };
+        if rating <= floor || inactive <= self.config.rating_decay_after_ns {
+            return rating;
+        }
+        let weeks = (inactive - self.config.rating_decay_after_ns) / (7 * DAY_NS);
+        let decay = weeks.saturating_mul(self.config.rating_decay_per_week as u64).min(u32::MAX as u64) as u32;
+        rating.saturating_sub(decay).max(floor)
+    }
+
+    /// Moves both players' ratings by the game's result and records the change and the season on the game.
+    /// A game without a winner is a draw. Forfeiting, by resigning or by letting the clock run out, costs the loser
+    /// ContractConfig.forfeit_penalty_multiplier percent of a normal loss, while the winner gains as much as for any win.
+    pub(crate) fn update_ratings(&mut self, game: &mut Game) {
+        let ratings = game.players.iter().map(|player_id| self.rating_of(player_id)).collect::<Vec<u32>>();
+        let score = match game.winner {
+            Some(0) => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        };
+        let delta = elo_delta(ratings[0], ratings[1], score, self.config.elo_k_factor);
+
+        let mut deltas = vec![delta, -delta];
+        if let (Some(winner), Some(FinishReason::Resignation | FinishReason::Timeout)) = (game.winner, game.finish_reason) {
+            let loser = (winner as usize + 1) % 2;
+            deltas[loser] = deltas[loser] * self.config.forfeit_penalty_multiplier as i32 / 100;
+        }
+
+        let season_id = self.current_season_id();
+        game.rating_deltas = deltas;
+        game.season_id = season_id;
+        for (player_index, player_id) in game.players.iter().enumerate() {
+            if let Some(entry) = self.ratings.get(player_id) {
+                if entry.season_id != season_id {
+                    self.archive_season_rating(player_id, entry);
+                }
+            }
+            let rating = ratings[player_index].saturating_add_signed(game.rating_deltas[player_index]);
+            self.ratings.insert(player_id, &SeasonRating { season_id, rating });
+            self.last_ranked_game_at.insert(player_id, &env::block_timestamp());
+            self.update_leaderboard(player_id, rating);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{login_as, request_path};
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    fn play(contract: &mut Contract, loser: &str, winner: &str) -> String {
+        login_as(winner);
+        let game_id = contract.create_game(None);
+        login_as(loser);
+        contract.join_game(game_id.clone(), None);
+        contract.resign(game_id.clone());
+        return game_id;
+    }
+
+    #[test]
+    fn elo_math() {
+        assert_eq!(elo_delta(1500, 1500, 1.0, 32), 16);
+        assert_eq!(elo_delta(1500, 1500, 0.5, 32), 0);
+        assert_eq!(elo_delta(1600, 1400, 1.0, 32), 8);
+        assert_eq!(elo_delta(1400, 1600, 1.0, 32), 24);
+        assert_eq!(elo_delta(1400, 1600, 0.5, 32), 8);
+        assert_eq!(elo_delta(1400, 1600, 1.0, 16), 12);
+    }
+
+    #[test]
+    fn ratings_after_games() {
+        let mut contract = Contract::default();
+        assert_eq!(contract.get_rating("bob.near".parse().unwrap()), 1500);
+
+        let game_id = play(&mut contract, "alice.near", "bob.near");
+        assert_eq!(contract.get_rating("bob.near".parse().unwrap()), 1516);
+        assert_eq!(contract.get_rating("alice.near".parse().unwrap()), 1484);
+        assert_eq!(contract.games.get(&game_id).unwrap().rating_deltas, vec![16, -16]);
+
+        let game_id = play(&mut contract, "bob.near", "alice.near");
+        // alice.near was the underdog, so she gains more than 16
+        assert_eq!(contract.games.get(&game_id).unwrap().rating_deltas, vec![17, -17]);
+        assert_eq!(contract.get_rating("alice.near".parse().unwrap()), 1501);
+        assert_eq!(contract.get_rating("bob.near".parse().unwrap()), 1499);
+    }
+
+    #[test]
+    fn ratings_after_draw() {
+        let mut contract = Contract::default();
+        contract.ratings.insert(&"bob.near".to_string(), &SeasonRating { season_id: None, rating: 1400 });
+        contract.ratings.insert(&"alice.near".to_string(), &SeasonRating { season_id: None, rating: 1600 });
+        let mut game = Game {
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            status: GameStatus::Finished,
+            winner: None,
+            ..Default::default()
+        };
+
+        contract.update_ratings(&mut game);
+        assert_eq!(game.rating_deltas, vec![8, -8]);
+        assert_eq!(contract.get_rating("bob.near".parse().unwrap()), 1408);
+        assert_eq!(contract.get_rating("alice.near".parse().unwrap()), 1592);
+    }
+
+    #[test]
+    fn k_factor_from_config() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_elo_k_factor(16);
+
+        play(&mut contract, "alice.near", "bob.near");
+        assert_eq!(contract.get_rating("bob.near".parse().unwrap()), 1508);
+    }
+
+    #[test]
+    fn forfeit_costs_more_than_a_loss() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_forfeit_penalty_multiplier(150);
+
+        let mut lost = Game {
+            players: vec!["bob.near".to_string(), "carol.near".to_string()],
+            status: GameStatus::Finished,
+            winner: Some(0),
+            finish_reason: Some(FinishReason::CaptureOut),
+            ..Default::default()
+        };
+        contract.update_ratings(&mut lost);
+        assert_eq!(lost.rating_deltas, vec![16, -16]);
+
+        let game_id = play(&mut contract, "erin.near", "dave.near");
+        // dave.near gains as much as bob.near, erin.near loses half as much again as carol.near
+        assert_eq!(contract.games.get(&game_id).unwrap().rating_deltas, vec![16, -24]);
+        assert_eq!(contract.get_rating("erin.near".parse().unwrap()), 1476);
+        assert_eq!(contract.get_rating("carol.near".parse().unwrap()), 1484);
+
+        let mut timed_out = Game {
+            players: vec!["frank.near".to_string(), "grace.near".to_string()],
+            status: GameStatus::Finished,
+            winner: Some(0),
+            finish_reason: Some(FinishReason::Timeout),
+            ..Default::default()
+        };
+        contract.update_ratings(&mut timed_out);
+        assert_eq!(timed_out.rating_deltas, vec![16, -24]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Forfeit penalty can't be smaller than a normal loss")]
+    fn forfeit_penalty_below_loss() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_forfeit_penalty_multiplier(50);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can set the K-factor")]
+    fn k_factor_not_owner() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.set_elo_k_factor(16);
+    }
+
+    fn rating_at(contract: &Contract, account_id: &str, timestamp: u64) -> u32 {
+        testing_env!(VMContextBuilder::new().block_timestamp(timestamp).build());
+        contract.get_rating(account_id.parse().unwrap())
+    }
+
+    const WEEK_NS: u64 = 7 * DAY_NS;
+
+    #[test]
+    fn decay_with_inactivity() {
+        let mut contract = Contract::default();
+        play(&mut contract, "alice.near", "bob.near");
+
+        for (inactive, rating) in [(30 * DAY_NS, 1516), (30 * DAY_NS + WEEK_NS - 1, 1516), (30 * DAY_NS + WEEK_NS, 1506),
+            (30 * DAY_NS + 5 * WEEK_NS, 1466), (30 * DAY_NS + 20 * WEEK_NS, 1400)] {
+            assert_eq!(rating_at(&contract, "bob.near", inactive), rating, "{}", inactive);
+        }
+        // 1484 stops at the floor too, while ratings below it and accounts that never played don't decay
+        assert_eq!(rating_at(&contract, "alice.near", 30 * DAY_NS + 10 * WEEK_NS), 1400);
+        contract.ratings.insert(&"alice.near".to_string(), &SeasonRating { season_id: None, rating: 1380 });
+        assert_eq!(rating_at(&contract, "alice.near", 30 * DAY_NS + 10 * WEEK_NS), 1380);
+        assert_eq!(rating_at(&contract, "carol.near", 30 * DAY_NS + 10 * WEEK_NS), DEFAULT_RATING);
+    }
+
+    #[test]
+    fn decay_persisted_once() {
+        let mut contract = Contract::default();
+        play(&mut contract, "alice.near", "bob.near");
+
+        // bob.near comes back at 1466 and plays from there
+        let comeback = 30 * DAY_NS + 5 * WEEK_NS;
+        let mut game = Game {
+            players: vec!["bob.near".to_string(), "carol.near".to_string()],
+            status: GameStatus::Finished,
+            winner: None,
+            ..Default::default()
+        };
+        testing_env!(VMContextBuilder::new().block_timestamp(comeback).build());
+        contract.update_ratings(&mut game);
+        assert_eq!(game.rating_deltas, vec![2, -2]);
+        assert_eq!(contract.ratings.get(&"bob.near".to_string()).unwrap().rating, 1468);
+        assert_eq!(rating_at(&contract, "bob.near", comeback), 1468);
+        assert_eq!(rating_at(&contract, "bob.near", comeback + 30 * DAY_NS + WEEK_NS - 1), 1468);
+        assert_eq!(rating_at(&contract, "bob.near", comeback + 30 * DAY_NS + WEEK_NS), 1458);
+    }
+
+    #[test]
+    fn leaderboard_entry_decays_when_touched() {
+        let mut contract = Contract::default();
+        play(&mut contract, "alice.near", "bob.near");
+        assert_eq!(contract.get_leaderboard(0, 1)[0].rating, 1516);
+
+        let mut game = Game {
+            players: vec!["carol.near".to_string(), "dave.near".to_string()],
+            status: GameStatus::Finished,
+            winner: Some(0),
+            ..Default::default()
+        };
+        testing_env!(VMContextBuilder::new().block_timestamp(30 * DAY_NS + 2 * WEEK_NS).build());
+        contract.update_ratings(&mut game);
+        let ranking = contract.get_leaderboard(0, 10).into_iter().map(|entry| (entry.account_id, entry.rating)).collect::<Vec<(String, u32)>>();
+        assert_eq!(ranking, vec![
+            ("carol.near".to_string(), 1516),
+            ("bob.near".to_string(), 1496),
+            ("dave.near".to_string(), 1484),
+            ("alice.near".to_string(), 1464),
+        ]);
+    }
+
+    #[test]
+    fn web4_get_lobby_ratings() {
+        let mut contract = Contract::default();
+        contract.ratings.insert(&"bob.near".to_string(), &SeasonRating { season_id: None, rating: 1620 });
+        login_as("bob.near");
+        contract.create_game(None);
+
+        match contract.web4_get(request_path("/api/games")) {
+            Web4Response::Body { body, .. } => {
+                let games: serde_json::Value = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert_eq!(games[0]["id"], "1");
+                assert_eq!(games[0]["ratings"], serde_json::json!([1620, 1500]));
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, AccountId};
+
+use crate::*;
+
+/// How a finished game ended
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum FinishReason {
+    /// One player ran out of dice, the higher score wins
+    CaptureOut,
+    Resignation,
+    /// The loser's clock ran out, see Contract::claim_timeout_win
+    Timeout,
+    /// A clock ran out and nobody ended the game for ContractConfig.adjudicate_after_ns, it was scored as it stood
+    Adjudicated,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ResultCounts {
+    pub capture_out: u32,
+    pub resignation: u32,
+    #[serde(default)]
+    pub timeout: u32,
+    #[serde(default)]
+    pub adjudicated: u32,
+}
+
+impl ResultCounts {
+    fn add(&mut self, reason: FinishReason) {
+        match reason {
+            FinishReason::CaptureOut => self.capture_out += 1,
+            FinishReason::Resignation => self.resignation += 1,
+            FinishReason::Timeout => self.timeout += 1,
+            FinishReason::Adjudicated => self.adjudicated += 1,
+        }
+    }
+}
+
+/// An account's finished games, wins and losses split by how the game ended
+#[derive(Debug, Default, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PlayerRecord {
+    pub wins: ResultCounts,
+    pub losses: ResultCounts,
+    pub draws: u32,
+    /// Attacks and passes the account made in its finished games, and the time they took in all
+    #[serde(default)]
+    pub timed_moves: u32,
+    #[serde(default)]
+    pub move_time_ns: u64,
+}
+
+impl PlayerRecord {
+    /// Time the account takes over a move on average, None before it finished a game with a move in it
+    pub fn average_move_ns(&self) -> Option<u64> {
+        self.move_time_ns.checked_div(self.timed_moves as u64)
+    }
+
+    pub fn games_played(&self) -> u32 {
+        self.wins.capture_out + self.wins.resignation + self.wins.timeout + self.wins.adjudicated
+            + self.losses.capture_out + self.losses.resignation + self.losses.timeout + self.losses.adjudicated + self.draws
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_record(&self, account_id: AccountId) -> PlayerRecord {
+        self.records.get(&account_id.to_string()).unwrap_or_default()
+    }
+
+    /// Average time the account took over its attacks and passes in finished games, see PlayerRecord::average_move_ns
+    pub fn get_average_move_time(&self, account_id: AccountId) -> Option<u64> {
+        self.get_record(account_id).average_move_ns()
+    }
+
+    pub(crate) fn update_records(&mut self, game: &Game) {
+        let reason = match game.finish_reason {
+            Some(reason) => reason,
+            None => return,
+        };
+        for (player_index, player_id) in game.players.iter().enumerate() {
+            let mut record = self.records.get(player_id).unwrap_or_default();
+            match game.winner {
+                Some(winner) if winner as usize == player_index => record.wins.add(reason),
+                Some(_) => record.losses.add(reason),
+                None => record.draws += 1,
+            }
+            for move_record in game.history.iter().filter(|move_record| move_record.player as usize == player_index && !move_record.auto
+                && matches!(move_record.kind, MoveKind::Attack | MoveKind::Pass)) {
+                record.timed_moves += 1;
+                record.move_time_ns += move_record.elapsed_ns;
+            }
+            self.records.insert(player_id, &record);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::login_as;
+
+    fn insert_game(contract: &mut Contract, defender_dice: Vec<Die>) {
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 19 }], defender_dice],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn record_capture_out() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract, vec![Die { size: 4, value: 1 }]);
+        login_as("bob.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().finish_reason, Some(FinishReason::CaptureOut));
+        assert_eq!(contract.get_record("bob.near".parse().unwrap()).wins, ResultCounts { capture_out: 1, resignation: 0, timeout: 0, adjudicated: 0 });
+        assert_eq!(contract.get_record("alice.near".parse().unwrap()).losses, ResultCounts { capture_out: 1, resignation: 0, timeout: 0, adjudicated: 0 });
+    }
+
+    #[test]
+    fn record_resignation() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract, vec![Die { size: 4, value: 1 }]);
+        login_as("bob.near");
+        contract.resign("1".to_string());
+
+        assert_eq!(contract.get_record("alice.near".parse().unwrap()), PlayerRecord {
+            wins: ResultCounts { capture_out: 0, resignation: 1, timeout: 0, adjudicated: 0 },
+            ..Default::default()
+        });
+        assert_eq!(contract.get_record("bob.near".parse().unwrap()), PlayerRecord {
+            losses: ResultCounts { capture_out: 0, resignation: 1, timeout: 0, adjudicated: 0 },
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn record_draw() {
+        let mut contract = Contract::default();
+        // bob.near's captured d8 and remaining d20 score as much as alice.near's captured d10 and d8
+        let mut game = Game {
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 1 }], vec![]],
+            captured: vec![vec![8], vec![10, 8]],
+            ..Default::default()
+        };
+        finish_game(&mut game, None, FinishReason::CaptureOut);
+        assert_eq!(game.winner, None);
+        contract.update_records(&game);
+
+        assert_eq!(contract.get_record("bob.near".parse().unwrap()), PlayerRecord { draws: 1, ..Default::default() });
+        assert_eq!(contract.get_record("alice.near".parse().unwrap()), PlayerRecord { draws: 1, ..Default::default() });
+    }
+}
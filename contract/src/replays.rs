@@ -0,0 +1,194 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+/// Replays kept in the hall of fame, the least voted drop off beyond this
+const MAX_TOP_REPLAYS: usize = 50;
+
+/// Finished game put up for the hall of fame, with its vote tally
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Replay {
+    pub game_id: String,
+    pub votes: u32,
+    pub nominated_at: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Puts a finished game up for votes, only its players can
+    pub fn nominate_replay(&mut self, game_id: String) -> () {
+        let account_id = env::predecessor_account_id().to_string();
+        let game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        if !game.is_finished() {
+            fail(Error::GameNotFinished { game_id: game_id.to_string() });
+        }
+        if !game.players.contains(&account_id) {
+            fail(Error::NotJoined { player_id: account_id.to_string(), game_id: game_id.to_string() });
+        }
+        if self.replays.contains_key(&game_id) {
+            fail(Error::AlreadyNominated { game_id: game_id.to_string() });
+        }
+
+        let replay = Replay { game_id: game_id.clone(), votes: 0, nominated_at: env::block_timestamp() };
+        self.replays.insert(&game_id, &replay);
+        self.update_top_replays(replay);
+        emit_event("replay_nominated", serde_json::json!({
+            "game_id": game_id,
+            "account_id": account_id,
+        }));
+    }
+
+    /// One vote per nominated game, open to accounts that finished a game themselves
+    pub fn vote_replay(&mut self, game_id: String) -> () {
+        let account_id = env::predecessor_account_id();
+        let mut replay = self.replays.get(&game_id).unwrap_or_else(|| fail(Error::NotNominated { game_id: game_id.to_string() }));
+        if !self.is_known_player(account_id.clone()) {
+            fail(Error::NoFinishedGame);
+        }
+
+        let mut voted = self.replay_votes.get(&account_id.to_string()).unwrap_or_default();
+        if voted.contains(&game_id) {
+            fail(Error::AlreadyVoted { account_id: account_id.to_string(), game_id: game_id.to_string() });
+        }
+        voted.push(game_id.clone());
+        self.replay_votes.insert(&account_id.to_string(), &voted);
+
+        replay.votes += 1;
+        self.replays.insert(&game_id, &replay);
+        self.update_top_replays(replay);
+    }
+
+    pub fn get_replay(&self, game_id: String) -> Option<Replay> {
+        self.replays.get(&game_id)
+    }
+
+    /// Most voted replays, best first
+    pub fn get_top_replays(&self, limit: u64) -> Vec<Replay> {
+        self.top_replays.iter().take(limit as usize).cloned().collect()
+    }
+
+    /// Same rules as update_leaderboard: replays not on the list get in when there's room or they beat the lowest
+    fn update_top_replays(&mut self, replay: Replay) {
+        match self.top_replays.iter().position(|entry| entry.game_id == replay.game_id) {
+            Some(index) => self.top_replays[index] = replay,
+            None => {
+                if self.top_replays.len() >= MAX_TOP_REPLAYS && self.top_replays.last().is_none_or(|lowest| replay.votes <= lowest.votes) {
+                    return;
+                }
+                self.top_replays.push(replay);
+            },
+        }
+        // Stable, so a replay that got to a tally first stays ahead
+        self.top_replays.sort_by_key(|entry| std::cmp::Reverse(entry.votes));
+        self.top_replays.truncate(MAX_TOP_REPLAYS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{login_as, request_path};
+
+    fn finished_game(contract: &mut Contract, players: [&str; 2]) -> String {
+        contract.last_game_id += 1;
+        let game_id = contract.last_game_id.to_string();
+        contract.games.insert(&game_id, &Game {
+            id: game_id.clone(),
+            players: players.iter().map(|player_id| player_id.to_string()).collect(),
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 19 }], vec![Die { size: 4, value: 1 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+        login_as(players[1]);
+        contract.resign(game_id.clone());
+        return game_id;
+    }
+
+    fn nominated(contract: &mut Contract) -> String {
+        let game_id = finished_game(contract, ["bob.near", "alice.near"]);
+        login_as("bob.near");
+        contract.nominate_replay(game_id.clone());
+        return game_id;
+    }
+
+    fn vote(contract: &mut Contract, account_id: &str, game_id: &str) {
+        login_as(account_id);
+        contract.vote_replay(game_id.to_string());
+    }
+
+    fn top(contract: &Contract) -> Vec<(String, u32)> {
+        contract.get_top_replays(10).into_iter().map(|replay| (replay.game_id, replay.votes)).collect()
+    }
+
+    #[test]
+    fn top_replays_by_votes() {
+        let mut contract = Contract::default();
+        let first = nominated(&mut contract);
+        let second = nominated(&mut contract);
+        let third = nominated(&mut contract);
+        finished_game(&mut contract, ["carol.near", "dave.near"]);
+
+        vote(&mut contract, "carol.near", &second);
+        vote(&mut contract, "dave.near", &second);
+        vote(&mut contract, "carol.near", &third);
+        assert_eq!(top(&contract), vec![(second.clone(), 2), (third.clone(), 1), (first.clone(), 0)]);
+
+        // Catching up doesn't overtake
+        vote(&mut contract, "dave.near", &third);
+        assert_eq!(top(&contract), vec![(second.clone(), 2), (third.clone(), 2), (first, 0)]);
+        assert_eq!(contract.get_top_replays(1).len(), 1);
+
+        match contract.web4_get(request_path("/api/replays/top")) {
+            Web4Response::Body { body, .. } => {
+                let replays: Vec<Replay> = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert_eq!(replays, contract.get_top_replays(10));
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "carol.near already voted for game 1")]
+    fn vote_twice() {
+        let mut contract = Contract::default();
+        let game_id = nominated(&mut contract);
+        finished_game(&mut contract, ["carol.near", "dave.near"]);
+
+        vote(&mut contract, "carol.near", &game_id);
+        vote(&mut contract, "carol.near", &game_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Finish a game before voting")]
+    fn vote_without_finished_game() {
+        let mut contract = Contract::default();
+        let game_id = nominated(&mut contract);
+
+        vote(&mut contract, "carol.near", &game_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Player carol.near has not joined game 1")]
+    fn nominate_not_player() {
+        let mut contract = Contract::default();
+        let game_id = finished_game(&mut contract, ["bob.near", "alice.near"]);
+
+        login_as("carol.near");
+        contract.nominate_replay(game_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is not finished")]
+    fn nominate_unfinished() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        let game_id = contract.create_game(None);
+
+        contract.nominate_replay(game_id);
+    }
+}
@@ -0,0 +1,139 @@
+use near_sdk::{env, ext_contract, near_bindgen, Gas, PromiseError};
+
+use crate::*;
+
+const GAS_FOR_GAME_RESULT: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_RESULT_REPORTED: Gas = Gas(5_000_000_000_000);
+
+/// Interface a tournament contract implements to receive results of the games it created.
+#[ext_contract(ext_result_receiver)]
+#[allow(dead_code)]
+trait GameResultReceiver {
+    /// Scores are doubled, see score
+    fn on_game_result(&mut self, game_id: String, players: Vec<String>, winner: Option<u8>, scores: Vec<u32>);
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sends the result of a finished game to its report_to account, if it has one.
+    pub(crate) fn report_finished_game(&self, game: &Game) {
+        if let Some(report_to) = &game.report_to {
+            let scores = (0..game.players.len()).map(|player_index| score(game, player_index)).collect::<Vec<u32>>();
+            ext_result_receiver::ext(report_to.clone())
+                .with_static_gas(GAS_FOR_GAME_RESULT)
+                .on_game_result(game.id.clone(), game.players.clone(), game.winner, scores)
+                .then(Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESULT_REPORTED)
+                    .on_result_reported(game.id.clone()));
+        }
+    }
+
+    #[private]
+    pub fn on_result_reported(&mut self, game_id: String, #[callback_result] result: Result<(), PromiseError>) -> bool {
+        let mut game = match self.games.get(&game_id) {
+            Some(game) => game,
+            None => return false,
+        };
+
+        let delivered = result.is_ok();
+        game.result_reported = Some(delivered);
+        self.save_game(&game);
+        return delivered;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::login_as;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::get_created_receipts;
+
+    fn tournament_options() -> Option<CreateGameOptions> {
+        Some(CreateGameOptions {
+            report_to: Some("tournament.near".parse().unwrap()),
+            ..Default::default()
+        })
+    }
+
+    fn started_tournament_game(contract: &mut Contract) -> String {
+        login_as("bob.near");
+        let game_id = contract.create_game(tournament_options());
+        login_as("alice.near");
+        contract.join_game(game_id.clone(), None);
+
+        let mut game = contract.games.get(&game_id).unwrap();
+        game.current_player = 1;
+        game.dice = vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 6, value: 3 }]];
+        contract.games.insert(&game_id, &game);
+        return game_id;
+    }
+
+    #[test]
+    fn report_result_on_finish() {
+        let mut contract = Contract::default();
+        let game_id = started_tournament_game(&mut contract);
+
+        contract.attack(game_id.clone(), vec![0], 0, None, None, None);
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, "tournament.near".parse::<AccountId>().unwrap());
+        match &receipts[0].actions[0] {
+            VmAction::FunctionCall { function_name, args, .. } => {
+                assert_eq!(function_name, "on_game_result");
+                assert_eq!(String::from_utf8(args.clone()).unwrap(), serde_json::to_string(&serde_json::json!({
+                    "game_id": game_id,
+                    "players": ["bob.near", "alice.near"],
+                    "winner": 1,
+                    "scores": [0, 14],
+                })).unwrap());
+            },
+            _ => panic!("Unexpected action"),
+        }
+        match &receipts[1].actions[0] {
+            VmAction::FunctionCall { function_name, args, .. } => {
+                assert_eq!(function_name, "on_result_reported");
+                assert_eq!(String::from_utf8(args.clone()).unwrap(), format!(r#"{{"game_id":"{}"}}"#, game_id));
+            },
+            _ => panic!("Unexpected action"),
+        }
+        assert_eq!(contract.games.get(&game_id).unwrap().result_reported, None);
+    }
+
+    #[test]
+    fn report_result_delivered() {
+        let mut contract = Contract::default();
+        let game_id = started_tournament_game(&mut contract);
+        contract.attack(game_id.clone(), vec![0], 0, None, None, None);
+
+        login_as("alice.near");
+        assert!(contract.on_result_reported(game_id.clone(), Ok(())));
+        assert_eq!(contract.games.get(&game_id).unwrap().result_reported, Some(true));
+    }
+
+    #[test]
+    fn report_result_failed() {
+        let mut contract = Contract::default();
+        let game_id = started_tournament_game(&mut contract);
+        login_as("bob.near");
+        contract.resign(game_id.clone());
+
+        login_as("alice.near");
+        assert!(!contract.on_result_reported(game_id.clone(), Err(PromiseError::Failed)));
+        let game = contract.games.get(&game_id).unwrap();
+        assert_eq!(game.winner, Some(1));
+        assert_eq!(game.result_reported, Some(false));
+    }
+
+    #[test]
+    fn no_report_without_target() {
+        let mut contract = Contract::default();
+        let game_id = started_tournament_game(&mut contract);
+        let mut game = contract.games.get(&game_id).unwrap();
+        game.report_to = None;
+        contract.games.insert(&game_id, &game);
+
+        contract.attack(game_id.clone(), vec![0], 0, None, None, None);
+        assert!(get_created_receipts().is_empty());
+    }
+}
@@ -0,0 +1,111 @@
+use near_sdk::near_bindgen;
+use near_sdk::serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// Final scoreboard of a finished game
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GameResult {
+    pub players: Vec<String>,
+    /// None for a draw
+    pub winner: Option<u8>,
+    pub finish_reason: Option<FinishReason>,
+    /// Each player's final score, doubled so half points stay whole, see score
+    pub scores: Vec<u32>,
+    /// Sizes of the dice each player captured, in the order they were captured
+    pub captured: Vec<Vec<u8>>,
+    pub captured_value: Vec<u32>,
+    /// Attacks and passes played, see Game::moves_played
+    pub turns: u32,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// The result of a finished game, None while it's still going or if there's no such game
+    pub fn get_game_result(&self, game_id: String) -> Option<GameResult> {
+        let game = self.games.get(&game_id).filter(|game| game.is_finished())?;
+        Some(GameResult {
+            winner: game.winner,
+            finish_reason: game.finish_reason,
+            scores: scores(&game),
+            captured_value: game.captured.iter().map(|sizes| sizes.iter().map(|size| *size as u32).sum()).collect(),
+            turns: game.moves_played() as u32,
+            captured: game.captured,
+            players: game.players,
+        })
+    }
+}
+
+impl Contract {
+    /// /api/games/{id}/result, a 409 for a game that isn't finished
+    pub(crate) fn game_result_response(&self, game_id: &str) -> Web4Response {
+        if let Some(result) = self.get_game_result(game_id.to_string()) {
+            return Web4Response::Body {
+                content_type: "application/json".to_owned(),
+                body: serde_json::to_vec(&result).unwrap().into(),
+                status: None,
+            }
+        }
+        match self.games.get(&game_id.to_string()) {
+            Some(_) => error_response(Error::GameNotFinished { game_id: game_id.to_string() }),
+            None => error_response(Error::GameNotFound { game_id: game_id.to_string() }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{login_as, request_path};
+
+    fn result_response(contract: &Contract, game_id: &str) -> (Option<u16>, serde_json::Value) {
+        match contract.web4_get(request_path(&format!("/api/games/{}/result", game_id))) {
+            Web4Response::Body { body, status, .. } => (status, serde_json::from_slice(&Vec::<u8>::from(body)).unwrap()),
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    /// bob.near's d20 about to take alice.near's last d4, with a d6 of theirs captured already
+    fn last_capture_game(contract: &mut Contract) {
+        login_as("bob.near");
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 20 }, Die { size: 8, value: 3 }], vec![Die { size: 4, value: 1 }]],
+            captured: vec![vec![6], vec![]],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn finished_game_result() {
+        let mut contract = Contract::default();
+        last_capture_game(&mut contract);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+
+        let (status, result) = result_response(&contract, "1");
+        assert_eq!(status, None);
+        assert_eq!(result, serde_json::json!({
+            "players": ["bob.near", "alice.near"],
+            "winner": 0,
+            "finish_reason": "capture_out",
+            "scores": [2 * 10 + 28, 0],
+            "captured": [[6, 4], []],
+            "captured_value": [10, 0],
+            "turns": 1,
+        }));
+    }
+
+    #[test]
+    fn in_progress_game_result() {
+        let mut contract = Contract::default();
+        last_capture_game(&mut contract);
+
+        assert_eq!(contract.get_game_result("1".to_string()), None);
+        assert_eq!(result_response(&contract, "1"), (Some(409), serde_json::json!({ "error": "game_not_finished" })));
+        assert_eq!(result_response(&contract, "2"), (Some(404), serde_json::json!({ "error": "game_not_found" })));
+    }
+}
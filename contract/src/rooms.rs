@@ -0,0 +1,183 @@
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::{env, near_bindgen, AccountId, Gas, Promise, PromiseError};
+
+use crate::*;
+
+const GAS_FOR_ROOM_INIT: Gas = Gas(20_000_000_000_000);
+const GAS_FOR_ROOM_CREATED: Gas = Gas(10_000_000_000_000);
+
+/// Storage key of the wasm deployed to rooms
+const ROOM_CODE_KEY: &[u8] = b"room_code";
+
+#[near_bindgen]
+impl Contract {
+    /// Uploads the wasm create_room deploys. A contract can't read its own code,
+    /// so this has to be the same build that's deployed here for rooms to behave the same.
+    pub fn set_room_code(&mut self, code: Base64VecU8) -> () {
+        self.assert_owner("set the room code");
+
+        env::storage_write(ROOM_CODE_KEY, &code.0);
+    }
+
+    /// Deploys a room at `<name>.<this contract>`, owned by the same owner and serving the same frontend.
+    /// The attached deposit funds the room's account and has to cover at least ContractConfig.room_deposit.
+    #[payable]
+    pub fn create_room(&mut self, name: String) -> () {
+        self.assert_owner("create rooms");
+        let deposit = env::attached_deposit();
+        if deposit < self.config.room_deposit.0 {
+            fail(Error::DepositTooLow { amount: self.config.room_deposit.0, action: "create a room".to_string() });
+        }
+
+        let room_id: AccountId = format!("{}.{}", name, env::current_account_id()).parse()
+            .unwrap_or_else(|_| fail(Error::InvalidRoomName { name: name.to_string() }));
+        if self.rooms.iter().any(|id| id == room_id) {
+            fail(Error::RoomExists { room_id: room_id.to_string() });
+        }
+        let code = env::storage_read(ROOM_CODE_KEY).unwrap_or_else(|| fail(Error::NoRoomCode));
+
+        let init_args = serde_json::json!({
+            "owner_id": self.owner_id,
+            "web4_static_url": self.web4_static_url,
+        }).to_string().into_bytes();
+        Promise::new(room_id.clone())
+            .create_account()
+            .transfer(deposit)
+            .deploy_contract(code)
+            .function_call("new".to_string(), init_args, 0, GAS_FOR_ROOM_INIT)
+            .then(Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_ROOM_CREATED)
+                .on_room_created(room_id));
+    }
+
+    /// Registers the room once it's deployed. If any step failed, the deposit comes back to this contract.
+    #[private]
+    pub fn on_room_created(&mut self, room_id: AccountId, #[callback_result] result: Result<(), PromiseError>) -> bool {
+        if result.is_err() {
+            emit_event("room_creation_failed", serde_json::json!({ "room_id": room_id }));
+            return false;
+        }
+
+        self.rooms.push(&room_id);
+        emit_event("room_created", serde_json::json!({ "room_id": room_id }));
+        return true;
+    }
+
+    pub fn list_rooms(&self) -> Vec<AccountId> {
+        self.rooms.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_as, login_as, request_path};
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{get_created_receipts, get_logs};
+
+    const ROOM_DEPOSIT: u128 = 5_000_000_000_000_000_000_000_000;
+
+    fn contract_with_room_code() -> Contract {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_room_code(Base64VecU8(b"room wasm".to_vec()));
+        return contract;
+    }
+
+    #[test]
+    fn create_room() {
+        let mut contract = contract_with_room_code();
+        call_as("alice.near", 0, ROOM_DEPOSIT);
+        contract.create_room("finals".to_string());
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, "finals.alice.near".parse::<AccountId>().unwrap());
+        assert_eq!(receipts[0].actions[..3], vec![
+            VmAction::CreateAccount,
+            VmAction::Transfer { deposit: ROOM_DEPOSIT },
+            VmAction::DeployContract { code: b"room wasm".to_vec() },
+        ]);
+        match &receipts[0].actions[3] {
+            VmAction::FunctionCall { function_name, args, .. } => {
+                assert_eq!(function_name, "new");
+                assert_eq!(String::from_utf8(args.clone()).unwrap(), serde_json::to_string(&serde_json::json!({
+                    "owner_id": "alice.near",
+                    "web4_static_url": contract.web4_static_url,
+                })).unwrap());
+            },
+            _ => panic!("Unexpected action"),
+        }
+        match &receipts[1].actions[0] {
+            VmAction::FunctionCall { function_name, .. } => assert_eq!(function_name, "on_room_created"),
+            _ => panic!("Unexpected action"),
+        }
+        // The room is only listed once it's deployed
+        assert_eq!(contract.list_rooms(), vec![]);
+
+        login_as("alice.near");
+        assert!(contract.on_room_created("finals.alice.near".parse().unwrap(), Ok(())));
+        assert_eq!(contract.list_rooms(), vec!["finals.alice.near".parse::<AccountId>().unwrap()]);
+        match contract.web4_get(request_path("/api/rooms")) {
+            Web4Response::Body { content_type, body, .. } => {
+                assert_eq!(content_type, "application/json".to_owned());
+                assert_eq!(String::from_utf8(body.into()).unwrap(), r#"["finals.alice.near"]"#);
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    fn create_room_failed() {
+        let mut contract = contract_with_room_code();
+
+        assert!(!contract.on_room_created("finals.alice.near".parse().unwrap(), Err(PromiseError::Failed)));
+        assert_eq!(contract.list_rooms(), vec![]);
+        assert_eq!(get_logs(), vec![
+            r#"EVENT_JSON:{"standard":"buttonmen","version":"1.0.0","event":"room_creation_failed","data":[{"room_id":"finals.alice.near"}]}"#,
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Room finals.alice.near already exists")]
+    fn create_room_twice() {
+        let mut contract = contract_with_room_code();
+        contract.on_room_created("finals.alice.near".parse().unwrap(), Ok(()));
+
+        call_as("alice.near", 0, ROOM_DEPOSIT);
+        contract.create_room("finals".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Attach at least 5000000000000000000000000 yoctoNEAR to create a room")]
+    fn create_room_low_deposit() {
+        let mut contract = contract_with_room_code();
+        call_as("alice.near", 0, ROOM_DEPOSIT - 1);
+        contract.create_room("finals".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can create rooms")]
+    fn create_room_not_owner() {
+        let mut contract = contract_with_room_code();
+        call_as("bob.near", 0, ROOM_DEPOSIT);
+        contract.create_room("finals".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid room name: Finals!")]
+    fn create_room_invalid_name() {
+        let mut contract = contract_with_room_code();
+        call_as("alice.near", 0, ROOM_DEPOSIT);
+        contract.create_room("Finals!".to_string());
+    }
+
+    #[test]
+    fn room_owner_from_init() {
+        let mut contract = Contract::new("bob.near".parse().unwrap(), "ipfs://room".to_string());
+        assert_eq!(contract.web4_static_url, "ipfs://room");
+
+        login_as("bob.near");
+        contract.set_paused(true);
+        assert!(contract.get_config().paused);
+    }
+}
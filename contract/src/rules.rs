@@ -0,0 +1,101 @@
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, AccountId};
+
+use crate::*;
+
+/// Everything a game was set up with, so a player knows what they're joining
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GameRules {
+    /// Button in each seat, None for a seat nobody has taken
+    pub buttons: Vec<Option<ButtonDef>>,
+    /// Stake each player puts in, None for a game without a wager
+    pub stake: Option<U128>,
+    /// Counts for ratings, records and achievements, which games against the contract don't
+    pub rated: bool,
+    pub fog: bool,
+    pub hints: bool,
+    pub listed: bool,
+    pub time_control: Option<TimeControl>,
+    /// Turns that have to be played before resigning, see Contract::resign
+    pub min_turns_before_resign: u32,
+    pub report_to: Option<AccountId>,
+    pub tournament_id: Option<String>,
+    pub bot: Option<BotDifficulty>,
+    /// Date of the daily challenge the game is
+    pub daily: Option<String>,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_game_rules(&self, game_id: String) -> Option<GameRules> {
+        let game = self.games.get(&game_id)?;
+        Some(GameRules {
+            buttons: game.buttons.iter().map(|button| self.find_button(button)).collect(),
+            stake: self.wagers.get(&game_id).map(|wager| wager.stake),
+            rated: !game.against_contract(),
+            fog: game.fog,
+            hints: game.hints,
+            listed: !game.unlisted,
+            time_control: game.time_control,
+            min_turns_before_resign: if game.against_contract() { 0 } else { self.config.min_turns_before_resign },
+            report_to: game.report_to,
+            tournament_id: game.tournament_id,
+            bot: game.bot,
+            daily: game.daily,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{login_as, request_path};
+
+    fn rules_response(contract: &Contract, game_id: &str) -> (Option<u16>, serde_json::Value) {
+        match contract.web4_get(request_path(&format!("/api/games/{}/rules", game_id))) {
+            Web4Response::Body { body, status, .. } => (status, serde_json::from_slice(&Vec::<u8>::from(body)).unwrap()),
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    fn custom_game_rules() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_min_turns_before_resign(4);
+        login_as("bob.near");
+        let game_id = contract.create_game(Some(CreateGameOptions {
+            fog: true,
+            public: Some(false),
+            time_control: Some(TimeControl { bank_ns: 3 * DAY_NS, increment_ns: 60_000_000_000, per_move: false }),
+            ..Default::default()
+        }));
+
+        let (status, rules) = rules_response(&contract, &game_id);
+        assert_eq!(status, None);
+        assert_eq!(rules, serde_json::json!({
+            "buttons": [{"name": "Standard", "dice": [4, 6, 8, 10, 20], "auxiliary": [], "skills": []}, null],
+            "stake": null,
+            "rated": true,
+            "fog": true,
+            "hints": false,
+            "listed": false,
+            "time_control": {"bank_ns": 3 * DAY_NS, "increment_ns": 60_000_000_000u64, "per_move": false},
+            "min_turns_before_resign": 4,
+            "report_to": null,
+            "tournament_id": null,
+            "bot": null,
+            "daily": null,
+        }));
+    }
+
+    #[test]
+    fn rules_game_not_found() {
+        let contract = Contract::default();
+        let (status, body) = rules_response(&contract, "1");
+        assert_eq!(status, Some(404));
+        assert_eq!(body, serde_json::json!({ "error": "game_not_found" }));
+    }
+}
@@ -0,0 +1,161 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::*;
+use leaderboard::LeaderboardEntry;
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Season {
+    /// Starts at 1, games finished before the first season have no season
+    pub id: u32,
+    pub name: String,
+    pub started_at: u64,
+}
+
+/// Rating an account had in a season, the live one for the current season
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SeasonRating {
+    pub season_id: Option<u32>,
+    pub rating: u32,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Closes the current season and starts a new one. The closing season's leaderboard stays as its archive.
+    /// Ratings aren't touched here: each account's carries over into the new season, see ContractConfig.season_carryover_pct,
+    /// the first time it's read.
+    pub fn start_season(&mut self, name: String) -> () {
+        self.assert_owner("start seasons");
+
+        let season = Season {
+            id: self.seasons.len() as u32 + 1,
+            name,
+            started_at: env::block_timestamp(),
+        };
+        self.seasons.push(&season);
+
+        emit_event("season_started", serde_json::json!({
+            "season_id": season.id,
+            "name": season.name,
+        }));
+    }
+
+    /// Current season, None before the first one starts
+    pub fn get_season(&self) -> Option<Season> {
+        self.seasons.len().checked_sub(1).and_then(|index| self.seasons.get(index))
+    }
+
+    pub fn get_season_leaderboard(&self, season_id: u32) -> Vec<LeaderboardEntry> {
+        leaderboard::read_leaderboard(Some(season_id))
+    }
+
+    /// Final ratings of the account in past seasons it played in, oldest first
+    pub fn get_season_history(&self, account_id: AccountId) -> Vec<SeasonRating> {
+        let mut history = self.season_history.get(&account_id.to_string()).unwrap_or_default();
+        if let Some(entry) = self.ratings.get(&account_id.to_string()) {
+            if entry.season_id != self.current_season_id() {
+                history.push(entry);
+            }
+        }
+        return history;
+    }
+
+    pub(crate) fn current_season_id(&self) -> Option<u32> {
+        self.get_season().map(|season| season.id)
+    }
+
+    /// Rating a season starts from for an account that ended the previous one at `rating`
+    pub(crate) fn carried_over_rating(&self, rating: u32) -> u32 {
+        let distance = rating as i64 - ratings::DEFAULT_RATING as i64;
+        (ratings::DEFAULT_RATING as i64 + distance * self.config.season_carryover_pct as i64 / 100) as u32
+    }
+
+    /// Moves the account's rating from a past season into its season history
+    pub(crate) fn archive_season_rating(&mut self, account_id: &str, entry: SeasonRating) {
+        let mut history = self.season_history.get(&account_id.to_string()).unwrap_or_default();
+        history.push(entry);
+        self.season_history.insert(&account_id.to_string(), &history);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::login_as;
+
+    fn play(contract: &mut Contract, loser: &str, winner: &str) -> String {
+        login_as(winner);
+        let game_id = contract.create_game(None);
+        login_as(loser);
+        contract.join_game(game_id.clone(), None);
+        contract.resign(game_id.clone());
+        return game_id;
+    }
+
+    fn standings(leaderboard: Vec<LeaderboardEntry>) -> Vec<(String, u32)> {
+        leaderboard.into_iter().map(|entry| (entry.account_id, entry.rating)).collect()
+    }
+
+    #[test]
+    fn seasons_keep_standings_apart() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.start_season("Spring".to_string());
+        assert_eq!(contract.get_season().unwrap().name, "Spring");
+
+        let spring_game = play(&mut contract, "carol.near", "bob.near");
+        assert_eq!(contract.games.get(&spring_game).unwrap().season_id, Some(1));
+
+        login_as("alice.near");
+        contract.start_season("Summer".to_string());
+        assert_eq!(contract.get_season().unwrap().id, 2);
+        // Half of the distance from 1500 carries over
+        assert_eq!(contract.get_rating("bob.near".parse().unwrap()), 1508);
+        assert_eq!(contract.get_rating("carol.near".parse().unwrap()), 1492);
+
+        let summer_game = play(&mut contract, "erin.near", "dave.near");
+        assert_eq!(contract.games.get(&summer_game).unwrap().season_id, Some(2));
+        play(&mut contract, "carol.near", "bob.near");
+
+        assert_eq!(standings(contract.get_season_leaderboard(1)), vec![
+            ("bob.near".to_string(), 1516), ("carol.near".to_string(), 1484)]);
+        // bob.near starts from 1508 and gains 15 against carol.near's 1492
+        assert_eq!(standings(contract.get_season_leaderboard(2)), vec![
+            ("bob.near".to_string(), 1523), ("dave.near".to_string(), 1516), ("erin.near".to_string(), 1484), ("carol.near".to_string(), 1477)]);
+        let current = contract.get_leaderboard(0, 10).into_iter().map(|entry| (entry.account_id, entry.rating)).collect::<Vec<(String, u32)>>();
+        assert_eq!(current, standings(contract.get_season_leaderboard(2)));
+
+        assert_eq!(contract.get_season_history("bob.near".parse().unwrap()), vec![SeasonRating { season_id: Some(1), rating: 1516 }]);
+        assert_eq!(contract.get_season_history("dave.near".parse().unwrap()), vec![]);
+
+        login_as("alice.near");
+        contract.start_season("Autumn".to_string());
+        // dave.near's summer rating shows up before they play again
+        assert_eq!(contract.get_season_history("dave.near".parse().unwrap()), vec![SeasonRating { season_id: Some(2), rating: 1516 }]);
+        assert_eq!(contract.get_season_history("bob.near".parse().unwrap()), vec![
+            SeasonRating { season_id: Some(1), rating: 1516 }, SeasonRating { season_id: Some(2), rating: 1523 }]);
+    }
+
+    #[test]
+    fn hard_reset_without_carryover() {
+        let mut contract = Contract::default();
+        contract.config.season_carryover_pct = 0;
+        play(&mut contract, "carol.near", "bob.near");
+
+        login_as("alice.near");
+        contract.start_season("Spring".to_string());
+        assert_eq!(contract.get_rating("bob.near".parse().unwrap()), 1500);
+        assert_eq!(contract.get_season_history("bob.near".parse().unwrap()), vec![SeasonRating { season_id: None, rating: 1516 }]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can start seasons")]
+    fn start_season_not_owner() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.start_season("Spring".to_string());
+    }
+}
@@ -0,0 +1,394 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Balance, Promise};
+
+use crate::*;
+
+#[allow(clippy::all)]
+mod uint256 {
+    uint::construct_uint! {
+        /// Wide enough for products of yoctoNEAR amounts
+        pub struct U256(4);
+    }
+}
+
+use uint256::U256;
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Totals staked by spectators on each player of a game.
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SideBetPool {
+    /// Staked on each player, indexed like Game.players
+    pub totals: Vec<U128>,
+    /// Cut of the losing side taken into the treasury when the game finished
+    pub fee: U128,
+    /// Set once the game finished and the fee was taken
+    pub settled: bool,
+}
+
+/// One spectator's stakes in a game, paid out by claim_side_bet.
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SideBet {
+    /// Staked on each player, indexed like Game.players
+    pub stakes: Vec<U128>,
+    pub claimed: bool,
+}
+
+fn side_bet_key(game_id: &str, account_id: &str) -> String {
+    format!("{}:{}", game_id, account_id)
+}
+
+fn mul_div(a: Balance, b: Balance, c: Balance) -> Balance {
+    (U256::from(a) * U256::from(b) / U256::from(c)).as_u128()
+}
+
+/// What a bettor gets back once the game is over:
+/// - winner known: winning stakes plus a pro-rata share of the losing side minus the fee, losing stakes nothing,
+/// - draw, cancelled game or nobody backed the winner: all stakes back.
+fn side_bet_payout(game: &Game, pool: &SideBetPool, bet: &SideBet) -> Balance {
+    let refund = bet.stakes.iter().map(|stake| stake.0).sum();
+    let winner = match game.winner {
+        Some(winner) if game.is_finished() => winner as usize,
+        _ => return refund,
+    };
+
+    let winning_total = pool.totals[winner].0;
+    if winning_total == 0 {
+        return refund;
+    }
+
+    let losing_total = pool.totals.iter().map(|total| total.0).sum::<Balance>() - winning_total;
+    let stake = bet.stakes[winner].0;
+    stake + mul_div(stake, losing_total - pool.fee.0, winning_total)
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Stakes the attached deposit on the given player of a game in progress.
+    /// Bets are accepted until the game has had ContractConfig.side_bet_lock_after_moves moves.
+    #[payable]
+    pub fn place_side_bet(&mut self, game_id: String, player_index: u8) -> () {
+        self.assert_not_paused();
+
+        let amount = env::attached_deposit();
+        if amount == 0 {
+            fail(Error::NoDeposit { action: "bet on the game".to_string() });
+        }
+
+        let game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+        if player_index as usize >= game.players.len() {
+            fail(Error::InvalidPlayerIndex { player_index });
+        }
+        let bettor_id = env::predecessor_account_id().to_string();
+        if game.players.contains(&bettor_id) {
+            fail(Error::OwnGameBet);
+        }
+        // Nothing rides on these, so after_game_finished would never settle the bets
+        if game.against_contract() {
+            fail(Error::ContractGameBet { game_id: game_id.to_string() });
+        }
+        if game.moves_played() >= self.config.side_bet_lock_after_moves as usize {
+            fail(Error::SideBetsLocked { game_id: game_id.to_string() });
+        }
+
+        let mut pool = self.side_bets.get(&game_id).unwrap_or_else(|| SideBetPool {
+            totals: vec![U128(0); game.players.len()],
+            fee: U128(0),
+            settled: false,
+        });
+        pool.totals[player_index as usize].0 += amount;
+        self.side_bets.insert(&game_id, &pool);
+
+        let key = side_bet_key(&game_id, &bettor_id);
+        let mut bet = self.side_bet_stakes.get(&key).unwrap_or_else(|| SideBet {
+            stakes: vec![U128(0); game.players.len()],
+            claimed: false,
+        });
+        bet.stakes[player_index as usize].0 += amount;
+        self.side_bet_stakes.insert(&key, &bet);
+    }
+
+    /// Pays out the caller's side bet once the game is finished or cancelled.
+    pub fn claim_side_bet(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        if !game.is_finished() && game.status != GameStatus::Cancelled {
+            fail(Error::GameNotOver { game_id: game_id.to_string() });
+        }
+
+        let bettor_id = env::predecessor_account_id();
+        let key = side_bet_key(&game_id, bettor_id.as_str());
+        let mut bet = self.side_bet_stakes.get(&key).unwrap_or_else(|| fail(Error::NoSideBet { game_id: game_id.to_string() }));
+        if bet.claimed {
+            fail(Error::SideBetClaimed { game_id: game_id.to_string() });
+        }
+
+        let pool = self.side_bets.get(&game_id).unwrap_or_else(|| fail(Error::NoSideBet { game_id: game_id.to_string() }));
+        let payout = side_bet_payout(&game, &pool, &bet);
+        if payout == 0 {
+            fail(Error::SideBetLost { game_id: game_id.to_string() });
+        }
+
+        bet.claimed = true;
+        self.side_bet_stakes.insert(&key, &bet);
+        Promise::new(bettor_id).transfer(payout);
+    }
+
+    pub fn get_side_bets(&self, game_id: String) -> Option<SideBetPool> {
+        self.side_bets.get(&game_id)
+    }
+
+    pub fn get_side_bet(&self, game_id: String, account_id: AccountId) -> Option<SideBet> {
+        self.side_bet_stakes.get(&side_bet_key(&game_id, account_id.as_str()))
+    }
+
+    pub fn set_side_bet_config(&mut self, fee_bps: u16, lock_after_moves: u32) -> () {
+        self.assert_owner("configure side bets");
+        if fee_bps as u128 > BPS_DENOMINATOR {
+            fail(Error::FeeTooHigh);
+        }
+
+        self.config.side_bet_fee_bps = fee_bps;
+        self.config.side_bet_lock_after_moves = lock_after_moves;
+    }
+
+    /// Takes the fee out of the losing side once the game finished. Payouts are claimed individually.
+    pub(crate) fn settle_side_bets(&mut self, game: &Game) {
+        let mut pool = match self.side_bets.get(&game.id) {
+            Some(pool) if !pool.settled => pool,
+            _ => return,
+        };
+
+        if let Some(winner) = game.winner {
+            if pool.totals[winner as usize].0 > 0 {
+                let losing_total = pool.totals.iter().map(|total| total.0).sum::<Balance>() - pool.totals[winner as usize].0;
+                pool.fee = U128(mul_div(losing_total, self.config.side_bet_fee_bps as u128, BPS_DENOMINATOR));
+                self.treasury += pool.fee.0;
+            }
+        }
+        pool.settled = true;
+        self.side_bets.insert(&game.id, &pool);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_as, login_as, transfers};
+
+    fn insert_active_game(contract: &mut Contract) {
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 1,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 6, value: 3 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+    }
+
+    fn bet(contract: &mut Contract, account_id: &str, player_index: u8, amount: Balance) {
+        call_as(account_id, 0, amount);
+        contract.place_side_bet("1".to_string(), player_index);
+    }
+
+    fn claim(contract: &mut Contract, account_id: &str) -> Vec<(String, Balance)> {
+        call_as(account_id, 0, 0);
+        contract.claim_side_bet("1".to_string());
+        transfers()
+    }
+
+    /// alice.near captures bob.near's last die and wins
+    fn alice_wins(contract: &mut Contract) {
+        login_as("alice.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+    }
+
+    #[test]
+    fn side_bet_winners_share_losing_pool() {
+        let mut contract = Contract::default();
+        insert_active_game(&mut contract);
+        bet(&mut contract, "carol.near", 1, 300);
+        bet(&mut contract, "dave.near", 1, 100);
+        bet(&mut contract, "eve.near", 0, 1000);
+        alice_wins(&mut contract);
+
+        // 2% fee on the losing 1000, the remaining 980 is split 3:1
+        assert_eq!(contract.get_side_bets("1".to_string()).unwrap(), SideBetPool {
+            totals: vec![U128(1000), U128(400)],
+            fee: U128(20),
+            settled: true,
+        });
+        assert_eq!(contract.treasury, 20);
+        assert_eq!(claim(&mut contract, "carol.near"), vec![("carol.near".to_string(), 300 + 735)]);
+        assert_eq!(claim(&mut contract, "dave.near"), vec![("dave.near".to_string(), 100 + 245)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is against the contract, so it takes no bets")]
+    fn side_bet_on_bot_game_rejected() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), bot::BOT_ACCOUNT.to_string()],
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 6, value: 3 }]],
+            captured: vec![vec![], vec![]],
+            bot: Some(BotDifficulty::Greedy),
+            ..Default::default()
+        });
+        bet(&mut contract, "carol.near", 1, 300);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn side_bet_claim_paused() {
+        let mut contract = Contract::default();
+        insert_active_game(&mut contract);
+        bet(&mut contract, "carol.near", 1, 300);
+        bet(&mut contract, "eve.near", 0, 1000);
+        alice_wins(&mut contract);
+        contract.set_paused(true);
+
+        claim(&mut contract, "carol.near");
+    }
+
+    #[test]
+    fn side_bet_rounding_never_overpays() {
+        let mut contract = Contract::default();
+        insert_active_game(&mut contract);
+        bet(&mut contract, "carol.near", 1, 1);
+        bet(&mut contract, "dave.near", 1, 1);
+        bet(&mut contract, "frank.near", 1, 1);
+        bet(&mut contract, "eve.near", 0, 100);
+        alice_wins(&mut contract);
+
+        // 98 split three ways rounds down, the leftover yoctoNEAR stays in the contract
+        let paid = ["carol.near", "dave.near", "frank.near"].iter()
+            .map(|account_id| claim(&mut contract, account_id)[0].1)
+            .collect::<Vec<Balance>>();
+        assert_eq!(paid, vec![33, 33, 33]);
+        assert!(paid.iter().sum::<Balance>() + contract.treasury <= 103);
+    }
+
+    #[test]
+    fn side_bet_large_stakes() {
+        let mut contract = Contract::default();
+        insert_active_game(&mut contract);
+        let near = 10u128.pow(24);
+        bet(&mut contract, "carol.near", 1, 1_000_000 * near);
+        bet(&mut contract, "eve.near", 0, 3_000_000 * near);
+        alice_wins(&mut contract);
+
+        assert_eq!(claim(&mut contract, "carol.near"), vec![("carol.near".to_string(), 3_940_000 * near)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Side bet on game 1 is already claimed")]
+    fn side_bet_double_claim() {
+        let mut contract = Contract::default();
+        insert_active_game(&mut contract);
+        bet(&mut contract, "carol.near", 1, 100);
+        alice_wins(&mut contract);
+
+        claim(&mut contract, "carol.near");
+        claim(&mut contract, "carol.near");
+    }
+
+    #[test]
+    #[should_panic(expected = "Side bet on game 1 lost")]
+    fn side_bet_losing_claim() {
+        let mut contract = Contract::default();
+        insert_active_game(&mut contract);
+        bet(&mut contract, "carol.near", 1, 100);
+        bet(&mut contract, "eve.near", 0, 100);
+        alice_wins(&mut contract);
+
+        claim(&mut contract, "eve.near");
+    }
+
+    #[test]
+    fn side_bet_draw_refunds() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 1,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 4, value: 3 }, Die { size: 4, value: 3 }]],
+            captured: vec![vec![8], vec![]],
+            ..Default::default()
+        });
+        bet(&mut contract, "carol.near", 1, 300);
+        bet(&mut contract, "eve.near", 0, 100);
+        bet(&mut contract, "eve.near", 1, 50);
+        // alice.near ends up with 2 * 4 + 4 + 4, same as bob.near's 2 * 8
+        alice_wins(&mut contract);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().winner, None);
+
+        assert_eq!(contract.treasury, 0);
+        assert_eq!(claim(&mut contract, "carol.near"), vec![("carol.near".to_string(), 300)]);
+        assert_eq!(claim(&mut contract, "eve.near"), vec![("eve.near".to_string(), 150)]);
+    }
+
+    #[test]
+    fn side_bet_cancelled_refunds() {
+        let mut contract = Contract::default();
+        insert_active_game(&mut contract);
+        bet(&mut contract, "carol.near", 1, 300);
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        game.status = GameStatus::Cancelled;
+        contract.games.insert(&"1".to_string(), &game);
+
+        assert_eq!(claim(&mut contract, "carol.near"), vec![("carol.near".to_string(), 300)]);
+    }
+
+    #[test]
+    fn side_bet_nobody_backed_winner() {
+        let mut contract = Contract::default();
+        insert_active_game(&mut contract);
+        bet(&mut contract, "eve.near", 0, 100);
+        alice_wins(&mut contract);
+
+        assert_eq!(contract.treasury, 0);
+        assert_eq!(claim(&mut contract, "eve.near"), vec![("eve.near".to_string(), 100)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Side bets on game 1 are locked")]
+    fn side_bet_locked() {
+        let mut contract = Contract::default();
+        insert_active_game(&mut contract);
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        game.history = vec![MoveRecord::new(1, MoveKind::Pass), MoveRecord::new(0, MoveKind::Pass)];
+        contract.games.insert(&"1".to_string(), &game);
+
+        bet(&mut contract, "carol.near", 1, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is not over yet")]
+    fn side_bet_claim_in_progress() {
+        let mut contract = Contract::default();
+        insert_active_game(&mut contract);
+        bet(&mut contract, "carol.near", 1, 100);
+
+        claim(&mut contract, "carol.near");
+    }
+
+    #[test]
+    #[should_panic(expected = "Players can't bet on their own game")]
+    fn side_bet_by_player() {
+        let mut contract = Contract::default();
+        insert_active_game(&mut contract);
+
+        bet(&mut contract, "bob.near", 0, 100);
+    }
+}
@@ -0,0 +1,215 @@
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen, AccountId, Balance, Promise};
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Whether the account has finished at least one game and so creates games without the anti-spam deposit.
+    pub fn is_known_player(&self, account_id: AccountId) -> bool {
+        self.first_finished_at.contains_key(&account_id.to_string())
+    }
+
+    /// Refundable deposit held for a game created by a new account, if any.
+    pub fn get_spam_deposit(&self, game_id: String) -> Option<U128> {
+        self.spam_deposits.get(&game_id).map(U128)
+    }
+
+    pub fn set_new_account_deposit(&mut self, deposit: U128) -> () {
+        self.assert_owner("set the new account deposit");
+
+        self.config.new_account_deposit = deposit;
+    }
+
+    /// Lets anyone close a game nobody joined before ContractConfig.game_join_expiry_ns, taking it out of the lobby.
+    /// Wagered games are reclaimed by their creator with reclaim_wager instead.
+    pub fn expire_game(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        if game.is_started() {
+            fail(Error::GameAlreadyStarted { game_id: game_id.to_string() });
+        }
+        if game.verifying {
+            fail(Error::VerifyingButtons { game_id: game_id.to_string() });
+        }
+        if self.wagers.get(&game_id).is_some() {
+            fail(Error::HasWager { game_id: game_id.to_string() });
+        }
+        if env::block_timestamp() < game.created_at + self.config.game_join_expiry_ns {
+            fail(Error::NotExpired { game_id: game_id.to_string() });
+        }
+
+        self.cancel_unjoined(&mut game);
+    }
+
+    /// Closes a game that expired before anyone joined. Its creator's deposit is kept, the creator is the one
+    /// who didn't find an opponent.
+    pub(crate) fn cancel_unjoined(&mut self, game: &mut Game) {
+        game.status = GameStatus::Cancelled;
+        self.save_game(game);
+        self.remove_from_latest(&game.id);
+        self.open_games.retain(|open_game| open_game.game_id != game.id);
+        self.forfeit_spam_deposit(&game.id);
+    }
+
+    /// Anti-spam deposit the account has to attach when creating a game, 0 for known players.
+    pub(crate) fn required_spam_deposit(&self, account_id: &AccountId) -> Balance {
+        if self.is_known_player(account_id.clone()) {
+            0
+        } else {
+            self.config.new_account_deposit.0
+        }
+    }
+
+    pub(crate) fn hold_spam_deposit(&mut self, game_id: &str, deposit: Balance) {
+        if deposit > 0 {
+            self.spam_deposits.insert(&game_id.to_string(), &deposit);
+        }
+    }
+
+    /// Returns the deposit to the game creator.
+    pub(crate) fn refund_spam_deposit(&mut self, game: &Game) {
+        if let Some(deposit) = self.spam_deposits.remove(&game.id) {
+            let creator_id = game.players[0].parse().unwrap_or_else(|_| fail(Error::InvalidAccountId { account_id: game.players[0].to_string() }));
+            Promise::new(creator_id).transfer(deposit);
+        }
+    }
+
+    /// Keeps the deposit of a game which never got played in the treasury.
+    pub(crate) fn forfeit_spam_deposit(&mut self, game_id: &str) {
+        if let Some(deposit) = self.spam_deposits.remove(&game_id.to_string()) {
+            self.treasury += deposit;
+        }
+    }
+
+    /// Records the first finished game of each player, which exempts them from the anti-spam deposit.
+    pub(crate) fn mark_players_finished(&mut self, game: &Game) {
+        for player_id in game.players.iter() {
+            if !self.first_finished_at.contains_key(player_id) {
+                self.first_finished_at.insert(player_id, &env::block_timestamp());
+                self.count_weekly(|week| week.new_players += 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_as, login_as, transfers};
+
+    const DEPOSIT: Balance = 5_000;
+
+    fn contract_with_deposit() -> Contract {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_new_account_deposit(U128(DEPOSIT));
+        return contract;
+    }
+
+    #[test]
+    #[should_panic(expected = "Attach exactly 5000 yoctoNEAR to create a game")]
+    fn new_account_without_deposit() {
+        let mut contract = contract_with_deposit();
+        call_as("bob.near", 0, 0);
+        contract.create_game(None);
+    }
+
+    #[test]
+    fn deposit_refunded_on_finish() {
+        let mut contract = contract_with_deposit();
+        call_as("bob.near", 0, DEPOSIT);
+        let game_id = contract.create_game(None);
+        assert_eq!(contract.get_spam_deposit(game_id.clone()), Some(U128(DEPOSIT)));
+
+        call_as("carol.near", DAY_NS, 0);
+        contract.join_game(game_id.clone(), None);
+        contract.resign(game_id.clone());
+
+        assert_eq!(transfers(), vec![("bob.near".to_string(), DEPOSIT)]);
+        assert_eq!(contract.get_spam_deposit(game_id), None);
+        assert!(contract.is_known_player("bob.near".parse().unwrap()));
+        assert!(contract.is_known_player("carol.near".parse().unwrap()));
+        assert_eq!(contract.first_finished_at.get(&"carol.near".to_string()), Some(DAY_NS));
+    }
+
+    #[test]
+    fn known_player_exempt() {
+        let mut contract = contract_with_deposit();
+        contract.first_finished_at.insert(&"bob.near".to_string(), &0);
+
+        call_as("bob.near", 0, 0);
+        let game_id = contract.create_game(None);
+        assert_eq!(contract.get_spam_deposit(game_id), None);
+    }
+
+    #[test]
+    fn deposit_forfeited_on_expiry() {
+        let mut contract = contract_with_deposit();
+        call_as("bob.near", 0, DEPOSIT);
+        let game_id = contract.create_game(None);
+
+        call_as("eve.near", 7 * DAY_NS, 0);
+        contract.expire_game(game_id.clone());
+
+        let game = contract.games.get(&game_id).unwrap();
+        assert_eq!(game.status, GameStatus::Cancelled);
+        assert!(contract.latest_games.is_empty());
+        assert_eq!(contract.treasury, DEPOSIT);
+        assert_eq!(transfers(), vec![]);
+        assert!(!contract.is_known_player("bob.near".parse().unwrap()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 has not expired yet")]
+    fn expire_game_too_early() {
+        let mut contract = contract_with_deposit();
+        call_as("bob.near", 0, DEPOSIT);
+        contract.create_game(None);
+
+        call_as("eve.near", 7 * DAY_NS - 1, 0);
+        contract.expire_game("1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is verifying button ownership")]
+    fn expire_game_while_verifying() {
+        let mut contract = contract_with_deposit();
+        call_as("bob.near", 0, DEPOSIT);
+        contract.create_game(None);
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        game.verifying = true;
+        contract.games.insert(&"1".to_string(), &game);
+
+        call_as("eve.near", 7 * DAY_NS, 0);
+        contract.expire_game("1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 has already started")]
+    fn expire_game_started() {
+        let mut contract = contract_with_deposit();
+        call_as("bob.near", 0, DEPOSIT);
+        contract.create_game(None);
+        call_as("carol.near", 0, 0);
+        contract.join_game("1".to_string(), None);
+
+        call_as("eve.near", 7 * DAY_NS, 0);
+        contract.expire_game("1".to_string());
+    }
+
+    #[test]
+    fn deposit_forfeited_on_sweep() {
+        let mut contract = contract_with_deposit();
+        call_as("bob.near", 0, DEPOSIT + 100);
+        let game_id = contract.create_wagered_game(None);
+        assert_eq!(contract.get_wager(game_id.clone()).unwrap().stake, U128(100));
+
+        call_as("alice.near", 365 * DAY_NS, 0);
+        contract.sweep_abandoned_wager(game_id.clone(), "recovery.near".parse().unwrap());
+
+        assert_eq!(transfers(), vec![("recovery.near".to_string(), 100)]);
+        assert_eq!(contract.treasury, DEPOSIT);
+    }
+}
@@ -0,0 +1,158 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+/// Weeks of activity kept, older ones are overwritten
+const WEEKS_KEPT: u64 = 52;
+
+/// Activity of one ISO week
+#[derive(Debug, Default, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WeekStats {
+    /// `YYYY-Www`
+    pub week: String,
+    /// Weeks since the one 1970-01-01 is in
+    #[serde(skip)]
+    pub index: u64,
+    pub games_finished: u32,
+    /// Accounts that finished their first rated game
+    pub new_players: u32,
+    pub attacks: u32,
+}
+
+/// Weeks since the one 1970-01-01 is in. Weeks start on Monday and 1970-01-01 was a Thursday.
+fn week_index(timestamp: u64) -> u64 {
+    (timestamp / DAY_NS + 3) / 7
+}
+
+/// ISO 8601 `YYYY-Www` of a week: it belongs to the year its Thursday is in
+fn iso_week(index: u64) -> String {
+    let thursday = index * 7;
+    let (year, _, _) = daily::civil_date(thursday * DAY_NS);
+    // Days from 1970-01-01 to January 1st of the year, see http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let era = (year - 1).div_euclid(400);
+    let year_of_era = year - 1 - era * 400;
+    let january_first = era * 146_097 + year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + 306 - 719_468;
+    format!("{:04}-W{:02}", year, (thursday as i64 - january_first) / 7 + 1)
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Activity of the last weeks_back weeks, up to 52, oldest first and ending with the current week
+    pub fn get_weekly_stats(&self, weeks_back: u32) -> Vec<WeekStats> {
+        let current = week_index(env::block_timestamp());
+        let weeks = (weeks_back as u64).min(WEEKS_KEPT).min(current + 1);
+        (current + 1 - weeks..=current).map(|index| {
+            match self.weekly_stats.get((index % WEEKS_KEPT) as usize) {
+                Some(stats) if stats.index == index => stats.clone(),
+                _ => WeekStats { week: iso_week(index), index, ..Default::default() },
+            }
+        }).collect()
+    }
+}
+
+impl Contract {
+    /// Bumps a counter of the current week, taking over the slot of the week 52 weeks before it
+    pub(crate) fn count_weekly(&mut self, count: fn(&mut WeekStats)) {
+        let index = week_index(env::block_timestamp());
+        let slot = (index % WEEKS_KEPT) as usize;
+        if self.weekly_stats.len() <= slot {
+            self.weekly_stats.resize(slot + 1, WeekStats { index: u64::MAX, ..Default::default() });
+        }
+        if self.weekly_stats[slot].index != index {
+            self.weekly_stats[slot] = WeekStats { week: iso_week(index), index, ..Default::default() };
+        }
+        count(&mut self.weekly_stats[slot]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{login_as, request_path};
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    /// Monday 2024-01-01, the first day of 2024-W01
+    const MONDAY: u64 = 19_723 * DAY_NS;
+
+    fn at(timestamp: u64) {
+        testing_env!(VMContextBuilder::new().block_timestamp(timestamp).build());
+    }
+
+    fn counts(stats: &[WeekStats]) -> Vec<(String, u32, u32, u32)> {
+        stats.iter().map(|week| (week.week.clone(), week.games_finished, week.new_players, week.attacks)).collect()
+    }
+
+    #[test]
+    fn iso_weeks() {
+        assert_eq!(iso_week(week_index(0)), "1970-W01");
+        assert_eq!(iso_week(week_index(MONDAY)), "2024-W01");
+        assert_eq!(iso_week(week_index(MONDAY - 1)), "2023-W52");
+        // 2020-12-31 is a Thursday, so the 53rd week of 2020 runs until Sunday 2021-01-03
+        assert_eq!(iso_week(week_index(18_628 * DAY_NS)), "2020-W53");
+        assert_eq!(iso_week(week_index(18_630 * DAY_NS)), "2020-W53");
+    }
+
+    #[test]
+    fn weeks_bucketed() {
+        let mut contract = Contract::default();
+        at(MONDAY - 1);
+        contract.count_weekly(|week| week.attacks += 1);
+        at(MONDAY);
+        contract.count_weekly(|week| week.attacks += 1);
+        contract.count_weekly(|week| week.games_finished += 1);
+        at(MONDAY + 7 * DAY_NS - 1);
+        contract.count_weekly(|week| week.new_players += 1);
+
+        assert_eq!(counts(&contract.get_weekly_stats(3)), vec![
+            ("2023-W51".to_string(), 0, 0, 0),
+            ("2023-W52".to_string(), 0, 0, 1),
+            ("2024-W01".to_string(), 1, 1, 1),
+        ]);
+        match contract.web4_get(request_path("/api/stats/weekly")) {
+            Web4Response::Body { body, .. } => {
+                let stats: Vec<WeekStats> = serde_json::from_slice(&Vec::<u8>::from(body)).unwrap();
+                assert_eq!(counts(&stats), counts(&contract.get_weekly_stats(52)));
+                assert_eq!(stats.len(), 52);
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    fn oldest_week_overwritten() {
+        let mut contract = Contract::default();
+        at(MONDAY);
+        contract.count_weekly(|week| week.attacks += 1);
+
+        // 52 weeks later the same slot starts over
+        at(MONDAY + 52 * 7 * DAY_NS);
+        contract.count_weekly(|week| week.attacks += 1);
+        let stats = contract.get_weekly_stats(53);
+        assert_eq!(stats.len(), 52);
+        assert_eq!(counts(&stats[..1]), vec![("2024-W02".to_string(), 0, 0, 0)]);
+        assert_eq!(counts(&stats[51..]), vec![("2025-W01".to_string(), 0, 0, 1)]);
+        assert_eq!(contract.weekly_stats.len(), (week_index(MONDAY) % WEEKS_KEPT + 1) as usize);
+    }
+
+    #[test]
+    fn game_counted() {
+        let mut contract = Contract::default();
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 19 }], vec![Die { size: 4, value: 1 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+        login_as("bob.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+
+        assert_eq!(counts(&contract.get_weekly_stats(1)), vec![("1970-W01".to_string(), 1, 2, 1)]);
+    }
+}
@@ -0,0 +1,171 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::*;
+
+const STREAK_LEADERBOARD_KEY: &[u8] = b"streak_leaderboard";
+
+/// Wins in a row over rated games, a loss of any kind or a draw ends the current one
+#[derive(Debug, Default, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Streak {
+    pub current: u32,
+    pub best: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StreakEntry {
+    pub account_id: String,
+    pub best: u32,
+}
+
+fn read_streak_leaderboard() -> Vec<StreakEntry> {
+    match env::storage_read(STREAK_LEADERBOARD_KEY) {
+        Some(leaderboard_vec) => Vec::<StreakEntry>::try_from_slice(&leaderboard_vec).unwrap(),
+        None => vec![],
+    }
+}
+
+fn write_streak_leaderboard(leaderboard: &Vec<StreakEntry>) {
+    env::storage_write(STREAK_LEADERBOARD_KEY, &leaderboard.try_to_vec().unwrap());
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_streak(&self, account_id: AccountId) -> Streak {
+        self.streaks.get(&account_id.to_string()).unwrap_or_default()
+    }
+
+    /// Longest win streaks, best first. Holds as many accounts as the rating leaderboard.
+    pub fn get_streak_leaderboard(&self, limit: u64) -> Vec<StreakEntry> {
+        read_streak_leaderboard().into_iter().take(limit as usize).collect()
+    }
+
+    /// Extends the winner's streak and ends the loser's, or both on a draw
+    pub(crate) fn update_streaks(&mut self, game: &Game) {
+        for (player_index, player_id) in game.players.iter().enumerate() {
+            let mut streak = self.streaks.get(player_id).unwrap_or_default();
+            if game.winner == Some(player_index as u8) {
+                streak.current += 1;
+                if streak.current > streak.best {
+                    streak.best = streak.current;
+                    self.update_streak_leaderboard(player_id, streak.best);
+                    emit_event("best_streak", serde_json::json!({
+                        "account_id": player_id,
+                        "streak": streak.best,
+                        "game_id": game.id,
+                    }));
+                }
+            } else {
+                streak.current = 0;
+            }
+            self.streaks.insert(player_id, &streak);
+        }
+    }
+
+    pub(crate) fn remove_from_streak_leaderboard(&mut self, account_id: &str) {
+        let mut leaderboard = read_streak_leaderboard();
+        leaderboard.retain(|entry| entry.account_id != account_id);
+        write_streak_leaderboard(&leaderboard);
+    }
+
+    /// Same rules as update_leaderboard: newcomers get in when there's room or they beat the lowest streak
+    fn update_streak_leaderboard(&mut self, account_id: &str, best: u32) {
+        let size = self.config.leaderboard_size as usize;
+        let mut leaderboard = read_streak_leaderboard();
+
+        match leaderboard.iter().position(|entry| entry.account_id == account_id) {
+            Some(index) => leaderboard[index].best = best,
+            None => {
+                if leaderboard.len() >= size && leaderboard.last().is_none_or(|lowest| best <= lowest.best) {
+                    return;
+                }
+                leaderboard.push(StreakEntry { account_id: account_id.to_string(), best });
+            },
+        }
+        // Stable, so whoever got to a streak first stays ahead
+        leaderboard.sort_by_key(|entry| std::cmp::Reverse(entry.best));
+        leaderboard.truncate(size);
+        write_streak_leaderboard(&leaderboard);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::login_as;
+
+    /// Plays a game between bob.near and alice.near where the loser resigns
+    fn play(contract: &mut Contract, loser: &str) {
+        contract.last_game_id += 1;
+        let game_id = contract.last_game_id.to_string();
+        contract.games.insert(&game_id, &Game {
+            id: game_id.clone(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 0,
+            status: GameStatus::Active,
+            dice: vec![vec![Die { size: 20, value: 19 }], vec![Die { size: 4, value: 1 }]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        });
+        login_as(loser);
+        contract.resign(game_id);
+    }
+
+    fn streak(contract: &Contract, account_id: &str) -> (u32, u32) {
+        let streak = contract.get_streak(account_id.parse().unwrap());
+        (streak.current, streak.best)
+    }
+
+    fn best_streak_events() -> Vec<String> {
+        near_sdk::test_utils::get_logs().into_iter().filter(|log| log.contains(r#""event":"best_streak""#)).collect()
+    }
+
+    #[test]
+    fn win_win_loss_win() {
+        let mut contract = Contract::default();
+
+        play(&mut contract, "alice.near");
+        assert_eq!(streak(&contract, "bob.near"), (1, 1));
+        play(&mut contract, "alice.near");
+        assert_eq!(streak(&contract, "bob.near"), (2, 2));
+        assert_eq!(best_streak_events(), vec![
+            r#"EVENT_JSON:{"standard":"buttonmen","version":"1.0.0","event":"best_streak","data":[{"account_id":"bob.near","streak":2,"game_id":"2"}]}"#,
+        ]);
+
+        // Resigning breaks the streak
+        play(&mut contract, "bob.near");
+        assert_eq!(streak(&contract, "bob.near"), (0, 2));
+        assert_eq!(streak(&contract, "alice.near"), (1, 1));
+
+        play(&mut contract, "alice.near");
+        assert_eq!(streak(&contract, "bob.near"), (1, 2));
+        assert_eq!(streak(&contract, "alice.near"), (0, 1));
+        assert!(best_streak_events().is_empty());
+
+        assert_eq!(contract.get_streak_leaderboard(10), vec![
+            StreakEntry { account_id: "bob.near".to_string(), best: 2 },
+            StreakEntry { account_id: "alice.near".to_string(), best: 1 },
+        ]);
+        assert_eq!(contract.get_streak_leaderboard(1).len(), 1);
+    }
+
+    #[test]
+    fn draw_ends_streak() {
+        let mut contract = Contract::default();
+        play(&mut contract, "alice.near");
+
+        let mut game = Game {
+            id: "2".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            dice: vec![vec![], vec![]],
+            captured: vec![vec![], vec![]],
+            ..Default::default()
+        };
+        finish_game(&mut game, None, FinishReason::CaptureOut);
+        contract.update_streaks(&game);
+        assert_eq!(streak(&contract, "bob.near"), (0, 1));
+    }
+}
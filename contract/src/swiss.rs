@@ -0,0 +1,259 @@
+use std::collections::{HashMap, HashSet};
+
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::near_bindgen;
+
+use crate::*;
+use tournament::{Match, Tournament, TournamentStatus};
+
+/// Pairing attempts before giving up on avoiding rematches, keeps the gas of a round bounded
+const PAIRING_BUDGET: u32 = 10_000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Standing {
+    pub account_id: String,
+    /// A point for every match won, byes included
+    pub points: u32,
+    /// Sum of the points of everyone the player has played, breaks ties in points
+    pub buchholz: u32,
+}
+
+fn opponents(tournament: &Tournament) -> HashMap<String, Vec<String>> {
+    let mut opponents = HashMap::<String, Vec<String>>::new();
+    for m in tournament.rounds.iter().flatten().filter(|m| m.players.len() == 2) {
+        opponents.entry(m.players[0].clone()).or_default().push(m.players[1].clone());
+        opponents.entry(m.players[1].clone()).or_default().push(m.players[0].clone());
+    }
+    return opponents;
+}
+
+/// Players by points, then Buchholz, then seed
+pub(crate) fn standings(tournament: &Tournament) -> Vec<Standing> {
+    let mut points = HashMap::<String, u32>::new();
+    for winner in tournament.rounds.iter().flatten().filter_map(|m| m.winner.clone()) {
+        *points.entry(winner).or_default() += 1;
+    }
+    let opponents = opponents(tournament);
+
+    let mut standings = tournament.players.iter().map(|player_id| Standing {
+        account_id: player_id.clone(),
+        points: points.get(player_id).copied().unwrap_or(0),
+        buchholz: opponents.get(player_id).map_or(0, |ids| ids.iter().map(|id| points.get(id).copied().unwrap_or(0)).sum()),
+    }).collect::<Vec<Standing>>();
+    // Stable, so players level on both keep their seed order
+    standings.sort_by_key(|standing| std::cmp::Reverse((standing.points, standing.buchholz)));
+    return standings;
+}
+
+/// Pairs each player, best ranked first, with the closest ranked player they haven't met yet,
+/// backtracking when that leaves someone without a new opponent
+fn pair_without_rematches(players: &[String], met: &HashSet<(String, String)>, budget: &mut u32) -> Option<Vec<(String, String)>> {
+    if players.is_empty() {
+        return Some(vec![]);
+    }
+    if *budget == 0 {
+        return None;
+    }
+    *budget -= 1;
+
+    for index in 1..players.len() {
+        if met.contains(&(players[0].clone(), players[index].clone())) {
+            continue;
+        }
+        let rest = players[1..].iter().enumerate()
+            .filter(|(rest_index, _)| rest_index + 1 != index)
+            .map(|(_, player_id)| player_id.clone())
+            .collect::<Vec<String>>();
+        if let Some(mut pairs) = pair_without_rematches(&rest, met, budget) {
+            pairs.insert(0, (players[0].clone(), players[index].clone()));
+            return Some(pairs);
+        }
+    }
+    return None;
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Pairs the next round of a Swiss tournament once every match of the current one is decided. Anyone can call it.
+    pub fn start_next_round(&mut self, tournament_id: String) -> () {
+        self.assert_not_paused();
+
+        let mut tournament = self.tournaments.get(&tournament_id).unwrap_or_else(|| fail(Error::TournamentNotFound { tournament_id: tournament_id.to_string() }));
+        if tournament.swiss_rounds.is_none() {
+            fail(Error::NotSwiss { tournament_id: tournament_id.to_string() });
+        }
+        if tournament.status != TournamentStatus::Running {
+            fail(Error::TournamentNotRunning { tournament_id: tournament_id.to_string() });
+        }
+        if tournament.rounds.last().is_some_and(|round| round.iter().any(|m| m.winner.is_none())) {
+            fail(Error::RoundInProgress { round: tournament.rounds.len(), tournament_id: tournament_id.to_string() });
+        }
+
+        self.pair_swiss_round(&mut tournament);
+        self.tournaments.insert(&tournament_id, &tournament);
+    }
+
+    pub fn get_standings(&self, tournament_id: String) -> Vec<Standing> {
+        let tournament = self.tournaments.get(&tournament_id).unwrap_or_else(|| fail(Error::TournamentNotFound { tournament_id: tournament_id.to_string() }));
+        standings(&tournament)
+    }
+
+    /// Adds a round of pairings by the current standings. With an odd number of players
+    /// the lowest ranked player who hasn't had a bye yet gets one.
+    pub(crate) fn pair_swiss_round(&mut self, tournament: &mut Tournament) {
+        let mut ranked = standings(tournament).into_iter().map(|standing| standing.account_id).collect::<Vec<String>>();
+
+        let bye = if ranked.len() % 2 == 1 {
+            let had_bye = tournament.rounds.iter().flatten()
+                .filter(|m| m.players.len() == 1)
+                .map(|m| m.players[0].clone())
+                .collect::<HashSet<String>>();
+            let index = ranked.iter().rposition(|player_id| !had_bye.contains(player_id)).unwrap_or(ranked.len() - 1);
+            Some(ranked.remove(index))
+        } else {
+            None
+        };
+
+        let met = opponents(tournament).into_iter()
+            .flat_map(|(player_id, ids)| ids.into_iter().map(move |id| (player_id.clone(), id)))
+            .collect::<HashSet<(String, String)>>();
+        let mut budget = PAIRING_BUDGET;
+        let pairs = pair_without_rematches(&ranked, &met, &mut budget)
+            // Everyone left has met already, or it took too long to find out, so some play again
+            .unwrap_or_else(|| ranked.chunks(2).map(|pair| (pair[0].clone(), pair[1].clone())).collect());
+
+        let mut round = pairs.into_iter()
+            .map(|(a, b)| self.create_match(&[a, b], &tournament.id))
+            .collect::<Vec<Match>>();
+        if let Some(player_id) = bye {
+            round.push(Match { players: vec![player_id.clone()], game_id: "".to_string(), wins: vec![0], winner: Some(player_id) });
+        }
+        tournament.rounds.push(round);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{login_as, request_path};
+    use tournament::CreateTournamentOptions;
+
+    fn swiss_tournament(contract: &mut Contract, players: usize, rounds: u32) -> String {
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(CreateTournamentOptions {
+            name: "Swiss".to_string(),
+            max_players: players as u32,
+            entry_fee: None,
+            match_length: None,
+            starts_at: None,
+            swiss_rounds: Some(rounds),
+            prize_split: None,
+        });
+        for seed in 1..=players {
+            login_as(&format!("seed{}.near", seed));
+            contract.register(tournament_id.clone());
+        }
+        return tournament_id;
+    }
+
+    fn pairings(contract: &Contract, tournament_id: &str, round: usize) -> Vec<Vec<String>> {
+        contract.get_tournament(tournament_id.to_string()).unwrap().rounds[round].iter().map(|m| m.players.clone()).collect()
+    }
+
+    fn names(pairs: &[&[&str]]) -> Vec<Vec<String>> {
+        pairs.iter().map(|pair| pair.iter().map(|name| format!("{}.near", name)).collect()).collect()
+    }
+
+    /// Every game of the round goes to the better seed
+    fn better_seeds_win(contract: &mut Contract, tournament_id: &str, round: usize) {
+        let tournament = contract.get_tournament(tournament_id.to_string()).unwrap();
+        for m in tournament.rounds[round].iter().filter(|m| m.players.len() == 2) {
+            let loser = m.players.iter().max().unwrap();
+            login_as(loser);
+            contract.resign(m.game_id.clone());
+        }
+    }
+
+    #[test]
+    fn six_players_three_rounds() {
+        let mut contract = Contract::default();
+        let tournament_id = swiss_tournament(&mut contract, 6, 3);
+        assert_eq!(pairings(&contract, &tournament_id, 0), names(&[&["seed1", "seed2"], &["seed3", "seed4"], &["seed5", "seed6"]]));
+
+        better_seeds_win(&mut contract, &tournament_id, 0);
+        // The next round waits for start_next_round
+        assert_eq!(contract.get_tournament(tournament_id.clone()).unwrap().rounds.len(), 1);
+        contract.start_next_round(tournament_id.clone());
+        assert_eq!(pairings(&contract, &tournament_id, 1), names(&[&["seed1", "seed3"], &["seed5", "seed2"], &["seed4", "seed6"]]));
+
+        better_seeds_win(&mut contract, &tournament_id, 1);
+        contract.start_next_round(tournament_id.clone());
+        // seed5.near and seed6.near already met, so the pairing backtracks
+        assert_eq!(pairings(&contract, &tournament_id, 2), names(&[&["seed1", "seed4"], &["seed2", "seed6"], &["seed3", "seed5"]]));
+
+        better_seeds_win(&mut contract, &tournament_id, 2);
+        let standings = contract.get_standings(tournament_id.clone()).into_iter()
+            .map(|standing| (standing.account_id, standing.points, standing.buchholz))
+            .collect::<Vec<(String, u32, u32)>>();
+        assert_eq!(standings, vec![
+            ("seed1.near".to_string(), 3, 5),
+            // Level on points with seed2.near, but played stronger opponents
+            ("seed3.near".to_string(), 2, 5),
+            ("seed2.near".to_string(), 2, 4),
+            ("seed4.near".to_string(), 1, 5),
+            ("seed5.near".to_string(), 1, 4),
+            ("seed6.near".to_string(), 0, 4),
+        ]);
+
+        let tournament = contract.get_tournament(tournament_id.clone()).unwrap();
+        assert_eq!(tournament.status, TournamentStatus::Finished);
+        assert_eq!(tournament.winner, Some("seed1.near".to_string()));
+
+        match contract.web4_get(request_path(&format!("/api/tournaments/{}/standings", tournament_id))) {
+            Web4Response::Body { content_type, body, .. } => {
+                assert_eq!(content_type, "application/json".to_owned());
+                assert_eq!(String::from_utf8(body.into()).unwrap(),
+                    serde_json::to_string(&contract.get_standings(tournament_id)).unwrap());
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    fn byes_rotate() {
+        let mut contract = Contract::default();
+        let tournament_id = swiss_tournament(&mut contract, 3, 2);
+        assert_eq!(pairings(&contract, &tournament_id, 0), names(&[&["seed1", "seed2"], &["seed3"]]));
+
+        better_seeds_win(&mut contract, &tournament_id, 0);
+        contract.start_next_round(tournament_id.clone());
+        // seed2.near is last and hasn't had a bye
+        assert_eq!(pairings(&contract, &tournament_id, 1), names(&[&["seed1", "seed3"], &["seed2"]]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Round 1 of tournament 1 is still being played")]
+    fn next_round_waits_for_games() {
+        let mut contract = Contract::default();
+        let tournament_id = swiss_tournament(&mut contract, 4, 2);
+        contract.start_next_round(tournament_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tournament 1 isn't a Swiss tournament")]
+    fn next_round_single_elimination() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(CreateTournamentOptions {
+            name: "Cup".to_string(),
+            max_players: 2,
+            entry_fee: None,
+            match_length: None,
+            starts_at: None,
+            swiss_rounds: None,
+            prize_split: None,
+        });
+        contract.start_next_round(tournament_id);
+    }
+}
@@ -0,0 +1,169 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, AccountId};
+
+use crate::*;
+
+/// Title shown for ratings from min_rating up to the next title's
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RatingTitle {
+    pub min_rating: u32,
+    pub title: String,
+}
+
+pub(crate) fn default_rating_titles() -> Vec<RatingTitle> {
+    [("Novice", 0), ("Veteran", 1600), ("Master", 1800)].iter()
+        .map(|(title, min_rating)| RatingTitle { min_rating: *min_rating, title: title.to_string() })
+        .collect()
+}
+
+struct BadgeRule {
+    id: &'static str,
+    icon: &'static str,
+    /// Achievements an account needs all of to show the badge
+    requires: &'static [&'static str],
+}
+
+const BADGES: &[BadgeRule] = &[
+    BadgeRule { id: "champion", icon: "🏆", requires: &["first_win", "rout"] },
+    BadgeRule { id: "perfectionist", icon: "💎", requires: &["flawless"] },
+    BadgeRule { id: "giant_killer", icon: "🗡️", requires: &["giant_slayer"] },
+    BadgeRule { id: "old_guard", icon: "🎖️", requires: &["veteran"] },
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Badge {
+    pub id: String,
+    pub icon: String,
+}
+
+/// What's shown next to an account's name, worked out from its rating, achievements, streak and club when read
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Standing {
+    pub rating: u32,
+    pub title: Option<String>,
+    pub badges: Vec<Badge>,
+    pub streak: Streak,
+    pub club_id: Option<String>,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_standing(&self, account_id: AccountId) -> Standing {
+        let rating = self.rating_of(account_id.as_str());
+        Standing {
+            rating,
+            title: self.title_for(rating),
+            badges: self.badges_of(account_id.as_str()),
+            streak: self.get_streak(account_id.clone()),
+            club_id: self.get_club_of(account_id.clone()),
+        }
+    }
+
+    /// Replaces the rating titles, given from the lowest rating up
+    pub fn set_rating_titles(&mut self, titles: Vec<RatingTitle>) -> () {
+        self.assert_owner("set rating titles");
+        if titles.windows(2).any(|pair| pair[0].min_rating >= pair[1].min_rating) {
+            fail(Error::TitlesOutOfOrder);
+        }
+
+        self.config.rating_titles = titles;
+    }
+
+    /// Highest title the rating reaches, None below the lowest one
+    pub(crate) fn title_for(&self, rating: u32) -> Option<String> {
+        self.config.rating_titles.iter()
+            .take_while(|title| title.min_rating <= rating)
+            .last()
+            .map(|title| title.title.clone())
+    }
+
+    pub(crate) fn badges_of(&self, account_id: &str) -> Vec<Badge> {
+        let earned = self.achievements.get(&account_id.to_string()).unwrap_or_default();
+        BADGES.iter()
+            .filter(|badge| badge.requires.iter().all(|id| earned.iter().any(|achievement| achievement.id == *id)))
+            .map(|badge| Badge { id: badge.id.to_string(), icon: badge.icon.to_string() })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::login_as;
+
+    fn rated(contract: &mut Contract, account_id: &str, rating: u32) {
+        contract.ratings.insert(&account_id.to_string(), &SeasonRating { season_id: None, rating });
+    }
+
+    fn earn(contract: &mut Contract, account_id: &str, ids: &[&str]) {
+        let earned = ids.iter().map(|id| EarnedAchievement { id: id.to_string(), earned_at: 0 }).collect();
+        contract.achievements.insert(&account_id.to_string(), &earned);
+    }
+
+    fn badge_ids(standing: &Standing) -> Vec<String> {
+        standing.badges.iter().map(|badge| badge.id.clone()).collect()
+    }
+
+    #[test]
+    fn titles_at_thresholds() {
+        let mut contract = Contract::default();
+        for (rating, title) in [(1599, "Novice"), (1600, "Veteran"), (1799, "Veteran"), (1800, "Master"), (2400, "Master")] {
+            rated(&mut contract, "bob.near", rating);
+            assert_eq!(contract.get_standing("bob.near".parse().unwrap()).title, Some(title.to_string()), "{}", rating);
+        }
+    }
+
+    #[test]
+    fn no_games() {
+        let contract = Contract::default();
+        assert_eq!(contract.get_standing("bob.near".parse().unwrap()), Standing {
+            rating: ratings::DEFAULT_RATING,
+            title: Some("Novice".to_string()),
+            badges: vec![],
+            streak: Streak::default(),
+            club_id: None,
+        });
+    }
+
+    #[test]
+    fn custom_titles() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_rating_titles(vec![
+            RatingTitle { min_rating: 1550, title: "Contender".to_string() },
+            RatingTitle { min_rating: 1700, title: "Champion".to_string() },
+        ]);
+
+        rated(&mut contract, "bob.near", 1549);
+        assert_eq!(contract.get_standing("bob.near".parse().unwrap()).title, None);
+        rated(&mut contract, "bob.near", 1550);
+        assert_eq!(contract.get_standing("bob.near".parse().unwrap()).title, Some("Contender".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Titles must go up in rating")]
+    fn titles_out_of_order() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_rating_titles(vec![
+            RatingTitle { min_rating: 1600, title: "Veteran".to_string() },
+            RatingTitle { min_rating: 1600, title: "Master".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn badges_need_every_achievement() {
+        let mut contract = Contract::default();
+        earn(&mut contract, "bob.near", &["first_win", "flawless"]);
+        assert_eq!(badge_ids(&contract.get_standing("bob.near".parse().unwrap())), vec!["perfectionist"]);
+
+        earn(&mut contract, "bob.near", &["first_win", "flawless", "rout"]);
+        let standing = contract.get_standing("bob.near".parse().unwrap());
+        assert_eq!(badge_ids(&standing), vec!["champion", "perfectionist"]);
+        assert_eq!(standing.badges[0].icon, "🏆");
+    }
+}
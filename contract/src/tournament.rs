@@ -0,0 +1,749 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, Balance, Promise};
+
+use crate::*;
+
+/// Bounds the number of games created at once when the bracket is drawn
+const MAX_TOURNAMENT_PLAYERS: u32 = 64;
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Match {
+    /// A single player for a bye, who advances without playing
+    pub players: Vec<String>,
+    /// Game currently being played, replaced by the next one until the match is decided. Empty for a bye
+    pub game_id: String,
+    /// Games won by each player
+    pub wins: Vec<u32>,
+    pub winner: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum TournamentStatus {
+    Registration,
+    Running,
+    Finished,
+    /// Called off before it started, entry fees can be claimed back
+    Cancelled,
+}
+
+/// Prize or refund owed to an account, paid out by claim_tournament_payout
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Payout {
+    pub account_id: String,
+    pub amount: U128,
+    pub claimed: bool,
+}
+
+/// Single elimination bracket, or Swiss rounds when swiss_rounds is set. Every round's matches are played
+/// as regular games. The next elimination round is paired up once all of them have a winner,
+/// a Swiss round with start_next_round.
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Tournament {
+    pub id: String,
+    pub name: String,
+    pub organizer: String,
+    pub status: TournamentStatus,
+    pub max_players: u32,
+    pub entry_fee: U128,
+    /// Games a player has to win to take a match
+    pub match_length: u32,
+    /// Block timestamp after which anyone can start the tournament with the players registered so far
+    pub starts_at: Option<u64>,
+    /// Entry fees collected, split by prize_split minus ContractConfig.tournament_fee_bps
+    pub prize_pool: U128,
+    /// Percentage of the prize pool for each place, best first, adding up to 100
+    pub prize_split: Vec<u32>,
+    /// Rounds of a Swiss tournament, None for single elimination
+    pub swiss_rounds: Option<u32>,
+    /// Players in registration order, then in seed order (best first) once the bracket is drawn
+    pub players: Vec<String>,
+    pub rounds: Vec<Vec<Match>>,
+    pub winner: Option<String>,
+    /// Prizes once the tournament is finished, or entry fees to give back once it's cancelled
+    pub payouts: Vec<Payout>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CreateTournamentOptions {
+    pub name: String,
+    pub max_players: u32,
+    /// Attached by every player to register, none by default
+    pub entry_fee: Option<U128>,
+    /// Defaults to a single game per match
+    pub match_length: Option<u32>,
+    /// Without a start time the tournament starts once it's full
+    pub starts_at: Option<u64>,
+    /// Plays this many Swiss rounds instead of single elimination
+    pub swiss_rounds: Option<u32>,
+    /// Percentages of the prize pool by place, e.g. [60, 30, 10]. Everything goes to the winner by default
+    pub prize_split: Option<Vec<u32>>,
+}
+
+/// Bracket positions of seeds 1..=count, so that the top seeds can only meet in the last rounds,
+/// e.g. 1, 4, 2, 3 for four players: 1 plays 4 and 2 plays 3.
+fn seed_order(count: usize) -> Vec<usize> {
+    let mut order = vec![1];
+    while order.len() < count {
+        let sum = order.len() * 2 + 1;
+        order = order.iter().flat_map(|seed| [*seed, sum - seed]).collect();
+    }
+    return order;
+}
+
+/// Places of a finished tournament, best first: Swiss by standings, elimination by how far each player got,
+/// seed breaking ties between players knocked out in the same round
+fn final_standings(tournament: &Tournament) -> Vec<String> {
+    if tournament.swiss_rounds.is_some() {
+        return swiss::standings(tournament).into_iter().map(|standing| standing.account_id).collect();
+    }
+
+    let reached = |player_id: &String| {
+        let last_round = tournament.rounds.iter().rposition(|round| round.iter().any(|m| m.players.contains(player_id))).unwrap_or(0);
+        last_round + (tournament.winner.as_ref() == Some(player_id)) as usize
+    };
+    let mut standings = tournament.players.clone();
+    standings.sort_by_key(|player_id| std::cmp::Reverse(reached(player_id)));
+    return standings;
+}
+
+/// Splits the pool by the percentages, rounding down. What rounding leaves over,
+/// along with the shares of places nobody finished in, goes to first place.
+fn split_prizes(pool: Balance, split: &[u32], places: usize) -> Vec<Balance> {
+    let mut prizes = split.iter().take(places).map(|pct| pool * *pct as Balance / 100).collect::<Vec<Balance>>();
+    prizes[0] += pool - prizes.iter().sum::<Balance>();
+    return prizes;
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Opens a single elimination tournament for registration, see register.
+    pub fn create_tournament(&mut self, options: CreateTournamentOptions) -> String {
+        self.assert_not_paused();
+
+        if options.max_players < 2 || options.max_players > MAX_TOURNAMENT_PLAYERS {
+            fail(Error::InvalidMaxPlayers { max: MAX_TOURNAMENT_PLAYERS });
+        }
+        let match_length = options.match_length.unwrap_or(1);
+        if match_length < 1 {
+            fail(Error::InvalidMatchLength);
+        }
+        if options.swiss_rounds == Some(0) {
+            fail(Error::NoSwissRounds);
+        }
+        let prize_split = options.prize_split.unwrap_or(vec![100]);
+        if prize_split.is_empty() || prize_split.iter().sum::<u32>() != 100 {
+            fail(Error::InvalidPrizeSplit);
+        }
+        if prize_split.len() as u32 > options.max_players {
+            fail(Error::TooManyPrizePlaces);
+        }
+
+        self.last_tournament_id += 1;
+        let tournament_id = format!("{}", self.last_tournament_id);
+
+        self.tournaments.insert(&tournament_id, &Tournament {
+            id: tournament_id.clone(),
+            name: options.name,
+            organizer: env::predecessor_account_id().to_string(),
+            status: TournamentStatus::Registration,
+            max_players: options.max_players,
+            entry_fee: options.entry_fee.unwrap_or(U128(0)),
+            match_length,
+            starts_at: options.starts_at,
+            prize_pool: U128(0),
+            prize_split,
+            swiss_rounds: options.swiss_rounds,
+            players: vec![],
+            rounds: vec![],
+            winner: None,
+            payouts: vec![],
+        });
+
+        return tournament_id;
+    }
+
+    /// Signs the caller up, attaching exactly the entry fee. Registering the last free spot draws the bracket.
+    #[payable]
+    pub fn register(&mut self, tournament_id: String) -> () {
+        self.assert_not_paused();
+
+        let mut tournament = self.tournaments.get(&tournament_id).unwrap_or_else(|| fail(Error::TournamentNotFound { tournament_id: tournament_id.to_string() }));
+        if tournament.status != TournamentStatus::Registration {
+            fail(Error::RegistrationClosed { tournament_id: tournament_id.to_string() });
+        }
+        let player_id = env::predecessor_account_id().to_string();
+        if tournament.players.contains(&player_id) {
+            fail(Error::AlreadyRegistered { player_id: player_id.to_string(), tournament_id: tournament_id.to_string() });
+        }
+        if env::attached_deposit() != tournament.entry_fee.0 {
+            fail(Error::WrongDeposit { amount: tournament.entry_fee.0, action: "register".to_string() });
+        }
+
+        tournament.players.push(player_id);
+        tournament.prize_pool = U128(tournament.prize_pool.0 + tournament.entry_fee.0);
+        if tournament.players.len() as u32 == tournament.max_players {
+            self.draw_bracket(&mut tournament);
+        }
+        self.tournaments.insert(&tournament_id, &tournament);
+    }
+
+    /// Starts the tournament with whoever registered once its start time has passed.
+    /// With fewer than 2 players it's cancelled and the entry fees can be claimed back.
+    pub fn start_tournament(&mut self, tournament_id: String) -> () {
+        self.assert_not_paused();
+
+        let mut tournament = self.tournaments.get(&tournament_id).unwrap_or_else(|| fail(Error::TournamentNotFound { tournament_id: tournament_id.to_string() }));
+        if tournament.status != TournamentStatus::Registration {
+            fail(Error::RegistrationClosed { tournament_id: tournament_id.to_string() });
+        }
+        if tournament.starts_at.is_none_or(|starts_at| env::block_timestamp() < starts_at) {
+            fail(Error::TooEarlyToStart { tournament_id: tournament_id.to_string() });
+        }
+
+        if tournament.players.len() < 2 {
+            self.cancel(&mut tournament);
+        } else {
+            self.draw_bracket(&mut tournament);
+        }
+        self.tournaments.insert(&tournament_id, &tournament);
+    }
+
+    /// Calls off a tournament that hasn't started yet. Only its organizer or the owner can.
+    pub fn cancel_tournament(&mut self, tournament_id: String) -> () {
+        let mut tournament = self.tournaments.get(&tournament_id).unwrap_or_else(|| fail(Error::TournamentNotFound { tournament_id: tournament_id.to_string() }));
+        if env::predecessor_account_id().to_string() != tournament.organizer && !self.is_owner() {
+            fail(Error::NotOrganizer { tournament_id: tournament_id.to_string() });
+        }
+        if tournament.status != TournamentStatus::Registration {
+            fail(Error::TournamentStarted { tournament_id: tournament_id.to_string() });
+        }
+
+        self.cancel(&mut tournament);
+        self.tournaments.insert(&tournament_id, &tournament);
+    }
+
+    /// Pays the caller what they're owed by a finished or cancelled tournament, a prize or their entry fee back.
+    pub fn claim_tournament_payout(&mut self, tournament_id: String) -> () {
+        self.assert_not_paused();
+
+        let mut tournament = self.tournaments.get(&tournament_id).unwrap_or_else(|| fail(Error::TournamentNotFound { tournament_id: tournament_id.to_string() }));
+        let account_id = env::predecessor_account_id();
+        let payout = tournament.payouts.iter_mut().find(|payout| payout.account_id == account_id.as_str())
+            .unwrap_or_else(|| fail(Error::NoPayout { tournament_id: tournament_id.to_string() }));
+        if payout.claimed {
+            fail(Error::PayoutClaimed { tournament_id: tournament_id.to_string() });
+        }
+
+        payout.claimed = true;
+        let amount = payout.amount;
+        self.tournaments.insert(&tournament_id, &tournament);
+        Promise::new(account_id.clone()).transfer(amount.0);
+
+        self.audit("claim_tournament_payout", format!("tournament {}: {} yoctoNEAR to {}", tournament_id, amount.0, account_id));
+        emit_event("tournament_payout_claimed", serde_json::json!({
+            "tournament_id": tournament_id,
+            "account_id": account_id,
+            "amount": amount,
+        }));
+    }
+
+    pub fn get_tournament(&self, tournament_id: String) -> Option<Tournament> {
+        self.tournaments.get(&tournament_id)
+    }
+
+    /// Most recently created tournaments first
+    pub fn get_latest_tournaments(&self, limit: u64) -> Vec<Tournament> {
+        (1..=self.last_tournament_id).rev()
+            .take(limit as usize)
+            .filter_map(|id| self.tournaments.get(&id.to_string()))
+            .collect()
+    }
+
+    /// Seeds the players by rating, registration order breaking ties, and creates the first round's games.
+    /// When the player count of an elimination bracket isn't a power of two the top seeds get byes.
+    fn draw_bracket(&mut self, tournament: &mut Tournament) {
+        let mut seeded = tournament.players.clone();
+        seeded.sort_by_key(|player_id| std::cmp::Reverse(self.rating_of(player_id)));
+        tournament.players = seeded.clone();
+        tournament.status = TournamentStatus::Running;
+
+        if tournament.swiss_rounds.is_some() {
+            self.pair_swiss_round(tournament);
+        } else {
+            let slots = seed_order(seeded.len().next_power_of_two()).iter()
+                .map(|seed| seeded.get(seed - 1).cloned())
+                .collect::<Vec<Option<String>>>();
+            let first_round = slots.chunks(2).map(|pair| {
+                let players = pair.iter().flatten().cloned().collect::<Vec<String>>();
+                if players.len() == 2 {
+                    self.create_match(&players, &tournament.id)
+                } else {
+                    Match { winner: Some(players[0].clone()), players, game_id: "".to_string(), wins: vec![0] }
+                }
+            }).collect();
+            tournament.rounds = vec![first_round];
+        }
+
+        emit_event("tournament_started", serde_json::json!({
+            "tournament_id": tournament.id,
+            "players": tournament.players,
+        }));
+    }
+
+    pub(crate) fn create_match(&mut self, players: &[String], tournament_id: &str) -> Match {
+        Match {
+            players: players.to_vec(),
+            game_id: self.create_seated_game(players, Some(tournament_id.to_string())),
+            wins: vec![0; players.len()],
+            winner: None,
+        }
+    }
+
+    /// Records the result of a tournament game. Until a player has won match_length games
+    /// (draws don't count) the match goes on with a new game. Once every match of the round
+    /// is decided the winners are paired up for the next one, and the final pays out the prize.
+    /// A Swiss tournament waits for start_next_round instead, and is won by the top of the standings after its last round.
+    pub(crate) fn advance_tournament(&mut self, game: &Game) {
+        let mut tournament = match game.tournament_id.as_ref().and_then(|tournament_id| self.tournaments.get(tournament_id)) {
+            Some(tournament) => tournament,
+            None => return,
+        };
+        let tournament_id = tournament.id.clone();
+        let match_length = tournament.match_length;
+        let round = match tournament.rounds.last_mut() {
+            Some(round) => round,
+            None => return,
+        };
+        let match_index = match round.iter().position(|m| m.game_id == game.id) {
+            Some(match_index) => match_index,
+            None => return,
+        };
+
+        let current = &mut round[match_index];
+        if let Some(winner) = game.winner {
+            current.wins[winner as usize] += 1;
+            if current.wins[winner as usize] >= match_length {
+                current.winner = Some(game.players[winner as usize].clone());
+            }
+        }
+        if current.winner.is_none() {
+            let players = current.players.clone();
+            round[match_index].game_id = self.create_seated_game(&players, Some(tournament_id.clone()));
+        }
+
+        let swiss_rounds = tournament.swiss_rounds;
+        if round.iter().all(|m| m.winner.is_some()) && swiss_rounds.is_some() {
+            if Some(tournament.rounds.len() as u32) == swiss_rounds {
+                tournament.winner = Some(swiss::standings(&tournament)[0].account_id.clone());
+                self.finish_tournament(&mut tournament);
+            }
+        } else if round.iter().all(|m| m.winner.is_some()) {
+            let winners = round.iter().filter_map(|m| m.winner.clone()).collect::<Vec<String>>();
+            if winners.len() == 1 {
+                tournament.winner = Some(winners[0].clone());
+                self.finish_tournament(&mut tournament);
+            } else {
+                let next_round = winners.chunks(2).map(|pair| self.create_match(pair, &tournament_id)).collect();
+                tournament.rounds.push(next_round);
+            }
+        }
+
+        self.tournaments.insert(&tournament_id, &tournament);
+    }
+
+    /// Takes the fee and settles the prizes by the final standings. Winners claim them with claim_tournament_payout.
+    fn finish_tournament(&mut self, tournament: &mut Tournament) {
+        let fee = tournament.prize_pool.0 * self.config.tournament_fee_bps as Balance / 10_000;
+        self.treasury += fee;
+
+        let standings = final_standings(tournament);
+        let prizes = split_prizes(tournament.prize_pool.0 - fee, &tournament.prize_split, standings.len());
+        tournament.payouts = standings.into_iter().zip(prizes)
+            .filter(|(_, prize)| *prize > 0)
+            .map(|(account_id, prize)| Payout { account_id, amount: U128(prize), claimed: false })
+            .collect();
+        tournament.status = TournamentStatus::Finished;
+
+        if !tournament.payouts.is_empty() {
+            self.audit("settle_tournament", format!("tournament {}: {}", tournament.id, describe_payouts(&tournament.payouts)));
+        }
+        emit_event("tournament_finished", serde_json::json!({
+            "tournament_id": tournament.id,
+            "winner": tournament.winner,
+            "payouts": tournament.payouts,
+        }));
+    }
+
+    /// Marks the tournament cancelled and owes every player their entry fee back
+    fn cancel(&mut self, tournament: &mut Tournament) {
+        tournament.status = TournamentStatus::Cancelled;
+        if tournament.entry_fee.0 > 0 {
+            tournament.payouts = tournament.players.iter()
+                .map(|player_id| Payout { account_id: player_id.clone(), amount: tournament.entry_fee, claimed: false })
+                .collect();
+        }
+        tournament.prize_pool = U128(0);
+
+        if !tournament.payouts.is_empty() {
+            self.audit("cancel_tournament", format!("tournament {}: refunds {}", tournament.id, describe_payouts(&tournament.payouts)));
+        }
+        emit_event("tournament_cancelled", serde_json::json!({
+            "tournament_id": tournament.id,
+            "refunds": tournament.payouts,
+        }));
+    }
+}
+
+fn describe_payouts(payouts: &[Payout]) -> String {
+    payouts.iter().map(|payout| format!("{} yoctoNEAR to {}", payout.amount.0, payout.account_id)).collect::<Vec<String>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_as, call_at, login_as, request_path, transfers};
+
+    const ENTRY_FEE: Balance = 1_000_000_000_000_000_000_000_000;
+
+    fn options(max_players: u32) -> CreateTournamentOptions {
+        CreateTournamentOptions { name: "Cup".to_string(), max_players, entry_fee: None, match_length: None, starts_at: None, swiss_rounds: None, prize_split: None }
+    }
+
+    fn register_seeds(contract: &mut Contract, tournament_id: &str, count: usize, entry_fee: Balance) {
+        for seed in 1..=count {
+            call_as(&format!("seed{}.near", seed), 0, entry_fee);
+            contract.register(tournament_id.to_string());
+        }
+    }
+
+    /// The given player resigns the match's current game, so their opponent wins it
+    fn lose(contract: &mut Contract, tournament_id: &str, round: usize, match_index: usize, player_id: &str) {
+        let game_id = contract.get_tournament(tournament_id.to_string()).unwrap().rounds[round][match_index].game_id.clone();
+        call_as(player_id, 0, 0);
+        contract.resign(game_id);
+    }
+
+    #[test]
+    fn seed_order_keeps_top_seeds_apart() {
+        assert_eq!(seed_order(2), vec![1, 2]);
+        assert_eq!(seed_order(4), vec![1, 4, 2, 3]);
+        assert_eq!(seed_order(8), vec![1, 8, 4, 5, 2, 7, 3, 6]);
+    }
+
+    #[test]
+    fn four_player_tournament() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(CreateTournamentOptions { entry_fee: Some(U128(ENTRY_FEE)), ..options(4) });
+
+        register_seeds(&mut contract, &tournament_id, 3, ENTRY_FEE);
+        let tournament = contract.get_tournament(tournament_id.clone()).unwrap();
+        assert_eq!(tournament.status, TournamentStatus::Registration);
+        assert!(tournament.rounds.is_empty());
+
+        // The last spot draws the bracket
+        call_as("seed4.near", 0, ENTRY_FEE);
+        contract.register(tournament_id.clone());
+        let tournament = contract.get_tournament(tournament_id.clone()).unwrap();
+        assert_eq!(tournament.status, TournamentStatus::Running);
+        assert_eq!(tournament.prize_pool, U128(4 * ENTRY_FEE));
+        assert_eq!(tournament.rounds[0].iter().map(|m| m.players.clone()).collect::<Vec<Vec<String>>>(), vec![
+            vec!["seed1.near".to_string(), "seed4.near".to_string()],
+            vec!["seed2.near".to_string(), "seed3.near".to_string()],
+        ]);
+        for m in tournament.rounds[0].iter() {
+            let game = contract.games.get(&m.game_id).unwrap();
+            assert_eq!(game.status, GameStatus::Active);
+            assert_eq!(game.players, m.players);
+            assert_eq!(game.tournament_id, Some(tournament_id.clone()));
+        }
+
+        lose(&mut contract, &tournament_id, 0, 0, "seed4.near");
+        // The next round waits for every match of this one
+        assert_eq!(contract.get_tournament(tournament_id.clone()).unwrap().rounds.len(), 1);
+
+        lose(&mut contract, &tournament_id, 0, 1, "seed2.near");
+        let tournament = contract.get_tournament(tournament_id.clone()).unwrap();
+        assert_eq!(tournament.rounds[0][0].winner, Some("seed1.near".to_string()));
+        assert_eq!(tournament.rounds[0][1].winner, Some("seed3.near".to_string()));
+        assert_eq!(tournament.rounds[1].len(), 1);
+        assert_eq!(tournament.rounds[1][0].players, vec!["seed1.near".to_string(), "seed3.near".to_string()]);
+        assert_eq!(contract.games.get(&tournament.rounds[1][0].game_id).unwrap().status, GameStatus::Active);
+
+        lose(&mut contract, &tournament_id, 1, 0, "seed1.near");
+        // 5% of the pool goes to the treasury, the rest waits for the winner to claim it
+        assert_eq!(transfers(), vec![]);
+        assert_eq!(contract.get_treasury(), U128(4 * ENTRY_FEE / 20));
+        call_as("seed3.near", 0, 0);
+        contract.claim_tournament_payout(tournament_id.clone());
+        assert_eq!(transfers(), vec![("seed3.near".to_string(), 4 * ENTRY_FEE - 4 * ENTRY_FEE / 20)]);
+        let tournament = contract.get_tournament(tournament_id.clone()).unwrap();
+        assert_eq!(tournament.winner, Some("seed3.near".to_string()));
+        assert_eq!(tournament.status, TournamentStatus::Finished);
+        assert_eq!(tournament.payouts, vec![Payout { account_id: "seed3.near".to_string(), amount: U128(4 * ENTRY_FEE - 4 * ENTRY_FEE / 20), claimed: true }]);
+
+        match contract.web4_get(request_path(&format!("/api/tournaments/{}", tournament_id))) {
+            Web4Response::Body { content_type, body, .. } => {
+                assert_eq!(content_type, "application/json".to_owned());
+                assert_eq!(String::from_utf8(body.into()).unwrap(), serde_json::to_string(&tournament).unwrap());
+            },
+            _ => panic!("Unexpected response"),
+        }
+        match contract.web4_get(request_path("/api/tournaments")) {
+            Web4Response::Body { body, .. } => {
+                assert_eq!(String::from_utf8(body.into()).unwrap(), serde_json::to_string(&vec![tournament]).unwrap());
+            },
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    fn seeded_by_rating_with_byes() {
+        let mut contract = Contract::default();
+        contract.ratings.insert(&"seed3.near".to_string(), &SeasonRating { season_id: None, rating: 1600 });
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(CreateTournamentOptions { starts_at: Some(100), ..options(8) });
+        register_seeds(&mut contract, &tournament_id, 3, 0);
+
+        call_at("anyone.near", 100);
+        contract.start_tournament(tournament_id.clone());
+
+        let tournament = contract.get_tournament(tournament_id.clone()).unwrap();
+        assert_eq!(tournament.players, vec!["seed3.near", "seed1.near", "seed2.near"]);
+        // The top seed gets a bye in a bracket of four
+        assert_eq!(tournament.rounds[0][0], Match {
+            players: vec!["seed3.near".to_string()],
+            game_id: "".to_string(),
+            wins: vec![0],
+            winner: Some("seed3.near".to_string()),
+        });
+        assert_eq!(tournament.rounds[0][1].players, vec!["seed1.near".to_string(), "seed2.near".to_string()]);
+
+        lose(&mut contract, &tournament_id, 0, 1, "seed2.near");
+        let tournament = contract.get_tournament(tournament_id).unwrap();
+        assert_eq!(tournament.rounds[1][0].players, vec!["seed3.near".to_string(), "seed1.near".to_string()]);
+    }
+
+    #[test]
+    fn match_length_and_draws() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(CreateTournamentOptions { match_length: Some(2), ..options(2) });
+        register_seeds(&mut contract, &tournament_id, 2, 0);
+        let first_game = contract.get_tournament(tournament_id.clone()).unwrap().rounds[0][0].game_id.clone();
+
+        lose(&mut contract, &tournament_id, 0, 0, "seed2.near");
+        let tournament = contract.get_tournament(tournament_id.clone()).unwrap();
+        assert_eq!(tournament.rounds[0][0].wins, vec![1, 0]);
+        assert_eq!(tournament.rounds[0][0].winner, None);
+        assert_ne!(tournament.rounds[0][0].game_id, first_game);
+
+        // A draw is replayed without counting
+        let mut game = contract.games.get(&tournament.rounds[0][0].game_id).unwrap();
+        game.winner = None;
+        game.status = GameStatus::Finished;
+        contract.advance_tournament(&game);
+        let tournament = contract.get_tournament(tournament_id.clone()).unwrap();
+        assert_eq!(tournament.rounds[0][0].wins, vec![1, 0]);
+        assert_ne!(tournament.rounds[0][0].game_id, game.id);
+
+        lose(&mut contract, &tournament_id, 0, 0, "seed2.near");
+        assert_eq!(contract.get_tournament(tournament_id).unwrap().winner, Some("seed1.near".to_string()));
+    }
+
+    #[test]
+    fn cancelled_without_players() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(CreateTournamentOptions { entry_fee: Some(U128(ENTRY_FEE)), starts_at: Some(100), ..options(4) });
+        register_seeds(&mut contract, &tournament_id, 1, ENTRY_FEE);
+
+        call_at("anyone.near", 100);
+        contract.start_tournament(tournament_id.clone());
+        assert_eq!(contract.get_tournament(tournament_id.clone()).unwrap().status, TournamentStatus::Cancelled);
+
+        call_as("seed1.near", 0, 0);
+        contract.claim_tournament_payout(tournament_id);
+        assert_eq!(transfers(), vec![("seed1.near".to_string(), ENTRY_FEE)]);
+    }
+
+    #[test]
+    fn prize_split_rounding() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(CreateTournamentOptions { entry_fee: Some(U128(11)), prize_split: Some(vec![60, 30, 10]), ..options(4) });
+        register_seeds(&mut contract, &tournament_id, 4, 11);
+
+        lose(&mut contract, &tournament_id, 0, 0, "seed4.near");
+        lose(&mut contract, &tournament_id, 0, 1, "seed2.near");
+        lose(&mut contract, &tournament_id, 1, 0, "seed1.near");
+
+        // 2 of the 44 go to the treasury. 42 split 60/30/10 rounds down to 25, 12 and 4, first place gets the one left over.
+        // seed2.near and seed4.near both went out in the semifinals, the better seed is third.
+        let tournament = contract.get_tournament(tournament_id.clone()).unwrap();
+        assert_eq!(tournament.payouts.iter().map(|payout| (payout.account_id.as_str(), payout.amount.0)).collect::<Vec<(&str, Balance)>>(), vec![
+            ("seed3.near", 26), ("seed1.near", 12), ("seed2.near", 4)]);
+        assert_eq!(contract.get_treasury(), U128(2));
+
+        for account_id in ["seed1.near", "seed2.near"] {
+            call_as(account_id, 0, 0);
+            contract.claim_tournament_payout(tournament_id.clone());
+        }
+        assert_eq!(transfers(), vec![("seed2.near".to_string(), 4)]);
+        assert_eq!(contract.get_audit_log(0, 10).iter().map(|entry| entry.details.clone()).collect::<Vec<String>>(), vec![
+            "tournament 1: 26 yoctoNEAR to seed3.near, 12 yoctoNEAR to seed1.near, 4 yoctoNEAR to seed2.near".to_string(),
+            "tournament 1: 12 yoctoNEAR to seed1.near".to_string(),
+            "tournament 1: 4 yoctoNEAR to seed2.near".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn split_over_fewer_players() {
+        assert_eq!(split_prizes(100, &[50, 30, 20], 2), vec![70, 30]);
+        assert_eq!(split_prizes(10, &[100], 8), vec![10]);
+    }
+
+    #[test]
+    fn cancel_tournament() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(CreateTournamentOptions { entry_fee: Some(U128(ENTRY_FEE)), ..options(4) });
+        register_seeds(&mut contract, &tournament_id, 2, ENTRY_FEE);
+
+        login_as("organizer.near");
+        contract.cancel_tournament(tournament_id.clone());
+        let tournament = contract.get_tournament(tournament_id.clone()).unwrap();
+        assert_eq!(tournament.status, TournamentStatus::Cancelled);
+        assert_eq!(tournament.prize_pool, U128(0));
+        assert_eq!(tournament.payouts.len(), 2);
+
+        call_as("seed2.near", 0, 0);
+        contract.claim_tournament_payout(tournament_id.clone());
+        assert_eq!(transfers(), vec![("seed2.near".to_string(), ENTRY_FEE)]);
+        assert_eq!(contract.get_audit_log(0, 10)[0].action, "cancel_tournament");
+    }
+
+    #[test]
+    #[should_panic(expected = "Payout from tournament 1 is already claimed")]
+    fn claim_twice() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(CreateTournamentOptions { entry_fee: Some(U128(ENTRY_FEE)), ..options(4) });
+        register_seeds(&mut contract, &tournament_id, 1, ENTRY_FEE);
+        login_as("organizer.near");
+        contract.cancel_tournament(tournament_id.clone());
+
+        call_as("seed1.near", 0, 0);
+        contract.claim_tournament_payout(tournament_id.clone());
+        contract.claim_tournament_payout(tournament_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn claim_paused() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(CreateTournamentOptions { entry_fee: Some(U128(ENTRY_FEE)), ..options(4) });
+        register_seeds(&mut contract, &tournament_id, 1, ENTRY_FEE);
+        login_as("organizer.near");
+        contract.cancel_tournament(tournament_id.clone());
+        login_as("alice.near");
+        contract.set_paused(true);
+
+        call_as("seed1.near", 0, 0);
+        contract.claim_tournament_payout(tournament_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nothing to claim from tournament 1")]
+    fn claim_without_payout() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(CreateTournamentOptions { entry_fee: Some(U128(ENTRY_FEE)), ..options(2) });
+        register_seeds(&mut contract, &tournament_id, 2, ENTRY_FEE);
+        lose(&mut contract, &tournament_id, 0, 0, "seed2.near");
+
+        contract.claim_tournament_payout(tournament_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the organizer can cancel tournament 1")]
+    fn cancel_not_organizer() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(options(4));
+        login_as("bob.near");
+        contract.cancel_tournament(tournament_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Prize split must add up to 100%")]
+    fn prize_split_not_100() {
+        let mut contract = Contract::default();
+        contract.create_tournament(CreateTournamentOptions { prize_split: Some(vec![60, 30]), ..options(4) });
+    }
+
+    #[test]
+    #[should_panic(expected = "Tournament 1 can't start before its start time")]
+    fn start_before_start_time() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(CreateTournamentOptions { starts_at: Some(100), ..options(4) });
+        register_seeds(&mut contract, &tournament_id, 2, 0);
+
+        call_at("anyone.near", 99);
+        contract.start_tournament(tournament_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attach exactly 1000000000000000000000000 yoctoNEAR to register")]
+    fn register_wrong_fee() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(CreateTournamentOptions { entry_fee: Some(U128(ENTRY_FEE)), ..options(4) });
+        register_seeds(&mut contract, &tournament_id, 1, ENTRY_FEE - 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "seed1.near is already registered for tournament 1")]
+    fn register_twice() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(options(4));
+        register_seeds(&mut contract, &tournament_id, 1, 0);
+        register_seeds(&mut contract, &tournament_id, 1, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Registration for tournament 1 is closed")]
+    fn register_when_full() {
+        let mut contract = Contract::default();
+        login_as("organizer.near");
+        let tournament_id = contract.create_tournament(options(2));
+        register_seeds(&mut contract, &tournament_id, 3, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tournament needs 2 to 64 players")]
+    fn tournament_too_small() {
+        let mut contract = Contract::default();
+        contract.create_tournament(options(1));
+    }
+
+    #[test]
+    fn web4_get_tournament_not_found() {
+        let contract = Contract::default();
+        assert_eq!(contract.web4_get(request_path("/api/tournaments/1")), Web4Response::Body {
+            content_type: "application/json".to_owned(),
+            body: r#"{"error":"tournament_not_found"}"#.as_bytes().to_owned().into(),
+            status: Some(404),
+        });
+    }
+}
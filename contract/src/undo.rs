@@ -0,0 +1,195 @@
+use near_sdk::{env, near_bindgen};
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Asks the opponent to take back the caller's last move, see approve_undo.
+    pub fn request_undo(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+        let player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() }));
+        pauses::assert_running(&mut game);
+        match game.history.last() {
+            Some(record) if record.player == player_index as u8 && record.kind.is_move() => {},
+            _ => fail(Error::NotUndoable),
+        }
+
+        game.undo_requested_by = Some(player_index as u8);
+        self.save_game(&game);
+
+        notify(&game.players[(player_index + 1) % 2], "undo_requested", &game_id, &player_id);
+    }
+
+    /// Reverts the last move after its player asked with request_undo: dice go back to how they were before it,
+    /// a captured die is returned and the turn goes back, with the clocks as they were when the move was made.
+    pub fn approve_undo(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        assert_in_progress(&game);
+        let player_index = game.players.iter().position(|p| p == &player_id).unwrap_or_else(|| fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() }));
+        pauses::assert_running(&mut game);
+        match game.undo_requested_by {
+            Some(requested_by) if requested_by != player_index as u8 => {},
+            _ => fail(Error::NotRequested { request: "undo".to_string(), game_id: game_id.to_string() }),
+        }
+        // The opponent may have asked for more time since
+        if !game.history.last().is_some_and(|record| record.kind.is_move()) {
+            fail(Error::NotUndoable);
+        }
+
+        let record = game.history.pop().unwrap_or_else(|| fail(Error::NotUndoable));
+        if record.captured.is_some() {
+            game.captured[record.player as usize].pop();
+        }
+        game.dice = record.dice_before;
+        if !record.remaining_before.is_empty() {
+            game.remaining_ns = record.remaining_before;
+        }
+        game.current_player = record.player;
+        game.turn_started_at = env::block_timestamp();
+        game.undo_requested_by = None;
+        game.last_move_at = env::block_timestamp();
+        self.save_game(&game);
+
+        emit_event("move_undone", serde_json::json!({
+            "game_id": game_id,
+            "player": record.player,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_at, login_as};
+
+    const HOUR_NS: u64 = 60 * 60 * 1_000_000_000;
+
+    fn insert_game(contract: &mut Contract) {
+        contract.games.insert(&"1".to_string(), &Game {
+            id: "1".to_string(),
+            players: vec!["bob.near".to_string(), "alice.near".to_string()],
+            current_player: 1,
+            status: GameStatus::Active,
+            dice: vec![
+                vec![Die { size: 4, value: 1 }, Die { size: 6, value: 5 }],
+                vec![Die { size: 6, value: 3 }, Die { size: 8, value: 2 }]],
+            captured: vec![vec![], vec![10]],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn undo_attack() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract);
+        let before = contract.games.get(&"1".to_string()).unwrap();
+
+        login_as("alice.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        contract.request_undo("1".to_string());
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().undo_requested_by, Some(1));
+
+        login_as("bob.near");
+        contract.approve_undo("1".to_string());
+
+        let game = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(game.dice, before.dice);
+        assert_eq!(game.captured, before.captured);
+        assert_eq!(game.current_player, 1);
+        assert!(game.history.is_empty());
+        assert_eq!(game.undo_requested_by, None);
+    }
+
+    #[test]
+    fn undo_pass() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract);
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        game.dice[1] = vec![Die { size: 4, value: 1 }];
+        game.dice[0] = vec![Die { size: 6, value: 5 }];
+        contract.games.insert(&"1".to_string(), &game);
+
+        login_as("alice.near");
+        contract.pass("1".to_string(), None, None);
+        contract.request_undo("1".to_string());
+        login_as("bob.near");
+        contract.approve_undo("1".to_string());
+
+        let undone = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(undone.dice, game.dice);
+        assert_eq!(undone.current_player, 1);
+    }
+
+    #[test]
+    fn undo_gives_the_time_back() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract);
+        let mut game = contract.games.get(&"1".to_string()).unwrap();
+        game.time_control = Some(TimeControl { bank_ns: DAY_NS, increment_ns: HOUR_NS, per_move: false });
+        game.remaining_ns = vec![DAY_NS, DAY_NS];
+        contract.games.insert(&"1".to_string(), &game);
+
+        call_at("alice.near", 2 * HOUR_NS);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().remaining_ns, vec![DAY_NS, DAY_NS - HOUR_NS]);
+        contract.request_undo("1".to_string());
+        call_at("bob.near", 3 * HOUR_NS);
+        contract.approve_undo("1".to_string());
+
+        let undone = contract.games.get(&"1".to_string()).unwrap();
+        assert_eq!(undone.remaining_ns, vec![DAY_NS, DAY_NS]);
+        assert_eq!(undone.turn_started_at, 3 * HOUR_NS);
+
+        // alice.near's clock runs again from the approval
+        call_at("alice.near", 4 * HOUR_NS);
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().remaining_ns, vec![DAY_NS, DAY_NS]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the last move can be undone, by the player who made it")]
+    fn request_undo_opponent_move() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract);
+
+        login_as("alice.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        login_as("bob.near");
+        contract.request_undo("1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "No undo requested by the opponent in game 1")]
+    fn approve_own_undo() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract);
+
+        login_as("alice.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        contract.request_undo("1".to_string());
+        contract.approve_undo("1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "No undo requested by the opponent in game 1")]
+    fn undo_request_cleared_by_move() {
+        let mut contract = Contract::default();
+        insert_game(&mut contract);
+
+        login_as("alice.near");
+        contract.attack("1".to_string(), vec![0], 0, None, None, None);
+        contract.request_undo("1".to_string());
+
+        // bob.near moves on instead of approving
+        login_as("bob.near");
+        contract.attack("1".to_string(), vec![0], 1, None, None, None);
+        contract.approve_undo("1".to_string());
+    }
+}
@@ -0,0 +1,537 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Balance, Promise};
+
+use crate::*;
+
+#[derive(Debug, Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Wager {
+    /// Stake each player puts in, in yoctoNEAR
+    pub stake: U128,
+    /// Set before any payout, so escrow can only ever be released once
+    pub settled: bool,
+}
+
+fn transfer(account_id: &str, amount: Balance) {
+    let account_id: AccountId = account_id.parse().unwrap_or_else(|_| fail(Error::InvalidAccountId { account_id: account_id.to_string() }));
+    Promise::new(account_id).transfer(amount);
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Creates a game where each player stakes the attached deposit and the winner takes the pot.
+    /// Past the daily free games the game creation fee, and for new accounts the anti-spam deposit,
+    /// are taken out of the attached deposit first.
+    #[payable]
+    pub fn create_wagered_game(&mut self, options: Option<CreateGameOptions>) -> String {
+        self.assert_not_paused();
+
+        let fee = self.charge_game_creation();
+        let spam_deposit = self.required_spam_deposit(&env::predecessor_account_id());
+        let stake = env::attached_deposit().saturating_sub(fee + spam_deposit);
+        if stake == 0 {
+            fail(Error::NoDeposit { action: "wager on the game".to_string() });
+        }
+        self.assert_min_stake(stake);
+
+        let game_id = self.internal_create_game(options);
+        self.hold_spam_deposit(&game_id, spam_deposit);
+        self.wagers.insert(&game_id, &Wager {
+            stake: stake.into(),
+            settled: false,
+        });
+
+        return game_id;
+    }
+
+    #[payable]
+    pub fn join_wagered_game(&mut self, game_id: String, button: Option<String>) -> () {
+        self.assert_not_paused();
+
+        let wager = self.wagers.get(&game_id).unwrap_or_else(|| fail(Error::NoWager { game_id: game_id.to_string() }));
+        if env::attached_deposit() != wager.stake.0 {
+            fail(Error::WrongDeposit { amount: wager.stake.0, action: format!("join game {}", game_id) });
+        }
+        // The minimum may have been raised since the game was created
+        self.assert_min_stake(wager.stake.0);
+
+        self.internal_join_game(game_id, button);
+    }
+
+    pub fn get_wager(&self, game_id: String) -> Option<Wager> {
+        self.wagers.get(&game_id)
+    }
+
+    /// Sets the cut of won pots kept in the treasury, withdrawn with withdraw_treasury
+    pub fn set_wager_fee(&mut self, fee_bps: u16) -> () {
+        self.assert_owner("set the wager fee");
+        if fee_bps > 10_000 {
+            fail(Error::FeeTooHigh);
+        }
+
+        self.config.wager_fee_bps = fee_bps;
+    }
+
+    /// Won pots not withdrawn yet
+    pub fn get_winnings(&self, account_id: String) -> U128 {
+        self.winnings.get(&account_id).unwrap_or(0).into()
+    }
+
+    /// Transfers everything the caller won in wagered games, so the move ending a game doesn't pay for the transfer
+    pub fn withdraw_winnings(&mut self) -> U128 {
+        self.assert_not_paused();
+
+        let account_id = env::predecessor_account_id();
+        let amount = self.winnings.remove(&account_id.to_string()).unwrap_or_else(|| fail(Error::NoWinnings { account_id: account_id.to_string() }));
+        Promise::new(account_id.clone()).transfer(amount);
+
+        self.audit("withdraw_winnings", format!("{} yoctoNEAR to {}", amount, account_id));
+        return amount.into();
+    }
+
+    /// Keeps dust wagers out, 0 allows any stake
+    pub fn set_min_stake(&mut self, min_stake: U128) -> () {
+        self.assert_owner("set the minimum stake");
+
+        self.config.min_stake = min_stake;
+    }
+
+    /// Lets the creator take their stake back when nobody joined before the start expiry.
+    pub fn reclaim_wager(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        self.assert_wager_open(&game_id);
+        if env::predecessor_account_id().to_string() != game.players[0] {
+            fail(Error::NotCreator { action: "reclaim the wager".to_string() });
+        }
+        if game.is_started() {
+            fail(Error::GameAlreadyStarted { game_id: game_id.to_string() });
+        }
+        if game.verifying {
+            fail(Error::VerifyingButtons { game_id: game_id.to_string() });
+        }
+        if env::block_timestamp() < game.created_at + self.config.wager_start_expiry_ns {
+            fail(Error::TooEarlyToReclaim { game_id: game_id.to_string() });
+        }
+
+        let wager = self.take_wager(&game_id);
+        game.status = GameStatus::Cancelled;
+        self.save_game(&game);
+        self.remove_from_latest(&game_id);
+        self.forfeit_spam_deposit(&game_id);
+
+        transfer(&game.players[0], wager.stake.0);
+    }
+
+    /// Lets either player split the pot back when the game stalled mid-way
+    /// and nobody moved before the stall deadline.
+    pub fn refund_stalled_wager(&mut self, game_id: String) -> () {
+        self.assert_not_paused();
+
+        let player_id = env::predecessor_account_id().to_string();
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        self.assert_wager_open(&game_id);
+        if !game.players.contains(&player_id) {
+            fail(Error::NotJoined { player_id: player_id.to_string(), game_id: game_id.to_string() });
+        }
+        if game.status != GameStatus::Active {
+            fail(Error::GameNotActive { game_id: game_id.to_string() });
+        }
+        pauses::assert_running(&mut game);
+        if env::block_timestamp() < game.last_move_at + self.config.wager_stall_refund_ns {
+            fail(Error::NotStalled { game_id: game_id.to_string() });
+        }
+
+        let wager = self.take_wager(&game_id);
+        game.status = GameStatus::Cancelled;
+        self.save_game(&game);
+        self.remove_from_latest(&game_id);
+        self.refund_spam_deposit(&game);
+
+        for player_id in game.players.iter() {
+            transfer(player_id, wager.stake.0);
+        }
+    }
+
+    /// Lets the owner move escrow of a long abandoned game to a recovery account.
+    pub fn sweep_abandoned_wager(&mut self, game_id: String, recovery_account_id: AccountId) -> () {
+        self.assert_not_paused();
+
+        self.assert_owner("sweep wagers");
+        let mut game = self.games.get(&game_id).unwrap_or_else(|| fail(Error::GameNotFound { game_id: game_id.to_string() }));
+        self.assert_wager_open(&game_id);
+        if env::block_timestamp() < game.last_move_at + self.config.wager_sweep_after_ns {
+            fail(Error::NotAbandoned { game_id: game_id.to_string() });
+        }
+
+        let wager = self.take_wager(&game_id);
+        let pot = wager.stake.0 * game.players.iter().filter(|p| !p.is_empty()).count() as Balance;
+        game.status = GameStatus::Cancelled;
+        self.save_game(&game);
+        self.remove_from_latest(&game_id);
+        self.forfeit_spam_deposit(&game_id);
+
+        self.audit("sweep_abandoned_wager", format!("game {}: {} yoctoNEAR to {}", game_id, pot, recovery_account_id));
+        transfer(recovery_account_id.as_str(), pot);
+    }
+
+    /// Pays out escrow of a game that just finished: winner is credited the pot minus ContractConfig.wager_fee_bps
+    /// to withdraw with withdraw_winnings, a draw credits both players their stake back in full.
+    pub(crate) fn settle_finished_wager(&mut self, game: &Game) {
+        if self.wagers.get(&game.id).is_none() {
+            return;
+        }
+
+        let wager = self.take_wager(&game.id);
+        match game.winner {
+            Some(winner) => {
+                let pot = wager.stake.0 * game.players.len() as Balance;
+                let fee = pot * self.config.wager_fee_bps as Balance / 10_000;
+                self.treasury += fee;
+                self.credit_winnings(&game.players[winner as usize], pot - fee);
+            },
+            None => {
+                for player_id in game.players.iter() {
+                    self.credit_winnings(player_id, wager.stake.0);
+                }
+            },
+        }
+    }
+
+    fn credit_winnings(&mut self, account_id: &String, amount: Balance) {
+        self.winnings.insert(account_id, &(self.get_winnings(account_id.clone()).0 + amount));
+    }
+
+    /// Refunds the stake of a player whose seat was taken away before the game started.
+    pub(crate) fn refund_released_seat(&mut self, game_id: &str, player_id: &str) {
+        if let Some(wager) = self.wagers.get(&game_id.to_string()) {
+            if !wager.settled {
+                transfer(player_id, wager.stake.0);
+            }
+        }
+    }
+
+    fn assert_min_stake(&self, stake: Balance) {
+        if stake < self.config.min_stake.0 {
+            fail(Error::StakeTooLow { stake, min_stake: self.config.min_stake.0 });
+        }
+    }
+
+    fn assert_wager_open(&self, game_id: &str) -> Wager {
+        let wager = self.wagers.get(&game_id.to_string()).unwrap_or_else(|| fail(Error::NoWager { game_id: game_id.to_string() }));
+        if wager.settled {
+            fail(Error::WagerSettled { game_id: game_id.to_string() });
+        }
+        return wager;
+    }
+
+    /// Marks the wager settled before any transfer happens.
+    fn take_wager(&mut self, game_id: &str) -> Wager {
+        let mut wager = self.assert_wager_open(game_id);
+        wager.settled = true;
+        self.wagers.insert(&game_id.to_string(), &wager);
+        return wager;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{call_as, login_as, request_path, transfers};
+
+    const STAKE: Balance = 1_000_000;
+
+    fn started_wagered_game(contract: &mut Contract) -> String {
+        call_as("bob.near", 0, STAKE);
+        let game_id = contract.create_wagered_game(None);
+        call_as("alice.near", DAY_NS, STAKE);
+        contract.join_wagered_game(game_id.clone(), None);
+        return game_id;
+    }
+
+    #[test]
+    fn wagered_game_winner_takes_pot() {
+        let mut contract = Contract::default();
+        let game_id = started_wagered_game(&mut contract);
+
+        let mut game = contract.games.get(&game_id).unwrap();
+        game.current_player = 0;
+        game.dice = vec![vec![Die { size: 20, value: 20 }], vec![Die { size: 4, value: 1 }]];
+        contract.games.insert(&game_id, &game);
+
+        login_as("bob.near");
+        contract.attack(game_id.clone(), vec![0], 0, None, None, None);
+
+        assert_eq!(transfers(), vec![]);
+        assert_eq!(contract.get_winnings("bob.near".to_string()), U128(2 * STAKE));
+        assert!(contract.get_wager(game_id).unwrap().settled);
+
+        assert_eq!(contract.withdraw_winnings(), U128(2 * STAKE));
+        assert_eq!(transfers(), vec![("bob.near".to_string(), 2 * STAKE)]);
+        assert_eq!(contract.get_winnings("bob.near".to_string()), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "No winnings to withdraw for alice.near")]
+    fn withdraw_winnings_twice() {
+        let mut contract = Contract::default();
+        contract.winnings.insert(&"alice.near".to_string(), &STAKE);
+        login_as("alice.near");
+        contract.withdraw_winnings();
+
+        contract.withdraw_winnings();
+    }
+
+    #[test]
+    fn wagered_game_fee_on_win() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_wager_fee(250);
+        let game_id = started_wagered_game(&mut contract);
+
+        let mut game = contract.games.get(&game_id).unwrap();
+        game.current_player = 0;
+        game.dice = vec![vec![Die { size: 20, value: 20 }], vec![Die { size: 4, value: 1 }]];
+        contract.games.insert(&game_id, &game);
+
+        login_as("bob.near");
+        contract.attack(game_id.clone(), vec![0], 0, None, None, None);
+
+        assert_eq!(contract.get_winnings("bob.near".to_string()), U128(2 * STAKE - 50_000));
+        assert_eq!(contract.treasury, 50_000);
+    }
+
+    #[test]
+    fn wagered_game_draw_credits_stakes() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_wager_fee(250);
+        let game_id = started_wagered_game(&mut contract);
+
+        let mut game = contract.games.get(&game_id).unwrap();
+        game.current_player = 0;
+        game.dice = vec![vec![Die { size: 4, value: 1 }], vec![Die { size: 4, value: 3 }]];
+        game.captured = vec![vec![], vec![]];
+        finish_game(&mut game, None, FinishReason::CaptureOut);
+        contract.settle_finished_wager(&game);
+
+        assert_eq!(transfers(), vec![]);
+        assert_eq!(contract.get_winnings("bob.near".to_string()), U128(STAKE));
+        assert_eq!(contract.get_winnings("alice.near".to_string()), U128(STAKE));
+        assert_eq!(contract.treasury, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn withdraw_winnings_paused() {
+        let mut contract = Contract::default();
+        contract.winnings.insert(&"bob.near".to_string(), &STAKE);
+        login_as("alice.near");
+        contract.set_paused(true);
+
+        login_as("bob.near");
+        contract.withdraw_winnings();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can set the wager fee")]
+    fn wager_fee_not_owner() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.set_wager_fee(250);
+    }
+
+    #[test]
+    fn wager_at_min_stake() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_min_stake(U128(STAKE));
+
+        let game_id = started_wagered_game(&mut contract);
+        assert_eq!(contract.get_wager(game_id.clone()).unwrap().stake, U128(STAKE));
+        assert_eq!(contract.games.get(&game_id).unwrap().status, GameStatus::Active);
+    }
+
+    #[test]
+    #[should_panic(expected = "Stake of 999999 yoctoNEAR is below the minimum of 1000000 yoctoNEAR")]
+    fn wager_below_min_stake() {
+        let mut contract = Contract::default();
+        login_as("alice.near");
+        contract.set_min_stake(U128(STAKE));
+
+        call_as("bob.near", 0, STAKE - 1);
+        contract.create_wagered_game(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Stake of 1000000 yoctoNEAR is below the minimum of 2000000 yoctoNEAR")]
+    fn join_wager_below_raised_min_stake() {
+        let mut contract = Contract::default();
+        call_as("bob.near", 0, STAKE);
+        let game_id = contract.create_wagered_game(None);
+        login_as("alice.near");
+        contract.set_min_stake(U128(2 * STAKE));
+
+        call_as("alice.near", 0, STAKE);
+        contract.join_wagered_game(game_id, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can set the minimum stake")]
+    fn min_stake_not_owner() {
+        let mut contract = Contract::default();
+        login_as("bob.near");
+        contract.set_min_stake(U128(STAKE));
+    }
+
+    #[test]
+    #[should_panic(expected = "Attach exactly 1000000 yoctoNEAR to join game 1")]
+    fn join_wagered_game_wrong_deposit() {
+        let mut contract = Contract::default();
+        call_as("bob.near", 0, STAKE);
+        contract.create_wagered_game(None);
+
+        call_as("alice.near", 0, STAKE - 1);
+        contract.join_wagered_game("1".to_string(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 requires a wager of 1000000 yoctoNEAR, use join_wagered_game")]
+    fn join_game_wagered() {
+        let mut contract = Contract::default();
+        call_as("bob.near", 0, STAKE);
+        contract.create_wagered_game(None);
+
+        call_as("alice.near", 0, 0);
+        contract.join_game("1".to_string(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Wager for game 1 can't be reclaimed yet")]
+    fn reclaim_wager_too_early() {
+        let mut contract = Contract::default();
+        call_as("bob.near", 0, STAKE);
+        contract.create_wagered_game(None);
+
+        call_as("bob.near", 7 * DAY_NS - 1, 0);
+        contract.reclaim_wager("1".to_string());
+    }
+
+    #[test]
+    fn reclaim_wager_unjoined() {
+        let mut contract = Contract::default();
+        call_as("bob.near", 0, STAKE);
+        contract.create_wagered_game(None);
+
+        call_as("bob.near", 7 * DAY_NS, 0);
+        contract.reclaim_wager("1".to_string());
+        assert_eq!(transfers(), vec![("bob.near".to_string(), STAKE)]);
+        assert_eq!(contract.games.get(&"1".to_string()).unwrap().status, GameStatus::Cancelled);
+        assert!(contract.wagers.get(&"1".to_string()).unwrap().settled);
+    }
+
+    #[test]
+    #[should_panic(expected = "Wager for game 1 is already settled")]
+    fn reclaim_wager_twice_rejected() {
+        let mut contract = Contract::default();
+        call_as("bob.near", 0, STAKE);
+        contract.create_wagered_game(None);
+
+        call_as("bob.near", 7 * DAY_NS, 0);
+        contract.reclaim_wager("1".to_string());
+        contract.reclaim_wager("1".to_string());
+    }
+
+    #[test]
+    fn reclaimed_game_leaves_lobby() {
+        let mut contract = Contract::default();
+        call_as("bob.near", 0, STAKE);
+        contract.create_wagered_game(None);
+        assert_eq!(contract.latest_games, vec!["1".to_string()]);
+
+        call_as("bob.near", 7 * DAY_NS, 0);
+        contract.reclaim_wager("1".to_string());
+        assert!(contract.latest_games.is_empty());
+        match contract.web4_get(request_path("/api/games")) {
+            Web4Response::Body { body, .. } => assert_eq!(Vec::<u8>::from(body), b"[]".to_vec()),
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is not stalled yet")]
+    fn refund_stalled_wager_too_early() {
+        let mut contract = Contract::default();
+        let game_id = started_wagered_game(&mut contract);
+
+        call_as("bob.near", DAY_NS + 30 * DAY_NS - 1, 0);
+        contract.refund_stalled_wager(game_id);
+    }
+
+    #[test]
+    fn refund_stalled_wager_splits_pot() {
+        let mut contract = Contract::default();
+        let game_id = started_wagered_game(&mut contract);
+
+        call_as("alice.near", DAY_NS + 30 * DAY_NS, 0);
+        contract.refund_stalled_wager(game_id.clone());
+        assert_eq!(transfers(), vec![("bob.near".to_string(), STAKE), ("alice.near".to_string(), STAKE)]);
+        assert!(contract.wagers.get(&game_id).unwrap().settled);
+    }
+
+    #[test]
+    #[should_panic(expected = "Wager for game 1 is already settled")]
+    fn refund_stalled_wager_twice_rejected() {
+        let mut contract = Contract::default();
+        let game_id = started_wagered_game(&mut contract);
+
+        call_as("alice.near", DAY_NS + 30 * DAY_NS, 0);
+        contract.refund_stalled_wager(game_id.clone());
+
+        // The game is closed and the escrow can't be paid out again
+        login_as("bob.near");
+        contract.refund_stalled_wager(game_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can sweep wagers")]
+    fn sweep_abandoned_wager_not_owner() {
+        let mut contract = Contract::default();
+        let game_id = started_wagered_game(&mut contract);
+
+        call_as("bob.near", 400 * DAY_NS, 0);
+        contract.sweep_abandoned_wager(game_id, "recovery.near".parse().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "Game 1 is not abandoned yet")]
+    fn sweep_abandoned_wager_too_early() {
+        let mut contract = Contract::default();
+        let game_id = started_wagered_game(&mut contract);
+
+        call_as("alice.near", 365 * DAY_NS, 0);
+        contract.sweep_abandoned_wager(game_id, "recovery.near".parse().unwrap());
+    }
+
+    #[test]
+    fn sweep_abandoned_wager() {
+        let mut contract = Contract::default();
+        let game_id = started_wagered_game(&mut contract);
+
+        // alice.near is the contract account in the default test context
+        call_as("alice.near", 366 * DAY_NS, 0);
+        contract.sweep_abandoned_wager(game_id.clone(), "recovery.near".parse().unwrap());
+
+        assert_eq!(transfers(), vec![("recovery.near".to_string(), 2 * STAKE)]);
+        assert_eq!(contract.get_audit_log(0, 10), vec![AuditEntry {
+            timestamp: 366 * DAY_NS,
+            actor: "alice.near".to_string(),
+            action: "sweep_abandoned_wager".to_string(),
+            details: "game 1: 2000000 yoctoNEAR to recovery.near".to_string(),
+        }]);
+        assert_eq!(contract.games.get(&game_id).unwrap().status, GameStatus::Cancelled);
+    }
+}